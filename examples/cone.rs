@@ -0,0 +1,46 @@
+//! Example: vision cones facing each of the four cardinal directions.
+//!
+//! Restricts FOV to a 90 degree `Cone` with `FovComputer::visible_grid_in_cone` and writes
+//! the four renders side by side to `OUT_DIR/cone.png` (`OUT_DIR` defaults to `examples_out`).
+//!
+//! ```text
+//! cargo run --example cone [OUT_DIR]
+//! ```
+
+use std::path::Path;
+
+use fov2d::drawing::{self, compose_grid, ImageFormat, Renderer};
+use fov2d::fov::Cone;
+use fov2d::maps::{Coords, TileMap};
+use fov2d::math::Degrees;
+use fov2d::simple::FovSet16;
+use fov2d::{FovComputer, FovError, FovRadius, FovShape, QFactor};
+
+const SIZE: usize = 33;
+const RADIUS: usize = 16;
+
+fn main() -> Result<(), FovError> {
+    let out_dir = std::env::args().nth(1).unwrap_or_else(|| "examples_out".to_string());
+    std::fs::create_dir_all(&out_dir)?;
+
+    let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default())?;
+    let map = TileMap::random(SIZE, SIZE, 0.08, 42)?;
+    let origin = Coords::new(16, 16);
+    let renderer = Renderer::new(8);
+
+    // Angles run counter-clockwise from east
+    let facings = [("east", 0.0), ("north", 90.0), ("west", 180.0), ("south", 270.0)];
+    let mut renders = Vec::new();
+    for (name, facing) in facings {
+        let cone = Cone::new(origin, Degrees(facing), Degrees(90.0));
+        let grid = fov_set.visible_grid_in_cone(&cone, RADIUS, &map);
+        let label = format!("cone {name}: {} tiles", grid.len());
+        renders.push((label, renderer.render(&map, &grid, origin)));
+    }
+
+    let renders: Vec<_> = renders.iter().map(|(l, c)| (l.as_str(), c)).collect();
+    let path = Path::new(&out_dir).join("cone.png");
+    drawing::save(&path, &compose_grid(&renders, 2), ImageFormat::Png)?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,123 @@
+//! Example: seeds the fuzz corpora from the matching writers.
+//!
+//! Writes a few valid inputs per fuzz target (see `fuzz/`) to `OUT_DIR/<target>/`
+//! (`OUT_DIR` defaults to `fuzz/corpus`), so fuzzing starts from well-formed files rather
+//! than from magic bytes it has to guess.
+//!
+//! ```text
+//! cargo run --example fuzz_seeds [OUT_DIR]
+//! ```
+
+use std::path::Path;
+
+use fov2d::cache::write_fov_set;
+use fov2d::drawing::Renderer;
+use fov2d::fov::Algorithm;
+use fov2d::maps::{Coords, OpacityProvider, TileMap};
+use fov2d::scenes::encode_layout;
+use fov2d::simple::fovcalc::get_visible_grid;
+use fov2d::simple::FovSet16;
+use fov2d::{bench_scenes, FovError, FovRadius, FovSetAny, FovShape, QFactor, VisibilityGrid};
+
+fn main() -> Result<(), FovError> {
+    let out_dir = std::env::args().nth(1).unwrap_or_else(|| "fuzz/corpus".to_string());
+    let save = |target: &str, name: &str, bytes: &[u8]| -> Result<(), FovError> {
+        let dir = Path::new(&out_dir).join(target);
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join(name), bytes)?;
+        Ok(())
+    };
+
+    // A small crop of each bench scene, and the view from its middle
+    let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default())?;
+    for scene in bench_scenes() {
+        let map = crop(&scene.map, 16, 12)?;
+        let origin = Coords::new(8, 6);
+        let grid = get_visible_grid(&fov_set, &map, origin, 8);
+
+        save("ascii_map", scene.name, ascii(&map).as_bytes())?;
+        save("scene_layout", scene.name, encode_layout(&map).as_bytes())?;
+        save("visibility_rle", scene.name, &rle_bytes(&grid)?)?;
+
+        let canvas = Renderer::new(2).render(&map, &grid, origin);
+        let (mut png, mut ppm) = (Vec::new(), Vec::new());
+        canvas.write_png(&mut png)?;
+        canvas.write_ppm(&mut ppm)?;
+        save("gray_image", &format!("{}.png", scene.name), &png)?;
+        save("gray_image", &format!("{}.ppm", scene.name), &ppm)?;
+
+        let mut pgm = format!("P5\n{} {}\n255\n", grid.width(), grid.height()).into_bytes();
+        pgm.extend(grid.to_image_mask());
+        save("gray_image", &format!("{}.pgm", scene.name), &pgm)?;
+
+        let case = test_case(scene.name, &map, origin, 8, &grid);
+        save("test_cases", scene.name, case.as_bytes())?;
+    }
+    save("ascii_map", "tagged", b"#####\n#.~.#\n#...\n#####\n")?;
+    save("visibility_rle", "empty", &rle_bytes(&VisibilityGrid::new(0, 0))?)?;
+
+    // Both Q factors, and shapes culling nodes inside the radius
+    let configs = [
+        ("r8_single", FovRadius::R8, QFactor::Single, FovShape::default()),
+        ("r8_culled", FovRadius::R8, QFactor::Single, FovShape::Circle { adjust: -0.5 }),
+        ("r16_double", FovRadius::R16, QFactor::Double, FovShape::default()),
+        ("r16_diamond", FovRadius::R16, QFactor::Single, FovShape::Diamond),
+    ];
+    for (name, rfov, qfactor, shape) in configs {
+        let fov_set = FovSetAny::new(Algorithm::Simple, rfov, qfactor, shape)?;
+        let mut bytes = Vec::new();
+        write_fov_set(&fov_set, &mut bytes)?;
+        save("fov_set_cache", name, &bytes)?;
+    }
+
+    println!("wrote seeds to {out_dir}");
+    Ok(())
+}
+
+/// Returns the bottom-left `width` by `height` tiles of `map`.
+fn crop(map: &TileMap, width: usize, height: usize) -> Result<TileMap, FovError> {
+    let mut out = TileMap::new(width, height)?;
+    for y in 0..height as i32 {
+        for x in 0..width as i32 {
+            out.set_body_opaque(Coords::new(x, y), map.body_opaque(Coords::new(x, y)));
+        }
+    }
+    Ok(out)
+}
+
+/// Returns `map` as a classic ASCII map, as read by `TileMap::from_ascii`.
+fn ascii(map: &TileMap) -> String {
+    (0..map.height() as i32)
+        .rev()
+        .map(|y| {
+            (0..map.width() as i32)
+                .map(|x| match map.body_opaque(Coords::new(x, y)) {
+                    true => '#',
+                    false => '.',
+                })
+                .collect::<String>()
+                + "\n"
+        })
+        .collect()
+}
+
+/// Returns a shared JSON test case document (see `cases`) expecting `grid` from `origin`.
+fn test_case(name: &str, map: &TileMap, origin: Coords, r: usize, grid: &VisibilityGrid) -> String {
+    let rows: Vec<String> = ascii(map).lines().map(|row| format!("\"{row}\"")).collect();
+    let visible: Vec<String> = grid.iter().map(|c| format!("[{}, {}]", c.x, c.y)).collect();
+    format!(
+        "{{\"version\": 1, \"cases\": [{{\"name\": \"{name}\", \"map\": [{}], \
+         \"origin\": [{}, {}], \"radius\": {r}, \"visible\": [{}]}}]}}\n",
+        rows.join(", "),
+        origin.x,
+        origin.y,
+        visible.join(", ")
+    )
+}
+
+/// Returns `grid` run-length encoded.
+fn rle_bytes(grid: &VisibilityGrid) -> Result<Vec<u8>, FovError> {
+    let mut bytes = Vec::new();
+    grid.write_rle(&mut bytes)?;
+    Ok(bytes)
+}
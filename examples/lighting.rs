@@ -0,0 +1,64 @@
+//! Example: torch lighting under low ambient light.
+//!
+//! Shades the observer's view by `get_light_levels`, so tiles too dark to make out drop from
+//! view, and writes the render to `OUT_DIR/lighting.png` (`OUT_DIR` defaults to
+//! `examples_out`).
+//!
+//! ```text
+//! cargo run --example lighting [OUT_DIR]
+//! ```
+
+use std::path::Path;
+
+use fov2d::drawing::{self, ImageFormat, Marker, Renderer, Rgba, Visibility};
+use fov2d::fov::{Falloff, Light};
+use fov2d::maps::{Coords, TileMap};
+use fov2d::simple::fovcalc::get_light_levels;
+use fov2d::simple::FovSet16;
+use fov2d::{FovError, FovRadius, FovShape, QFactor, VisibilityGrid};
+
+const SIZE: usize = 41;
+const RADIUS: usize = 16;
+
+fn main() -> Result<(), FovError> {
+    let out_dir = std::env::args().nth(1).unwrap_or_else(|| "examples_out".to_string());
+    std::fs::create_dir_all(&out_dir)?;
+
+    let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default())?;
+    let map = TileMap::random(SIZE, SIZE, 0.07, 3)?;
+    let origin = Coords::new(20, 20);
+    let torches = [Coords::new(30, 22), Coords::new(12, 10), Coords::new(24, 33)];
+    let lights: Vec<_> = torches
+        .iter()
+        .map(|&c| Light::new(c, 8, Falloff::Quadratic))
+        .collect();
+
+    let levels = get_light_levels(&fov_set, &map, origin, RADIUS, 0.2, &lights);
+    let mut grid = VisibilityGrid::for_map(&map);
+    for id in (0..levels.len()).filter(|&id| levels[id] > 0.0) {
+        grid.insert_id(id);
+    }
+
+    let mut renderer = Renderer::new(12);
+    renderer.legend = true;
+    renderer.caption = Some(format!("lighting: {} of {} tiles lit", grid.len(), SIZE * SIZE));
+    for torch in torches {
+        renderer.annotate(torch, Marker::Light);
+    }
+
+    // Darker tiles get a more opaque black overlay
+    let canvas = renderer.render_with(&map, &grid, origin, |coords, _, visibility| {
+        match (visibility, map.tile_id(coords)) {
+            (Visibility::Visible, Some(id)) => {
+                let shade = ((1.0 - levels[id]) * 200.0).round() as u8;
+                Rgba::new(0, 0, 0, shade)
+            }
+            _ => Rgba::TRANSPARENT,
+        }
+    });
+
+    let path = Path::new(&out_dir).join("lighting.png");
+    drawing::save(&path, &canvas, ImageFormat::Png)?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,35 @@
+//! Example: one report comparing supported _Simple_ FOV configurations on the same map.
+//!
+//! Writes renders, node dumps, metrics and a manifest to `OUT_DIR/report` (`OUT_DIR`
+//! defaults to `examples_out`).
+//!
+//! ```text
+//! cargo run --example report [OUT_DIR]
+//! ```
+
+use std::path::Path;
+
+use fov2d::maps::{Coords, TileMap};
+use fov2d::fov::{supported_configs, Algorithm};
+use fov2d::{FovError, Report, ReportConfig};
+
+fn main() -> Result<(), FovError> {
+    let out_dir = std::env::args().nth(1).unwrap_or_else(|| "examples_out".to_string());
+    let map = TileMap::random(65, 65, 0.06, 5)?;
+    let origin = Coords::new(32, 32);
+
+    // Supported Simple configs small enough to query within the map
+    let configs = supported_configs()
+        .filter(|c| c.algorithm == Algorithm::Simple && c.rfov.to_int() <= 32)
+        .map(|c| ReportConfig::new(&c.name(), c.rfov, c.qfactor, origin));
+    let written = Report::builder()
+        .map(&map)
+        .configs(configs)
+        .write(Path::new(&out_dir).join("report"))?;
+
+    for path in written {
+        println!("wrote {}", path.display());
+    }
+
+    Ok(())
+}
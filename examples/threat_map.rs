@@ -0,0 +1,68 @@
+//! Example: threat map of the tiles watched by a group of guards.
+//!
+//! Counts, for every tile of a generated cave, how many guards can see it, tints tiles by
+//! that count, and writes the render to `OUT_DIR/threat_map.png` (`OUT_DIR` defaults to
+//! `examples_out`). Unwatched floor is where the observer can move unseen.
+//!
+//! ```text
+//! cargo run --example threat_map [OUT_DIR]
+//! ```
+
+use std::path::Path;
+
+use fov2d::drawing::{self, ImageFormat, Marker, Renderer, Rgba};
+use fov2d::maps::Coords;
+use fov2d::simple::fovcalc::get_visible_grid;
+use fov2d::simple::FovSet16;
+use fov2d::{bench_scenes, FovError, FovRadius, FovShape, QFactor, VisibilityGrid};
+
+const RADIUS: usize = 12;
+const GUARDS: usize = 5;
+
+fn main() -> Result<(), FovError> {
+    let out_dir = std::env::args().nth(1).unwrap_or_else(|| "examples_out".to_string());
+    std::fs::create_dir_all(&out_dir)?;
+
+    let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default())?;
+    let scene = bench_scenes().into_iter().find(|s| s.name == "caves").expect("caves scene!");
+    let (map, observer) = (&scene.map, scene.origins[0]);
+    let guards: Vec<Coords> = scene.origins[1..=GUARDS].to_vec();
+
+    let mut threat = vec![0u8; map.width() * map.height()];
+    let mut watched = VisibilityGrid::for_map(map);
+    for &guard in guards.iter() {
+        let grid = get_visible_grid(&fov_set, map, guard, RADIUS);
+        for id in grid.iter_ids() {
+            threat[id] += 1;
+        }
+        watched.union_with(&grid);
+    }
+
+    let mut renderer = Renderer::new(10);
+    renderer.legend = true;
+    renderer.caption = Some(format!(
+        "threat map: {} guards watch {} tiles, observer {}",
+        guards.len(),
+        watched.len(),
+        match watched.contains(observer) {
+            true => "seen",
+            false => "hidden",
+        }
+    ));
+    for &guard in guards.iter() {
+        renderer.annotate(guard, Marker::Guard);
+    }
+
+    // Each guard watching a tile adds more red
+    let canvas = renderer.render_with(map, &watched, observer, |coords, _, _| {
+        match map.tile_id(coords).map(|id| threat[id]) {
+            Some(count) if count > 0 => Rgba::new(220, 30, 30, (60 * count as u32).min(220) as u8),
+            _ => Rgba::TRANSPARENT,
+        }
+    });
+
+    let path = Path::new(&out_dir).join("threat_map.png");
+    drawing::save(&path, &canvas, ImageFormat::Png)?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
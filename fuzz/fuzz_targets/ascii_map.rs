@@ -0,0 +1,23 @@
+//! Fuzzes `TileMap::from_ascii_with`.
+
+#![no_main]
+
+use fov2d::maps::{Glyphs, Tile, TileMap};
+use fov2d_fuzz::{CappedAlloc, MAX_INPUT};
+use libfuzzer_sys::fuzz_target;
+
+#[global_allocator]
+static ALLOC: CappedAlloc = CappedAlloc;
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() > MAX_INPUT {
+        return;
+    }
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    let glyphs = Glyphs::default().with('~', Tile { tag: 1, ..Tile::new() });
+    if let Ok(map) = TileMap::from_ascii_with(text, &glyphs) {
+        assert_eq!(map.stats().tiles, map.width() * map.height());
+    }
+});
@@ -0,0 +1,24 @@
+//! Fuzzes `cache::read_fov_set`, then queries every set it loads.
+
+#![no_main]
+
+use fov2d::cache::read_fov_set;
+use fov2d::maps::{Coords, TileMap};
+use fov2d::FovComputer;
+use fov2d_fuzz::{CappedAlloc, MAX_INPUT};
+use libfuzzer_sys::fuzz_target;
+
+#[global_allocator]
+static ALLOC: CappedAlloc = CappedAlloc;
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() > MAX_INPUT {
+        return;
+    }
+    let Ok(fov_set) = read_fov_set(data) else {
+        return;
+    };
+    let map = TileMap::random(33, 33, 0.1, 1950).unwrap();
+    let radius = fov_set.radius().to_int() as usize;
+    fov_set.visible_tiles(Coords::new(16, 16), radius, &map);
+});
@@ -0,0 +1,19 @@
+//! Fuzzes `GrayImage::decode` (PNG, PGM and PPM).
+
+#![no_main]
+
+use fov2d::image::GrayImage;
+use fov2d_fuzz::{CappedAlloc, MAX_INPUT};
+use libfuzzer_sys::fuzz_target;
+
+#[global_allocator]
+static ALLOC: CappedAlloc = CappedAlloc;
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() > MAX_INPUT {
+        return;
+    }
+    if let Ok(image) = GrayImage::decode(data) {
+        assert!(image.get(image.width() - 1, image.height() - 1).is_some());
+    }
+});
@@ -0,0 +1,22 @@
+//! Fuzzes `scenes::decode_layout`; layouts it decodes must round trip.
+
+#![no_main]
+
+use fov2d::scenes::{decode_layout, encode_layout};
+use fov2d_fuzz::{CappedAlloc, MAX_INPUT};
+use libfuzzer_sys::fuzz_target;
+
+#[global_allocator]
+static ALLOC: CappedAlloc = CappedAlloc;
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() > MAX_INPUT {
+        return;
+    }
+    let Ok(layout) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Some(map) = decode_layout(layout) {
+        assert_eq!(decode_layout(&encode_layout(&map)).as_ref(), Some(&map));
+    }
+});
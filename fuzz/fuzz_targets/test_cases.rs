@@ -0,0 +1,23 @@
+//! Fuzzes `TestCase::parse_json`; cases it parses must stay in bounds.
+
+#![no_main]
+
+use fov2d::cases::TestCase;
+use fov2d_fuzz::{CappedAlloc, MAX_INPUT};
+use libfuzzer_sys::fuzz_target;
+
+#[global_allocator]
+static ALLOC: CappedAlloc = CappedAlloc;
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() > MAX_INPUT {
+        return;
+    }
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    for case in TestCase::parse_json(text).into_iter().flatten() {
+        assert!(case.map.in_bounds(case.origin));
+        assert!(case.visible.iter().all(|&c| case.map.in_bounds(c)));
+    }
+});
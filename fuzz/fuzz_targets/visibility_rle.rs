@@ -0,0 +1,22 @@
+//! Fuzzes `VisibilityGrid::read_rle`; grids it loads must round trip.
+
+#![no_main]
+
+use fov2d::VisibilityGrid;
+use fov2d_fuzz::{CappedAlloc, MAX_INPUT};
+use libfuzzer_sys::fuzz_target;
+
+#[global_allocator]
+static ALLOC: CappedAlloc = CappedAlloc;
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() > MAX_INPUT {
+        return;
+    }
+    let Ok(grid) = VisibilityGrid::read_rle(data) else {
+        return;
+    };
+    let mut bytes = Vec::new();
+    grid.write_rle(&mut bytes).unwrap();
+    assert_eq!(VisibilityGrid::read_rle(&bytes[..]).unwrap(), grid);
+});
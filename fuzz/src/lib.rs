@@ -0,0 +1,46 @@
+//! Shared setup for the `fov2d` fuzz targets.
+//!
+//! Notes:
+//! - Every target installs `CappedAlloc`, so a single allocation over `MAX_ALLOC` aborts
+//!   the run as a crash instead of exhausting memory. The parsers bound their own
+//!   allocations below it (`2^24` tiles for ASCII maps, layouts and test cases, `2^30` bits
+//!   for visibility grids), so hitting the cap is a bug.
+//! - Inputs over `MAX_INPUT` bytes are skipped, which bounds what compressed PNG data can
+//!   inflate to.
+//! - Corpora are seeded from the matching writers: `cargo run --example fuzz_seeds`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+
+/// Largest single allocation a target may make, in bytes.
+pub const MAX_ALLOC: usize = 256 << 20;
+/// Largest input passed on to a parser, in bytes.
+pub const MAX_INPUT: usize = 64 << 10;
+
+/// System allocator that aborts on any allocation over `MAX_ALLOC` bytes.
+pub struct CappedAlloc;
+
+unsafe impl GlobalAlloc for CappedAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        check(layout.size());
+        System.alloc(layout)
+    }
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        check(layout.size());
+        System.alloc_zeroed(layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        check(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+/// Aborts if `size` is over `MAX_ALLOC`.
+fn check(size: usize) {
+    if size > MAX_ALLOC {
+        eprintln!("fuzz: allocation of {size} bytes is over the {MAX_ALLOC} byte cap!");
+        std::process::abort();
+    }
+}
@@ -0,0 +1,95 @@
+//! FOV Visualization - Rust (2D): benchmark over realistic dungeon scenes.
+//!
+//! Times _Simple_ FOV queries from every origin of each `bench_scenes` map, so runtime
+//! optimizations are judged on representative occlusion, and writes a CSV table of scene
+//! statistics (density, open fraction, wall edges), mean visible tiles and mean microseconds
+//! per query.
+//!
+//! Each scene is timed with a `FovSet16` (Q16) and a `FovSet128` (R64, Q128), at radius 16
+//! and at `NEAR_RADIUS`. Near queries visit only the nodes near the origin, so their time
+//! bounds what any optimization of near nodes alone (e.g. fewer bits per node) can save.
+//!
+//! Usage:
+//!
+//! ```text
+//! bench [OUT_CSV]
+//! ```
+//!
+//! `OUT_CSV` defaults to `bench.csv`. Build with `--release` for meaningful timings.
+
+use std::time::Instant;
+
+use fov2d::analysis::{Cell, Table};
+use fov2d::bench_scenes;
+use fov2d::files::save_csv;
+use fov2d::scenes::BenchScene;
+use fov2d::simple::fovcalc::get_visible_grid;
+use fov2d::simple::{FovBits, FovSet, FovSet128, FovSet16};
+use fov2d::{FovError, FovRadius, FovShape, QFactor};
+
+const RADIUS: usize = 16;
+const NEAR_RADIUS: usize = 4;
+const SHAPE: FovShape = FovShape::Circle { adjust: 0.5 };
+const ITERATIONS: u32 = 100;
+
+fn main() -> Result<(), FovError> {
+    let path = std::env::args().nth(1).unwrap_or_else(|| "bench.csv".to_string());
+    let q16 = FovSet16::new(FovRadius::R16, QFactor::Single, SHAPE)?;
+    let q128 = FovSet128::new(FovRadius::R64, QFactor::Double, SHAPE)?;
+    let columns = [
+        "scene", "q", "density", "open", "walls", "origins", "mean_visible", "micros",
+        "near_micros",
+    ];
+    let mut table = Table::new(&columns);
+
+    for scene in bench_scenes() {
+        let stats = scene.map.stats();
+        let timings = [(16, time_queries(&q16, &scene)), (128, time_queries(&q128, &scene))];
+
+        for (q, (visible, micros, near_micros)) in timings {
+            println!(
+                "{} (Q{q}): {micros:.2} us/query, {near_micros:.2} us/query at r {NEAR_RADIUS}",
+                scene.name
+            );
+            table.push_row(vec![
+                Cell::Text(scene.name.to_string()),
+                Cell::Int(q),
+                Cell::Float(stats.density()),
+                Cell::Float(stats.open_fraction()),
+                Cell::Int(stats.walls as i64),
+                Cell::Int(scene.origins.len() as i64),
+                Cell::Float(visible),
+                Cell::Float(micros),
+                Cell::Float(near_micros),
+            ]);
+        }
+    }
+
+    save_csv(&path, &table)?;
+    println!("wrote {path}");
+
+    Ok(())
+}
+
+/// Returns mean visible tiles at `RADIUS`, and mean microseconds per query at `RADIUS` and
+/// at `NEAR_RADIUS`, over every origin of `scene`.
+fn time_queries<B: FovBits>(fov_set: &FovSet<B>, scene: &BenchScene) -> (f64, f64, f64) {
+    let origins = scene.origins.len() as f64;
+    let visible: usize = scene
+        .origins
+        .iter()
+        .map(|&origin| get_visible_grid(fov_set, &scene.map, origin, RADIUS).len())
+        .sum();
+
+    let micros = |r: usize| {
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            for &origin in scene.origins.iter() {
+                std::hint::black_box(get_visible_grid(fov_set, &scene.map, origin, r));
+            }
+        }
+        start.elapsed().as_secs_f64() * 1e6 / (ITERATIONS as f64 * origins)
+    };
+
+    (visible as f64 / origins, micros(RADIUS), micros(NEAR_RADIUS))
+}
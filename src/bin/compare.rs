@@ -0,0 +1,138 @@
+//! FOV Visualization - Rust (2D): ruleset comparison harness.
+//!
+//! Runs _Simple_ FOV, Milazzo's beveled-wall FOV and libtcod-style raycasting over shared
+//! fixture maps, and writes side-by-side renders, diff renders against _Simple_ FOV, and a
+//! CSV table of visible counts, agreement and per-query timings.
+//!
+//! Usage:
+//!
+//! ```text
+//! compare [OUT_DIR]
+//! ```
+//!
+//! `OUT_DIR` defaults to `compare`. Images are written as PNG, tables as CSV.
+
+use std::path::Path;
+use std::time::Instant;
+
+use fov2d::analysis::{Cell, Table};
+use fov2d::compare::{agreement_table, Ruleset};
+use fov2d::drawing::{self, compose_grid, Canvas, ImageFormat, Renderer};
+use fov2d::files::save_csv;
+use fov2d::maps::{Coords, Prefab, Rotation, TileMap};
+use fov2d::simple::FovSet16;
+use fov2d::{FovError, FovRadius, FovShape, QFactor, VisibilityGrid};
+
+const SIZE: usize = 33;
+const RADIUS: usize = 16;
+const SHAPE: FovShape = FovShape::Circle { adjust: 0.5 };
+const TILE_PX: usize = 8;
+const ITERATIONS: u32 = 200;
+
+fn main() -> Result<(), FovError> {
+    let out_dir = std::env::args().nth(1).unwrap_or_else(|| "compare".to_string());
+    let out_dir = Path::new(&out_dir);
+    std::fs::create_dir_all(out_dir)?;
+
+    let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, SHAPE)?;
+    let origin = Coords::new(16, 16);
+    let fixtures = fixtures();
+
+    for (name, map) in fixtures.iter() {
+        let grids: Vec<_> = Ruleset::ALL
+            .iter()
+            .map(|ruleset| ruleset.visible_grid(&fov_set, map, origin, RADIUS))
+            .collect();
+
+        // Top row: each ruleset; bottom row: tiles where each ruleset differs from Simple
+        let mut canvases = Vec::new();
+        let mut labels = Vec::new();
+        for (ruleset, grid) in Ruleset::ALL.iter().zip(grids.iter()) {
+            canvases.push(render(map, grid, origin));
+            labels.push(ruleset.label().to_string());
+        }
+        for (ruleset, grid) in Ruleset::ALL.iter().zip(grids.iter()) {
+            let mut changed = VisibilityGrid::new(SIZE, SIZE);
+            for (coords, _) in grid.diff(&grids[0]) {
+                changed.insert(coords);
+            }
+            labels.push(format!("{} vs simple: {}", ruleset.label(), changed.len()));
+            canvases.push(render(map, &changed, origin));
+        }
+
+        let renders: Vec<_> = labels.iter().map(|l| l.as_str()).zip(canvases.iter()).collect();
+        let path = out_dir.join(format!("{name}.png"));
+        let canvas = compose_grid(&renders, Ruleset::ALL.len());
+        drawing::save(&path, &canvas, ImageFormat::Png)?;
+        println!("wrote {}", path.display());
+    }
+
+    // Agreement table, with mean microseconds per query appended
+    let table = agreement_table(&fov_set, &fixtures, origin, RADIUS);
+    let mut columns: Vec<_> = table.columns().iter().map(|c| c.as_str()).collect();
+    columns.push("micros");
+    let mut timed = Table::new(&columns);
+
+    for (row, (fixture, ruleset)) in table.rows().zip(cases(&fixtures)) {
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            std::hint::black_box(ruleset.visible_grid(&fov_set, fixture, origin, RADIUS));
+        }
+        let micros = start.elapsed().as_secs_f64() * 1e6 / ITERATIONS as f64;
+
+        let mut row = row.clone();
+        row.push(Cell::Float(micros));
+        timed.push_row(row);
+    }
+
+    let path = out_dir.join("comparison.csv");
+    save_csv(&path, &timed)?;
+    println!("wrote {}", path.display());
+
+    Ok(())
+}
+
+/// Named fixture maps shared by all rulesets.
+fn fixtures() -> Vec<(&'static str, TileMap)> {
+    let mut pillars = TileMap::new(SIZE, SIZE).unwrap();
+    for (x, y) in [(19, 16), (20, 20), (13, 19), (12, 12), (17, 11), (24, 14)] {
+        pillars.set_body_opaque(Coords::new(x, y), true);
+    }
+
+    // Walls touching only at their corners, where beveling matters most
+    let mut diagonal = TileMap::new(SIZE, SIZE).unwrap();
+    for i in 0..6 {
+        diagonal.set_body_opaque(Coords::new(18 + i, 20 + i), true);
+        diagonal.set_body_opaque(Coords::new(19 + i, 19 + i), true);
+    }
+
+    // Bunkers with slit doorways, stamped facing each of the four directions
+    let rows = ["#####", "#...#", "....#", "#...#", "#####"];
+    let bunker = Prefab::from_rows(&rows, Coords::new(2, 2)).unwrap();
+    let mut bunkers = TileMap::new(SIZE, SIZE).unwrap();
+    let sites = [(24, 16), (16, 24), (8, 16), (16, 8)];
+    for (rotation, (x, y)) in Rotation::ALL.into_iter().zip(sites) {
+        bunkers.stamp(&bunker, Coords::new(x, y), rotation);
+    }
+
+    vec![
+        ("open", TileMap::new(SIZE, SIZE).unwrap()),
+        ("pillars", pillars),
+        ("diagonal", diagonal),
+        ("bunkers", bunkers),
+        ("random_10", TileMap::random(SIZE, SIZE, 0.10, 11).unwrap()),
+        ("random_25", TileMap::random(SIZE, SIZE, 0.25, 25).unwrap()),
+    ]
+}
+
+/// Fixture and ruleset of each `agreement_table` row, in row order.
+fn cases<'a>(fixtures: &'a [(&str, TileMap)]) -> impl Iterator<Item = (&'a TileMap, Ruleset)> + 'a {
+    fixtures
+        .iter()
+        .flat_map(|(_, map)| Ruleset::ALL.iter().map(move |ruleset| (map, *ruleset)))
+}
+
+/// Renders `grid` over `map` without legend or caption; labels come from `compose_grid`.
+fn render(map: &TileMap, grid: &VisibilityGrid, origin: Coords) -> Canvas {
+    Renderer::new(TILE_PX).render(map, grid, origin)
+}
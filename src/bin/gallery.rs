@@ -0,0 +1,289 @@
+//! FOV Visualization - Rust (2D): example gallery generator.
+//!
+//! Runs a curated set of FOV scenarios and writes one labeled image per scenario, plus a
+//! combined overview and a vector octant diagram, so the full visual documentation set
+//! comes straight from the current code.
+//!
+//! Usage:
+//!
+//! ```text
+//! gallery [OUT_DIR]
+//! ```
+//!
+//! `OUT_DIR` defaults to `gallery`. Images are written as PNG, diagrams as PDF.
+
+use std::ops::ControlFlow;
+use std::path::Path;
+
+use fov2d::analysis::visible_outline;
+use fov2d::diagram::octant_diagram;
+use fov2d::drawing::{
+    self, compose_grid, config_caption, Canvas, ImageFormat, Marker, Minimap, Renderer, Rgb,
+    WaterReflection,
+};
+use fov2d::files::save_pdf;
+use fov2d::fov::{Falloff, Light, VisInfo};
+use fov2d::maps::{Coords, TileMap};
+use fov2d::simple::fovcalc::{get_light_levels, get_visible_grid, get_visible_grid_filtered};
+use fov2d::simple::FovSet16;
+use fov2d::{Became, FovError, FovRadius, FovShape, QFactor, VisibilityGrid};
+
+const SIZE: usize = 33;
+const RADIUS: usize = 16;
+const SHAPE: FovShape = FovShape::Circle { adjust: 0.5 };
+const TILE_PX: usize = 12;
+
+/// A single rendered gallery scenario.
+struct Scenario {
+    name: &'static str,
+    canvas: Canvas,
+}
+
+fn main() -> Result<(), FovError> {
+    let out_dir = std::env::args().nth(1).unwrap_or_else(|| "gallery".to_string());
+    let out_dir = Path::new(&out_dir);
+    std::fs::create_dir_all(out_dir)?;
+
+    let (rfov, qfactor) = (FovRadius::R16, QFactor::Single);
+    let fov_set = FovSet16::new(rfov, qfactor, SHAPE)?;
+    let config = config_caption("simple", rfov, qfactor, SHAPE);
+
+    let scenarios = [
+        pillars(&fov_set, &config),
+        corridors(&fov_set, &config),
+        cone(&fov_set, &config),
+        lighting(&fov_set, &config),
+        night(&fov_set, &config),
+        water(&fov_set, &config),
+        explore(&fov_set, &config),
+        diff(&fov_set, &config),
+        split(&fov_set),
+    ];
+
+    for (i, scenario) in scenarios.iter().enumerate() {
+        let path = out_dir.join(format!("{:02}_{}.png", i + 1, scenario.name));
+        drawing::save(&path, &scenario.canvas, ImageFormat::Png)?;
+        println!("wrote {}", path.display());
+    }
+
+    let renders: Vec<_> = scenarios.iter().map(|s| (s.name, &s.canvas)).collect();
+    let path = out_dir.join("overview.png");
+    drawing::save(&path, &compose_grid(&renders, 3), ImageFormat::Png)?;
+    println!("wrote {}", path.display());
+
+    let path = out_dir.join("octant.pdf");
+    save_pdf(&path, &octant_diagram(rfov, qfactor, SHAPE, 12.0))?;
+    println!("wrote {}", path.display());
+
+    Ok(())
+}
+
+/// Returns a `Renderer` with a legend and a scenario caption.
+fn renderer(caption: String) -> Renderer {
+    let mut renderer = Renderer::new(TILE_PX);
+    renderer.legend = true;
+    renderer.caption = Some(caption);
+    renderer
+}
+
+/// Scattered single-tile pillars casting shadows.
+fn pillars(fov_set: &FovSet16, config: &str) -> Scenario {
+    let origin = Coords::new(16, 16);
+    let mut map = TileMap::new(SIZE, SIZE).unwrap();
+    for (x, y) in [(19, 16), (20, 20), (13, 19), (12, 12), (17, 11), (24, 14)] {
+        map.set_body_opaque(Coords::new(x, y), true);
+    }
+
+    let grid = get_visible_grid(fov_set, &map, origin, RADIUS);
+    let canvas = renderer(format!("pillars: {config}")).render(&map, &grid, origin);
+    Scenario { name: "pillars", canvas }
+}
+
+/// Rooms joined by corridors: FOV leaks only through the doorways.
+fn corridors(fov_set: &FovSet16, config: &str) -> Scenario {
+    let origin = Coords::new(8, 16);
+    let mut map = TileMap::new(SIZE, SIZE).unwrap();
+    for i in 0..SIZE as i32 {
+        for x in [14, 18] {
+            if !(15..=17).contains(&i) {
+                map.set_body_opaque(Coords::new(x, i), true);
+            }
+        }
+        if !(14..=18).contains(&i) {
+            map.set_body_opaque(Coords::new(i, 14), true);
+            map.set_body_opaque(Coords::new(i, 18), true);
+        }
+    }
+
+    let grid = get_visible_grid(fov_set, &map, origin, RADIUS);
+    let canvas = renderer(format!("corridors: {config}")).render(&map, &grid, origin);
+    Scenario { name: "corridors", canvas }
+}
+
+/// 90-degree vision cone facing east, via a `VisibilityFilter`, with its outline traced.
+fn cone(fov_set: &FovSet16, config: &str) -> Scenario {
+    let origin = Coords::new(8, 16);
+    let map = TileMap::random(SIZE, SIZE, 0.08, 42).unwrap();
+
+    let mut facing_east = |c: Coords, _: &VisInfo| (c.y - origin.y).abs() <= c.x - origin.x;
+    let grid = get_visible_grid_filtered(fov_set, &map, origin, RADIUS, &mut facing_east);
+    let renderer = renderer(format!("cone (east, 90 deg, outlined): {config}"));
+    let mut canvas = renderer.render(&map, &grid, origin);
+    renderer.draw_outline(&mut canvas, &map, &visible_outline(&grid), Marker::Target.color());
+    Scenario { name: "cone", canvas }
+}
+
+/// Torch lighting: visible tiles shaded by a quadratic `Falloff`.
+fn lighting(fov_set: &FovSet16, config: &str) -> Scenario {
+    let origin = Coords::new(16, 16);
+    let map = TileMap::random(SIZE, SIZE, 0.08, 7).unwrap();
+    let falloff = Falloff::Quadratic;
+
+    let mut brightness = vec![0.0; SIZE * SIZE];
+    let _ = fov_set.compute_with(&map, origin, RADIUS, |coords, info| {
+        if let Some(id) = map.tile_id(coords) {
+            brightness[id] = falloff.brightness((info.dist_sq as f64).sqrt(), RADIUS as f64);
+        }
+        ControlFlow::<()>::Continue(())
+    });
+
+    let grid = get_visible_grid(fov_set, &map, origin, RADIUS);
+    let mut renderer = renderer(format!("lighting (quadratic): {config}"));
+    renderer.annotate(origin, Marker::Light);
+    let mut canvas = renderer.render(&map, &grid, origin);
+    shade(&mut canvas, &renderer, &grid, &brightness);
+
+    Scenario { name: "lighting", canvas }
+}
+
+/// Night scene: low ambient light shortens sight, except where torches light the way.
+fn night(fov_set: &FovSet16, config: &str) -> Scenario {
+    let origin = Coords::new(8, 16);
+    let map = TileMap::random(SIZE, SIZE, 0.06, 11).unwrap();
+    let ambient = 0.25;
+    let torches = [Coords::new(20, 16), Coords::new(14, 24)];
+    let lights: Vec<_> = torches
+        .iter()
+        .map(|&c| Light::new(c, 6, Falloff::Quadratic))
+        .collect();
+
+    let levels = get_light_levels(fov_set, &map, origin, RADIUS, ambient, &lights);
+    let mut grid = VisibilityGrid::for_map(&map);
+    for id in (0..levels.len()).filter(|&id| levels[id] > 0.0) {
+        grid.insert_id(id);
+    }
+
+    let mut renderer = renderer(format!("night (ambient {ambient:.2}): {config}"));
+    for torch in torches {
+        renderer.annotate(torch, Marker::Light);
+    }
+    let mut canvas = renderer.render(&map, &grid, origin);
+    shade(&mut canvas, &renderer, &grid, &levels);
+
+    Scenario { name: "night", canvas }
+}
+
+/// Pond below a row of pillars: visible water mirrors the visible tiles north of it.
+fn water(fov_set: &FovSet16, config: &str) -> Scenario {
+    const WATER: u8 = 1;
+    let origin = Coords::new(16, 20);
+    let mut map = TileMap::new(SIZE, SIZE).unwrap();
+    for x in (8..=24).step_by(4) {
+        map.set_body_opaque(Coords::new(x, 14), true);
+    }
+    for y in 6..=13 {
+        for x in 6..=26 {
+            map.get_mut(Coords::new(x, y)).unwrap().tag = WATER;
+        }
+    }
+
+    let grid = get_visible_grid(fov_set, &map, origin, RADIUS);
+    let renderer = renderer(format!("water reflections: {config}"));
+    let reflection = WaterReflection::new(WATER);
+    let layer = reflection.layer(&renderer, &map, &grid);
+    let canvas = renderer.render_with(&map, &grid, origin, layer);
+    Scenario { name: "water", canvas }
+}
+
+/// Walk through rooms, with a minimap of explored memory and current FOV in the corner.
+fn explore(fov_set: &FovSet16, config: &str) -> Scenario {
+    let map = TileMap::random(SIZE, SIZE, 0.12, 23).unwrap();
+    let path: Vec<_> = (4..=28).step_by(4).map(|x| Coords::new(x, 8 + x / 2)).collect();
+    let mut explored = VisibilityGrid::for_map(&map);
+    let mut grid = VisibilityGrid::for_map(&map);
+    for &step in path.iter() {
+        grid = get_visible_grid(fov_set, &map, step, RADIUS / 2);
+        explored.union_with(&grid);
+    }
+
+    let observer = path[path.len() - 1];
+    let renderer = renderer(format!("explore (minimap inset): {config}"));
+    let mut canvas = renderer.render(&map, &grid, observer);
+    Minimap::default().render_inset(&mut canvas, &map, &explored, &grid, observer);
+    Scenario { name: "explore", canvas }
+}
+
+/// Darkens each visible tile's pixels by its `brightness`; the footer is left untouched.
+fn shade(canvas: &mut Canvas, renderer: &Renderer, grid: &VisibilityGrid, brightness: &[f64]) {
+    let floor = renderer.palette.floor;
+    for id in grid.iter_ids() {
+        let Some(Coords { x, y }) = Coords::from_index(id, SIZE) else {
+            continue;
+        };
+        let (left, top) = (x * TILE_PX as i32, (SIZE as i32 - 1 - y) * TILE_PX as i32);
+        for py in top..top + TILE_PX as i32 {
+            for px in left..left + TILE_PX as i32 {
+                if let Some(color) = canvas.get(px, py) {
+                    canvas.set(px, py, blend(floor, color, brightness[id]));
+                }
+            }
+        }
+    }
+}
+
+/// Visibility changes after the observer steps past a wall.
+fn diff(fov_set: &FovSet16, config: &str) -> Scenario {
+    let (before, after) = (Coords::new(10, 16), Coords::new(14, 16));
+    let mut map = TileMap::new(SIZE, SIZE).unwrap();
+    for y in 8..=24 {
+        map.set_body_opaque(Coords::new(12, y), true);
+    }
+    map.set_body_opaque(Coords::new(12, 16), false);
+
+    let old = get_visible_grid(fov_set, &map, before, RADIUS);
+    let new = get_visible_grid(fov_set, &map, after, RADIUS);
+    let mut changed = VisibilityGrid::new(SIZE, SIZE);
+    let (mut shown, mut hidden) = (0, 0);
+    for (coords, became) in new.diff(&old) {
+        changed.insert(coords);
+        match became {
+            Became::Visible => shown += 1,
+            Became::Hidden => hidden += 1,
+        }
+    }
+
+    let caption = format!("diff: {shown} became visible, {hidden} hidden: {config}");
+    let mut renderer = renderer(caption);
+    renderer.annotate(before, Marker::Target);
+    let canvas = renderer.render(&map, &changed, after);
+    Scenario { name: "diff", canvas }
+}
+
+/// Split screen of two observers, where one sees the other but is not seen back.
+fn split(fov_set: &FovSet16) -> Scenario {
+    let map = TileMap::random(SIZE, SIZE, 0.12, 7).unwrap();
+    let (a, b) = (Coords::new(16, 16), Coords::new(20, 6));
+    let (seen_by_a, seen_by_b) = (
+        get_visible_grid(fov_set, &map, a, RADIUS),
+        get_visible_grid(fov_set, &map, b, RADIUS),
+    );
+
+    let canvas = Renderer::new(TILE_PX).render_split(&map, &[(a, &seen_by_a), (b, &seen_by_b)]);
+    Scenario { name: "split", canvas }
+}
+
+/// Linearly blends color `a` into `b` by `t` (`0.0` is `a`, `1.0` is `b`).
+fn blend(a: Rgb, b: Rgb, t: f64) -> Rgb {
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+    Rgb::new(mix(a.r, b.r), mix(a.g, b.g), mix(a.b, b.b))
+}
@@ -0,0 +1,122 @@
+//! FOV Visualization - Rust (2D): live map editing.
+//!
+//! Loads a map image (see `files::load_map_from_image`), renders _Simple_ FOV from an
+//! origin to an image, then watches the map file and reloads and re-renders it whenever
+//! it changes. Edit the map in any paint program, keep the output open in an image viewer
+//! that refreshes on change, and the FOV updates on every save.
+//!
+//! Usage:
+//!
+//! ```text
+//! watch MAP_IMAGE [OUT_PNG] [X Y]
+//! ```
+//!
+//! `OUT_PNG` defaults to `watch.png`, and the origin to the center of the map. Pixels
+//! darker than mid-gray are walls. Build with `--features watch`.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use fov2d::drawing::{self, config_caption, ImageFormat, Renderer};
+use fov2d::files::load_map_from_image;
+use fov2d::maps::Coords;
+use fov2d::simple::fovcalc::get_visible_grid;
+use fov2d::simple::FovSet16;
+use fov2d::{FovError, FovRadius, FovShape, QFactor};
+
+const RADIUS: usize = 16;
+const SHAPE: FovShape = FovShape::Circle { adjust: 0.5 };
+const TILE_PX: usize = 8;
+const THRESHOLD: u8 = 128;
+/// Quiet period after a change before reloading, so an editor's burst of writes (or a
+/// write-then-rename save) triggers one reload of the finished file.
+const DEBOUNCE: Duration = Duration::from_millis(150);
+
+fn main() -> Result<(), FovError> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some(map_path) = args.first().map(PathBuf::from) else {
+        eprintln!("usage: watch MAP_IMAGE [OUT_PNG] [X Y]");
+        std::process::exit(2);
+    };
+    let out_path = PathBuf::from(args.get(1).map_or("watch.png", |s| s.as_str()));
+    let origin = match (args.get(2), args.get(3)) {
+        (Some(x), Some(y)) => Some(Coords::new(parse(x)?, parse(y)?)),
+        _ => None,
+    };
+
+    let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, SHAPE)?;
+    render(&fov_set, &map_path, &out_path, origin)?;
+
+    // Watch the parent directory: editors often save by replacing the file, which drops
+    // a watch on the file itself.
+    let dir = match map_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(io::Error::other)?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive).map_err(io::Error::other)?;
+    println!("watching {} (ctrl-c to quit)", map_path.display());
+
+    for event in rx.iter() {
+        // Access events are skipped: loading the map would otherwise trigger another reload
+        let touches_map = match event {
+            Ok(event) if matches!(event.kind, EventKind::Access(_)) => false,
+            Ok(event) => event.paths.iter().any(|p| p.file_name() == map_path.file_name()),
+            Err(e) => {
+                eprintln!("watch error: {e}");
+                false
+            }
+        };
+        if !touches_map {
+            continue;
+        }
+        while rx.recv_timeout(DEBOUNCE).is_ok() {}
+
+        // A failed load (e.g. a half-written file) keeps the last render; the next save retries
+        if let Err(e) = render(&fov_set, &map_path, &out_path, origin) {
+            eprintln!("reload failed: {e}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads the map at `map_path`, and renders FOV from `origin` (or its center) to `out_path`.
+fn render(
+    fov_set: &FovSet16,
+    map_path: &Path,
+    out_path: &Path,
+    origin: Option<Coords>,
+) -> Result<(), FovError> {
+    let map = load_map_from_image(map_path, THRESHOLD)?;
+    let center = Coords::new(map.width() as i32 / 2, map.height() as i32 / 2);
+    let origin = origin.filter(|&c| map.in_bounds(c)).unwrap_or(center);
+
+    let grid = get_visible_grid(fov_set, &map, origin, RADIUS);
+    let mut renderer = Renderer::new(TILE_PX);
+    renderer.legend = true;
+    let caption = config_caption("simple", fov_set.radius(), QFactor::Single, SHAPE);
+    renderer.caption = Some(caption);
+    drawing::save(out_path, &renderer.render(&map, &grid, origin), ImageFormat::Png)?;
+
+    println!(
+        "rendered {} ({}x{}, {} visible from {},{})",
+        out_path.display(),
+        map.width(),
+        map.height(),
+        grid.len(),
+        origin.x,
+        origin.y
+    );
+    Ok(())
+}
+
+/// Parses a tile coordinate argument.
+fn parse(arg: &str) -> io::Result<i32> {
+    arg.parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, arg.to_string()))
+}
@@ -0,0 +1,150 @@
+//! bracket-lib style FOV calls, for maps written against bracket-lib's FOV traits.
+//!
+//! Notes:
+//! - bracket-lib's `field_of_view` reads a map through `BaseMap::is_opaque(idx)` and
+//!   `Algorithm2D::dimensions()`. `BracketMap` mirrors just those two calls, so this crate
+//!   needs no bracket-lib dependency. It is not implemented for bracket-lib maps: implement
+//!   it by forwarding to the bracket-lib traits, as below.
+//! - `BracketAdapter` wraps a `BracketMap` as an `OpacityProvider`, so it works with every
+//!   query in this crate.
+//! - `field_of_view` and `field_of_view_set` return visible tiles in the shapes bracket-lib
+//!   returns (a `Vec` and a `HashSet` of points), as `Coords` (`Point::new(c.x, c.y)`).
+//! - Tile indexes are `y * width + x`, as bracket-lib's default `point2d_to_index`.
+//!
+//! Bridging a bracket-lib map:
+//!
+//! ```text
+//! impl fov2d::bracket::BracketMap for Map {
+//!     fn is_opaque(&self, idx: usize) -> bool {
+//!         BaseMap::is_opaque(self, idx)
+//!     }
+//!     fn dimensions(&self) -> (i32, i32) {
+//!         let p = Algorithm2D::dimensions(self);
+//!         (p.x, p.y)
+//!     }
+//! }
+//! ```
+
+use std::collections::HashSet;
+
+use crate::{
+    maps::{Coords, OpacityProvider},
+    simple::{fovcalc, FovSet16},
+};
+
+/// The parts of bracket-lib's `BaseMap` and `Algorithm2D` used for FOV.
+pub trait BracketMap {
+    /// Returns `true` if the tile at index `idx` blocks sight (`BaseMap::is_opaque`).
+    fn is_opaque(&self, idx: usize) -> bool;
+    /// Returns the map's `(width, height)` in tiles (`Algorithm2D::dimensions`).
+    fn dimensions(&self) -> (i32, i32);
+}
+
+/// Wraps a `BracketMap` as an `OpacityProvider`.
+#[derive(Debug, Clone, Copy)]
+pub struct BracketAdapter<'a, M: ?Sized> {
+    map: &'a M,
+}
+
+impl<'a, M: BracketMap + ?Sized> BracketAdapter<'a, M> {
+    /// Creates a new `BracketAdapter` over `map`.
+    pub fn new(map: &'a M) -> Self {
+        Self { map }
+    }
+    /// Returns the wrapped map.
+    pub fn map(&self) -> &'a M {
+        self.map
+    }
+}
+
+impl<M: BracketMap + ?Sized> OpacityProvider for BracketAdapter<'_, M> {
+    fn width(&self) -> usize {
+        self.map.dimensions().0.max(0) as usize
+    }
+    fn height(&self) -> usize {
+        self.map.dimensions().1.max(0) as usize
+    }
+    fn body_opaque(&self, coords: Coords) -> bool {
+        self.tile_id(coords).is_some_and(|idx| self.map.is_opaque(idx))
+    }
+}
+
+/// Returns tiles visible from `start` out to `range`, in tile index order.
+///
+/// Matches bracket-lib's `field_of_view`: `start` is included and out of bounds tiles are
+/// not. `range` is clamped to `0..=16`, the radius of `fov_set`.
+pub fn field_of_view<M: BracketMap + ?Sized>(
+    fov_set: &FovSet16,
+    start: Coords,
+    range: i32,
+    map: &M,
+) -> Vec<Coords> {
+    let map = BracketAdapter::new(map);
+    let grid = fovcalc::get_visible_grid(fov_set, &map, start, range.max(0) as usize);
+    grid.iter().collect()
+}
+
+/// Returns tiles visible from `start` out to `range`, as bracket-lib's `field_of_view_set`.
+pub fn field_of_view_set<M: BracketMap + ?Sized>(
+    fov_set: &FovSet16,
+    start: Coords,
+    range: i32,
+    map: &M,
+) -> HashSet<Coords> {
+    field_of_view(fov_set, start, range, map).into_iter().collect()
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maps::TileMap;
+    use crate::{FovRadius, FovShape, QFactor};
+
+    /// Minimal bracket-lib style map: one opacity flag per tile index.
+    struct Walls {
+        width: i32,
+        opaque: Vec<bool>,
+    }
+
+    impl BracketMap for Walls {
+        fn is_opaque(&self, idx: usize) -> bool {
+            self.opaque[idx]
+        }
+        fn dimensions(&self) -> (i32, i32) {
+            (self.width, self.opaque.len() as i32 / self.width)
+        }
+    }
+
+    // Adapted maps see exactly what an equivalent `TileMap` sees.
+    #[test]
+    fn field_of_view_matches_tile_map() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let mut tile_map = TileMap::new(24, 20).unwrap();
+        let mut walls = Walls {
+            width: 24,
+            opaque: vec![false; 24 * 20],
+        };
+        for (x, y) in [(14, 10), (9, 13), (12, 6), (12, 7)] {
+            tile_map.set_body_opaque(Coords::new(x, y), true);
+            walls.opaque[(y * 24 + x) as usize] = true;
+        }
+
+        let start = Coords::new(12, 10);
+        let expected = fovcalc::get_visible_grid(&fov_set, &tile_map, start, 8);
+        let tiles = field_of_view(&fov_set, start, 8, &walls);
+        assert_eq!(tiles, expected.iter().collect::<Vec<_>>());
+        assert!(tiles.contains(&start) && !tiles.contains(&Coords::new(16, 10)));
+        assert_eq!(field_of_view_set(&fov_set, start, 8, &walls).len(), tiles.len());
+
+        let map = BracketAdapter::new(&walls);
+        assert!(map.body_opaque(Coords::new(9, 13)));
+        assert!(!map.body_opaque(Coords::new(24, 0)));
+        assert_eq!(field_of_view(&fov_set, start, -3, &walls), vec![start]);
+    }
+}
@@ -0,0 +1,623 @@
+//! Map analysis for FOV Visualization - Rust (2D).
+//!
+//! Notes:
+//! - _Rooms_ are 4-connected regions of tiles with transparent bodies.
+//! - Movement between neighboring tiles is blocked by an opaque `wall_n` or `wall_w`.
+//! - _Choke points_ are doorway or corridor tiles whose removal splits their room in two.
+//! - Analysis results are exported as a `Table`, written as CSV or JSON.
+//! - `circle_adjust_sweep` tabulates FOV circle fit per `FovShape::Circle` `adjust`, to tune
+//!   the culling adjustment.
+//! - `visible_outline` traces the boundary of the visible area as polygons of tile corners,
+//!   for crisp FOV outlines or collision shapes of the lit area.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use super::error::FovError;
+use super::maps::{Coords, TileMap};
+use super::visibility::VisibilityGrid;
+use crate::simple::{fovcalc::get_visible_grid, FovSet16};
+use crate::{FovRadius, FovShape, QFactor};
+
+/// Single value in an analysis `Table`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl Cell {
+    /// Formats the cell as a CSV field, quoting text where required.
+    fn to_csv(&self) -> String {
+        match self {
+            Cell::Int(v) => v.to_string(),
+            Cell::Float(v) => v.to_string(),
+            Cell::Text(v) if v.contains([',', '"', '\n', '\r']) => {
+                format!("\"{}\"", v.replace('"', "\"\""))
+            }
+            Cell::Text(v) => v.clone(),
+        }
+    }
+    /// Formats the cell as a JSON value. Non-finite floats become `null`.
+    fn to_json(&self) -> String {
+        match self {
+            Cell::Int(v) => v.to_string(),
+            Cell::Float(v) if v.is_finite() => v.to_string(),
+            Cell::Float(_) => "null".to_string(),
+            Cell::Text(v) => json_string(v),
+        }
+    }
+}
+
+impl From<i64> for Cell {
+    fn from(v: i64) -> Self {
+        Cell::Int(v)
+    }
+}
+
+impl From<usize> for Cell {
+    fn from(v: usize) -> Self {
+        Cell::Int(v as i64)
+    }
+}
+
+impl From<f64> for Cell {
+    fn from(v: f64) -> Self {
+        Cell::Float(v)
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(v: &str) -> Self {
+        Cell::Text(v.to_string())
+    }
+}
+
+impl From<String> for Cell {
+    fn from(v: String) -> Self {
+        Cell::Text(v)
+    }
+}
+
+/// Tabular analysis results with named columns, exportable as CSV or JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    columns: Vec<String>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    /// Creates a new, empty `Table` with the given column names.
+    pub fn new(columns: &[&str]) -> Self {
+        Self {
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+    /// Appends a row. Panics if the row length does not match the column count.
+    pub fn push_row(&mut self, row: Vec<Cell>) {
+        assert_eq!(row.len(), self.columns.len(), "row length must match column count!");
+        self.rows.push(row);
+    }
+    /// Returns the column names.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+    /// Returns an iterator over the rows of the table.
+    pub fn rows(&self) -> std::slice::Iter<'_, Vec<Cell>> {
+        self.rows.iter()
+    }
+    /// Returns the number of rows.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+    /// Returns `true` if the table has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+    /// Writes the table as CSV with a header row.
+    pub fn write_csv<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let header: Vec<_> = self.columns.iter().map(|c| Cell::Text(c.clone()).to_csv()).collect();
+        writeln!(w, "{}", header.join(","))?;
+
+        for row in self.rows.iter() {
+            let fields: Vec<_> = row.iter().map(Cell::to_csv).collect();
+            writeln!(w, "{}", fields.join(","))?;
+        }
+
+        Ok(())
+    }
+    /// Writes the table as a JSON array of objects keyed by column name.
+    pub fn write_json<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let mut out = String::from("[");
+
+        for (i, row) in self.rows.iter().enumerate() {
+            out.push_str(if i == 0 { "\n  {" } else { ",\n  {" });
+            for (j, (col, cell)) in self.columns.iter().zip(row.iter()).enumerate() {
+                let sep = if j == 0 { "" } else { ", " };
+                let _ = write!(out, "{sep}{}: {}", json_string(col), cell.to_json());
+            }
+            out.push('}');
+        }
+
+        out.push_str(if self.rows.is_empty() { "]\n" } else { "\n]\n" });
+        w.write_all(out.as_bytes())
+    }
+}
+
+/// Formats `s` as a quoted, escaped JSON string.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+/// Room labels for every tile of a `TileMap`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoomMap {
+    width: usize,
+    height: usize,
+    labels: Vec<Option<usize>>,
+    sizes: Vec<usize>,
+}
+
+impl RoomMap {
+    /// Creates a new `RoomMap` by labeling the connected rooms of `map`.
+    ///
+    /// Rooms are numbered from `0` in tile ID order of their first tile.
+    pub fn new(map: &TileMap) -> Self {
+        let mut labels = vec![None; map.width() * map.height()];
+        let mut sizes = Vec::new();
+        let mut stack = Vec::new();
+
+        for start in 0..labels.len() {
+            if labels[start].is_some() || !is_open(map, start) {
+                continue;
+            }
+            let room = sizes.len();
+            let mut size = 0;
+            labels[start] = Some(room);
+            stack.push(start);
+
+            while let Some(id) = stack.pop() {
+                size += 1;
+                for next in neighbors(map, id).into_iter().flatten() {
+                    if labels[next].is_none() {
+                        labels[next] = Some(room);
+                        stack.push(next);
+                    }
+                }
+            }
+
+            sizes.push(size);
+        }
+
+        Self {
+            width: map.width(),
+            height: map.height(),
+            labels,
+            sizes,
+        }
+    }
+    /// Returns the number of rooms.
+    pub fn len(&self) -> usize {
+        self.sizes.len()
+    }
+    /// Returns `true` if the map has no rooms (no open tiles).
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+    /// Returns the room containing the tile at `coords`, or `None` for opaque or out of bounds tiles.
+    pub fn room_at(&self, coords: Coords) -> Option<usize> {
+        let in_bounds = coords.x >= 0
+            && coords.y >= 0
+            && (coords.x as usize) < self.width
+            && (coords.y as usize) < self.height;
+
+        match in_bounds {
+            true => self.labels[coords.to_index(self.width)?],
+            false => None,
+        }
+    }
+    /// Returns the number of tiles in `room`.
+    pub fn room_size(&self, room: usize) -> usize {
+        self.sizes[room]
+    }
+    /// Returns an iterator over the coordinates of all tiles in `room`.
+    pub fn tiles(&self, room: usize) -> impl Iterator<Item = Coords> + '_ {
+        self.labels
+            .iter()
+            .enumerate()
+            .filter(move |(_, label)| **label == Some(room))
+            .filter_map(|(id, _)| Coords::from_index(id, self.width))
+    }
+    /// Returns the fraction (`0.0` to `1.0`) of each room's tiles visible in `grid`.
+    pub fn coverage(&self, grid: &VisibilityGrid) -> Vec<f64> {
+        let mut seen = vec![0usize; self.len()];
+
+        for id in grid.iter_ids() {
+            if let Some(room) = self.labels[id] {
+                seen[room] += 1;
+            }
+        }
+
+        seen.iter()
+            .zip(self.sizes.iter())
+            .map(|(&seen, &size)| seen as f64 / size as f64)
+            .collect()
+    }
+    /// Returns a `Table` of per-room coverage (`room`, `tiles`, `visible`, `coverage`).
+    pub fn coverage_table(&self, grid: &VisibilityGrid) -> Table {
+        let mut table = Table::new(&["room", "tiles", "visible", "coverage"]);
+
+        for (room, coverage) in self.coverage(grid).into_iter().enumerate() {
+            let size = self.sizes[room];
+            let visible = (coverage * size as f64).round() as usize;
+            table.push_row(vec![room.into(), size.into(), visible.into(), coverage.into()]);
+        }
+
+        table
+    }
+}
+
+/// Returns the choke points of `map` in tile ID order.
+///
+/// A choke point is an articulation point of the room graph (a tile whose
+/// removal disconnects its room) that is also enclosed on opposite sides,
+/// such as a doorway or a corridor tile.
+pub fn choke_points(map: &TileMap) -> Vec<Coords> {
+    const UNSEEN: usize = usize::MAX;
+
+    let n = map.width() * map.height();
+    let mut disc = vec![UNSEEN; n];
+    let mut low = vec![0; n];
+    let mut is_choke = vec![false; n];
+    let mut time = 0;
+    // Depth-first stack of (tile, parent, next neighbor to visit)
+    let mut stack: Vec<(usize, usize, usize)> = Vec::new();
+
+    for root in 0..n {
+        if disc[root] != UNSEEN || !is_open(map, root) {
+            continue;
+        }
+        let mut root_children = 0;
+        disc[root] = time;
+        low[root] = time;
+        time += 1;
+        stack.push((root, UNSEEN, 0));
+
+        while let Some(&(v, parent, k)) = stack.last() {
+            if k < 4 {
+                stack.last_mut().unwrap().2 += 1;
+                let Some(w) = neighbors(map, v)[k] else {
+                    continue;
+                };
+                if disc[w] == UNSEEN {
+                    disc[w] = time;
+                    low[w] = time;
+                    time += 1;
+                    root_children += (v == root) as usize;
+                    stack.push((w, v, 0));
+                } else if w != parent {
+                    low[v] = low[v].min(disc[w]);
+                }
+            } else {
+                stack.pop();
+                if let Some(&(u, _, _)) = stack.last() {
+                    low[u] = low[u].min(low[v]);
+                    if u != root && low[v] >= disc[u] {
+                        is_choke[u] = true;
+                    }
+                }
+            }
+        }
+
+        is_choke[root] = root_children > 1;
+    }
+
+    is_choke
+        .iter()
+        .enumerate()
+        .filter(|&(id, choke)| *choke && is_enclosed(map, id))
+        .filter_map(|(id, _)| Coords::from_index(id, map.width()))
+        .collect()
+}
+
+/// Sweeps circular culling adjustments `adjusts` and tabulates FOV circle fit on an open map.
+///
+/// Each `adjust` builds a _Simple_ R16 FOV set with `FovShape::Circle { adjust }`, queried at
+/// every radius `1..=16` from the center of an open map. Columns:
+/// - `adjust`, `radius`: the configuration.
+/// - `visible`: number of visible tiles.
+/// - `ideal`: area of a circle of that radius (`pi * r^2`), in tiles.
+/// - `error`: `visible - ideal`; `rel_error`: `error / ideal`.
+///
+/// Culling only applies at the full radius, so the `radius = 16` rows decide `adjust`.
+/// Returns an error for a NaN or infinite `adjust`.
+pub fn circle_adjust_sweep(adjusts: &[f64]) -> Result<Table, FovError> {
+    let mut table = Table::new(&["adjust", "radius", "visible", "ideal", "error", "rel_error"]);
+    let r_max = FovRadius::R16.to_int() as usize;
+    let size = 2 * r_max + 1;
+    let map = TileMap::new(size, size)?;
+    let origin = Coords::new(r_max as i32, r_max as i32);
+
+    for &adjust in adjusts {
+        let shape = FovShape::Circle { adjust };
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, shape)?;
+        for r in 1..=r_max {
+            let visible = get_visible_grid(&fov_set, &map, origin, r).len();
+            let ideal = std::f64::consts::PI * (r * r) as f64;
+            let error = visible as f64 - ideal;
+            table.push_row(vec![
+                adjust.into(),
+                r.into(),
+                visible.into(),
+                ideal.into(),
+                error.into(),
+                (error / ideal).into(),
+            ]);
+        }
+    }
+
+    Ok(table)
+}
+
+/// Returns the boundary contours of the visible tiles in `grid`, as closed polygons.
+///
+/// Each contour lists the tile corners where the boundary turns, without repeating the first;
+/// corner `(x, y)` is the south-west corner of tile `(x, y)`. Outer boundaries run
+/// counter-clockwise (`+y` north) and holes clockwise, so the visible area is always on the
+/// left. Tiles touching only at a corner get separate contours, so no contour touches
+/// itself. Contours are ordered by their first corner, the lowest then leftmost.
+pub fn visible_outline(grid: &VisibilityGrid) -> Vec<Vec<Coords>> {
+    // Boundary edges between visible and hidden tiles, keyed by start corner as `(y, x)`
+    let mut edges: BTreeMap<(i32, i32), Vec<Coords>> = BTreeMap::new();
+    for Coords { x, y } in grid.iter() {
+        let sides = [
+            ((0, -1), (x, y), (x + 1, y)),
+            ((1, 0), (x + 1, y), (x + 1, y + 1)),
+            ((0, 1), (x + 1, y + 1), (x, y + 1)),
+            ((-1, 0), (x, y + 1), (x, y)),
+        ];
+        for ((dx, dy), (x0, y0), (x1, y1)) in sides {
+            if !grid.contains(Coords::new(x + dx, y + dy)) {
+                edges.entry((y0, x0)).or_default().push(Coords::new(x1, y1));
+            }
+        }
+    }
+
+    let mut contours = Vec::new();
+    while let Some((&(y, x), _)) = edges.iter().next() {
+        let start = Coords::new(x, y);
+        let mut corners = vec![start];
+        let mut heading = (0, 0);
+        let mut here = start;
+
+        // At a corner shared by two diagonal tiles, turning left keeps them apart
+        while let Some(next) = take_edge(&mut edges, here, heading) {
+            heading = (next.x - here.x, next.y - here.y);
+            here = next;
+            if here == start {
+                break;
+            }
+            corners.push(here);
+        }
+
+        contours.push(turning_corners(&corners));
+    }
+
+    contours
+}
+
+/// Removes and returns the end of the boundary edge leaving `from`, turning as far left of
+/// `heading` as possible.
+fn take_edge(
+    edges: &mut BTreeMap<(i32, i32), Vec<Coords>>,
+    from: Coords,
+    heading: (i32, i32),
+) -> Option<Coords> {
+    let ends = edges.get_mut(&(from.y, from.x))?;
+    let turn = |end: &Coords| {
+        let (dx, dy) = (end.x - from.x, end.y - from.y);
+        heading.0 * dy - heading.1 * dx
+    };
+    let ix = (0..ends.len()).max_by_key(|&i| turn(&ends[i]))?;
+    let end = ends.swap_remove(ix);
+    if ends.is_empty() {
+        edges.remove(&(from.y, from.x));
+    }
+    Some(end)
+}
+
+/// Returns the corners of closed polygon `corners` where it changes direction.
+fn turning_corners(corners: &[Coords]) -> Vec<Coords> {
+    let n = corners.len();
+    (0..n)
+        .filter(|&i| {
+            let (prev, here, next) = (corners[(i + n - 1) % n], corners[i], corners[(i + 1) % n]);
+            let (ax, ay) = (here.x - prev.x, here.y - prev.y);
+            let (bx, by) = (next.x - here.x, next.y - here.y);
+            ax * by - ay * bx != 0
+        })
+        .map(|i| corners[i])
+        .collect()
+}
+
+/// Returns `true` if the tile with ID `id` has a transparent body.
+fn is_open(map: &TileMap, id: usize) -> bool {
+    map.tile(id).is_some_and(|t| !t.body_opaque)
+}
+
+/// Returns `true` if the tile with ID `id` is blocked on both the E/W or both the N/S sides.
+fn is_enclosed(map: &TileMap, id: usize) -> bool {
+    let [east, north, west, south] = neighbors(map, id);
+    (east.is_none() && west.is_none()) || (north.is_none() && south.is_none())
+}
+
+/// Returns the open, reachable neighbors (E, N, W, S) of the tile with ID `id`.
+fn neighbors(map: &TileMap, id: usize) -> [Option<usize>; 4] {
+    let Some(Coords { x, y }) = Coords::from_index(id, map.width()) else {
+        return [None; 4];
+    };
+    let wall_n = |c: Coords| map.get(c).is_some_and(|t| t.wall_n_opaque);
+    let wall_w = |c: Coords| map.get(c).is_some_and(|t| t.wall_w_opaque);
+    let open = |c: Coords| map.tile_id(c).filter(|&next| is_open(map, next));
+
+    let here = Coords::new(x, y);
+    let east = Coords::new(x + 1, y);
+    let north = Coords::new(x, y + 1);
+    let west = Coords::new(x - 1, y);
+    let south = Coords::new(x, y - 1);
+
+    [
+        open(east).filter(|_| !wall_w(east)),
+        open(north).filter(|_| !wall_n(here)),
+        open(west).filter(|_| !wall_w(here)),
+        open(south).filter(|_| !wall_n(south)),
+    ]
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two 3x3 rooms joined by a one-tile doorway at (3, 1):
+    //
+    // ```text
+    // ...#...
+    // .......
+    // ...#...
+    // ```
+    fn two_rooms() -> TileMap {
+        let mut map = TileMap::new(7, 3).unwrap();
+        map.set_body_opaque(Coords::new(3, 0), true);
+        map.set_body_opaque(Coords::new(3, 2), true);
+        map
+    }
+
+    #[test]
+    fn rooms_and_choke_points() {
+        let mut map = two_rooms();
+        assert_eq!(RoomMap::new(&map).len(), 1);
+        assert_eq!(choke_points(&map), vec![Coords::new(3, 1)]);
+
+        map.set_body_opaque(Coords::new(3, 1), true);
+        let rooms = RoomMap::new(&map);
+        assert_eq!(rooms.len(), 2);
+        assert_eq!(rooms.room_at(Coords::new(0, 0)), Some(0));
+        assert_eq!(rooms.room_at(Coords::new(6, 2)), Some(1));
+        assert_eq!(rooms.room_at(Coords::new(3, 1)), None);
+        assert!(choke_points(&map).is_empty());
+
+        let mut grid = VisibilityGrid::new(7, 3);
+        for coords in rooms.tiles(0).take(6) {
+            grid.insert(coords);
+        }
+        let coverage = rooms.coverage(&grid);
+        assert!((coverage[0] - 6.0 / 9.0).abs() < 1e-9);
+        assert_eq!(coverage[1], 0.0);
+    }
+
+    // Larger adjustments keep more far tiles; one row per radius per adjustment.
+    #[test]
+    fn circle_adjust_sweep_rows() {
+        let table = circle_adjust_sweep(&[0.0, 0.5, 1.5]).unwrap();
+        let full_radius: Vec<_> = table
+            .rows()
+            .filter(|row| row[1] == Cell::Int(16))
+            .map(|row| match row[2] {
+                Cell::Int(v) => v,
+                _ => panic!("visible count must be an integer!"),
+            })
+            .collect();
+
+        assert_eq!(table.len(), 3 * 16);
+        assert_eq!(table.columns()[4], "error");
+        assert!(full_radius.windows(2).all(|w| w[0] <= w[1]));
+        assert!(full_radius[0] < full_radius[2]);
+    }
+
+    #[test]
+    fn table_csv_json() {
+        let mut table = Table::new(&["name", "tiles", "coverage"]);
+        table.push_row(vec!["hall, east".into(), 9usize.into(), 0.5.into()]);
+        table.push_row(vec!["say \"hi\"".into(), 4usize.into(), f64::NAN.into()]);
+
+        let mut csv = Vec::new();
+        table.write_csv(&mut csv).unwrap();
+        let expected_csv = "name,tiles,coverage\n\"hall, east\",9,0.5\n\"say \"\"hi\"\"\",4,NaN\n";
+        assert_eq!(String::from_utf8(csv).unwrap(), expected_csv);
+
+        let mut json = Vec::new();
+        table.write_json(&mut json).unwrap();
+        let expected_json = concat!(
+            "[\n",
+            "  {\"name\": \"hall, east\", \"tiles\": 9, \"coverage\": 0.5},\n",
+            "  {\"name\": \"say \\\"hi\\\"\", \"tiles\": 4, \"coverage\": null}\n",
+            "]\n",
+        );
+        assert_eq!(String::from_utf8(json).unwrap(), expected_json);
+    }
+
+    // Outlines run counter-clockwise around visible areas and clockwise around holes, and
+    // tiles touching only at a corner are traced apart.
+    #[test]
+    fn visible_outline_contours() {
+        let c = Coords::new;
+        let mut grid = VisibilityGrid::new(8, 8);
+        grid.insert(c(5, 5));
+        assert_eq!(visible_outline(&grid), vec![vec![c(5, 5), c(6, 5), c(6, 6), c(5, 6)]]);
+
+        grid.insert(c(4, 4));
+        let contours = visible_outline(&grid);
+        assert_eq!(contours.len(), 2);
+        assert_eq!(contours[0], vec![c(4, 4), c(5, 4), c(5, 5), c(4, 5)]);
+
+        // 3x3 ring on the map edge: outer boundary and hole
+        let mut ring = VisibilityGrid::new(8, 8);
+        for (x, y) in (0..3).flat_map(|x| (0..3).map(move |y| (x, y))) {
+            ring.insert(c(x, y));
+        }
+        ring.remove(c(1, 1));
+        let contours = visible_outline(&ring);
+        assert_eq!(contours[0], vec![c(0, 0), c(3, 0), c(3, 3), c(0, 3)]);
+        assert_eq!(contours[1], vec![c(1, 1), c(1, 2), c(2, 2), c(2, 1)]);
+
+        // Shoelace area: positive outside, negative for holes, summing to the visible tiles
+        let area = |contour: &Vec<Coords>| -> i32 {
+            let n = contour.len();
+            let cross = |(a, b): (Coords, Coords)| a.x * b.y - b.x * a.y;
+            (0..n).map(|i| cross((contour[i], contour[(i + 1) % n]))).sum::<i32>() / 2
+        };
+        let map = TileMap::random(33, 33, 0.12, 4).unwrap();
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let grid = get_visible_grid(&fov_set, &map, Coords::new(16, 16), 16);
+        let contours = visible_outline(&grid);
+        assert!(area(&contours[0]) > 0);
+        assert_eq!(contours.iter().map(area).sum::<i32>(), grid.len() as i32);
+    }
+}
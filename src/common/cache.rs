@@ -0,0 +1,287 @@
+//! Binary cache files of prebuilt FOV sets for FOV Visualization - Rust (2D).
+//!
+//! Building large _Simple_ FOV sets (e.g. `FovSet128` at R64, Q-Factor 2) runs millions of
+//! line intersections. Write a set once with `write_fov_set` (or `files::save_fov_set`), and
+//! load it in milliseconds with `read_fov_set` (or `files::load_fov_set`).
+//!
+//! Format notes (version 1):
+//! - header: magic `FOVC`, format version (`u8`), FOV radius and Q-Factor (`u8` each, as
+//!   from `to_int`), node count (`u32`) and an FNV-1a checksum of the nodes (`u64`), all
+//!   little-endian.
+//! - body: per node, `body` bits (Q-value / 8 bytes, little-endian), then `dpri` and `dsec`
+//!   (`u8` each), in storage order. Octants share their nodes, so they are stored once.
+//! - Only _Simple_ FOV sets are cached: _Standard_ FOV sets are R16 only, and quick to build.
+//! - Origin rules, traversal orders and LOD distances are settings rather than node data:
+//!   they are not cached, and loaded sets start with the defaults.
+//! - The header must name a supported configuration (`fov::supported_configs`), the
+//!   checksum must match, and node offsets must pass `FovSet::from_nodes` (storage order,
+//!   within the radius), so truncated or corrupt files are rejected rather than loaded.
+
+use std::io::{self, Read, Write};
+
+use crate::{
+    error::FovError,
+    fov::{supported_configs, Algorithm},
+    simple::{self, FovBits, FovNode},
+    visibility::fnv1a,
+    FovComputer, FovRadius, FovSetAny, Octant, QFactor,
+};
+
+/// Magic bytes starting an FOV set cache file.
+const CACHE_MAGIC: &[u8; 4] = b"FOVC";
+/// Current FOV set cache format version.
+pub const CACHE_VERSION: u8 = 1;
+
+/// Writes a _Simple_ FOV set to `w` in the binary cache format.
+///
+/// Returns an `Unsupported` I/O error for _Standard_ FOV sets.
+pub fn write_fov_set<W: Write>(fov_set: &FovSetAny, w: W) -> Result<(), FovError> {
+    let rfov = fov_set.radius();
+    let written = match fov_set {
+        FovSetAny::Simple8(s) => write_nodes(w, rfov, s.octant(Octant::O1).iter().as_slice()),
+        FovSetAny::Simple16(s) => write_nodes(w, rfov, s.octant(Octant::O1).iter().as_slice()),
+        FovSetAny::Simple32(s) => write_nodes(w, rfov, s.octant(Octant::O1).iter().as_slice()),
+        FovSetAny::Simple64(s) => write_nodes(w, rfov, s.octant(Octant::O1).iter().as_slice()),
+        FovSetAny::Simple128(s) => write_nodes(w, rfov, s.octant(Octant::O1).iter().as_slice()),
+        FovSetAny::Standard16(_) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "only simple FOV sets can be cached",
+        )),
+    };
+    written.map_err(FovError::from)
+}
+
+/// Reads an FOV set written by `write_fov_set`.
+pub fn read_fov_set<R: Read>(mut r: R) -> Result<FovSetAny, FovError> {
+    let mut header = [0u8; 19];
+    r.read_exact(&mut header)?;
+    if &header[..4] != CACHE_MAGIC {
+        return Err(FovError::invalid("not an FOV set cache"));
+    }
+    if header[4] != CACHE_VERSION {
+        return Err(FovError::invalid("unsupported FOV set cache version"));
+    }
+
+    let config = supported_configs().find(|c| {
+        c.algorithm == Algorithm::Simple
+            && c.rfov.to_int() == header[5]
+            && c.qfactor.to_int() == header[6]
+    });
+    let Some(config) = config else {
+        return Err(FovError::invalid("unsupported FOV set cache configuration"));
+    };
+    let (rfov, qfactor) = (config.rfov, config.qfactor);
+    let count = u32::from_le_bytes(header[7..11].try_into().unwrap()) as usize;
+    let checksum = u64::from_le_bytes(header[11..19].try_into().unwrap());
+
+    // Nodes of a full, unculled octant: an upper bound for any culled one
+    let radius = rfov.to_int() as usize;
+    if count == 0 || count > (radius + 1) * (radius + 2) / 2 {
+        return Err(FovError::invalid("FOV set cache node count out of range"));
+    }
+
+    Ok(match config.q() {
+        8 => FovSetAny::Simple8(simple::FovSet8::from_nodes(
+            rfov,
+            qfactor,
+            &read_nodes(&mut r, count, checksum)?,
+        )?),
+        16 => FovSetAny::Simple16(simple::FovSet16::from_nodes(
+            rfov,
+            qfactor,
+            &read_nodes(&mut r, count, checksum)?,
+        )?),
+        32 => FovSetAny::Simple32(simple::FovSet32::from_nodes(
+            rfov,
+            qfactor,
+            &read_nodes(&mut r, count, checksum)?,
+        )?),
+        64 => FovSetAny::Simple64(simple::FovSet64::from_nodes(
+            rfov,
+            qfactor,
+            &read_nodes(&mut r, count, checksum)?,
+        )?),
+        _ => FovSetAny::Simple128(simple::FovSet128::from_nodes(
+            rfov,
+            qfactor,
+            &read_nodes(&mut r, count, checksum)?,
+        )?),
+    })
+}
+
+/// Writes the header and `nodes` of a _Simple_ FOV set with radius `rfov` to `w`.
+fn write_nodes<B: FovBits, W: Write>(
+    mut w: W,
+    rfov: FovRadius,
+    nodes: &[FovNode<B>],
+) -> io::Result<()> {
+    let qfactor = match B::BITS == rfov.to_int() as u32 {
+        true => QFactor::Single,
+        false => QFactor::Double,
+    };
+    let width = B::BITS as usize / 8;
+
+    let mut out = Vec::with_capacity(19 + nodes.len() * (width + 2));
+    out.extend_from_slice(CACHE_MAGIC);
+    out.push(CACHE_VERSION);
+    out.push(rfov.to_int());
+    out.push(qfactor.to_int());
+    out.extend_from_slice(&(nodes.len() as u32).to_le_bytes());
+    out.extend_from_slice(&checksum(nodes).to_le_bytes());
+
+    for node in nodes {
+        out.extend_from_slice(&node.body.to_mask().to_le_bytes()[..width]);
+        out.extend_from_slice(&[node.dpri, node.dsec]);
+    }
+
+    w.write_all(&out)
+}
+
+/// Reads `count` nodes with `B` FOV bits, and checks them against `checksum`.
+///
+/// Node offsets are checked by `FovSet::from_nodes`.
+fn read_nodes<B: FovBits, R: Read>(
+    r: &mut R,
+    count: usize,
+    checksum: u64,
+) -> Result<Vec<FovNode<B>>, FovError> {
+    let width = B::BITS as usize / 8;
+    let mut bytes = vec![0u8; count * (width + 2)];
+    r.read_exact(&mut bytes)?;
+
+    let nodes: Vec<FovNode<B>> = bytes
+        .chunks_exact(width + 2)
+        .map(|chunk| {
+            let mut mask = [0u8; 16];
+            mask[..width].copy_from_slice(&chunk[..width]);
+            FovNode {
+                body: B::from_mask(u128::from_le_bytes(mask)),
+                dpri: chunk[width],
+                dsec: chunk[width + 1],
+            }
+        })
+        .collect();
+
+    if self::checksum(&nodes) != checksum {
+        return Err(FovError::invalid("FOV set cache checksum mismatch"));
+    }
+
+    Ok(nodes)
+}
+
+/// Returns a 64-bit FNV-1a checksum of `nodes`.
+fn checksum<B: FovBits>(nodes: &[FovNode<B>]) -> u64 {
+    let words: Vec<u64> = nodes
+        .iter()
+        .flat_map(|n| {
+            let mask = n.body.to_mask();
+            [mask as u64, (mask >> 64) as u64, (n.dpri as u64) << 8 | n.dsec as u64]
+        })
+        .collect();
+    fnv1a(words.iter())
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maps::{Coords, TileMap};
+    use crate::rng::Rng;
+    use crate::FovShape;
+
+    // Cached sets load back with identical nodes and query results, at every node width.
+    #[test]
+    fn fov_set_cache_round_trip() {
+        let mut map = TileMap::random(80, 80, 0.1, 5).unwrap();
+        let origin = Coords::new(40, 40);
+        map.set_body_opaque(origin, false);
+
+        let configs = supported_configs().filter(|c| c.algorithm == Algorithm::Simple);
+        for config in configs.filter(|c| c.rfov.to_int() <= 32) {
+            let shape = FovShape::default();
+            let fov_set =
+                FovSetAny::new(config.algorithm, config.rfov, config.qfactor, shape).unwrap();
+            let mut bytes = Vec::new();
+            write_fov_set(&fov_set, &mut bytes).unwrap();
+
+            let loaded = read_fov_set(bytes.as_slice()).unwrap();
+            assert_eq!(loaded.radius(), config.rfov, "{}", config.name());
+            assert_eq!(
+                loaded.visible_tiles(origin, 32, &map),
+                fov_set.visible_tiles(origin, 32, &map),
+                "{}",
+                config.name()
+            );
+        }
+
+        // Shapes culling inside the radius leave no nodes at `dpri == rfov`
+        let shape = FovShape::Circle { adjust: -0.5 };
+        let fov_set = FovSetAny::new(Algorithm::Simple, FovRadius::R8, QFactor::Single, shape)
+            .unwrap();
+        let mut bytes = Vec::new();
+        write_fov_set(&fov_set, &mut bytes).unwrap();
+        let loaded = read_fov_set(bytes.as_slice()).unwrap();
+        assert_eq!(loaded.visible_tiles(origin, 8, &map), fov_set.visible_tiles(origin, 8, &map));
+    }
+
+    // Corrupt, truncated and unsupported caches are errors, never panics.
+    #[test]
+    fn fov_set_cache_rejects_bad_input() {
+        let (single, shape) = (QFactor::Single, FovShape::default());
+        let fov_set = FovSetAny::new(Algorithm::Simple, FovRadius::R8, single, shape).unwrap();
+        let mut bytes = Vec::new();
+        write_fov_set(&fov_set, &mut bytes).unwrap();
+
+        let read = |bytes: &[u8]| read_fov_set(bytes).map(|_| ()).unwrap_err().to_string();
+        let mut flipped = bytes.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+        assert!(read(&flipped).contains("checksum"));
+        let mut body = bytes.clone();
+        body[19] ^= 0x10;
+        assert!(read(&body).contains("checksum"));
+        assert!(read(&bytes[..bytes.len() - 1]).contains("fill whole buffer"));
+
+        let mut radius = bytes.clone();
+        radius[5] = 12;
+        assert!(read(&radius).contains("configuration"));
+        let mut count = bytes.clone();
+        count[7..11].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(read(&count).contains("node count"));
+        let mut magic = bytes.clone();
+        magic[3] = b'G';
+        assert!(read(&magic).contains("not an FOV set cache"));
+
+        // Node offsets out of storage order are rejected even with a matching checksum
+        let node = |(dpri, dsec)| FovNode { body: 1u8, dpri, dsec };
+        let swapped = [(0, 0), (1, 1), (1, 0)].map(node);
+        let mut unordered = Vec::new();
+        write_nodes(&mut unordered, FovRadius::R8, &swapped).unwrap();
+        assert!(read(&unordered).contains("storage order"));
+
+        let standard = FovSetAny::new(Algorithm::Standard, FovRadius::R16, single, shape).unwrap();
+        let err = write_fov_set(&standard, Vec::new()).unwrap_err();
+        assert!(matches!(err, FovError::Io(e) if e.kind() == io::ErrorKind::Unsupported));
+    }
+
+    // Mutated caches load as queryable FOV sets or errors, never panics.
+    #[test]
+    fn read_mutated_fov_set_cache() {
+        let (single, shape) = (QFactor::Single, FovShape::default());
+        let fov_set = FovSetAny::new(Algorithm::Simple, FovRadius::R8, single, shape).unwrap();
+        let mut bytes = Vec::new();
+        write_fov_set(&fov_set, &mut bytes).unwrap();
+        let map = TileMap::random(20, 20, 0.2, 3).unwrap();
+
+        let mut rng = Rng::new(1950);
+        for _ in 0..5000 {
+            if let Ok(loaded) = read_fov_set(rng.mutate(&bytes).as_slice()) {
+                loaded.visible_tiles(Coords::new(10, 10), 8, &map);
+            }
+        }
+    }
+}
@@ -0,0 +1,425 @@
+//! Shared FOV test cases for FOV Visualization - Rust (2D).
+//!
+//! Test cases are exchanged as JSON with the sibling Python and JS FOV visualization
+//! projects, so the same expectations run against every implementation.
+//!
+//! Schema (version 1):
+//!
+//! ```text
+//! {
+//!   "version": 1,
+//!   "cases": [
+//!     {
+//!       "name": "pillar",
+//!       "map": ["#....", ".....", "..#.."],
+//!       "origin": [2, 0],
+//!       "radius": 4,
+//!       "visible": [[2, 0], [1, 0], [3, 1]]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Notes:
+//! - `map` holds one string per row, northmost (highest `y`) first, as in rendered images;
+//!   `#` is an opaque tile body, any other character is open. Short rows are padded open.
+//! - `origin` and `visible` are `[x, y]` map coordinates; `visible` is the expected visible
+//!   set, in any order. Unknown keys are ignored, so exporters may add metadata.
+//! - The JSON reader is self-contained and strict; malformed input returns an error.
+
+use super::error::FovError;
+use super::maps::{ascii_from_rows, Coords, Glyphs, TileMap};
+use super::visibility::{Became, VisibilityGrid};
+
+/// Version of the test case schema written in `"version"`.
+pub const CASES_VERSION: u64 = 1;
+
+/// Largest accepted test case map, in tiles, so corrupt input cannot force huge allocations.
+const MAX_CASE_TILES: usize = 1 << 24;
+
+/// Deepest accepted JSON nesting, so corrupt input cannot overflow the stack.
+const MAX_DEPTH: usize = 32;
+
+/// FOV test case: a map, a query, and the expected visible tiles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCase {
+    pub name: String,
+    pub map: TileMap,
+    pub origin: Coords,
+    pub radius: usize,
+    pub visible: Vec<Coords>,
+}
+
+impl TestCase {
+    /// Parses test cases from a JSON document in the schema above.
+    pub fn parse_json(text: &str) -> Result<Vec<TestCase>, FovError> {
+        let mut parser = Parser { bytes: text.as_bytes(), pos: 0 };
+        let doc = parser.value(0)?;
+        parser.skip_whitespace();
+        if parser.pos != parser.bytes.len() {
+            return Err(FovError::invalid("trailing characters after JSON document"));
+        }
+
+        match doc.get("version").and_then(Json::as_uint) {
+            Some(CASES_VERSION) => {}
+            Some(_) => return Err(FovError::invalid("unsupported test case version")),
+            None => return Err(FovError::invalid("missing test case version")),
+        }
+        let cases = required(doc.get("cases").and_then(Json::as_array), "missing cases")?;
+        cases.iter().map(TestCase::from_json).collect()
+    }
+    /// Returns the expected visible tiles as a `VisibilityGrid` for the case's map.
+    pub fn expected_grid(&self) -> VisibilityGrid {
+        let mut grid = VisibilityGrid::for_map(&self.map);
+        for &coords in self.visible.iter() {
+            grid.insert(coords);
+        }
+        grid
+    }
+    /// Returns tiles where `grid` differs from the expected visible set, in tile ID order.
+    ///
+    /// `Became::Visible` marks tiles visible in `grid` only; `Became::Hidden` marks
+    /// expected tiles missing from `grid`. An empty result means the case passes.
+    pub fn mismatches(&self, grid: &VisibilityGrid) -> Vec<(Coords, Became)> {
+        grid.diff(&self.expected_grid()).collect()
+    }
+    /// Builds a test case from one entry of `"cases"`.
+    fn from_json(case: &Json) -> Result<TestCase, FovError> {
+        let name = case.get("name").and_then(Json::as_str).unwrap_or("").to_string();
+        let rows = required(case.get("map").and_then(Json::as_array), "missing map")?;
+        let rows: Vec<&str> = rows
+            .iter()
+            .map(|row| row.as_str().ok_or_else(|| FovError::invalid("map rows must be strings")))
+            .collect::<Result<_, _>>()?;
+        let map = map_from_rows(&rows)?;
+
+        let origin = coords(required(case.get("origin"), "missing origin")?)?;
+        let radius = required(case.get("radius").and_then(Json::as_uint), "missing radius")?;
+        let visible = required(case.get("visible").and_then(Json::as_array), "missing visible")?;
+        let visible: Vec<Coords> = visible.iter().map(coords).collect::<Result<_, _>>()?;
+        if !map.in_bounds(origin) || visible.iter().any(|&c| !map.in_bounds(c)) {
+            return Err(FovError::invalid("test case coordinates out of bounds"));
+        }
+
+        Ok(TestCase {
+            name,
+            map,
+            origin,
+            radius: radius as usize,
+            visible,
+        })
+    }
+}
+
+/// Returns a `TileMap` from `rows`, northmost first; `#` is opaque.
+fn map_from_rows(rows: &[&str]) -> Result<TileMap, FovError> {
+    let height = rows.len();
+    let width = rows.iter().map(|r| r.chars().count()).max().unwrap_or(0);
+    if width.checked_mul(height).is_none_or(|tiles| tiles > MAX_CASE_TILES) {
+        return Err(FovError::invalid("test case map too large"));
+    }
+
+    TileMap::from_ascii_with(&ascii_from_rows(rows), &Glyphs::default())
+}
+
+/// Returns coordinates from a JSON `[x, y]` pair.
+fn coords(value: &Json) -> Result<Coords, FovError> {
+    let pair = value.as_array().filter(|a| a.len() == 2);
+    let xy = pair.and_then(|a| Some((a[0].as_int()?, a[1].as_int()?)));
+    let err = || FovError::invalid("coordinates must be [x, y] integers");
+    xy.map(|(x, y)| Coords::new(x, y)).ok_or_else(err)
+}
+
+/// Returns `value`, or an `InvalidData` error with `msg` if it is `None`.
+fn required<T>(value: Option<T>, msg: &str) -> Result<T, FovError> {
+    value.ok_or_else(|| FovError::invalid(msg))
+}
+
+/// Parsed JSON value. Objects keep their keys in document order.
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Returns the value of `key` if this is an object holding it.
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+    fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+    /// Returns the number if it is an integer that fits an `i32`.
+    fn as_int(&self) -> Option<i32> {
+        match self {
+            Json::Number(n) if n.fract() == 0.0 && n.abs() <= i32::MAX as f64 => Some(*n as i32),
+            _ => None,
+        }
+    }
+    /// Returns the number if it is a non-negative integer that fits a `u32`.
+    fn as_uint(&self) -> Option<u64> {
+        match self {
+            Json::Number(n) if n.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(n) => {
+                Some(*n as u64)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Recursive descent JSON parser over UTF-8 bytes.
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.bytes.get(self.pos).is_some_and(|b| b" \t\r\n".contains(b)) {
+            self.pos += 1;
+        }
+    }
+    /// Consumes `literal` if it comes next.
+    fn eat(&mut self, literal: &[u8]) -> bool {
+        let found = self.bytes[self.pos..].starts_with(literal);
+        if found {
+            self.pos += literal.len();
+        }
+        found
+    }
+    /// Parses the value starting at the next non-whitespace byte, nested `depth` levels deep.
+    fn value(&mut self, depth: usize) -> Result<Json, FovError> {
+        if depth > MAX_DEPTH {
+            return Err(FovError::invalid("JSON nested too deeply"));
+        }
+        self.skip_whitespace();
+        match self.bytes.get(self.pos) {
+            Some(b'{') => self.object(depth),
+            Some(b'[') => self.array(depth),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            _ if self.eat(b"true") => Ok(Json::Bool(true)),
+            _ if self.eat(b"false") => Ok(Json::Bool(false)),
+            _ if self.eat(b"null") => Ok(Json::Null),
+            _ => Err(FovError::invalid("unexpected character in JSON")),
+        }
+    }
+    fn object(&mut self, depth: usize) -> Result<Json, FovError> {
+        self.pos += 1;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.eat(b"}") {
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.bytes.get(self.pos) != Some(&b'"') {
+                return Err(FovError::invalid("expected JSON object key"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            if !self.eat(b":") {
+                return Err(FovError::invalid("expected ':' in JSON object"));
+            }
+            members.push((key, self.value(depth + 1)?));
+            self.skip_whitespace();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(members));
+                }
+                _ => return Err(FovError::invalid("expected ',' or '}' in JSON object")),
+            }
+        }
+    }
+    fn array(&mut self, depth: usize) -> Result<Json, FovError> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.eat(b"]") {
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                _ => return Err(FovError::invalid("expected ',' or ']' in JSON array")),
+            }
+        }
+    }
+    fn number(&mut self) -> Result<Json, FovError> {
+        let start = self.pos;
+        while self.bytes.get(self.pos).is_some_and(|b| b"+-.eE0123456789".contains(b)) {
+            self.pos += 1;
+        }
+        let text = std::str::from_utf8(&self.bytes[start..self.pos]).unwrap_or("");
+        match text.parse::<f64>() {
+            Ok(n) if n.is_finite() => Ok(Json::Number(n)),
+            _ => Err(FovError::invalid("bad JSON number")),
+        }
+    }
+    /// Parses the 4 hex digits of a `\u` escape.
+    fn hex4(&mut self) -> Result<u32, FovError> {
+        let hex = self.bytes.get(self.pos..self.pos + 4).unwrap_or(&[]);
+        let hex = std::str::from_utf8(hex).unwrap_or("");
+        let code = required(u32::from_str_radix(hex, 16).ok(), "bad JSON escape")?;
+        self.pos += 4;
+        Ok(code)
+    }
+    /// Parses a string literal, starting at its opening quote.
+    fn string(&mut self) -> Result<String, FovError> {
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        loop {
+            match self.bytes.get(self.pos) {
+                None => return Err(FovError::invalid("unterminated JSON string")),
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    let escaped = self.bytes.get(self.pos + 1).copied();
+                    self.pos += 2;
+                    let c = match escaped {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            // Surrogate pairs are not combined; lone halves become U+FFFD
+                            let code = self.hex4()?;
+                            char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        _ => return Err(FovError::invalid("bad JSON escape")),
+                    };
+                    bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                Some(&b) if b < 0x20 => {
+                    return Err(FovError::invalid("control character in JSON string"))
+                }
+                Some(&b) => {
+                    bytes.push(b);
+                    self.pos += 1;
+                }
+            }
+        }
+        self.pos += 1;
+        String::from_utf8(bytes).map_err(|_| FovError::invalid("JSON string is not UTF-8"))
+    }
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use crate::simple::{fovcalc::get_visible_grid, FovSet16};
+    use crate::{FovRadius, FovShape, QFactor};
+
+    const CASES: &str = r#"{
+        "version": 1,
+        "exporter": "fov-visualization-python",
+        "cases": [
+            {
+                "name": "open \"3x3\"",
+                "map": ["...", "...", "..."],
+                "origin": [1, 1],
+                "radius": 1,
+                "visible": [[0, 0], [1, 0], [2, 0], [0, 1], [1, 1], [2, 1], [0, 2], [1, 2], [2, 2]]
+            },
+            {
+                "name": "corridor wall",
+                "map": ["..#.."],
+                "origin": [0, 0],
+                "radius": 4,
+                "visible": [[0, 0], [1, 0], [2, 0]]
+            }
+        ]
+    }"#;
+
+    // Shared cases parse and pass against _Simple_ FOV; mismatches are reported per tile.
+    #[test]
+    fn shared_test_cases() {
+        let cases = TestCase::parse_json(CASES).unwrap();
+        assert_eq!(cases.len(), 2);
+        assert_eq!(cases[0].name, "open \"3x3\"");
+        assert!(cases[1].map.get(Coords::new(2, 0)).unwrap().body_opaque);
+
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        for case in cases.iter() {
+            let grid = get_visible_grid(&fov_set, &case.map, case.origin, case.radius);
+            assert_eq!(case.mismatches(&grid), vec![], "{}", case.name);
+        }
+
+        let mut grid = cases[1].expected_grid();
+        grid.remove(Coords::new(1, 0));
+        grid.insert(Coords::new(3, 0));
+        let expected = [(Coords::new(1, 0), Became::Hidden), (Coords::new(3, 0), Became::Visible)];
+        assert_eq!(cases[1].mismatches(&grid), expected);
+    }
+
+    // Malformed documents and out of range values are errors, never panics.
+    #[test]
+    fn malformed_test_cases() {
+        let case = |fields: &str| [r#"{"version": 1, "cases": [{"#, fields, "}]}"].concat();
+        let bad = [
+            "".to_string(),
+            "{".to_string(),
+            r#"{"version": 2, "cases": []}"#.to_string(),
+            r#"{"cases": []}"#.to_string(),
+            r#"{"version": 1, "cases": []} x"#.to_string(),
+            r#"{"version": 1, "cases": ["\u12"]}"#.to_string(),
+            "[".repeat(100),
+            case(r#""map": ["."], "origin": [1, 0], "radius": 1, "visible": []"#),
+            case(r#""map": ["."], "origin": [0], "radius": 1, "visible": []"#),
+            case(r#""map": [7], "origin": [0, 0], "radius": 1, "visible": []"#),
+            case(r#""map": ["."], "origin": [0, 0], "radius": -1, "visible": []"#),
+        ];
+        for text in bad.iter() {
+            assert!(TestCase::parse_json(text).is_err(), "{text}");
+        }
+        assert_eq!(TestCase::parse_json(r#"{"version": 1, "cases": []}"#).unwrap(), vec![]);
+    }
+
+    // Mutated documents parse to in-bounds cases or errors, never panics.
+    #[test]
+    fn parse_mutated_test_cases() {
+        let mut rng = Rng::new(1950);
+        for _ in 0..5000 {
+            let text = String::from_utf8_lossy(&rng.mutate(CASES.as_bytes())).into_owned();
+            for case in TestCase::parse_json(&text).into_iter().flatten() {
+                assert!(case.map.in_bounds(case.origin));
+                assert!(case.visible.iter().all(|&c| case.map.in_bounds(c)));
+            }
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! FOV computation over every FOV set type for FOV Visualization - Rust (2D).
+//!
+//! Notes:
+//! - Each radius and Q tier is its own concrete FOV set type. `FovComputer` is implemented
+//!   by all of them (_Simple_ `FovSet<B>`, i.e. `FovSet8` to `FovSet128`, and _Standard_
+//!   `FovSet16`), so downstream code can be written once, generically or over `&dyn FovComputer`.
+//! - `FovSetAny` holds any one FOV set type, chosen at runtime from an `Algorithm`, radius
+//!   and Q-Factor (e.g. a `fov::SupportedConfig` picked from settings).
+//! - The map is a `TileMap`, which holds both tile bodies and walls, so the trait stays
+//!   object safe. The free `get_visible_*` functions accept other map types.
+
+use crate::{
+    error::{check_config, FovError},
+    fov::{Algorithm, Cone, FovShape, ShapeSpans, VisibleTile},
+    maps::{Coords, TileMap},
+    math::Delta,
+    simple::{self, FovBits},
+    standard, FovRadius, Octant, QFactor, VisibilityGrid,
+};
+
+/// Computes visible tiles with an FOV set, whatever its radius, Q-value and algorithm.
+pub trait FovComputer {
+    /// Returns the FOV radius of the FOV set.
+    fn radius(&self) -> FovRadius;
+    /// Returns visible tiles (and their visible subparts) from `origin` out to radius `r`.
+    ///
+    /// `r` is clamped to the FOV set's radius. Tiles are returned once each, by tile ID.
+    fn visible_tiles(&self, origin: Coords, r: usize, map: &TileMap) -> Vec<VisibleTile>;
+    /// Returns a `VisibilityGrid` of tiles with any subpart visible.
+    fn visible_grid(&self, origin: Coords, r: usize, map: &TileMap) -> VisibilityGrid {
+        let mut grid = VisibilityGrid::for_map(map);
+        for tile in self.visible_tiles(origin, r, map) {
+            grid.insert_id(tile.id);
+        }
+        grid
+    }
+    /// Returns a `VisibilityGrid` of tiles with any subpart visible within `shape` around
+    /// `origin` (e.g. an `FovShape::Ellipse`), out to the FOV set's radius.
+    ///
+    /// The shape is reduced to integer row spans once, so tiles are tested in integers.
+    fn visible_grid_shaped(
+        &self,
+        origin: Coords,
+        shape: FovShape,
+        map: &TileMap,
+    ) -> VisibilityGrid {
+        let r = self.radius().to_int();
+        let spans = ShapeSpans::new(shape, r);
+        let mut grid = VisibilityGrid::for_map(map);
+        for tile in self.visible_tiles(origin, r as usize, map) {
+            let within = Coords::from_index(tile.id, map.width()).is_some_and(|coords| {
+                spans.contains(Delta::new(coords.x - origin.x, coords.y - origin.y))
+            });
+            if within {
+                grid.insert_id(tile.id);
+            }
+        }
+        grid
+    }
+    /// Returns a `VisibilityGrid` of tiles with any subpart visible within vision `cone`, seen
+    /// from the cone's origin.
+    ///
+    /// Tiles outside the cone still block sight, so any FOV set can serve 90 or 120 degree
+    /// vision cones without being rebuilt. Like `Cone` itself, this is outside the
+    /// determinism guarantee.
+    fn visible_grid_in_cone(&self, cone: &Cone, r: usize, map: &TileMap) -> VisibilityGrid {
+        let mut grid = VisibilityGrid::for_map(map);
+        for tile in self.visible_tiles(cone.origin, r, map) {
+            let coords = Coords::from_index(tile.id, map.width());
+            if coords.is_some_and(|coords| cone.contains(coords)) {
+                grid.insert_id(tile.id);
+            }
+        }
+        grid
+    }
+}
+
+impl<B: FovBits> FovComputer for simple::FovSet<B> {
+    fn radius(&self) -> FovRadius {
+        self.radius()
+    }
+    fn visible_tiles(&self, origin: Coords, r: usize, map: &TileMap) -> Vec<VisibleTile> {
+        simple::fovcalc::get_visible_tiles(self, map, origin, r)
+    }
+}
+
+impl FovComputer for standard::FovSet16 {
+    fn radius(&self) -> FovRadius {
+        self.radius()
+    }
+    fn visible_tiles(&self, origin: Coords, r: usize, map: &TileMap) -> Vec<VisibleTile> {
+        standard::fovcalc::get_visible_tiles(self, map, origin, r)
+    }
+}
+
+/// Any one FOV set type, for FOV configurations chosen at runtime. Clones share nodes.
+#[derive(Clone)]
+pub enum FovSetAny {
+    Simple8(simple::FovSet8),
+    Simple16(simple::FovSet16),
+    Simple32(simple::FovSet32),
+    Simple64(simple::FovSet64),
+    Simple128(simple::FovSet128),
+    Standard16(standard::FovSet16),
+}
+
+impl FovSetAny {
+    /// Creates the FOV set type for `algorithm` whose Q-value matches `rfov` and `qfactor`.
+    ///
+    /// Returns an error for unsupported pairings (see `fov::supported_configs`) or shapes.
+    pub fn new(
+        algorithm: Algorithm,
+        rfov: FovRadius,
+        qfactor: QFactor,
+        shape: FovShape,
+    ) -> Result<Self, FovError> {
+        check_config("FovSetAny", algorithm, None, rfov, qfactor, shape)?;
+        let fov_set = match (algorithm, rfov.to_int() as usize * qfactor.to_int() as usize) {
+            (Algorithm::Standard, _) => {
+                Self::Standard16(standard::FovSet16::new(rfov, qfactor, shape)?)
+            }
+            (Algorithm::Simple, 8) => Self::Simple8(simple::FovSet8::new(rfov, qfactor, shape)?),
+            (Algorithm::Simple, 16) => {
+                Self::Simple16(simple::FovSet16::new(rfov, qfactor, shape)?)
+            }
+            (Algorithm::Simple, 32) => {
+                Self::Simple32(simple::FovSet32::new(rfov, qfactor, shape)?)
+            }
+            (Algorithm::Simple, 64) => {
+                Self::Simple64(simple::FovSet64::new(rfov, qfactor, shape)?)
+            }
+            (Algorithm::Simple, _) => {
+                Self::Simple128(simple::FovSet128::new(rfov, qfactor, shape)?)
+            }
+        };
+        Ok(fov_set)
+    }
+    /// Returns the algorithm of the held FOV set.
+    pub fn algorithm(&self) -> Algorithm {
+        match self {
+            Self::Standard16(_) => Algorithm::Standard,
+            _ => Algorithm::Simple,
+        }
+    }
+    /// Returns the octant 1 nodes of the held FOV set as `(dpri, dsec, body)`.
+    pub fn octant_1_nodes(&self) -> Vec<(u8, u8, u128)> {
+        match self {
+            Self::Simple8(fov_set) => simple_octant_1_nodes(fov_set),
+            Self::Simple16(fov_set) => simple_octant_1_nodes(fov_set),
+            Self::Simple32(fov_set) => simple_octant_1_nodes(fov_set),
+            Self::Simple64(fov_set) => simple_octant_1_nodes(fov_set),
+            Self::Simple128(fov_set) => simple_octant_1_nodes(fov_set),
+            Self::Standard16(fov_set) => {
+                let nodes = fov_set.octant(Octant::O1).iter();
+                nodes.map(|n| (n.dpri, n.dsec, n.body as u128)).collect()
+            }
+        }
+    }
+    /// Returns the memory held by node data of the held FOV set, in bytes.
+    pub fn node_bytes(&self) -> usize {
+        match self {
+            Self::Simple8(fov_set) => fov_set.node_bytes(),
+            Self::Simple16(fov_set) => fov_set.node_bytes(),
+            Self::Simple32(fov_set) => fov_set.node_bytes(),
+            Self::Simple64(fov_set) => fov_set.node_bytes(),
+            Self::Simple128(fov_set) => fov_set.node_bytes(),
+            Self::Standard16(fov_set) => fov_set.node_bytes(),
+        }
+    }
+    /// Returns the held FOV set as a `FovComputer`.
+    pub fn as_computer(&self) -> &dyn FovComputer {
+        match self {
+            Self::Simple8(fov_set) => fov_set,
+            Self::Simple16(fov_set) => fov_set,
+            Self::Simple32(fov_set) => fov_set,
+            Self::Simple64(fov_set) => fov_set,
+            Self::Simple128(fov_set) => fov_set,
+            Self::Standard16(fov_set) => fov_set,
+        }
+    }
+}
+
+/// Returns the octant 1 nodes of `fov_set` as `(dpri, dsec, body)`.
+fn simple_octant_1_nodes<B: FovBits>(fov_set: &simple::FovSet<B>) -> Vec<(u8, u8, u128)> {
+    let nodes = fov_set.octant(Octant::O1).iter();
+    nodes.map(|n| (n.dpri, n.dsec, n.body.to_mask())).collect()
+}
+
+impl FovComputer for FovSetAny {
+    fn radius(&self) -> FovRadius {
+        self.as_computer().radius()
+    }
+    fn visible_tiles(&self, origin: Coords, r: usize, map: &TileMap) -> Vec<VisibleTile> {
+        self.as_computer().visible_tiles(origin, r, map)
+    }
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fov::supported_configs;
+
+    /// Generic downstream code: counts visible tiles with any FOV set type.
+    fn count_visible<C: FovComputer + ?Sized>(fov: &C, map: &TileMap, origin: Coords) -> usize {
+        fov.visible_tiles(origin, 8, map).len()
+    }
+
+    // Every supported config builds as `FovSetAny`, and matches its concrete FOV set type.
+    #[test]
+    fn fov_set_any_matches_concrete() {
+        let mut map = TileMap::random(40, 40, 0.1, 9).unwrap();
+        let origin = Coords::new(20, 20);
+        map.set_body_opaque(origin, false);
+        map.get_mut(Coords::new(22, 20)).unwrap().wall_w_opaque = true;
+
+        for config in supported_configs().filter(|c| c.rfov.to_int() <= 32) {
+            let shape = FovShape::default();
+            let any = FovSetAny::new(config.algorithm, config.rfov, config.qfactor, shape).unwrap();
+            assert_eq!(any.algorithm(), config.algorithm);
+            assert_eq!(any.radius(), config.rfov);
+            assert_eq!(any.visible_grid(origin, 8, &map).len(), count_visible(&any, &map, origin));
+        }
+
+        let (rfov, qfactor) = (FovRadius::R16, QFactor::Single);
+        let concrete = simple::FovSet16::new(rfov, qfactor, FovShape::default()).unwrap();
+        let any = FovSetAny::new(Algorithm::Simple, rfov, qfactor, FovShape::default()).unwrap();
+        assert_eq!(any.visible_tiles(origin, 8, &map), concrete.visible_tiles(origin, 8, &map));
+        assert_eq!(any.node_bytes(), concrete.node_bytes());
+        let nodes = concrete.octant(Octant::O1).iter();
+        let expected: Vec<_> = nodes.map(|n| (n.dpri, n.dsec, n.body as u128)).collect();
+        assert_eq!(any.octant_1_nodes(), expected);
+
+        // Standard FOV reports walls through the same trait; Simple FOV ignores them
+        let standard =
+            FovSetAny::new(Algorithm::Standard, rfov, qfactor, FovShape::default()).unwrap();
+        let computers: [&dyn FovComputer; 2] = [&concrete, standard.as_computer()];
+        let walls = computers.map(|c| c.visible_tiles(origin, 8, &map).iter().any(|t| t.wall_w));
+        assert_eq!(walls, [false, true]);
+        assert_eq!(standard.octant_1_nodes(), any.octant_1_nodes());
+        assert_eq!(standard.node_bytes(), 8 * expected.len() * 8);
+    }
+
+    // Ellipses restrict every FOV set type alike, and match the `FovSet16` query.
+    #[test]
+    fn visible_grid_shaped() {
+        let map = TileMap::new(40, 40).unwrap();
+        let origin = Coords::new(20, 20);
+        let squint = FovShape::Ellipse { rx: 16.5, ry: 4.5 };
+
+        let concrete =
+            simple::FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let grid = concrete.visible_grid_shaped(origin, squint, &map);
+        assert_eq!(grid, concrete.compute_shaped(&map, origin, squint));
+        assert!(grid.contains(Coords::new(36, 20)) && grid.contains(Coords::new(20, 24)));
+        assert!(!grid.contains(Coords::new(20, 25)) && !grid.contains(Coords::new(36, 22)));
+
+        for config in supported_configs().filter(|c| c.rfov == FovRadius::R16) {
+            let shape = FovShape::default();
+            let any = FovSetAny::new(config.algorithm, config.rfov, config.qfactor, shape).unwrap();
+            assert_eq!(any.visible_grid_shaped(origin, squint, &map), grid, "{}", config.name());
+        }
+    }
+
+    // Cones restrict every FOV set type alike, and match filtering a `FovSet16` query.
+    #[test]
+    fn visible_grid_in_cone() {
+        use crate::math::Degrees;
+
+        let mut map = TileMap::random(40, 40, 0.1, 4).unwrap();
+        let origin = Coords::new(20, 20);
+        map.set_body_opaque(origin, false);
+        let mut cone = Cone::new(origin, Degrees(0.0), Degrees(90.0));
+
+        let concrete =
+            simple::FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let grid = concrete.visible_grid_in_cone(&cone, 16, &map);
+        assert_eq!(grid, concrete.compute_filtered(&map, origin, &mut cone));
+        assert!(grid.contains(origin) && grid.iter().all(|c| c.x >= origin.x));
+        assert!(grid.len() < concrete.visible_grid(origin, 16, &map).len());
+
+        for config in supported_configs().filter(|c| c.rfov.to_int() <= 32) {
+            let shape = FovShape::default();
+            let any = FovSetAny::new(config.algorithm, config.rfov, config.qfactor, shape).unwrap();
+            let all = any.visible_grid(origin, 16, &map);
+            let in_cone = any.visible_grid_in_cone(&cone, 16, &map);
+            assert!(in_cone.iter().all(|c| all.contains(c) && cone.contains(c)));
+            assert_eq!(in_cone.len(), all.iter().filter(|&c| cone.contains(c)).count());
+        }
+    }
+}
@@ -0,0 +1,479 @@
+//! Vector diagrams for FOV Visualization - Rust (2D).
+//!
+//! Notes:
+//! - A `Diagram` is a list of vector `Shape`s, measured in points (1/72 inch).
+//! - Diagrams are written out as single-page PDF (see `files`) or SVG, with exact geometry
+//!   rather than rasterized pixels, so figures stay sharp at print resolution.
+//! - Like PDF, diagram `+y` is up, which matches map `+y` north and FOV `+sec`.
+//! - The PDF writer is self-contained: uncompressed content, built-in Helvetica font.
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use super::drawing::{config_caption, Rgb};
+use super::error::FovError;
+use super::fov::{get_fov_lines, FovLines, FovShape};
+use super::math::Line;
+use crate::{simple::build_fov_nodes, FovRadius, Octant, QFactor};
+
+/// Vector shape drawn in a `Diagram`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// Straight line, stroked with `width` points.
+    Line { line: Line, color: Rgb, width: f64 },
+    /// Axis-aligned rectangle from its bottom-left corner, filled and/or outlined.
+    Rect {
+        x: f64,
+        y: f64,
+        w: f64,
+        h: f64,
+        fill: Option<Rgb>,
+        stroke: Option<Rgb>,
+    },
+    /// Single line of text, from its baseline start, `size` points tall.
+    Text { x: f64, y: f64, size: f64, text: String, color: Rgb },
+}
+
+/// Vector diagram of `width` by `height` points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagram {
+    width: f64,
+    height: f64,
+    shapes: Vec<Shape>,
+}
+
+impl Diagram {
+    /// Creates a new, empty `Diagram` of `width` by `height` points.
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            shapes: Vec::new(),
+        }
+    }
+    /// Returns the width of the diagram, in points.
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+    /// Returns the height of the diagram, in points.
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+    /// Returns the diagram's shapes, in drawing order.
+    pub fn shapes(&self) -> &[Shape] {
+        &self.shapes
+    }
+    /// Adds a `line` stroked with `width` points.
+    pub fn line(&mut self, line: Line, color: Rgb, width: f64) {
+        self.shapes.push(Shape::Line { line, color, width });
+    }
+    /// Adds a rectangle with bottom-left corner `(x, y)`, filled and/or outlined.
+    pub fn rect(&mut self, x: f64, y: f64, w: f64, h: f64, fill: Option<Rgb>, stroke: Option<Rgb>) {
+        self.shapes.push(Shape::Rect { x, y, w, h, fill, stroke });
+    }
+    /// Adds `text` with baseline start `(x, y)`, `size` points tall.
+    pub fn text(&mut self, x: f64, y: f64, size: f64, text: &str, color: Rgb) {
+        let text = text.to_string();
+        self.shapes.push(Shape::Text { x, y, size, text, color });
+    }
+    /// Writes the diagram as a single-page PDF document.
+    pub fn write_pdf<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let content = self.pdf_content();
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /Font << /F1 5 0 R >> >> /Contents 4 0 R >>",
+                num(self.width),
+                num(self.height)
+            ),
+            format!("<< /Length {} >>\nstream\n{content}endstream", content.len()),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        ];
+
+        // Byte offsets of each object are recorded for the cross-reference table
+        let mut pdf = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            let _ = write!(pdf, "{} 0 obj\n{object}\nendobj\n", i + 1);
+        }
+
+        let xref = pdf.len();
+        let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            let _ = writeln!(pdf, "{offset:010} 00000 n ");
+        }
+        let _ = write!(
+            pdf,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        );
+
+        w.write_all(pdf.as_bytes())
+    }
+    /// Writes the diagram as an SVG document, flipping `+y` to point down as SVG expects.
+    pub fn write_svg<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let (width, height) = (num(self.width), num(self.height));
+        let y = |v: f64| num(self.height - v);
+        let hex = |c: Rgb| format!("#{:02x}{:02x}{:02x}", c.r, c.g, c.b);
+        let paint = |c: &Option<Rgb>| c.map_or("none".to_string(), hex);
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {width} {height}\">\n"
+        );
+
+        for shape in self.shapes.iter() {
+            let _ = match shape {
+                Shape::Line { line, color, width } => writeln!(
+                    svg,
+                    "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" \
+                     stroke-width=\"{}\"/>",
+                    num(line.x1),
+                    y(line.y1),
+                    num(line.x2),
+                    y(line.y2),
+                    hex(*color),
+                    num(*width)
+                ),
+                Shape::Rect { x, y: bottom, w, h, fill, stroke } => writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" \
+                     stroke=\"{}\" stroke-width=\"0.5\"/>",
+                    num(*x),
+                    y(bottom + h),
+                    num(*w),
+                    num(*h),
+                    paint(fill),
+                    paint(stroke)
+                ),
+                Shape::Text { x, y: base, size, text, color } => writeln!(
+                    svg,
+                    "<text x=\"{}\" y=\"{}\" font-family=\"Helvetica, Arial, sans-serif\" \
+                     font-size=\"{}\" fill=\"{}\">{}</text>",
+                    num(*x),
+                    y(*base),
+                    num(*size),
+                    hex(*color),
+                    escape_xml(text)
+                ),
+            };
+        }
+        svg.push_str("</svg>\n");
+
+        w.write_all(svg.as_bytes())
+    }
+    /// Returns the PDF content stream drawing all shapes.
+    fn pdf_content(&self) -> String {
+        let mut s = String::new();
+        let color = |c: Rgb| {
+            let f = |v: u8| num(v as f64 / 255.0);
+            format!("{} {} {}", f(c.r), f(c.g), f(c.b))
+        };
+
+        for shape in self.shapes.iter() {
+            let _ = match shape {
+                Shape::Line { line, color: c, width } => writeln!(
+                    s,
+                    "{} RG {} w {} {} m {} {} l S",
+                    color(*c),
+                    num(*width),
+                    num(line.x1),
+                    num(line.y1),
+                    num(line.x2),
+                    num(line.y2)
+                ),
+                Shape::Rect { x, y, w, h, fill, stroke } => {
+                    let rect = format!("{} {} {} {} re", num(*x), num(*y), num(*w), num(*h));
+                    match (fill, stroke) {
+                        (Some(f), Some(c)) => writeln!(s, "{} rg {} RG 0.5 w {rect} B", color(*f), color(*c)),
+                        (Some(f), None) => writeln!(s, "{} rg {rect} f", color(*f)),
+                        (None, Some(c)) => writeln!(s, "{} RG 0.5 w {rect} S", color(*c)),
+                        (None, None) => Ok(()),
+                    }
+                }
+                Shape::Text { x, y, size, text, color: c } => writeln!(
+                    s,
+                    "{} rg BT /F1 {} Tf {} {} Td ({}) Tj ET",
+                    color(*c),
+                    num(*size),
+                    num(*x),
+                    num(*y),
+                    escape(text)
+                ),
+            };
+        }
+
+        s
+    }
+}
+
+/// Returns a diagram of one FOV octant: its tiles and FOV lines, in `(pri, sec)` coordinates.
+///
+/// Tiles with `0 <= dsec <= dpri <= radius` are outlined, with the origin tile shaded.
+/// `pri` runs right and `sec` runs up; each tile is `tile_pt` points wide. A caption
+/// summarizing the configuration and FOV `shape` is drawn below the octant.
+pub fn octant_diagram(rfov: FovRadius, qfactor: QFactor, shape: FovShape, tile_pt: f64) -> Diagram {
+    let r = rfov.to_int() as usize;
+    let margin = 2.0 * tile_pt;
+    let caption_pt = tile_pt.clamp(6.0, 12.0);
+    let (left, bottom) = (margin, margin + 2.0 * caption_pt);
+    let side = (r + 1) as f64 * tile_pt;
+    let mut diagram = Diagram::new(side + 2.0 * margin, side + bottom + margin);
+
+    let grid = Rgb::new(150, 150, 160);
+    let origin = Rgb::new(220, 40, 40);
+    let fov = Rgb::new(40, 90, 200);
+
+    for dp in 0..=r {
+        for ds in 0..=dp {
+            let (x, y) = (left + dp as f64 * tile_pt, bottom + ds as f64 * tile_pt);
+            let fill = (dp == 0).then_some(Rgb::new(250, 220, 220));
+            diagram.rect(x, y, tile_pt, tile_pt, fill, Some(grid));
+        }
+    }
+
+    for line in get_fov_lines(rfov, qfactor) {
+        let scaled = Line::new(
+            left + line.x1 * tile_pt,
+            bottom + line.y1 * tile_pt,
+            left + line.x2 * tile_pt,
+            bottom + line.y2 * tile_pt,
+        );
+        diagram.line(scaled, fov, 0.25);
+    }
+
+    let (ox, oy) = (left + 0.5 * tile_pt, bottom + 0.5 * tile_pt);
+    let dot = 0.2 * tile_pt;
+    diagram.rect(ox - dot, oy - dot, 2.0 * dot, 2.0 * dot, Some(origin), None);
+
+    let caption = config_caption("octant", rfov, qfactor, shape);
+    diagram.text(left, margin, caption_pt, &caption, Rgb::BLACK);
+
+    diagram
+}
+
+/// Returns a diagram of _Simple_ FOV octant `octant`: its tiles, FOV lines, and the FOV bit
+/// coverage of each node, in world `(x, y)` orientation around the origin.
+///
+/// Nodes (culled to the default `FovShape`) are shaded by the share of FOV lines
+/// passing through them, from white (none) to blue (all), and labeled with their bit count.
+/// Tiles past the culling radius are only outlined. Each tile is `tile_pt` points wide.
+///
+/// Returns an error for R128 at Q-Factor 2 (Q256), which has no node type.
+pub fn coverage_diagram(
+    rfov: FovRadius,
+    qfactor: QFactor,
+    octant: Octant,
+    tile_pt: f64,
+) -> Result<Diagram, FovError> {
+    let r = rfov.to_int() as usize;
+    let margin = 2.0 * tile_pt;
+    let caption_pt = tile_pt.clamp(6.0, 12.0);
+    let (left, bottom) = (margin, margin + 2.0 * caption_pt);
+    let side = (r + 1) as f64 * tile_pt;
+    let mut diagram = Diagram::new(side + 2.0 * margin, side + bottom + margin);
+
+    // Octant `(pri, sec)` points to diagram points: axes swap and flip per octant, so the
+    // origin tile lands in the corner of the octant's own quadrant
+    let (pri, sec) = (octant.dpds_to_delta(1, 0), octant.dpds_to_delta(0, 1));
+    let flip = |v: f64, sign: i32| match sign < 0 {
+        true => (r + 1) as f64 - v,
+        false => v,
+    };
+    let point = |p: f64, s: f64| match pri.dx != 0 {
+        true => (left + flip(p, pri.dx) * tile_pt, bottom + flip(s, sec.dy) * tile_pt),
+        false => (left + flip(s, sec.dx) * tile_pt, bottom + flip(p, pri.dy) * tile_pt),
+    };
+    let tile = |dp: usize, ds: usize| {
+        let (x, y) = point(dp as f64 + 0.5, ds as f64 + 0.5);
+        (x - 0.5 * tile_pt, y - 0.5 * tile_pt)
+    };
+
+    let fov_lines = FovLines::new(rfov, qfactor);
+    let nodes = build_fov_nodes::<u128>(rfov, &fov_lines, FovShape::default())?;
+    let total = fov_lines.len() as f64;
+    let grid = Rgb::new(150, 150, 160);
+    let shade = |bits: u32| {
+        let t = bits as f64 / total;
+        let lerp = |a: f64, b: f64| (a + (b - a) * t).round() as u8;
+        Rgb::new(lerp(255.0, 90.0), lerp(255.0, 150.0), lerp(255.0, 230.0))
+    };
+
+    for dp in 0..=r {
+        for ds in 0..=dp {
+            let (x, y) = tile(dp, ds);
+            diagram.rect(x, y, tile_pt, tile_pt, None, Some(grid));
+        }
+    }
+    let label_pt = 0.35 * tile_pt;
+    for node in nodes.iter() {
+        let (x, y) = tile(node.dpri as usize, node.dsec as usize);
+        let bits = node.body.count_ones();
+        diagram.rect(x, y, tile_pt, tile_pt, Some(shade(bits)), Some(grid));
+        let label = bits.to_string();
+        diagram.text(x + 0.15 * tile_pt, y + 0.35 * tile_pt, label_pt, &label, Rgb::BLACK);
+    }
+
+    let fov = Rgb::new(200, 60, 40);
+    for line in fov_lines.iter() {
+        let ((x1, y1), (x2, y2)) = (point(line.x1, line.y1), point(line.x2, line.y2));
+        diagram.line(Line::new(x1, y1, x2, y2), fov, 0.25);
+    }
+
+    let algorithm = format!("coverage {octant:?}");
+    let caption = config_caption(&algorithm, rfov, qfactor, FovShape::default());
+    diagram.text(left, margin, caption_pt, &caption, Rgb::BLACK);
+
+    Ok(diagram)
+}
+
+/// Formats `v` as a PDF real number, with at most 3 decimal places.
+fn num(v: f64) -> String {
+    let s = format!("{v:.3}");
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+
+    match s {
+        "-0" | "" => "0".to_string(),
+        _ => s.to_string(),
+    }
+}
+
+/// Escapes `text` for use in a PDF string literal.
+fn escape(text: &str) -> String {
+    let mut s = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                s.push('\\');
+                s.push(c);
+            }
+            ' '..='~' => s.push(c),
+            _ => s.push('?'),
+        }
+    }
+    s
+}
+
+/// Escapes `text` for use in SVG (XML) text content.
+fn escape_xml(text: &str) -> String {
+    let mut s = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => s.push_str("&amp;"),
+            '<' => s.push_str("&lt;"),
+            '>' => s.push_str("&gt;"),
+            _ => s.push(c),
+        }
+    }
+    s
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One outlined tile per octant node, one line per FOV line, plus origin and caption.
+    #[test]
+    fn octant_diagram_shapes() {
+        let diagram = octant_diagram(FovRadius::R16, QFactor::Double, FovShape::default(), 10.0);
+        let lines = diagram.shapes().iter().filter(|s| matches!(s, Shape::Line { .. })).count();
+        let tiles = diagram.shapes().iter().filter(|s| matches!(s, Shape::Rect { .. })).count();
+
+        assert_eq!(lines, 32);
+        assert_eq!(tiles, 17 * 18 / 2 + 1);
+        assert_eq!(diagram.width(), 210.0);
+        assert!(diagram.shapes().contains(&Shape::Text {
+            x: 20.0,
+            y: 20.0,
+            size: 10.0,
+            text: "octant R16 Q32 circle 0.50".to_string(),
+            color: Rgb::BLACK,
+        }));
+    }
+
+    // Every node is shaded by coverage; octants mirror the origin into their own corner.
+    #[test]
+    fn coverage_diagram_octants() {
+        let (rfov, qfactor) = (FovRadius::R8, QFactor::Single);
+        let fov_lines = FovLines::new(rfov, qfactor);
+        let nodes = build_fov_nodes::<u128>(rfov, &fov_lines, FovShape::default()).unwrap().len();
+        let (left, right, bottom, top) = (20.0, 100.0, 40.0, 120.0);
+        let origins = [
+            (Octant::O1, left, bottom),
+            (Octant::O2, left, bottom),
+            (Octant::O3, right, bottom),
+            (Octant::O4, right, bottom),
+            (Octant::O5, right, top),
+            (Octant::O6, right, top),
+            (Octant::O7, left, top),
+            (Octant::O8, left, top),
+        ];
+
+        for (octant, x, y) in origins {
+            let diagram = coverage_diagram(rfov, qfactor, octant, 10.0).unwrap();
+            let fills: Vec<_> = diagram
+                .shapes()
+                .iter()
+                .filter_map(|s| match s {
+                    Shape::Rect { x, y, fill: Some(fill), .. } => Some((*x, *y, *fill)),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(fills.len(), nodes);
+            // Every FOV line crosses the origin node
+            assert!(fills.contains(&(x, y, Rgb::new(90, 150, 230))), "{octant:?}");
+        }
+    }
+
+    // SVG output flips `y` and escapes text.
+    #[test]
+    fn write_svg_shapes() {
+        let mut diagram = Diagram::new(100.0, 50.0);
+        diagram.line(Line::new(0.0, 0.0, 100.0, 50.0), Rgb::BLACK, 1.0);
+        diagram.rect(10.0, 5.0, 20.0, 10.0, Some(Rgb::WHITE), None);
+        diagram.text(5.0, 5.0, 8.0, "a < b & c", Rgb::new(255, 0, 16));
+
+        let mut svg = Vec::new();
+        diagram.write_svg(&mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+
+        assert!(svg.contains("viewBox=\"0 0 100 50\""));
+        assert!(svg.contains("<line x1=\"0\" y1=\"50\" x2=\"100\" y2=\"0\" stroke=\"#000000\""));
+        assert!(svg.contains("<rect x=\"10\" y=\"35\" width=\"20\" height=\"10\" fill=\"#ffffff\" \
+                              stroke=\"none\""));
+        assert!(svg.contains("fill=\"#ff0010\">a &lt; b &amp; c</text>"));
+    }
+
+    // Cross-reference offsets point at their objects, so readers can parse the file.
+    #[test]
+    fn write_pdf_structure() {
+        let mut diagram = Diagram::new(100.0, 50.0);
+        diagram.line(Line::new(0.0, 0.0, 100.0, 50.0), Rgb::BLACK, 1.0);
+        diagram.text(5.0, 5.0, 8.0, "f(x) \\ 1", Rgb::BLACK);
+
+        let mut pdf = Vec::new();
+        diagram.write_pdf(&mut pdf).unwrap();
+        let pdf = String::from_utf8(pdf).unwrap();
+
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("/MediaBox [0 0 100 50]"));
+        assert!(pdf.contains("0 0 0 RG 1 w 0 0 m 100 50 l S"));
+        assert!(pdf.contains("(f\\(x\\) \\\\ 1) Tj"));
+
+        let xref: usize = pdf.lines().rev().nth(1).unwrap().parse().unwrap();
+        assert!(pdf[xref..].starts_with("xref\n0 6\n"));
+        for (i, entry) in pdf[xref..].lines().skip(3).take(5).enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj\n", i + 1)));
+        }
+    }
+}
@@ -1 +1,1072 @@
 //! Drawing functionality for FOV Visualization - Rust (2D)
+//!
+//! Notes:
+//! - A `Canvas` is an RGB raster image, written out as PNG, BMP or PPM (see `save`).
+//! - A `Renderer` draws a `TileMap` and its `VisibilityGrid` onto a `Canvas` in layers:
+//!   tiles, visible tiles, grid lines, origin, then annotations.
+//! - Map `+y` is north, so tile row `y = 0` is drawn at the _bottom_ of the image.
+//! - An optional footer holds a caption and a legend, so exported figures are self-describing.
+//! - `compose_grid` tiles several labeled renders into a single comparison figure.
+//! - `Renderer::render_with` takes a per-tile `Rgba` color callback for game-specific skins,
+//!   composited over the palette colors.
+//! - `WaterReflection` is such a callback: water tiles mirror the visible tile north of them.
+//! - `Minimap` draws a 1 to 2 pixel per tile overview of explored memory and current FOV,
+//!   standalone or inset into a main render.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::diagram::coverage_diagram;
+use super::error::FovError;
+use super::font::{self, ADVANCE, GLYPH_H, GLYPH_W};
+use super::fov::FovShape;
+use super::image::{encode_bmp, encode_png};
+use super::maps::{Coords, Direction, Tile, TileMap};
+use super::visibility::VisibilityGrid;
+use crate::{FovRadius, Octant, QFactor};
+
+/// 24-bit RGB color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb::new(0, 0, 0);
+    pub const WHITE: Rgb = Rgb::new(255, 255, 255);
+
+    /// Creates a new `Rgb` color.
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
+/// 32-bit RGBA color, composited over an `Rgb` background by alpha (`0` is transparent).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Rgba {
+    pub const TRANSPARENT: Rgba = Rgba::new(0, 0, 0, 0);
+
+    /// Creates a new `Rgba` color.
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+    /// Creates a new, fully opaque `Rgba` color from `rgb`.
+    pub const fn opaque(rgb: Rgb) -> Self {
+        Self::new(rgb.r, rgb.g, rgb.b, u8::MAX)
+    }
+    /// Returns this color composited over `base` (source-over, rounded).
+    pub fn over(&self, base: Rgb) -> Rgb {
+        let a = self.a as u32;
+        let mix = |top: u8, bottom: u8| {
+            ((top as u32 * a + bottom as u32 * (255 - a) + 127) / 255) as u8
+        };
+        Rgb::new(mix(self.r, base.r), mix(self.g, base.g), mix(self.b, base.b))
+    }
+}
+
+/// Visibility of a tile passed to `Renderer::render_with` color callbacks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Visibility {
+    Hidden,
+    Visible,
+}
+
+/// RGB raster image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Canvas {
+    /// Creates a new `Canvas` filled with color `fill`.
+    pub fn new(width: usize, height: usize, fill: Rgb) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![fill; width * height],
+        }
+    }
+    /// Returns the width of the canvas, in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    /// Returns the height of the canvas, in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    /// Returns the pixel color at `(x, y)`, or `None` if out of bounds.
+    pub fn get(&self, x: i32, y: i32) -> Option<Rgb> {
+        self.index(x, y).map(|i| self.pixels[i])
+    }
+    /// Sets the pixel at `(x, y)` to `color`. Out of bounds pixels are ignored.
+    pub fn set(&mut self, x: i32, y: i32, color: Rgb) {
+        if let Some(i) = self.index(x, y) {
+            self.pixels[i] = color;
+        }
+    }
+    /// Fills a `w` by `h` rectangle with top-left corner `(x, y)`.
+    pub fn fill_rect(&mut self, x: i32, y: i32, w: i32, h: i32, color: Rgb) {
+        for py in y..y + h {
+            for px in x..x + w {
+                self.set(px, py, color);
+            }
+        }
+    }
+    /// Draws the outline of a `w` by `h` rectangle with top-left corner `(x, y)`.
+    pub fn draw_rect(&mut self, x: i32, y: i32, w: i32, h: i32, color: Rgb) {
+        self.draw_line(x, y, x + w - 1, y, color);
+        self.draw_line(x, y + h - 1, x + w - 1, y + h - 1, color);
+        self.draw_line(x, y, x, y + h - 1, color);
+        self.draw_line(x + w - 1, y, x + w - 1, y + h - 1, color);
+    }
+    /// Draws a line from `(x0, y0)` to `(x1, y1)` (Bresenham).
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Rgb) {
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let (mut x, mut y, mut err) = (x0, y0, dx + dy);
+
+        loop {
+            self.set(x, y, color);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+    /// Fills a circle of radius `r` centered on `(cx, cy)`.
+    pub fn fill_circle(&mut self, cx: i32, cy: i32, r: i32, color: Rgb) {
+        for py in -r..=r {
+            for px in -r..=r {
+                if px * px + py * py <= r * r {
+                    self.set(cx + px, cy + py, color);
+                }
+            }
+        }
+    }
+    /// Draws `text` with its top-left corner at `(x, y)`, scaling glyphs by `scale`.
+    pub fn draw_text(&mut self, x: i32, y: i32, text: &str, color: Rgb, scale: usize) {
+        let s = scale as i32;
+
+        for (i, c) in text.chars().enumerate() {
+            let gx = x + (i * ADVANCE * scale) as i32;
+            for (row, bits) in font::glyph(c).iter().enumerate() {
+                for col in 0..GLYPH_W {
+                    if bits & (1 << (GLYPH_W - 1 - col)) != 0 {
+                        self.fill_rect(gx + col as i32 * s, y + row as i32 * s, s, s, color);
+                    }
+                }
+            }
+        }
+    }
+    /// Copies all pixels of `other` onto `self`, with `other`'s top-left corner at `(x, y)`.
+    pub fn blit(&mut self, other: &Canvas, x: i32, y: i32) {
+        for oy in 0..other.height {
+            for ox in 0..other.width {
+                self.set(x + ox as i32, y + oy as i32, other.pixels[oy * other.width + ox]);
+            }
+        }
+    }
+    /// Writes the canvas as a binary PPM (`P6`) image.
+    pub fn write_ppm<W: Write>(&self, mut w: W) -> io::Result<()> {
+        write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+        w.write_all(&self.rgb_bytes())
+    }
+    /// Writes the canvas as a PNG image.
+    pub fn write_png<W: Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(&encode_png(self.width, self.height, &self.rgb_bytes()))
+    }
+    /// Writes the canvas as a 24-bit BMP image.
+    pub fn write_bmp<W: Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(&encode_bmp(self.width, self.height, &self.rgb_bytes()))
+    }
+    /// Writes the canvas in image `format`.
+    pub fn write_image<W: Write>(&self, w: W, format: ImageFormat) -> io::Result<()> {
+        match format {
+            ImageFormat::Png => self.write_png(w),
+            ImageFormat::Bmp => self.write_bmp(w),
+            ImageFormat::Ppm => self.write_ppm(w),
+        }
+    }
+    /// Returns the pixels as row-major RGB triples.
+    fn rgb_bytes(&self) -> Vec<u8> {
+        self.pixels.iter().flat_map(|p| [p.r, p.g, p.b]).collect()
+    }
+    /// Returns the pixel index of `(x, y)`, or `None` if out of bounds.
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        let in_bounds = x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height;
+
+        match in_bounds {
+            true => Some(y as usize * self.width + x as usize),
+            false => None,
+        }
+    }
+}
+
+/// Image file format for saving a `Canvas`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ImageFormat {
+    /// Compressed PNG; viewable everywhere. The default.
+    #[default]
+    Png,
+    /// Uncompressed 24-bit BMP.
+    Bmp,
+    /// Binary PPM (`P6`); simplest to parse.
+    Ppm,
+}
+
+impl ImageFormat {
+    /// Returns the format matching the extension of `path` (`png`, `bmp`, `ppm`), if any.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "png" => Some(ImageFormat::Png),
+            "bmp" => Some(ImageFormat::Bmp),
+            "ppm" => Some(ImageFormat::Ppm),
+            _ => None,
+        }
+    }
+    /// Returns the file extension of the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Bmp => "bmp",
+            ImageFormat::Ppm => "ppm",
+        }
+    }
+}
+
+/// Saves `canvas` to `path` in image `format`.
+///
+/// This is the sink for every figure-producing feature; pick the format with
+/// `ImageFormat::from_path` to follow the file extension.
+pub fn save<P: AsRef<Path>>(path: P, canvas: &Canvas, format: ImageFormat) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    canvas.write_image(&mut writer, format)?;
+    writer.flush()
+}
+
+/// Renders `map`, its visible tiles in `grid` and the `origin` with the default `Renderer`,
+/// and saves the render to `path` as PNG.
+///
+/// Opaque tiles, floors, their visible counterparts and the origin marker are drawn in the
+/// default `Palette` colors. For captions, legends, annotations or other formats, use a
+/// `Renderer` and `save` directly.
+pub fn render_fov_png<P: AsRef<Path>>(
+    map: &TileMap,
+    grid: &VisibilityGrid,
+    origin: Coords,
+    path: P,
+) -> io::Result<()> {
+    let canvas = Renderer::default().render(map, grid, origin);
+    save(path, &canvas, ImageFormat::Png)
+}
+
+/// Saves a `diagram::coverage_diagram` of _Simple_ FOV octant `octant` to `path` as SVG: its
+/// tile grid, quantized FOV lines and per-node FOV bit coverage.
+///
+/// Returns an error for R128 at Q-Factor 2 (Q256), which has no node type.
+pub fn render_fov_lines_svg<P: AsRef<Path>>(
+    rfov: FovRadius,
+    qfactor: QFactor,
+    octant: Octant,
+    path: P,
+) -> Result<(), FovError> {
+    let diagram = coverage_diagram(rfov, qfactor, octant, 16.0)?;
+    let mut writer = BufWriter::new(File::create(path)?);
+    diagram.write_svg(&mut writer)?;
+    Ok(writer.flush()?)
+}
+
+/// Tile annotation drawn on top of the FOV layers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Marker {
+    /// Observer: filled circle.
+    Observer,
+    /// Target: diagonal cross.
+    Target,
+    /// Light source: filled diamond.
+    Light,
+    /// Guard: square outline.
+    Guard,
+}
+
+impl Marker {
+    /// Returns the legend label of the marker.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Marker::Observer => "observer",
+            Marker::Target => "target",
+            Marker::Light => "light",
+            Marker::Guard => "guard",
+        }
+    }
+    /// Returns the color used to draw the marker.
+    pub fn color(&self) -> Rgb {
+        match self {
+            Marker::Observer => Rgb::new(220, 40, 40),
+            Marker::Target => Rgb::new(250, 140, 0),
+            Marker::Light => Rgb::new(255, 230, 60),
+            Marker::Guard => Rgb::new(60, 110, 230),
+        }
+    }
+}
+
+/// Colors used by the `Renderer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub background: Rgb,
+    pub floor: Rgb,
+    pub wall: Rgb,
+    pub visible_floor: Rgb,
+    pub visible_wall: Rgb,
+    pub grid: Rgb,
+    pub origin: Rgb,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            background: Rgb::BLACK,
+            floor: Rgb::new(40, 40, 48),
+            wall: Rgb::new(90, 90, 100),
+            visible_floor: Rgb::new(210, 200, 150),
+            visible_wall: Rgb::new(245, 245, 245),
+            grid: Rgb::new(20, 20, 24),
+            origin: Marker::Observer.color(),
+        }
+    }
+}
+
+/// Renders tile maps and FOV results to a `Canvas`.
+#[derive(Debug, Clone)]
+pub struct Renderer {
+    /// Size of one tile, in pixels.
+    pub tile_px: usize,
+    /// Colors for each layer.
+    pub palette: Palette,
+    /// If `true`, tile boundaries are outlined.
+    pub grid_lines: bool,
+    /// Markers drawn on top of the FOV layers.
+    pub annotations: Vec<(Coords, Marker)>,
+    /// If `true`, a legend (color to meaning) is drawn in the footer.
+    pub legend: bool,
+    /// Caption drawn in the footer, such as a `config_caption` summary.
+    pub caption: Option<String>,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Self {
+            tile_px: 16,
+            palette: Palette::default(),
+            grid_lines: true,
+            annotations: Vec::new(),
+            legend: false,
+            caption: None,
+        }
+    }
+}
+
+impl Renderer {
+    /// Creates a new `Renderer` with `tile_px` pixels per tile.
+    pub fn new(tile_px: usize) -> Self {
+        Self {
+            tile_px,
+            ..Self::default()
+        }
+    }
+    /// Adds a `marker` annotation at `coords`.
+    pub fn annotate(&mut self, coords: Coords, marker: Marker) {
+        self.annotations.push((coords, marker));
+    }
+    /// Renders `map` from each observer's view side by side, as a split screen.
+    ///
+    /// `views` pairs each observer with their visible tiles. Each panel marks the other
+    /// observers as `Marker::Target`s and is labeled with the observers it sees; a `!` flags
+    /// one that does not see it back, exposing asymmetric FOV between the two.
+    pub fn render_split(&self, map: &TileMap, views: &[(Coords, &VisibilityGrid)]) -> Canvas {
+        let labels = split_labels(views);
+        let mut panels = Vec::with_capacity(views.len());
+
+        for (i, &(observer, grid)) in views.iter().enumerate() {
+            let mut renderer = self.clone();
+            for (j, &(other, _)) in views.iter().enumerate() {
+                if j != i {
+                    renderer.annotate(other, Marker::Target);
+                }
+            }
+            panels.push(renderer.render(map, grid, observer));
+        }
+
+        let renders: Vec<_> = labels.iter().map(String::as_str).zip(panels.iter()).collect();
+        compose_grid(&renders, views.len())
+    }
+    /// Renders `map`, its visible tiles in `grid`, and the `origin` to a new `Canvas`.
+    pub fn render(&self, map: &TileMap, grid: &VisibilityGrid, origin: Coords) -> Canvas {
+        self.render_with(map, grid, origin, |_, _, _| Rgba::TRANSPARENT)
+    }
+    /// Renders like `render`, with each tile's color chosen by `color`.
+    ///
+    /// `color` receives each tile's coordinates, tile and visibility, and returns a color
+    /// composited over the palette color for that tile: fully opaque colors replace it
+    /// (terrain skins), translucent ones tint it (highlights). Grid lines, the origin,
+    /// annotations and the footer are drawn on top as usual.
+    pub fn render_with<F>(
+        &self,
+        map: &TileMap,
+        grid: &VisibilityGrid,
+        origin: Coords,
+        color: F,
+    ) -> Canvas
+    where
+        F: Fn(Coords, &Tile, Visibility) -> Rgba,
+    {
+        let px = self.tile_px as i32;
+        let pal = &self.palette;
+        let mut canvas =
+            Canvas::new(map.width() * self.tile_px, map.height() * self.tile_px, pal.background);
+
+        for y in 0..map.height() as i32 {
+            for x in 0..map.width() as i32 {
+                let coords = Coords::new(x, y);
+                let tile = map.get(coords).copied().unwrap_or_default();
+                let visibility = match grid.contains(coords) {
+                    true => Visibility::Visible,
+                    false => Visibility::Hidden,
+                };
+                let base = self.tile_color(&tile, visibility);
+                let (left, top) = self.tile_origin(map, coords);
+                canvas.fill_rect(left, top, px, px, color(coords, &tile, visibility).over(base));
+                if self.grid_lines && px > 2 {
+                    canvas.draw_rect(left, top, px, px, pal.grid);
+                }
+            }
+        }
+
+        if map.in_bounds(origin) {
+            let (cx, cy) = self.tile_center(map, origin);
+            canvas.fill_circle(cx, cy, (px / 3).max(1), pal.origin);
+        }
+
+        for (coords, marker) in self.annotations.iter() {
+            self.draw_marker(&mut canvas, map, *coords, *marker);
+        }
+
+        match self.legend || self.caption.is_some() {
+            true => self.add_footer(canvas),
+            false => canvas,
+        }
+    }
+    /// Draws `contours` of tile corners (see `analysis::visible_outline`) onto `canvas`,
+    /// a render of `map`, as lines along tile edges.
+    pub fn draw_outline(
+        &self,
+        canvas: &mut Canvas,
+        map: &TileMap,
+        contours: &[Vec<Coords>],
+        color: Rgb,
+    ) {
+        let px = self.tile_px as i32;
+        let (right, bottom) = (map.width() as i32 * px - 1, map.height() as i32 * px - 1);
+        // Corners on the east and south map edges fall just outside the map's pixels
+        let pixel = |c: Coords| {
+            let row = map.height() as i32 - c.y;
+            ((c.x * px).min(right), (row * px).min(bottom))
+        };
+
+        for contour in contours {
+            for (i, &corner) in contour.iter().enumerate() {
+                let next = contour[(i + 1) % contour.len()];
+                let ((x0, y0), (x1, y1)) = (pixel(corner), pixel(next));
+                canvas.draw_line(x0, y0, x1, y1, color);
+            }
+        }
+    }
+    /// Returns the palette color of `tile` with `visibility`, before any color callback.
+    pub fn tile_color(&self, tile: &Tile, visibility: Visibility) -> Rgb {
+        let pal = &self.palette;
+        match (visibility, tile.body_opaque) {
+            (Visibility::Visible, true) => pal.visible_wall,
+            (Visibility::Visible, false) => pal.visible_floor,
+            (Visibility::Hidden, true) => pal.wall,
+            (Visibility::Hidden, false) => pal.floor,
+        }
+    }
+    /// Returns the legend entries for the current palette and annotations.
+    pub fn legend_entries(&self) -> Vec<(Rgb, String)> {
+        let pal = &self.palette;
+        let mut entries = vec![
+            (pal.floor, "floor".to_string()),
+            (pal.wall, "wall".to_string()),
+            (pal.visible_floor, "visible floor".to_string()),
+            (pal.visible_wall, "visible wall".to_string()),
+            (pal.origin, "origin".to_string()),
+        ];
+
+        for (_, marker) in self.annotations.iter() {
+            let entry = (marker.color(), marker.label().to_string());
+            if !entries.contains(&entry) {
+                entries.push(entry);
+            }
+        }
+
+        entries
+    }
+    /// Returns a new canvas with `map_canvas` on top and a caption/legend footer below.
+    fn add_footer(&self, map_canvas: Canvas) -> Canvas {
+        let scale = (self.tile_px / 8).max(1);
+        let pad = 4 * scale;
+        let line_h = (GLYPH_H + 3) * scale;
+        let width = map_canvas.width();
+        let max_chars = (width.saturating_sub(2 * pad) / (ADVANCE * scale)).max(1);
+
+        // Caption, wrapped to the canvas width
+        let caption: Vec<char> = self.caption.as_deref().unwrap_or("").chars().collect();
+        let lines: Vec<String> = caption.chunks(max_chars).map(String::from_iter).collect();
+
+        // Legend: color swatch and label per entry, flowing left to right
+        let mut swatches = Vec::new();
+        let (mut x, mut y) = (pad, pad + lines.len() * line_h);
+        if self.legend {
+            for (color, label) in self.legend_entries() {
+                let entry_w = (GLYPH_W + 3) * scale + font::text_width(&label, scale);
+                if x > pad && x + entry_w > width.saturating_sub(pad) {
+                    x = pad;
+                    y += line_h;
+                }
+                swatches.push((x, y, color, label));
+                x += entry_w + 3 * pad;
+            }
+            y += line_h;
+        }
+
+        let footer_h = y + pad;
+        let mut canvas =
+            Canvas::new(width, map_canvas.height() + footer_h, self.palette.background);
+        let top = map_canvas.height();
+        canvas.blit(&map_canvas, 0, 0);
+
+        for (i, line) in lines.iter().enumerate() {
+            let ly = top + pad + i * line_h;
+            canvas.draw_text(pad as i32, ly as i32, line, Rgb::WHITE, scale);
+        }
+        for (sx, sy, color, label) in swatches {
+            let (sx, sy) = (sx as i32, (top + sy) as i32);
+            let side = (GLYPH_H * scale) as i32;
+            canvas.fill_rect(sx, sy, side, side, color);
+            canvas.draw_rect(sx, sy, side, side, Rgb::WHITE);
+            canvas.draw_text(sx + side + 2 * scale as i32, sy, &label, Rgb::WHITE, scale);
+        }
+
+        canvas
+    }
+    /// Draws a single `marker` at `coords`.
+    fn draw_marker(&self, canvas: &mut Canvas, map: &TileMap, coords: Coords, marker: Marker) {
+        if !map.in_bounds(coords) {
+            return;
+        }
+        let (cx, cy) = self.tile_center(map, coords);
+        let r = (self.tile_px as i32 / 3).max(1);
+        let color = marker.color();
+
+        match marker {
+            Marker::Observer => canvas.fill_circle(cx, cy, r, color),
+            Marker::Target => {
+                for t in -1..=1 {
+                    canvas.draw_line(cx - r + t, cy - r, cx + r + t, cy + r, color);
+                    canvas.draw_line(cx - r + t, cy + r, cx + r + t, cy - r, color);
+                }
+            }
+            Marker::Light => {
+                for dy in -r..=r {
+                    let half = r - dy.abs();
+                    canvas.draw_line(cx - half, cy + dy, cx + half, cy + dy, color);
+                }
+            }
+            Marker::Guard => {
+                canvas.draw_rect(cx - r, cy - r, 2 * r + 1, 2 * r + 1, color);
+                canvas.draw_rect(cx - r + 1, cy - r + 1, 2 * r - 1, 2 * r - 1, color);
+            }
+        }
+    }
+    /// Returns the top-left pixel of the tile at `coords`.
+    fn tile_origin(&self, map: &TileMap, coords: Coords) -> (i32, i32) {
+        let px = self.tile_px as i32;
+        let row = map.height() as i32 - 1 - coords.y;
+        (coords.x * px, row * px)
+    }
+    /// Returns the center pixel of the tile at `coords`.
+    fn tile_center(&self, map: &TileMap, coords: Coords) -> (i32, i32) {
+        let (left, top) = self.tile_origin(map, coords);
+        let half = self.tile_px as i32 / 2;
+        (left + half, top + half)
+    }
+}
+
+/// Mirror layer for water tiles, used as a `Renderer::render_with` color callback.
+///
+/// Tiles tagged `water_tag` are water. A visible water tile reflects the tile directly north
+/// of it (`+y`, drawn above it) if that tile is visible too: the tile's palette color is
+/// blended over `tint` by `strength` (`0` to `255`). Other water shows plain `tint`, faded
+/// when hidden. Purely visual: FOV treats water as ordinary floor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WaterReflection {
+    pub water_tag: u8,
+    pub tint: Rgb,
+    pub strength: u8,
+}
+
+impl WaterReflection {
+    /// Creates a new `WaterReflection` for tiles tagged `water_tag`, with a blue tint.
+    pub fn new(water_tag: u8) -> Self {
+        Self {
+            water_tag,
+            tint: Rgb::new(40, 90, 170),
+            strength: 110,
+        }
+    }
+    /// Returns a `render_with` color callback drawing reflections of `map` as seen in `grid`.
+    pub fn layer<'a>(
+        &'a self,
+        renderer: &'a Renderer,
+        map: &'a TileMap,
+        grid: &'a VisibilityGrid,
+    ) -> impl Fn(Coords, &Tile, Visibility) -> Rgba + 'a {
+        move |coords, tile, visibility| {
+            if tile.tag != self.water_tag {
+                return Rgba::TRANSPARENT;
+            }
+            let above = coords.step(Direction::North);
+            match (visibility, map.get(above)) {
+                (Visibility::Visible, Some(mirrored)) if grid.contains(above) => {
+                    let color = renderer.tile_color(mirrored, Visibility::Visible);
+                    let reflected = Rgba::new(color.r, color.g, color.b, self.strength);
+                    Rgba::opaque(reflected.over(self.tint))
+                }
+                (Visibility::Visible, _) => Rgba::opaque(self.tint),
+                (Visibility::Hidden, _) => Rgba::new(self.tint.r, self.tint.g, self.tint.b, 96),
+            }
+        }
+    }
+}
+
+/// Compact overview of a map (1 or 2 pixels per tile), for HUDs and quick-glance output.
+///
+/// Tiles in the current FOV are bright, remembered (explored) tiles are dim, and the rest
+/// is `unexplored`. The observer is a small cross, so it stays visible at 1 pixel per tile.
+/// The caller keeps explored memory, e.g. by `union_with` of each turn's FOV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Minimap {
+    /// Size of one tile, in pixels.
+    pub tile_px: usize,
+    pub unexplored: Rgb,
+    pub explored_floor: Rgb,
+    pub explored_wall: Rgb,
+    pub visible_floor: Rgb,
+    pub visible_wall: Rgb,
+    pub observer: Rgb,
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        let pal = Palette::default();
+        Self {
+            tile_px: 2,
+            unexplored: Rgb::BLACK,
+            explored_floor: pal.floor,
+            explored_wall: pal.wall,
+            visible_floor: pal.visible_floor,
+            visible_wall: pal.visible_wall,
+            observer: pal.origin,
+        }
+    }
+}
+
+impl Minimap {
+    /// Creates a new `Minimap` with `tile_px` pixels per tile. Panics if `tile_px` is `0`.
+    pub fn new(tile_px: usize) -> Self {
+        assert!(tile_px > 0, "Minimap requires at least 1 pixel per tile!");
+        Self {
+            tile_px,
+            ..Self::default()
+        }
+    }
+    /// Renders `map` with `explored` memory, the current FOV in `visible`, and the `observer`.
+    pub fn render(
+        &self,
+        map: &TileMap,
+        explored: &VisibilityGrid,
+        visible: &VisibilityGrid,
+        observer: Coords,
+    ) -> Canvas {
+        let px = self.tile_px as i32;
+        let (width, height) = (map.width() * self.tile_px, map.height() * self.tile_px);
+        let mut canvas = Canvas::new(width, height, self.unexplored);
+
+        for coords in explored.iter().chain(visible.iter()) {
+            let Some(tile) = map.get(coords) else {
+                continue;
+            };
+            let color = match (visible.contains(coords), tile.body_opaque) {
+                (true, true) => self.visible_wall,
+                (true, false) => self.visible_floor,
+                (false, true) => self.explored_wall,
+                (false, false) => self.explored_floor,
+            };
+            let top = (map.height() as i32 - 1 - coords.y) * px;
+            canvas.fill_rect(coords.x * px, top, px, px, color);
+        }
+
+        if map.in_bounds(observer) {
+            let (cx, cy) = (observer.x * px, (map.height() as i32 - 1 - observer.y) * px);
+            canvas.fill_rect(cx - px, cy, 3 * px, px, self.observer);
+            canvas.fill_rect(cx, cy - px, px, 3 * px, self.observer);
+        }
+
+        canvas
+    }
+    /// Renders like `render` and draws the result, framed, in the top-right corner of `canvas`.
+    pub fn render_inset(
+        &self,
+        canvas: &mut Canvas,
+        map: &TileMap,
+        explored: &VisibilityGrid,
+        visible: &VisibilityGrid,
+        observer: Coords,
+    ) {
+        let minimap = self.render(map, explored, visible, observer);
+        let (w, h) = (minimap.width() as i32, minimap.height() as i32);
+        let (x, y) = (canvas.width() as i32 - w - 4, 4);
+        canvas.draw_rect(x - 1, y - 1, w + 2, h + 2, self.visible_wall);
+        canvas.blit(&minimap, x, y);
+    }
+}
+
+/// Returns split screen panel labels: each observer, then the observers it sees (`!` if
+/// not seen back).
+fn split_labels(views: &[(Coords, &VisibilityGrid)]) -> Vec<String> {
+    let mut labels = Vec::with_capacity(views.len());
+
+    for (i, &(observer, grid)) in views.iter().enumerate() {
+        let mut label = format!("#{} {},{} sees", i + 1, observer.x, observer.y);
+        for (j, &(other, other_grid)) in views.iter().enumerate().filter(|&(j, _)| j != i) {
+            if grid.contains(other) {
+                let flag = match other_grid.contains(observer) {
+                    true => "",
+                    false => "!",
+                };
+                label.push_str(&format!(" #{}{flag}", j + 1));
+            }
+        }
+        labels.push(label);
+    }
+
+    labels
+}
+
+/// Tiles labeled `renders` into a single comparison image, `cols` renders per row.
+///
+/// Cells are sized to the largest render; each label is drawn above its render.
+pub fn compose_grid(renders: &[(&str, &Canvas)], cols: usize) -> Canvas {
+    let cols = cols.clamp(1, renders.len().max(1));
+    let rows = renders.len().div_ceil(cols);
+    let cell_w = renders.iter().map(|(_, c)| c.width()).max().unwrap_or(0);
+    let cell_h = renders.iter().map(|(_, c)| c.height()).max().unwrap_or(0);
+    let scale = (cell_w / 256).max(1);
+    let pad = 4 * scale;
+    let label_h = (GLYPH_H + 3) * scale;
+
+    let width = cols * (cell_w + pad) + pad;
+    let height = rows * (label_h + cell_h + pad) + pad;
+    let mut canvas = Canvas::new(width, height, Rgb::BLACK);
+
+    for (i, (label, render)) in renders.iter().enumerate() {
+        let x = pad + (i % cols) * (cell_w + pad);
+        let y = pad + (i / cols) * (label_h + cell_h + pad);
+        canvas.draw_text(x as i32, y as i32, label, Rgb::WHITE, scale);
+        canvas.blit(render, x as i32, (y + label_h) as i32);
+    }
+
+    canvas
+}
+
+/// Returns a figure caption summarizing an FOV configuration.
+///
+/// Example: `"simple R16 Q16 circle 0.50"` for the default `FovShape`.
+pub fn config_caption(
+    algorithm: &str,
+    rfov: FovRadius,
+    qfactor: QFactor,
+    shape: FovShape,
+) -> String {
+    let r = rfov.to_int() as usize;
+    let q = r * qfactor.to_int() as usize;
+    format!("{algorithm} R{r} Q{q} {shape}")
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Snapshots are PNG files at the default tile size, with distinct layer colors.
+    #[test]
+    fn render_fov_png_snapshot() {
+        let mut map = TileMap::new(4, 3).unwrap();
+        map.set_body_opaque(Coords::new(3, 0), true);
+        let mut grid = VisibilityGrid::for_map(&map);
+        grid.insert(Coords::new(0, 0));
+        grid.insert(Coords::new(1, 0));
+
+        let path = std::env::temp_dir().join(format!("fov2d_snapshot_{}.png", std::process::id()));
+        render_fov_png(&map, &grid, Coords::new(0, 0), &path).unwrap();
+        let image = crate::image::GrayImage::decode(&std::fs::read(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((image.width(), image.height()), (64, 48));
+        // Row `y = 0` is drawn at the bottom: origin, visible floor, floor, then a wall
+        let shade = |x: usize| image.get(x * 16 + 8, 40).unwrap();
+        let shades = [shade(0), shade(1), shade(2), shade(3)];
+        assert!(shades.iter().enumerate().all(|(i, a)| shades[i + 1..].iter().all(|b| a != b)));
+    }
+
+    // SVG exports write a standalone document of the octant's coverage diagram.
+    #[test]
+    fn render_fov_lines_svg_file() {
+        let path = std::env::temp_dir().join(format!("fov2d_lines_{}.svg", std::process::id()));
+        render_fov_lines_svg(FovRadius::R8, QFactor::Double, Octant::O3, &path).unwrap();
+        let svg = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<line ").count(), 16);
+        assert!(svg.contains(">coverage O3 R8 Q16 circle 0.50</text>"));
+    }
+
+    // Tiles are laid out with `+y` up; annotations are drawn over the FOV layers.
+    #[test]
+    fn render_layers_and_markers() {
+        let mut map = TileMap::new(3, 2).unwrap();
+        map.set_body_opaque(Coords::new(2, 1), true);
+        let mut grid = VisibilityGrid::new(3, 2);
+        grid.insert(Coords::new(0, 0));
+        grid.insert(Coords::new(2, 1));
+
+        let mut renderer = Renderer::new(8);
+        renderer.grid_lines = false;
+        renderer.annotate(Coords::new(1, 0), Marker::Guard);
+        let canvas = renderer.render(&map, &grid, Coords::new(0, 0));
+        let pal = renderer.palette;
+
+        assert_eq!((canvas.width(), canvas.height()), (24, 16));
+        assert_eq!(canvas.get(0, 15), Some(pal.visible_floor));
+        assert_eq!(canvas.get(4, 12), Some(pal.origin));
+        assert_eq!(canvas.get(23, 0), Some(pal.visible_wall));
+        assert_eq!(canvas.get(0, 0), Some(pal.floor));
+        assert_eq!(canvas.get(8 + 4 - 2, 12 - 2), Some(Marker::Guard.color()));
+
+        let mut ppm = Vec::new();
+        canvas.write_ppm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n24 16\n255\n"));
+        assert_eq!(ppm.len(), 13 + 24 * 16 * 3);
+    }
+
+    // Footer extends the image below the map; the map itself is unchanged.
+    #[test]
+    fn render_legend_and_caption() {
+        let map = TileMap::new(8, 4).unwrap();
+        let grid = VisibilityGrid::new(8, 4);
+        let mut renderer = Renderer::new(8);
+        let plain = renderer.render(&map, &grid, Coords::new(0, 0));
+
+        renderer.legend = true;
+        let shape = FovShape::default();
+        let caption = config_caption("simple", FovRadius::R16, QFactor::Double, shape);
+        renderer.caption = Some(caption);
+        renderer.annotate(Coords::new(3, 3), Marker::Target);
+        let figure = renderer.render(&map, &grid, Coords::new(0, 0));
+
+        assert_eq!(renderer.caption.as_deref(), Some("simple R16 Q32 circle 0.50"));
+        assert_eq!(renderer.legend_entries().len(), 6);
+        assert_eq!(figure.width(), plain.width());
+        assert!(figure.height() > plain.height());
+        assert_eq!(figure.get(0, 0), plain.get(0, 0));
+    }
+
+    #[test]
+    fn compose_grid_layout() {
+        let red = Canvas::new(10, 6, Rgb::new(255, 0, 0));
+        let blue = Canvas::new(8, 8, Rgb::new(0, 0, 255));
+        let renders = [("simple", &red), ("standard", &blue), ("simple", &red)];
+        let grid = compose_grid(&renders, 2);
+
+        // 2 columns of 10px cells, 2 rows of (10px label + 8px cell), 4px padding
+        assert_eq!((grid.width(), grid.height()), (32, 48));
+        assert_eq!(grid.get(4, 14), Some(Rgb::new(255, 0, 0)));
+        assert_eq!(grid.get(18, 14), Some(Rgb::new(0, 0, 255)));
+        assert_eq!(grid.get(4, 36), Some(Rgb::new(255, 0, 0)));
+        assert_eq!(grid.get(18, 36), Some(Rgb::BLACK));
+    }
+
+    // Color callbacks replace or tint palette colors per tile; markers still draw on top.
+    #[test]
+    fn render_with_color_callback() {
+        let mut map = TileMap::new(3, 1).unwrap();
+        map.get_mut(Coords::new(2, 0)).unwrap().tag = 7;
+        let mut grid = VisibilityGrid::new(3, 1);
+        grid.insert(Coords::new(1, 0));
+        let mut renderer = Renderer::new(4);
+        renderer.grid_lines = false;
+        renderer.annotate(Coords::new(2, 0), Marker::Guard);
+
+        let water = Rgb::new(30, 60, 200);
+        let canvas = renderer.render_with(&map, &grid, Coords::new(-1, 0), |_, tile, vis| {
+            match (tile.tag, vis) {
+                (7, _) => Rgba::opaque(water),
+                (_, Visibility::Visible) => Rgba::new(255, 0, 0, 128),
+                _ => Rgba::TRANSPARENT,
+            }
+        });
+        let pal = renderer.palette;
+
+        assert_eq!(canvas.get(0, 0), Some(pal.floor));
+        assert_eq!(canvas.get(4, 1), Some(Rgba::new(255, 0, 0, 128).over(pal.visible_floor)));
+        assert_eq!(canvas.get(8, 0), Some(water));
+        assert_eq!(canvas.get(9, 1), Some(Marker::Guard.color()));
+        assert_eq!(Rgba::new(255, 0, 0, 128).over(Rgb::BLACK), Rgb::new(128, 0, 0));
+    }
+
+    // Formats follow file extensions; each writer emits its format's signature.
+    #[test]
+    fn image_formats() {
+        assert_eq!(ImageFormat::from_path("out/figure.PNG"), Some(ImageFormat::Png));
+        assert_eq!(ImageFormat::from_path("a.bmp"), Some(ImageFormat::Bmp));
+        assert_eq!(ImageFormat::from_path("a.gif"), None);
+        assert_eq!(ImageFormat::from_path("noext"), None);
+
+        let canvas = Canvas::new(3, 2, Rgb::new(10, 20, 30));
+        let signatures: [(ImageFormat, &[u8]); 3] = [
+            (ImageFormat::Png, b"\x89PNG\r\n\x1a\n"),
+            (ImageFormat::Bmp, b"BM"),
+            (ImageFormat::Ppm, b"P6\n3 2\n"),
+        ];
+        for (format, signature) in signatures {
+            let mut bytes = Vec::new();
+            canvas.write_image(&mut bytes, format).unwrap();
+            assert!(bytes.starts_with(signature), "{format:?}");
+            assert_eq!(ImageFormat::from_path(format!("x.{}", format.extension())), Some(format));
+        }
+    }
+
+    // Visible water mirrors the visible tile north of it; hidden water is faded tint.
+    #[test]
+    fn water_reflections() {
+        let mut map = TileMap::new(2, 3).unwrap();
+        map.set_body_opaque(Coords::new(0, 2), true);
+        for x in 0..2 {
+            map.get_mut(Coords::new(x, 1)).unwrap().tag = 3;
+            map.get_mut(Coords::new(x, 0)).unwrap().tag = 3;
+        }
+        let mut grid = VisibilityGrid::new(2, 3);
+        for c in [(0, 2), (0, 1), (1, 1), (0, 0)] {
+            grid.insert(Coords::new(c.0, c.1));
+        }
+        let mut renderer = Renderer::new(2);
+        renderer.grid_lines = false;
+        let water = WaterReflection::new(3);
+        let layer = water.layer(&renderer, &map, &grid);
+        let canvas = renderer.render_with(&map, &grid, Coords::new(-1, 0), layer);
+
+        let (pal, tint) = (renderer.palette, water.tint);
+        let mirror = |c: Rgb| Rgba::new(c.r, c.g, c.b, water.strength).over(tint);
+        // Rows top to bottom: y = 2 (wall, floor), y = 1 (water), y = 0 (water)
+        assert_eq!(canvas.get(0, 0), Some(pal.visible_wall));
+        assert_eq!(canvas.get(0, 2), Some(mirror(pal.visible_wall)));
+        assert_eq!(canvas.get(2, 2), Some(tint));
+        assert_eq!(canvas.get(0, 4), Some(mirror(pal.visible_floor)));
+        assert_eq!(canvas.get(2, 4), Some(Rgba::new(tint.r, tint.g, tint.b, 96).over(pal.floor)));
+    }
+
+    // Minimaps: current FOV bright, memory dim, the rest dark; the observer is a cross.
+    #[test]
+    fn minimap_layers() {
+        let mut map = TileMap::new(4, 3).unwrap();
+        map.set_body_opaque(Coords::new(3, 0), true);
+        let mut explored = VisibilityGrid::for_map(&map);
+        let mut visible = VisibilityGrid::for_map(&map);
+        for x in 0..4 {
+            explored.insert(Coords::new(x, 0));
+        }
+        visible.insert(Coords::new(0, 2));
+        visible.insert(Coords::new(1, 2));
+
+        let minimap = Minimap::new(1);
+        let canvas = minimap.render(&map, &explored, &visible, Coords::new(1, 2));
+        assert_eq!((canvas.width(), canvas.height()), (4, 3));
+        // Rows top to bottom: y = 2 (visible, observer), y = 1 (unexplored), y = 0 (explored)
+        assert_eq!(canvas.get(3, 0), Some(minimap.unexplored));
+        assert_eq!(canvas.get(2, 0), Some(minimap.observer));
+        assert_eq!(canvas.get(1, 1), Some(minimap.observer));
+        assert_eq!(canvas.get(0, 1), Some(minimap.unexplored));
+        assert_eq!(canvas.get(0, 2), Some(minimap.explored_floor));
+        assert_eq!(canvas.get(3, 2), Some(minimap.explored_wall));
+
+        let mut main = Canvas::new(40, 30, Rgb::WHITE);
+        Minimap::default().render_inset(&mut main, &map, &explored, &visible, Coords::new(0, 0));
+        assert_eq!(main.get(28, 4), Some(minimap.visible_floor));
+        assert_eq!(main.get(27, 3), Some(minimap.visible_wall));
+        assert_eq!(main.get(20, 20), Some(Rgb::WHITE));
+    }
+
+    // Panels sit side by side; labels flag observers that are seen but do not see back.
+    #[test]
+    fn split_screen_asymmetry() {
+        let map = TileMap::new(8, 6).unwrap();
+        let (a, b, c) = (Coords::new(1, 1), Coords::new(6, 4), Coords::new(1, 4));
+        let mut sees_a = VisibilityGrid::new(8, 6);
+        let mut sees_b = VisibilityGrid::new(8, 6);
+        let mut sees_c = VisibilityGrid::new(8, 6);
+        let views = [(&mut sees_a, [a, b]), (&mut sees_b, [b, c]), (&mut sees_c, [c, b])];
+        for (grid, coords) in views {
+            for coords in coords {
+                grid.insert(coords);
+            }
+        }
+        let views = [(a, &sees_a), (b, &sees_b), (c, &sees_c)];
+        assert_eq!(split_labels(&views), ["#1 1,1 sees #2!", "#2 6,4 sees #3", "#3 1,4 sees #2"]);
+
+        let renderer = Renderer::new(4);
+        let split = renderer.render_split(&map, &views);
+        let panel = renderer.render(&map, &sees_a, a);
+        assert_eq!(split.width(), 3 * (panel.width() + 4) + 4);
+        assert!(split.height() > panel.height());
+    }
+}
@@ -0,0 +1,204 @@
+//! Errors for FOV Visualization - Rust (2D).
+//!
+//! Notes:
+//! - FOV set and map constructors, and node builders, return a `FovError` for invalid
+//!   arguments (e.g. `FovSet16::new` with an unsupported radius and Q-Factor).
+//! - File functions and parsers return a `FovError` too: `Io` for failed reads and writes,
+//!   `InvalidData` for malformed input. `FovError` converts from `io::Error`, so one
+//!   function can `?` both, e.g. to load a cached FOV set or else build one.
+
+use std::fmt;
+use std::io;
+
+use super::fov::{supported_configs, Algorithm, FovRadius, FovShape, QFactor};
+use super::maps::Coords;
+use super::math::MathError;
+
+/// Error building or loading FOV data.
+#[derive(Debug)]
+pub enum FovError {
+    /// FOV radius and Q-Factor pairing not supported by the FOV set type (see
+    /// `fov::supported_configs`).
+    UnsupportedConfig {
+        set_type: &'static str,
+        rfov: FovRadius,
+        qfactor: QFactor,
+    },
+    /// FOV shape has a NaN or infinite circle adjustment, or ellipse radii that aren't
+    /// positive and finite (see `FovShape::is_valid`).
+    InvalidShape(FovShape),
+    /// FOV shape differs by octant, but the FOV set type shares nodes across octants.
+    AsymmetricShape {
+        set_type: &'static str,
+        shape: FovShape,
+    },
+    /// More FOV lines than the node type has bits (see `FovBits`).
+    TooManyLines { q: usize, lines: usize },
+    /// FOV line `index` is NaN, infinite or has zero length.
+    InvalidLine { index: usize, error: MathError },
+    /// Map width or height is `0`.
+    MapTooSmall { width: usize, height: usize },
+    /// Coordinates lie outside a `width` by `height` map.
+    OutOfBounds {
+        coords: Coords,
+        width: usize,
+        height: usize,
+    },
+    /// Reading or writing a file failed.
+    Io(io::Error),
+    /// Input being parsed or loaded is malformed.
+    InvalidData(String),
+}
+
+impl fmt::Display for FovError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FovError::UnsupportedConfig {
+                set_type,
+                rfov,
+                qfactor,
+            } => write!(f, "{set_type} does not support {rfov:?} with {qfactor:?} Q-Factor"),
+            FovError::InvalidShape(FovShape::Circle { adjust }) => {
+                write!(f, "circle adjustment must be finite, got {adjust}")
+            }
+            FovError::InvalidShape(FovShape::Ellipse { rx, ry }) => {
+                write!(f, "ellipse radii must be positive and finite, got {rx} by {ry}")
+            }
+            FovError::InvalidShape(shape) => write!(f, "invalid FOV shape {shape:?}"),
+            FovError::AsymmetricShape { set_type, shape } => {
+                write!(f, "{set_type} requires a shape symmetric across octants, got {shape:?}")
+            }
+            FovError::TooManyLines { q, lines } => {
+                write!(f, "Q{q} nodes hold at most {q} FOV lines, got {lines}")
+            }
+            FovError::InvalidLine { index, error } => {
+                write!(f, "FOV line {index} is invalid: {error}")
+            }
+            FovError::MapTooSmall { width, height } => {
+                write!(f, "map must be at least 1x1, got {width}x{height}")
+            }
+            FovError::OutOfBounds {
+                coords,
+                width,
+                height,
+            } => write!(f, "{coords:?} is outside the {width}x{height} map"),
+            FovError::Io(e) => write!(f, "I/O error: {e}"),
+            FovError::InvalidData(msg) => write!(f, "invalid data: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for FovError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FovError::InvalidLine { error, .. } => Some(error),
+            FovError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FovError {
+    fn from(e: io::Error) -> Self {
+        FovError::Io(e)
+    }
+}
+
+impl FovError {
+    /// Returns an `InvalidData` error for malformed input, described by `msg`.
+    pub(crate) fn invalid(msg: impl Into<String>) -> Self {
+        FovError::InvalidData(msg.into())
+    }
+}
+
+/// Returns an error unless `rfov` and `qfactor` are a supported configuration of `algorithm`
+/// with Q-value `q` (any Q-value for `None`), and `shape` is valid. _Simple_ FOV octants
+/// share their nodes, so its `shape` must also be symmetric across octants.
+pub(crate) fn check_config(
+    set_type: &'static str,
+    algorithm: Algorithm,
+    q: Option<usize>,
+    rfov: FovRadius,
+    qfactor: QFactor,
+    shape: FovShape,
+) -> Result<(), FovError> {
+    let supported = supported_configs().any(|c| {
+        c.algorithm == algorithm
+            && c.rfov == rfov
+            && c.qfactor == qfactor
+            && q.is_none_or(|q| c.q() == q)
+    });
+    let shared = algorithm == Algorithm::Simple;
+    match (supported, shape.is_valid(), shape.is_symmetric() || !shared) {
+        (false, _, _) => Err(FovError::UnsupportedConfig {
+            set_type,
+            rfov,
+            qfactor,
+        }),
+        (true, false, _) => Err(FovError::InvalidShape(shape)),
+        (true, true, false) => Err(FovError::AsymmetricShape { set_type, shape }),
+        (true, true, true) => Ok(()),
+    }
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maps::{EdgeMap, TileMap};
+    use crate::{simple, standard, FovSetAny};
+
+    // Constructors accept every supported configuration, and report the rest.
+    #[test]
+    fn new_errors() {
+        let (r16, single, double) = (FovRadius::R16, QFactor::Single, QFactor::Double);
+        let circle = FovShape::default();
+        assert!(simple::FovSet16::new(r16, single, circle).is_ok());
+        assert!(simple::FovSet32::new(r16, double, FovShape::Square).is_ok());
+        assert!(standard::FovSet16::new(r16, single, circle).is_ok());
+
+        let err = simple::FovSet16::new(r16, double, circle).err().unwrap();
+        assert!(matches!(err, FovError::UnsupportedConfig { set_type: "FovSet16", .. }));
+        assert_eq!(err.to_string(), "FovSet16 does not support R16 with Double Q-Factor");
+        assert!(simple::FovSet8::new(FovRadius::R32, single, circle).is_err());
+        assert!(standard::FovSet16::new(FovRadius::R8, single, circle).is_err());
+        let nan = FovShape::Circle { adjust: f64::NAN };
+        let err = simple::FovSet64::new(FovRadius::R64, single, nan).err().unwrap();
+        assert!(matches!(err, FovError::InvalidShape(FovShape::Circle { .. })));
+        assert_eq!(err.to_string(), "circle adjustment must be finite, got NaN");
+
+        // Elliptical _Standard_ octants are built separately, _Simple_ ones share nodes
+        let ellipse = FovShape::Ellipse { rx: 16.5, ry: 8.5 };
+        assert!(standard::FovSet16::new(r16, single, ellipse).is_ok());
+        let err = simple::FovSet16::new(r16, single, ellipse).err().unwrap();
+        assert!(matches!(err, FovError::AsymmetricShape { set_type: "FovSet16", .. }));
+
+        let any = FovSetAny::new(Algorithm::Simple, FovRadius::R128, double, circle);
+        assert!(matches!(any, Err(FovError::UnsupportedConfig { set_type: "FovSetAny", .. })));
+
+        // Maps need a tile, and coordinates off the map are reported
+        let err = TileMap::new(0, 4).unwrap_err();
+        assert!(matches!(err, FovError::MapTooSmall { width: 0, height: 4 }));
+        assert_eq!(err.to_string(), "map must be at least 1x1, got 0x4");
+        assert!(TileMap::from_ascii(" \n").is_err() && EdgeMap::new(3, 0).is_err());
+        let mut map = TileMap::new(6, 3).unwrap();
+        let err = map.set_wall_hp(Coords::new(6, 0), 4).unwrap_err();
+        assert_eq!(err.to_string(), "Coords { x: 6, y: 0 } is outside the 6x3 map");
+
+        // I/O and parse errors convert, so loaders and builders share one error type
+        let load = || -> Result<(), FovError> {
+            crate::files::load_fov_set("/nonexistent/fov_set.bin")?;
+            Ok(())
+        };
+        let err = load().unwrap_err();
+        assert!(matches!(&err, FovError::Io(e) if e.kind() == io::ErrorKind::NotFound));
+        assert!(std::error::Error::source(&err).is_some());
+        let err = TileMap::from_ascii("#@").unwrap_err();
+        assert!(matches!(err, FovError::InvalidData(_)));
+    }
+}
@@ -1 +1,111 @@
 //! File handling for Fov Visualization - Rust (2D)
+//!
+//! Every function returns a `FovError`: `Io` if the file can't be read or written, or the
+//! loader's own error (e.g. `InvalidData`) for malformed contents.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use super::analysis::Table;
+use super::cache::{read_fov_set, write_fov_set};
+#[cfg(feature = "experimental")]
+use super::cases::TestCase;
+use super::computer::FovSetAny;
+use super::diagram::Diagram;
+use super::drawing::Canvas;
+use super::error::FovError;
+use super::image::GrayImage;
+use super::maps::{Coords, Glyphs, TileMap};
+use super::visibility::VisibilityGrid;
+
+/// Saves an analysis `Table` to `path` as CSV.
+pub fn save_csv<P: AsRef<Path>>(path: P, table: &Table) -> Result<(), FovError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    table.write_csv(&mut writer)?;
+    Ok(writer.flush()?)
+}
+
+/// Saves an analysis `Table` to `path` as JSON.
+pub fn save_json<P: AsRef<Path>>(path: P, table: &Table) -> Result<(), FovError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    table.write_json(&mut writer)?;
+    Ok(writer.flush()?)
+}
+
+/// Saves a `Canvas` to `path` as a binary PPM image.
+pub fn save_ppm<P: AsRef<Path>>(path: P, canvas: &Canvas) -> Result<(), FovError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    canvas.write_ppm(&mut writer)?;
+    Ok(writer.flush()?)
+}
+
+/// Saves a vector `Diagram` to `path` as a single-page PDF document.
+pub fn save_pdf<P: AsRef<Path>>(path: P, diagram: &Diagram) -> Result<(), FovError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    diagram.write_pdf(&mut writer)?;
+    Ok(writer.flush()?)
+}
+
+/// Saves a `VisibilityGrid` (e.g. explored tiles) to `path`, run-length encoded.
+pub fn save_grid<P: AsRef<Path>>(path: P, grid: &VisibilityGrid) -> Result<(), FovError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    grid.write_rle(&mut writer)?;
+    Ok(writer.flush()?)
+}
+
+/// Loads a `VisibilityGrid` saved by `save_grid`.
+pub fn load_grid<P: AsRef<Path>>(path: P) -> Result<VisibilityGrid, FovError> {
+    VisibilityGrid::read_rle(BufReader::new(File::open(path)?))
+}
+
+/// Saves a prebuilt _Simple_ FOV set to `path` in the binary cache format (see `cache`).
+pub fn save_fov_set<P: AsRef<Path>>(path: P, fov_set: &FovSetAny) -> Result<(), FovError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_fov_set(fov_set, &mut writer)?;
+    Ok(writer.flush()?)
+}
+
+/// Loads an FOV set saved by `save_fov_set`, without rebuilding its nodes.
+pub fn load_fov_set<P: AsRef<Path>>(path: P) -> Result<FovSetAny, FovError> {
+    read_fov_set(BufReader::new(File::open(path)?))
+}
+
+/// Loads shared FOV test cases from a JSON file (see `cases` for the schema).
+///
+/// Cases exported by the Python and JS FOV visualization projects run unchanged against
+/// the Rust calculators: compute each case's grid and check `TestCase::mismatches`.
+#[cfg(feature = "experimental")]
+#[cfg_attr(docsrs, doc(cfg(feature = "experimental")))]
+pub fn import_test_cases<P: AsRef<Path>>(path: P) -> Result<Vec<TestCase>, FovError> {
+    TestCase::parse_json(&std::fs::read_to_string(path)?)
+}
+
+/// Loads a `TileMap` from an ASCII map file, one tile per glyph (see `TileMap::from_ascii_with`).
+///
+/// Pass `&Glyphs::default()` for classic roguelike maps: `#` is an opaque body, `.` floor.
+pub fn load_ascii_map<P: AsRef<Path>>(path: P, glyphs: &Glyphs) -> Result<TileMap, FovError> {
+    TileMap::from_ascii_with(&std::fs::read_to_string(path)?, glyphs)
+}
+
+/// Loads a `TileMap` from a PNG or binary PGM/PPM image, one tile per pixel.
+///
+/// Pixels darker than `threshold` (gray level `0` to `255`) become opaque tiles, so maps
+/// can be sketched in black and white in any paint program. The top image row is the
+/// map's northmost row (highest `y`), matching rendered images.
+pub fn load_map_from_image<P: AsRef<Path>>(path: P, threshold: u8) -> Result<TileMap, FovError> {
+    let image = GrayImage::decode(&std::fs::read(path)?)?;
+    let (width, height) = (image.width(), image.height());
+    let mut map = TileMap::new(width, height)?;
+
+    for row in 0..height {
+        for x in 0..width {
+            if image.get(x, row).is_some_and(|v| v < threshold) {
+                let y = height - 1 - row;
+                map.set_body_opaque(Coords::new(x as i32, y as i32), true);
+            }
+        }
+    }
+
+    Ok(map)
+}
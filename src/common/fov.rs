@@ -1,6 +1,10 @@
 //! Common FOV types for FOV Visualization - Rust (2D).
 
-use super::math::{Delta, Line, Point};
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::maps::{Coords, Direction};
+use super::math::{radius_sq, Delta, Line, Point, Radians};
 
 /// Data for a visible tile and its subparts.
 /// 
@@ -8,17 +12,690 @@ use super::math::{Delta, Line, Point};
 /// - `body`: the main tile body.
 /// - `wall_n`: the north wall (`Standard` calc only).
 /// - `wall_w`: the west wall (`Standard` calc only).
-#[derive(Debug)]
+///
+/// `brightness` (`0.0` to `1.0`) is derived from a `Falloff` curve, and is `1.0` if
+/// no falloff is applied. `peeked` is `true` for tiles seen while leaning (see `peek`).
+/// `edges` holds the tile edges seen by the observer, if requested (see `Edges`).
+#[derive(Debug, Clone, PartialEq)]
 pub struct VisibleTile {
-    id: usize,
-    body: bool,
-    wall_n: bool,
-    wall_w: bool,
+    pub id: usize,
+    pub body: bool,
+    pub wall_n: bool,
+    pub wall_w: bool,
+    pub brightness: f64,
+    pub peeked: bool,
+    pub edges: Edges,
 }
 
-/// FOV radius used in calculations.
+impl VisibleTile {
+    /// Creates a new, fully bright `VisibleTile` with only its `body` visible.
+    pub fn body(id: usize) -> Self {
+        Self {
+            id,
+            body: true,
+            wall_n: false,
+            wall_w: false,
+            brightness: 1.0,
+            peeked: false,
+            edges: Edges::default(),
+        }
+    }
+}
+
+/// The four edges of a tile, e.g. the facades of a wall seen by an observer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Edges {
+    pub north: bool,
+    pub east: bool,
+    pub south: bool,
+    pub west: bool,
+}
+
+impl Edges {
+    /// Returns the edges of a tile at `(dx, dy)` from the observer that face the observer.
+    ///
+    /// A tile in octant 1 (ENE, `dx >= dy >= 0`) shows its west edge, and its south edge
+    /// unless `dy == 0`; other octants mirror this. The observer's own tile faces nowhere.
+    pub fn facing(d: Delta) -> Self {
+        Self {
+            north: d.dy < 0,
+            east: d.dx < 0,
+            south: d.dy > 0,
+            west: d.dx > 0,
+        }
+    }
+    /// Returns `true` if no edge is set.
+    pub fn is_empty(&self) -> bool {
+        !(self.north || self.east || self.south || self.west)
+    }
+    /// Returns the edge on side `direction`.
+    pub fn get(&self, direction: Direction) -> bool {
+        match direction {
+            Direction::East => self.east,
+            Direction::North => self.north,
+            Direction::West => self.west,
+            Direction::South => self.south,
+        }
+    }
+    /// Sets the edge on side `direction`.
+    pub fn set(&mut self, direction: Direction, value: bool) {
+        match direction {
+            Direction::East => self.east = value,
+            Direction::North => self.north = value,
+            Direction::West => self.west = value,
+            Direction::South => self.south = value,
+        }
+    }
+}
+
+/// Largest query radius whose visible tiles fit in a `SmallVisible`.
+pub const SMALL_RADIUS: usize = 4;
+
+/// Capacity of a `SmallVisible`: every tile of the square around a `SMALL_RADIUS` query.
+pub const SMALL_CAPACITY: usize = (2 * SMALL_RADIUS + 1) * (2 * SMALL_RADIUS + 1);
+
+/// Fixed-capacity, stack-allocated list of visible tile coordinates.
+///
+/// Returned by small-radius queries (radius up to `SMALL_RADIUS`, e.g. AI checks), which
+/// run often enough that a heap allocation per query matters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmallVisible {
+    len: usize,
+    coords: [Coords; SMALL_CAPACITY],
+}
+
+impl SmallVisible {
+    /// Creates a new, empty `SmallVisible`.
+    pub fn new() -> Self {
+        Self {
+            len: 0,
+            coords: [Coords::new(0, 0); SMALL_CAPACITY],
+        }
+    }
+    /// Returns the number of tiles.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Returns `true` if there are no tiles.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Returns the tiles, in insertion order.
+    pub fn as_slice(&self) -> &[Coords] {
+        &self.coords[..self.len]
+    }
+    /// Returns `true` if `coords` is in the list.
+    pub fn contains(&self, coords: Coords) -> bool {
+        self.as_slice().contains(&coords)
+    }
+    /// Returns an iterator over the tiles, in insertion order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Coords> {
+        self.as_slice().iter()
+    }
+    /// Appends `coords`. Panics if the list is full.
+    pub fn push(&mut self, coords: Coords) {
+        assert!(self.len < SMALL_CAPACITY, "SmallVisible is full!");
+        self.coords[self.len] = coords;
+        self.len += 1;
+    }
+}
+
+impl Default for SmallVisible {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Brightness falloff curve applied to distance from the FOV origin.
+///
+/// With `t = distance / radius` (clamped to `0.0..=1.0`):
+/// - `None`: always `1.0`.
+/// - `Linear`: `1 - t`.
+/// - `Quadratic`: `(1 - t)^2`.
+/// - `Lut`: lookup table indexed by rounded distance, in tiles. Distances past the end of
+///   the table use its last entry; an empty table is fully bright.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Falloff {
+    #[default]
+    None,
+    Linear,
+    Quadratic,
+    Lut(Vec<f64>),
+}
+
+impl Falloff {
+    /// Returns brightness (`0.0` to `1.0`) at `distance` tiles from the origin, for FOV `radius`.
+    pub fn brightness(&self, distance: f64, radius: f64) -> f64 {
+        let t = match radius > 0.0 {
+            true => (distance / radius).clamp(0.0, 1.0),
+            false => 0.0,
+        };
+
+        match self {
+            Falloff::None => 1.0,
+            Falloff::Linear => 1.0 - t,
+            Falloff::Quadratic => (1.0 - t) * (1.0 - t),
+            Falloff::Lut(lut) => {
+                let ix = (distance.round() as usize).min(lut.len().saturating_sub(1));
+                lut.get(ix).copied().unwrap_or(1.0).clamp(0.0, 1.0)
+            }
+        }
+    }
+}
+
+/// Point light source: lights the tiles it can see out to `radius`, dimmed by `falloff`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Light {
+    pub origin: Coords,
+    pub radius: usize,
+    pub falloff: Falloff,
+}
+
+impl Light {
+    pub fn new(origin: Coords, radius: usize, falloff: Falloff) -> Self {
+        Self { origin, radius, falloff }
+    }
+}
+
+/// Returns `true` if a tile at light level `light` (`0.0` to `1.0`), `distance` tiles away,
+/// is bright enough to make out for an observer with FOV `radius`.
+///
+/// Sight reaches `light * radius` tiles, by rounded distance: daylight (`1.0`) reaches the
+/// whole FOV, and total darkness (`0.0`) only the observer's own tile.
+pub fn lit_enough(light: f64, distance: f64, radius: f64) -> bool {
+    distance.round() <= light.clamp(0.0, 1.0) * radius
+}
+
+/// Fixed-point steps per full light level (`1.0`), for `lit_enough_sq`.
+pub const LIGHT_ONE: u64 = 1 << 16;
+
+/// Returns `true` if a tile at light level `light`, `dist_sq` squared tiles away, is bright
+/// enough to make out, as `lit_enough` does, deciding in integers.
+///
+/// `light` is rounded once to `1 / LIGHT_ONE` steps. A rounded distance is within `k` tiles
+/// exactly when `dist_sq <= k * k + k`, so no square root is taken.
+pub fn lit_enough_sq(light: f64, dist_sq: u32, radius: u32) -> bool {
+    let light = (light.clamp(0.0, 1.0) * LIGHT_ONE as f64).round() as u64;
+    let sight = light * radius as u64 / LIGHT_ONE;
+    dist_sq as u64 <= sight * sight + sight
+}
+
+/// Adaptive capacity estimate for query result `Vec`s, learned from previous results.
+///
+/// Sizing results for every node of the FOV set wastes memory on typical, occluded
+/// queries, while starting empty reallocates several times as a result grows. The hint
+/// tracks a slowly decaying maximum of recorded result sizes, so repeated queries of
+/// similar size allocate once. Owned by the caller, e.g. one per AI system.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CapacityHint {
+    estimate: usize,
+}
+
+impl CapacityHint {
+    /// Creates a new `CapacityHint` with no recorded results.
+    pub fn new() -> Self {
+        Self { estimate: 0 }
+    }
+    /// Returns the capacity to reserve for the next result: the estimate plus 1/8 headroom.
+    pub fn capacity(&self) -> usize {
+        self.estimate + self.estimate / 8
+    }
+    /// Records a result of `len` items. Larger results raise the estimate at once; smaller
+    /// ones lower it by 1/16 per query, so one unusually large result is soon forgotten.
+    pub fn record(&mut self, len: usize) {
+        self.estimate = len.max(self.estimate - self.estimate / 16);
+    }
+}
+
+/// Order of visible tiles returned by FOV queries.
+///
+/// - `Unspecified`: traversal order (octant by octant, outward from the origin). Fastest.
+/// - `ByDistance`: increasing squared distance from the origin, ties broken by angle.
+/// - `ByAngle`: counter-clockwise from east (`+x`), ties broken by distance.
+///
+/// Orders compare integer `(dx, dy)` offsets only, so they are deterministic across platforms.
+/// The origin is always first in `ByDistance` and `ByAngle` order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TileOrder {
+    #[default]
+    Unspecified,
+    ByDistance,
+    ByAngle,
+}
+
+impl TileOrder {
+    /// Compares two `(dx, dy)` offsets from the FOV origin. `Unspecified` treats all as equal.
+    pub fn compare(&self, a: Delta, b: Delta) -> Ordering {
+        let dist_sq = |d: Delta| d.dx * d.dx + d.dy * d.dy;
+
+        match self {
+            TileOrder::Unspecified => Ordering::Equal,
+            TileOrder::ByDistance => dist_sq(a)
+                .cmp(&dist_sq(b))
+                .then_with(|| compare_angle(a, b)),
+            TileOrder::ByAngle => compare_angle(a, b).then_with(|| dist_sq(a).cmp(&dist_sq(b))),
+        }
+    }
+}
+
+/// Compares offsets by angle counter-clockwise from east, using integer cross products.
+///
+/// The zero offset sorts before all others.
+fn compare_angle(a: Delta, b: Delta) -> Ordering {
+    // 0: origin; 1: angle in [0, 180); 2: angle in [180, 360)
+    let half = |d: Delta| match (d.dx, d.dy) {
+        (0, 0) => 0,
+        (dx, dy) if dy > 0 || (dy == 0 && dx > 0) => 1,
+        _ => 2,
+    };
+    let cross = a.dx as i64 * b.dy as i64 - a.dy as i64 * b.dx as i64;
+
+    half(a).cmp(&half(b)).then(0.cmp(&cross))
+}
+
+/// Strategy for the order in which a calculator visits the nodes of an FOV octant.
+///
+/// `order` receives the `(dpri, dsec)` offset of each node in storage order (by `dpri`,
+/// then `dsec`) and returns node indexes in visiting order. Any order works if each node
+/// comes after every node that can block it: all other nodes with `dpri' <= dpri` and
+/// `dsec' <= dsec` (see `is_valid_order`). Results are then identical; only the order of
+/// streamed tiles and the memory access pattern change.
+///
+/// Implemented by `DefaultOrder`, `NearestFirst` and `AngleSweep`; install one with
+/// `FovSet16::set_traversal_order`.
+pub trait TraversalOrder {
+    /// Returns node indexes into `offsets`, in visiting order.
+    fn order(&self, offsets: &[(u16, u16)]) -> Vec<usize>;
+}
+
+/// Storage order: radius band by band outward, each band from the axis to the diagonal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DefaultOrder;
+
+impl TraversalOrder for DefaultOrder {
+    fn order(&self, offsets: &[(u16, u16)]) -> Vec<usize> {
+        (0..offsets.len()).collect()
+    }
+}
+
+/// Increasing squared distance from the origin, ties in storage order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NearestFirst;
+
+impl TraversalOrder for NearestFirst {
+    fn order(&self, offsets: &[(u16, u16)]) -> Vec<usize> {
+        let dist_sq = |(dp, ds): (u16, u16)| dp as u32 * dp as u32 + ds as u32 * ds as u32;
+        let mut order: Vec<usize> = (0..offsets.len()).collect();
+        order.sort_by_key(|&ix| dist_sq(offsets[ix]));
+        order
+    }
+}
+
+/// Sweep from the octant's primary axis toward its diagonal: row by row of `dsec`, each
+/// row outward by `dpri`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AngleSweep;
+
+impl TraversalOrder for AngleSweep {
+    fn order(&self, offsets: &[(u16, u16)]) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..offsets.len()).collect();
+        order.sort_by_key(|&ix| (offsets[ix].1, offsets[ix].0));
+        order
+    }
+}
+
+/// Returns `true` if `order` visits each index of `offsets` once, after all its blockers.
+pub fn is_valid_order(offsets: &[(u16, u16)], order: &[usize]) -> bool {
+    let mut position = vec![usize::MAX; offsets.len()];
+    for (pos, &ix) in order.iter().enumerate() {
+        match position.get(ix) {
+            Some(&usize::MAX) => position[ix] = pos,
+            _ => return false,
+        }
+    }
+    if order.len() != offsets.len() {
+        return false;
+    }
+
+    (0..offsets.len()).all(|a| {
+        (0..offsets.len()).all(|b| {
+            let blocks = a != b && offsets[a].0 <= offsets[b].0 && offsets[a].1 <= offsets[b].1;
+            !blocks || position[a] < position[b]
+        })
+    })
+}
+
+/// Visibility details for a candidate visible tile, reported during FOV traversal.
+///
+/// - `unblocked_bits`: number of the node's FOV bits that are not blocked (at least `1`).
+/// - `total_bits`: number of FOV bits set in the node.
+/// - `dist_sq`: squared distance from the origin, in tiles (`dpri^2 + dsec^2`).
+///
+/// All fields are integers, so visibility rules built on them are deterministic across
+/// platforms. The float accessors `coverage()` and `distance()` are conveniences outside
+/// that guarantee; compare the fields directly (e.g. with a `Threshold`) in lockstep games.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VisInfo {
+    pub unblocked_bits: u32,
+    pub total_bits: u32,
+    pub dist_sq: u32,
+}
+
+impl VisInfo {
+    /// Returns the fraction (`0.0` to `1.0`] of the node's FOV bits that are not blocked.
+    pub fn coverage(&self) -> f64 {
+        self.unblocked_bits as f64 / self.total_bits as f64
+    }
+    /// Returns the distance from the origin, in tiles.
+    pub fn distance(&self) -> f64 {
+        (self.dist_sq as f64).sqrt()
+    }
+}
+
+/// User-supplied rule deciding whether a candidate visible tile is committed to the results.
+///
+/// Filters are invoked once per visible FOV node, before the tile is recorded. Tiles on
+/// octant boundaries belong to two octants and may be offered twice; a tile is visible if
+/// either offer is accepted.
+///
+/// Filters only hide tiles from the results: a rejected opaque tile still blocks FOV.
+///
+/// Closures of the form `FnMut(Coords, &VisInfo) -> bool` implement `VisibilityFilter`:
+///
+/// ```
+/// use fov2d::fov::VisInfo;
+/// use fov2d::maps::Coords;
+///
+/// // House rule: hide tiles with less than 25% of their FOV bits unblocked
+/// let mut filter = |_: Coords, info: &VisInfo| 4 * info.unblocked_bits >= info.total_bits;
+/// # let _ = &mut filter;
+/// ```
+pub trait VisibilityFilter {
+    /// Returns `true` if the tile at `coords` should be reported visible.
+    fn accept(&mut self, coords: Coords, info: &VisInfo) -> bool;
+}
+
+impl<F: FnMut(Coords, &VisInfo) -> bool> VisibilityFilter for F {
+    fn accept(&mut self, coords: Coords, info: &VisInfo) -> bool {
+        self(coords, info)
+    }
+}
+
+/// `VisibilityFilter` accepting every candidate tile.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AcceptAll;
+
+impl VisibilityFilter for AcceptAll {
+    fn accept(&mut self, _: Coords, _: &VisInfo) -> bool {
+        true
+    }
+}
+
+/// `VisibilityFilter` accepting candidate tiles with at least the given bit coverage.
+///
+/// Coverage is compared as a float; `Threshold::Fraction` is the integer-only equivalent.
+#[derive(Debug, Clone, Copy)]
+pub struct MinCoverage(pub f64);
+
+impl VisibilityFilter for MinCoverage {
+    fn accept(&mut self, _: Coords, info: &VisInfo) -> bool {
+        info.coverage() >= self.0
+    }
+}
+
+/// `VisibilityFilter` accepting candidate tiles within a vision cone.
+///
+/// The cone points at `facing` from the observer at `origin` and spans `width` in total,
+/// e.g. `Cone::new(origin, Degrees(90.0), Degrees(120.0))` for 120 degrees facing north.
+/// Tiles exactly on the cone's edges are accepted; the origin tile always is.
+///
+/// Angles are found with `atan2`, whose rounding may vary by platform, so cones are outside
+/// the determinism guarantee (see `VisInfo`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cone {
+    pub origin: Coords,
+    pub facing: Radians,
+    pub half_width: Radians,
+}
+
+impl Cone {
+    /// Creates a new `Cone` facing `facing` and `width` wide, in `Degrees` or `Radians`.
+    pub fn new(origin: Coords, facing: impl Into<Radians>, width: impl Into<Radians>) -> Self {
+        Self {
+            origin,
+            facing: facing.into(),
+            half_width: Radians(width.into().0 / 2.0),
+        }
+    }
+    /// Returns `true` if `coords` lies within the cone.
+    pub fn contains(&self, coords: Coords) -> bool {
+        let d = Delta::new(coords.x - self.origin.x, coords.y - self.origin.y);
+        if d == Delta::new(0, 0) {
+            return true;
+        }
+        let off = (Radians::of(d) - self.facing).normalized_signed();
+        // Tolerance keeps edge tiles (e.g. exact diagonals) inside despite rounding
+        off.0.abs() <= self.half_width.0 + 1e-9
+    }
+}
+
+impl VisibilityFilter for Cone {
+    fn accept(&mut self, coords: Coords, _: &VisInfo) -> bool {
+        self.contains(coords)
+    }
+}
+
+/// `VisibilityFilter` accepting candidate tiles with at least the given number of unblocked bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MinBits(pub u32);
+
+impl VisibilityFilter for MinBits {
+    fn accept(&mut self, _: Coords, info: &VisInfo) -> bool {
+        info.unblocked_bits >= self.0
+    }
+}
+
+/// Minimum share of a tile part's FOV bits that must be unblocked for the part to be visible.
+///
+/// - `Bits(n)`: at least `n` unblocked bits. `Bits(1)` (the default) is "any bit".
+/// - `Fraction { num, den }`: at least `num / den` of the part's bits, compared in integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Threshold {
+    Bits(u32),
+    Fraction { num: u32, den: u32 },
+}
+
+impl Default for Threshold {
+    fn default() -> Self {
+        Threshold::Bits(1)
+    }
+}
+
+impl Threshold {
+    /// Returns `true` if `info` meets the threshold.
+    pub fn accepts(&self, info: &VisInfo) -> bool {
+        match *self {
+            Threshold::Bits(n) => info.unblocked_bits >= n,
+            Threshold::Fraction { num, den } => {
+                info.unblocked_bits as u64 * den as u64 >= num as u64 * info.total_bits as u64
+            }
+        }
+    }
+}
+
+/// Per-part visibility thresholds for a single FOV query.
+///
+/// Walls are often held to a stricter threshold than bodies, so slivers of a wall seen
+/// past a corner are not drawn. _Standard_ FOV applies `body` to tile bodies and `wall` to
+/// `wall_n` and `wall_w` (see `standard::fovcalc::get_visible_tiles_thresholds`).
+/// _Simple_ FOV has bodies only, so as a `VisibilityFilter` only `body` applies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PartThresholds {
+    pub body: Threshold,
+    pub wall: Threshold,
+}
+
+impl VisibilityFilter for PartThresholds {
+    fn accept(&mut self, _: Coords, info: &VisInfo) -> bool {
+        self.body.accepts(info)
+    }
+}
+
+/// How the observer's own (origin) tile is treated by FOV queries.
+///
+/// Games disagree on the origin: some never report it, some let an observer standing
+/// inside an opaque tile (a pillar, a bush) see out, others leave them blind.
+/// The default reports the origin as visible and never lets it block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OriginRules {
+    /// Origin tile is reported as visible.
+    pub visible: bool,
+    /// An opaque origin tile blocks every other tile.
+    pub opaque_blocks: bool,
+}
+
+impl Default for OriginRules {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            opaque_blocks: false,
+        }
+    }
+}
+
+/// Shape FOV octant nodes are culled to, around the observer: the FOV silhouette.
+///
+/// - `Circle`: within the FOV radius plus `adjust`, the circular culling adjustment. The
+///   default, with `adjust` of `0.5`.
+/// - `Square`: within the FOV radius along both axes (Chebyshev distance), as in roguelikes
+///   where diagonal moves cost one turn.
+/// - `Diamond`: within the FOV radius in steps along the axes (Manhattan distance).
+/// - `Octagon`: within the FOV radius along the axes, and two thirds of it along the
+///   diagonals (`max + min / 2`), a cheap approximation of a circle.
+/// - `Ellipse`: within east-west radius `rx` and north-south radius `ry`, in tiles, e.g.
+///   `16.5` by `8.5` for a squinting or injured character. Radii past the FOV radius are
+///   clipped to it, as there are no nodes beyond.
+///
+/// The origin is always within the shape.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FovShape {
+    Circle { adjust: f64 },
+    Square,
+    Diamond,
+    Octagon,
+    Ellipse { rx: f64, ry: f64 },
+}
+
+impl Default for FovShape {
+    fn default() -> Self {
+        FovShape::Circle { adjust: 0.5 }
+    }
+}
+
+impl fmt::Display for FovShape {
+    /// Formats the shape for captions and tables, e.g. `circle 0.50` or `ellipse 16.50x8.50`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            FovShape::Circle { adjust } => write!(f, "circle {adjust:.2}"),
+            FovShape::Square => write!(f, "square"),
+            FovShape::Diamond => write!(f, "diamond"),
+            FovShape::Octagon => write!(f, "octagon"),
+            FovShape::Ellipse { rx, ry } => write!(f, "ellipse {rx:.2}x{ry:.2}"),
+        }
+    }
+}
+
+impl FovShape {
+    /// Returns `true` if offset `d` from the observer lies within the shape, for FOV radius `r`.
+    pub fn contains(&self, r: u8, d: Delta) -> bool {
+        let (dx, dy) = (d.dx.unsigned_abs() as f64, d.dy.unsigned_abs() as f64);
+        match *self {
+            FovShape::Circle { adjust } => dx * dx + dy * dy <= radius_sq(r as f64 + adjust),
+            FovShape::Square => dx.max(dy) <= r as f64,
+            FovShape::Diamond => dx + dy <= r as f64,
+            FovShape::Octagon => 2.0 * dx.max(dy) + dx.min(dy) <= 2.0 * r as f64,
+            // Compared without division, so offsets on the ellipse are inside
+            FovShape::Ellipse { rx, ry } => {
+                d == Delta::new(0, 0) || dx * dx * ry * ry + dy * dy * rx * rx <= rx * rx * ry * ry
+            }
+        }
+    }
+    /// Returns the shape in `octant`'s pri/sec axes, for `contains` with `Delta::new(dpri, dsec)`:
+    /// ellipse radii swap where the primary axis is north-south (see `Octant::dpds_to_delta`).
+    pub fn for_octant(&self, octant: Octant) -> Self {
+        match (*self, octant) {
+            (FovShape::Ellipse { rx, ry }, Octant::O2 | Octant::O3 | Octant::O6 | Octant::O7) => {
+                FovShape::Ellipse { rx: ry, ry: rx }
+            }
+            (shape, _) => shape,
+        }
+    }
+    /// Returns `true` unless the circle adjustment or ellipse radii are NaN or infinite, or
+    /// the radii aren't positive.
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            FovShape::Circle { adjust } => adjust.is_finite(),
+            FovShape::Ellipse { rx, ry } => {
+                rx.is_finite() && ry.is_finite() && rx > 0.0 && ry > 0.0
+            }
+            _ => true,
+        }
+    }
+    /// Returns `true` if the shape is the same in every octant, so octants can share nodes:
+    /// all shapes but ellipses with unequal radii.
+    pub fn is_symmetric(&self) -> bool {
+        match *self {
+            FovShape::Ellipse { rx, ry } => rx == ry,
+            _ => true,
+        }
+    }
+}
+
+/// `FovShape` reduced to integer row spans for one FOV radius, so queries test offsets
+/// without floating point.
+///
+/// Spans are found once, with `FovShape::contains` (IEEE-754 basic operations only, as node
+/// builds use), rather than once per visible tile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ShapeSpans {
+    /// Largest `|dx|` within the shape for each `|dy|` from `0` to the radius, if any.
+    max_dx: Vec<Option<u32>>,
+}
+
+impl ShapeSpans {
+    /// Creates the spans of `shape` for FOV radius `r`. Every shape is convex, so each row
+    /// is one span.
+    pub(crate) fn new(shape: FovShape, r: u8) -> Self {
+        let max_dx = (0..=r as i32)
+            .map(|dy| {
+                (0..=r as i32)
+                    .take_while(|&dx| shape.contains(r, Delta::new(dx, dy)))
+                    .last()
+                    .map(|dx| dx as u32)
+            })
+            .collect();
+        Self { max_dx }
+    }
+    /// Returns `true` if offset `d` from the observer lies within the shape.
+    pub(crate) fn contains(&self, d: Delta) -> bool {
+        let row = self.max_dx.get(d.dy.unsigned_abs() as usize).copied().flatten();
+        row.is_some_and(|max_dx| d.dx.unsigned_abs() <= max_dx)
+    }
+}
+
+/// FOV radius used in calculations.
+///
+/// `R8` suits the sight radii of 6 to 10 common in roguelikes: at Single Q its 8 FOV lines
+/// fit in `u8` node bits (see `simple::FovSet8`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FovRadius {
+    R8,
     R16,
     R32,
     R64,
@@ -29,6 +706,7 @@ impl FovRadius {
     /// Converts `FovRadius` into integer `u8` form.
     pub fn to_int(&self) -> u8 {
         match self {
+            FovRadius::R8 => 8,
             FovRadius::R16 => 16,
             FovRadius::R32 => 32,
             FovRadius::R64 => 64,
@@ -38,6 +716,7 @@ impl FovRadius {
     /// Converts `FovRadius` into float `u64` form.
     pub fn to_flt(&self) -> f64 {
         match self {
+            FovRadius::R8 => 8.0,
             FovRadius::R16 => 16.0,
             FovRadius::R32 => 32.0,
             FovRadius::R64 => 64.0,
@@ -46,7 +725,7 @@ impl FovRadius {
     }
 }
 
-/// The eight primary subdivisions of an FOV map.
+/// The eight primary subdivisions of an FOV set.
 /// 
 /// Visualized:
 /// ```text
@@ -61,7 +740,8 @@ impl FovRadius {
 ///  5   6 6  7 7   8    
 ///    6 6 6  7 7 7  
 /// ```
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Octant {
     /// Octant ENE of origin.
     O1,
@@ -82,12 +762,23 @@ pub enum Octant {
 }
 
 impl Octant {
+    /// All eight octants, in order.
+    pub const ALL: [Octant; 8] = [
+        Octant::O1,
+        Octant::O2,
+        Octant::O3,
+        Octant::O4,
+        Octant::O5,
+        Octant::O6,
+        Octant::O7,
+        Octant::O8,
+    ];
     /// Converts pri/sec `i32` deltas (`dp`, `ds`) to x/y deltas (`dx`, `dy`).
     ///
     /// Table:
     /// ```text
     /// Octant 1:   dx = (dpri *  1) + (dsec *  0)
-    /// 		    dy = (dpri *  0) + (dsec *  1)
+    ///             dy = (dpri *  0) + (dsec *  1)
     ///
     /// Octant 2:   dx = (dpri *  0) + (dsec *  1)
     ///             dy = (dpri *  1) + (dsec *  0)
@@ -108,7 +799,7 @@ impl Octant {
     ///             dy = (dpri * -1) + (dsec *  0)
     ///
     /// Octant 8:   dx = (dpri *  1) + (dsec *  0)
-    /// 			dy = (dpri *  0) + (dsec * -1)
+    ///             dy = (dpri *  0) + (dsec * -1)
     /// ```
     pub fn dpds_to_dxdy(&self, dpri: u16, dsec: u16) -> (i16, i16) {
         let dp = dpri as i16;
@@ -124,23 +815,48 @@ impl Octant {
             Octant::O7 => (ds, -dp),
             Octant::O8 => (dp, -ds),
         }
-    }    
-    // TODO: erase
-    // pub fn dpds_to_dxdy(&self, dpri: u8, dsec: u8) -> Delta {
-    //     let dp = dpri as i32;
-    //     let ds = dsec as i32;
-
-    //     match self {
-    //         Octant::O1 => Delta::new(dp, ds),
-    //         Octant::O2 => Delta::new(ds, dp),
-    //         Octant::O3 => Delta::new(-ds, dp),
-    //         Octant::O4 => Delta::new(-dp, ds),
-    //         Octant::O5 => Delta::new(-dp, -ds),
-    //         Octant::O6 => Delta::new(-ds, -dp),
-    //         Octant::O7 => Delta::new(ds, -dp),
-    //         Octant::O8 => Delta::new(dp, -ds),
-    //     }
-    // }
+    }
+    /// Converts pri/sec deltas (`dpri`, `dsec`) to an x/y `Delta`, per the table above.
+    ///
+    /// Unlike `dpds_to_dxdy`, every `u16` input converts exactly. Use with `Coords::offset`
+    /// to find the map tile of an octant node.
+    pub fn dpds_to_delta(&self, dpri: u16, dsec: u16) -> Delta {
+        let dp = dpri as i32;
+        let ds = dsec as i32;
+
+        match self {
+            Octant::O1 => Delta::new(dp, ds),
+            Octant::O2 => Delta::new(ds, dp),
+            Octant::O3 => Delta::new(-ds, dp),
+            Octant::O4 => Delta::new(-dp, ds),
+            Octant::O5 => Delta::new(-dp, -ds),
+            Octant::O6 => Delta::new(-ds, -dp),
+            Octant::O7 => Delta::new(ds, -dp),
+            Octant::O8 => Delta::new(dp, -ds),
+        }
+    }
+    /// Converts an x/y `Delta` to pri/sec deltas, the inverse of `dpds_to_delta`.
+    ///
+    /// Returns `None` if `d` lies outside the octant (`dpri >= dsec >= 0`) or past `u16`.
+    /// Deltas on an octant boundary belong to both octants that share it.
+    pub fn delta_to_dpds(&self, d: Delta) -> Option<(u16, u16)> {
+        let (dx, dy) = (d.dx as i64, d.dy as i64);
+        let (dp, ds) = match self {
+            Octant::O1 => (dx, dy),
+            Octant::O2 => (dy, dx),
+            Octant::O3 => (dy, -dx),
+            Octant::O4 => (-dx, dy),
+            Octant::O5 => (-dx, -dy),
+            Octant::O6 => (-dy, -dx),
+            Octant::O7 => (-dy, dx),
+            Octant::O8 => (dx, -dy),
+        };
+
+        match 0 <= ds && ds <= dp {
+            true => Some((u16::try_from(dp).ok()?, ds as u16)),
+            false => None,
+        }
+    }
     /// Converts pri/sec `f64` deltas (`dp`, `ds`) to x/y deltas (`dx`, `dy`).
     pub fn dpds_to_dxdy_flt(&self, dp: f64, ds: f64) -> Point {
         match self {
@@ -182,14 +898,191 @@ impl Octant {
     }
 }
 
+/// Cell on a ring around an origin: its octant, `(dpri, dsec)` within it, and map coordinates.
+pub type RingCell = (Octant, u16, u16, Coords);
+
+/// Returns the cells at Chebyshev radius `r` from `origin` (the square ring `dpri == r`).
+///
+/// Each of the `8 * r` cells is yielded once, counter-clockwise from east. Cells on octant
+/// boundaries belong to one octant: odd octants own `dsec == 0`, even octants own
+/// `dsec == dpri`. Radius `0` yields `origin` alone, in octant 1.
+pub fn ring(origin: Coords, r: u16) -> impl Iterator<Item = RingCell> {
+    let cells = move |octant: Octant| {
+        let odd = ring_owns_axis(octant);
+        (0..r.max(1)).map(move |i| {
+            let dsec = if odd { i } else { r - i };
+            ring_cell(origin, octant, r, dsec)
+        })
+    };
+
+    let octants = if r == 0 { &Octant::ALL[..1] } else { &Octant::ALL[..] };
+    octants.iter().flat_map(move |&octant| cells(octant))
+}
+
+/// Returns the cells at Euclidean radius `r` from `origin`: those whose distance rounds to `r`.
+///
+/// Rings for successive radii partition the map, so every cell lies on exactly one ring.
+/// Ordering and octant ownership are as for `ring`.
+pub fn ring_euclidean(origin: Coords, r: u16) -> impl Iterator<Item = RingCell> {
+    // Distance rounds to `r` if `(2r - 1)^2 <= 4 * dist_sq < (2r + 1)^2`, in integers
+    let (r2, lo) = (2 * r as u64, (2 * r as u64).saturating_sub(1).pow(2));
+    let hi = (r2 + 1).pow(2);
+    let on_ring = move |dp: u16, ds: u16| {
+        let dist_sq4 = 4 * (dp as u64 * dp as u64 + ds as u64 * ds as u64);
+        (r == 0 || lo <= dist_sq4) && dist_sq4 < hi
+    };
+
+    Octant::ALL.into_iter().flat_map(move |octant| {
+        let odd = ring_owns_axis(octant);
+        let mut nodes: Vec<(u16, u16)> = (0..=r)
+            .flat_map(|dp| (0..=dp).map(move |ds| (dp, ds)))
+            .filter(|&(dp, ds)| on_ring(dp, ds))
+            .filter(|&(dp, ds)| match odd {
+                true => ds < dp || dp == 0,
+                false => ds > 0,
+            })
+            .filter(|&(dp, _)| dp > 0 || octant == Octant::O1)
+            .collect();
+
+        // Counter-clockwise is increasing `dsec / dpri` in odd octants, decreasing in even
+        nodes.sort_by(|a, b| (a.1 as u32 * b.0 as u32).cmp(&(b.1 as u32 * a.0 as u32)));
+        if !odd {
+            nodes.reverse();
+        }
+        nodes.into_iter().map(move |(dp, ds)| ring_cell(origin, octant, dp, ds))
+    })
+}
+
+/// Returns `true` if `octant` owns its `dsec == 0` boundary on rings (odd octants).
+fn ring_owns_axis(octant: Octant) -> bool {
+    matches!(octant, Octant::O1 | Octant::O3 | Octant::O5 | Octant::O7)
+}
+
+/// Returns the `RingCell` for node `(dpri, dsec)` of `octant` around `origin`.
+fn ring_cell(origin: Coords, octant: Octant, dpri: u16, dsec: u16) -> RingCell {
+    (octant, dpri, dsec, origin.offset(octant.dpds_to_delta(dpri, dsec)))
+}
+
 /// Quantizing factor, multiplied by FOV radius to set FOV granularity.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum QFactor {
     Single,
     Double,
 }
 
+impl QFactor {
+    /// Converts `QFactor` into integer `u8` form (the radius multiplier).
+    pub fn to_int(&self) -> u8 {
+        match self {
+            QFactor::Single => 1,
+            QFactor::Double => 2,
+        }
+    }
+}
+
+/// FOV model, by the tile parts that obstruct sight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Algorithm {
+    /// Tile `body` only (`simple`).
+    Simple,
+    /// Tile `body`, north wall and west wall (`standard`).
+    Standard,
+}
+
+impl Algorithm {
+    /// Returns the lowercase name of the algorithm's module.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Simple => "simple",
+            Algorithm::Standard => "standard",
+        }
+    }
+}
+
+/// An FOV set configuration this crate can build, as listed by `supported_configs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SupportedConfig {
+    pub algorithm: Algorithm,
+    pub rfov: FovRadius,
+    pub qfactor: QFactor,
+    /// Recommended use case, in a few words.
+    pub use_case: &'static str,
+}
+
+impl SupportedConfig {
+    /// Returns the Q-value (FOV bits per node): radius times Q-Factor.
+    pub fn q(&self) -> usize {
+        self.rfov.to_int() as usize * self.qfactor.to_int() as usize
+    }
+    /// Returns a short name for the configuration, e.g. `simple_r16_q32`.
+    pub fn name(&self) -> String {
+        format!("{}_r{}_q{}", self.algorithm.name(), self.rfov.to_int(), self.q())
+    }
+    /// Returns the FOV set type that builds this configuration, e.g. `simple::FovSet32`.
+    pub fn set_type(&self) -> String {
+        format!("{}::FovSet{}", self.algorithm.name(), self.q())
+    }
+    /// Returns the estimated memory held by the FOV set's node data, in bytes.
+    ///
+    /// Counts the nodes of an octant culled to the default `FovShape`: _Simple_ FOV
+    /// octants share one node list, while _Standard_ FOV octants each hold their own.
+    pub fn memory_estimate(&self) -> usize {
+        use crate::{simple, standard};
+
+        let r = self.rfov.to_int() as u32;
+        let limit = (r as f64 + 0.5) * (r as f64 + 0.5);
+        let nodes = (0..=r)
+            .flat_map(|dpri| (0..=dpri).map(move |dsec| dpri * dpri + dsec * dsec))
+            .filter(|&dist_sq| dist_sq as f64 <= limit)
+            .count();
+        let node_size = match (self.algorithm, self.q()) {
+            (Algorithm::Standard, _) => size_of::<standard::FovNode16>() * 8,
+            (Algorithm::Simple, 8) => size_of::<simple::FovNode8>(),
+            (Algorithm::Simple, 16) => size_of::<simple::FovNode16>(),
+            (Algorithm::Simple, 32) => size_of::<simple::FovNode32>(),
+            (Algorithm::Simple, 64) => size_of::<simple::FovNode64>(),
+            (Algorithm::Simple, _) => size_of::<simple::FovNode128>(),
+        };
+        nodes * node_size
+    }
+}
+
+/// Every supported configuration, smallest first within each algorithm.
+const SUPPORTED_CONFIGS: [SupportedConfig; 9] = [
+    supported(Algorithm::Simple, FovRadius::R8, QFactor::Single, "small rooms, quick AI checks"),
+    supported(Algorithm::Simple, FovRadius::R16, QFactor::Single, "default roguelike player FOV"),
+    supported(Algorithm::Simple, FovRadius::R16, QFactor::Double, "R16 with finer shadow edges"),
+    supported(Algorithm::Simple, FovRadius::R32, QFactor::Single, "large open maps"),
+    supported(Algorithm::Simple, FovRadius::R32, QFactor::Double, "large maps, finer shadows"),
+    supported(Algorithm::Simple, FovRadius::R64, QFactor::Single, "outdoor long sight lines"),
+    supported(Algorithm::Simple, FovRadius::R64, QFactor::Double, "outdoor, finer shadows"),
+    supported(Algorithm::Simple, FovRadius::R128, QFactor::Single, "very long range, rare queries"),
+    supported(Algorithm::Standard, FovRadius::R16, QFactor::Single, "thin walls between tiles"),
+];
+
+/// Const constructor for `SUPPORTED_CONFIGS` entries.
+const fn supported(
+    algorithm: Algorithm,
+    rfov: FovRadius,
+    qfactor: QFactor,
+    use_case: &'static str,
+) -> SupportedConfig {
+    SupportedConfig { algorithm, rfov, qfactor, use_case }
+}
+
+/// Returns every valid (algorithm, radius, Q-Factor) configuration, smallest first within
+/// each algorithm.
+///
+/// Each builds with its `set_type`, e.g. `simple::FovSet32::new(rfov, qfactor, shape)`.
+/// Q256 (R128 with Q-Factor 2) is not supported.
+pub fn supported_configs() -> impl Iterator<Item = SupportedConfig> {
+    SUPPORTED_CONFIGS.into_iter()
+}
+
 /// A list of FOV lines.
+#[derive(Debug, Clone, PartialEq)]
 pub struct FovLines {
     pub radius: FovRadius,
     pub qfactor: QFactor,
@@ -206,13 +1099,17 @@ impl FovLines {
         }
     }
     /// Returns an iterator over the struct's FOV lines.
-    pub fn iter(&self) -> std::slice::Iter<Line> {
+    pub fn iter(&self) -> std::slice::Iter<'_, Line> {
         self.inner.iter()
     }
     /// Returns the number of FOV Nodes in the struct.
     pub fn len(&self) -> usize {
         self.inner.len()
     }
+    /// Returns `true` if the struct holds no FOV lines.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
 }
 
 /// Returns a list of FOV lines with specified radius and Q-value.
@@ -235,10 +1132,12 @@ fn get_fov_lines_single(rfov: FovRadius) -> Vec<Line> {
     let p0pri: f64 = 0.5;
     let p0sec: f64 = 0.5;
 
-    // FOV points with secondary delta just into neighboring tile
+    // FOV points with secondary delta just into neighboring tile. The nudge shrinks past R16
+    // so the first line still crosses the edge tile on the primary axis.
+    let nudge = (0.16 / radius).min(0.01);
     for n in 0..rfov.to_int() {
         let dpri = radius;
-        let dsec = n as f64 + 0.51;
+        let dsec = n as f64 + 0.5 + nudge;
 
         // One FOV point per tile along edge
         let pfpri = p0pri + dpri;
@@ -368,6 +1267,8 @@ mod tests {
     #[test]
     fn fov_line_count() {
         let suite = [
+            get_fov_lines(FovRadius::R8, QFactor::Single),
+            get_fov_lines(FovRadius::R8, QFactor::Double),
             get_fov_lines(FovRadius::R16, QFactor::Single),
             get_fov_lines(FovRadius::R16, QFactor::Double),
             get_fov_lines(FovRadius::R32, QFactor::Single),
@@ -379,11 +1280,122 @@ mod tests {
         ];
         let actual: Vec<_> = suite.iter().map(|lines| lines.len()).collect();
 
-        let expected = [16, 32, 32, 64, 64, 128, 128, 256];
+        let expected = [8, 16, 16, 32, 32, 64, 64, 128, 128, 256];
 
         assert_eq!(actual, expected);
     }
 
+    // Tile orders: distance rings outward, angles counter-clockwise from east.
+    #[test]
+    fn tile_order_compare() {
+        let mut offsets: Vec<Delta> = [(0, -1), (2, 0), (-1, 0), (0, 0), (1, 1), (0, 1), (1, 0)]
+            .into_iter()
+            .map(|(dx, dy)| Delta::new(dx, dy))
+            .collect();
+        let as_pairs = |v: &[Delta]| v.iter().map(|d| (d.dx, d.dy)).collect::<Vec<_>>();
+
+        offsets.sort_by(|a, b| TileOrder::ByDistance.compare(*a, *b));
+        let expected = [(0, 0), (1, 0), (0, 1), (-1, 0), (0, -1), (1, 1), (2, 0)];
+        assert_eq!(as_pairs(&offsets), expected);
+
+        offsets.sort_by(|a, b| TileOrder::ByAngle.compare(*a, *b));
+        let expected = [(0, 0), (1, 0), (2, 0), (1, 1), (0, 1), (-1, 0), (0, -1)];
+        assert_eq!(as_pairs(&offsets), expected);
+    }
+
+    #[test]
+    fn edges_facing_observer() {
+        let facing = |dx, dy| Edges::facing(Delta::new(dx, dy));
+
+        assert!(facing(0, 0).is_empty());
+        assert_eq!(facing(5, 0), Edges { west: true, ..Edges::default() });
+        assert_eq!(facing(5, 2), Edges { west: true, south: true, ..Edges::default() });
+        assert_eq!(facing(-2, -5), Edges { east: true, north: true, ..Edges::default() });
+    }
+
+    #[test]
+    fn thresholds() {
+        let info = VisInfo { unblocked_bits: 3, total_bits: 8, dist_sq: 4 };
+        let mut parts = PartThresholds {
+            wall: Threshold::Fraction { num: 1, den: 2 },
+            ..PartThresholds::default()
+        };
+
+        assert!(Threshold::default().accepts(&info));
+        assert!(!Threshold::Bits(4).accepts(&info));
+        assert!(Threshold::Fraction { num: 3, den: 8 }.accepts(&info));
+        assert!(!parts.wall.accepts(&info));
+        assert!(parts.accept(Coords::new(0, 0), &info));
+    }
+
+    #[test]
+    fn falloff_curves() {
+        let lut = Falloff::Lut(vec![1.0, 0.8, 0.5]);
+
+        assert_eq!(Falloff::None.brightness(8.0, 16.0), 1.0);
+        assert_eq!(Falloff::Linear.brightness(4.0, 16.0), 0.75);
+        assert_eq!(Falloff::Quadratic.brightness(8.0, 16.0), 0.25);
+        assert_eq!(Falloff::Linear.brightness(20.0, 16.0), 0.0);
+        assert_eq!(lut.brightness(0.9, 16.0), 0.8);
+        assert_eq!(lut.brightness(9.0, 16.0), 0.5);
+    }
+
+    // Octant transforms: each octant maps in-octant `(dpri, dsec)` deltas into its own region,
+    // and the 8 transforms are the 8 distinct symmetries of the square, in all three forms.
+    #[test]
+    fn octant_transforms_dihedral() {
+        let (dp, ds) = (5, 2);
+        let mut seen = Vec::new();
+
+        for (i, octant) in Octant::ALL.iter().enumerate() {
+            let (dx, dy) = octant.dpds_to_dxdy(dp, ds);
+            let (ax, ay) = (dx.abs(), dy.abs());
+
+            // Octants run counter-clockwise from ENE: even octants are steep (|dy| > |dx|)
+            assert_eq!(ay > ax, i % 4 == 1 || i % 4 == 2, "{octant:?}");
+            assert_eq!(dy > 0, i < 4, "{octant:?}");
+            assert_eq!(dx > 0, !(2..=5).contains(&i), "{octant:?}");
+            assert!(!seen.contains(&(dx, dy)));
+            seen.push((dx, dy));
+
+            let p = octant.dpds_to_dxdy_flt(dp as f64, ds as f64);
+            assert_eq!((p.x, p.y), (dx as f64, dy as f64));
+            assert_eq!(octant.dpds_to_delta(dp, ds), Delta::new(dx as i32, dy as i32));
+            assert_eq!(octant.delta_to_dpds(octant.dpds_to_delta(dp, ds)), Some((dp, ds)));
+            assert_eq!(octant.delta_to_dpds(octant.dpds_to_delta(ds, dp)), None);
+        }
+
+        // `Delta` conversions are exact past the `i16` range of `dpds_to_dxdy`
+        let d = Octant::O5.dpds_to_delta(u16::MAX, 40_000);
+        assert_eq!(d, Delta::new(-65_535, -40_000));
+        assert_eq!(Coords::new(i32::MIN + 1, 0).offset(d), Coords::new(i32::MIN, -40_000));
+    }
+
+    // Wall line tables: in every octant, `wall_n` and `wall_w` map onto the tile's north and
+    // west edges in `(x, y)` space. Points are reflected about tile centers, not corners.
+    #[test]
+    fn wall_lines_map_to_tile_edges() {
+        let (dp, ds) = (5, 2);
+
+        for octant in Octant::ALL {
+            let (dx, dy) = octant.dpds_to_dxdy(dp, ds);
+            let (dx, dy) = (dx as f64, dy as f64);
+            let to_xy = |p: f64, s: f64| {
+                let d = octant.dpds_to_dxdy_flt(p + dp as f64 - 0.5, s + ds as f64 - 0.5);
+                (d.x + 0.5, d.y + 0.5)
+            };
+            let edge = |line: Line| {
+                let (a, b) = (to_xy(line.x1, line.y1), to_xy(line.x2, line.y2));
+                let mut pts = [a, b];
+                pts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                pts
+            };
+
+            assert_eq!(edge(wall_n_line(octant)), [(dx, dy + 1.0), (dx + 1.0, dy + 1.0)], "{octant:?}");
+            assert_eq!(edge(wall_w_line(octant)), [(dx, dy), (dx, dy + 1.0)], "{octant:?}");
+        }
+    }
+
     // FOV node line sanity check: lines in some octant pairs should be identical.
     #[test]
     fn fov_node_line_match() {
@@ -408,4 +1420,184 @@ mod tests {
             assert_eq!(pair.0, pair.1);
         }
     }
+
+    // Chebyshev rings hold `8r` distinct cells, counter-clockwise from east.
+    #[test]
+    fn chebyshev_rings() {
+        let origin = Coords::new(10, -3);
+        assert_eq!(ring(origin, 0).collect::<Vec<_>>(), vec![(Octant::O1, 0, 0, origin)]);
+
+        for r in 1..=5u16 {
+            let cells: Vec<_> = ring(origin, r).collect();
+            let deltas: Vec<_> = cells
+                .iter()
+                .map(|c| Delta::new(c.3.x - origin.x, c.3.y - origin.y))
+                .collect();
+
+            assert_eq!(cells.len(), 8 * r as usize);
+            assert!(deltas.iter().all(|d| d.dx.abs().max(d.dy.abs()) == r as i32));
+            assert!(deltas.windows(2).all(|w| compare_angle(w[0], w[1]) == Ordering::Less));
+            assert!(cells.iter().all(|c| c.1 == r && c.2 <= r));
+        }
+
+        let first: Vec<_> = ring(origin, 2).take(3).map(|c| (c.0, c.2)).collect();
+        assert_eq!(first, vec![(Octant::O1, 0), (Octant::O1, 1), (Octant::O2, 2)]);
+    }
+
+    // Euclidean rings partition the disk, counter-clockwise from east, each cell once.
+    #[test]
+    fn euclidean_rings() {
+        let origin = Coords::new(0, 0);
+        let mut seen = std::collections::HashSet::new();
+
+        for r in 0..=8u16 {
+            let deltas: Vec<_> =
+                ring_euclidean(origin, r).map(|c| Delta::new(c.3.x, c.3.y)).collect();
+            assert!(deltas.windows(2).all(|w| compare_angle(w[0], w[1]) == Ordering::Less));
+            for d in deltas {
+                assert_eq!(((d.dx * d.dx + d.dy * d.dy) as f64).sqrt().round() as u16, r);
+                assert!(seen.insert(d));
+            }
+        }
+
+        let disk = (-8..=8)
+            .flat_map(|x| (-8..=8).map(move |y| (x, y)))
+            .filter(|&(x, y)| (((x * x + y * y) as f64).sqrt().round() as i32) <= 8)
+            .count();
+        assert_eq!(seen.len(), disk);
+    }
+
+    // Sight distance scales with light level.
+    #[test]
+    fn lit_enough_scales_sight() {
+        assert!(lit_enough(1.0, 16.0, 16.0));
+        assert!(lit_enough(0.25, 4.4, 16.0) && !lit_enough(0.25, 4.5, 16.0));
+        assert!(lit_enough(1.0, 16.49, 16.0));
+        assert!(lit_enough(0.0, 0.0, 16.0) && !lit_enough(-1.0, 1.0, 16.0));
+        for (light, dist_sq) in (-4..=68).flat_map(|l| (0..600).map(move |d| (l as f64 / 64.0, d))) {
+            let float = lit_enough(light, (dist_sq as f64).sqrt(), 16.0);
+            assert_eq!(lit_enough_sq(light, dist_sq, 16), float, "{light} {dist_sq}");
+        }
+    }
+
+    // Value types hash and compare, so results can be collected into sets and asserted on.
+    #[test]
+    fn value_types_hash_and_compare() {
+        use std::collections::HashSet;
+
+        let coords: HashSet<Coords> = ring(Coords::new(0, 0), 1).map(|c| c.3).collect();
+        assert_eq!(coords.len(), 8);
+        let deltas: HashSet<Delta> = [Delta::new(1, 0), Delta::new(1, 0)].into_iter().collect();
+        assert_eq!(deltas.len(), 1);
+
+        let tile = VisibleTile { brightness: 0.5, ..VisibleTile::body(3) };
+        assert_eq!(tile.clone(), tile);
+        assert_ne!(tile, VisibleTile::body(3));
+    }
+
+    // Ellipses include their edges and the origin, and swap radii in north-south octants.
+    #[test]
+    fn fov_shape_contains() {
+        let circle = FovShape::default();
+        assert!(circle.contains(8, Delta::new(8, 0)) && circle.contains(8, Delta::new(6, 5)));
+        assert!(!circle.contains(8, Delta::new(7, 5)));
+
+        let ellipse = FovShape::Ellipse { rx: 8.0, ry: 4.0 };
+        assert!(ellipse.contains(8, Delta::new(-8, 0)) && ellipse.contains(8, Delta::new(0, 4)));
+        assert!(!ellipse.contains(8, Delta::new(0, 5)) && !ellipse.contains(8, Delta::new(7, 3)));
+        assert!(FovShape::Ellipse { rx: 0.0, ry: 0.0 }.contains(8, Delta::new(0, 0)));
+
+        let north = ellipse.for_octant(Octant::O3);
+        assert_eq!(north, FovShape::Ellipse { rx: 4.0, ry: 8.0 });
+        assert_eq!(ellipse.for_octant(Octant::O8), ellipse);
+        assert_eq!(circle.for_octant(Octant::O2), circle);
+        assert!(circle.is_symmetric() && !ellipse.is_symmetric());
+    }
+
+    // Classic silhouettes: a square reaches the corners, a diamond only the axes, and an
+    // octagon two thirds of the way along the diagonals.
+    #[test]
+    fn fov_shape_silhouettes() {
+        let count = |shape: FovShape| {
+            let offsets = (-8..=8).flat_map(|x| (-8..=8).map(move |y| Delta::new(x, y)));
+            offsets.filter(|&d| shape.contains(8, d)).count()
+        };
+        assert_eq!(count(FovShape::Square), 17 * 17);
+        assert_eq!(count(FovShape::Diamond), 2 * 8 * 9 + 1);
+        assert!(FovShape::Octagon.contains(8, Delta::new(-5, 5)));
+        assert!(!FovShape::Octagon.contains(8, Delta::new(6, -5)));
+        assert!(FovShape::Octagon.contains(8, Delta::new(0, 8)));
+        let octagon = count(FovShape::Octagon);
+        assert!(count(FovShape::Diamond) < octagon && octagon < count(FovShape::default()));
+    }
+
+    // Integer row spans hold the same offsets as the shape, for every shape.
+    #[test]
+    fn shape_spans_match_shapes() {
+        let shapes = [
+            FovShape::default(),
+            FovShape::Circle { adjust: -0.5 },
+            FovShape::Square,
+            FovShape::Diamond,
+            FovShape::Octagon,
+            FovShape::Ellipse { rx: 16.5, ry: 4.5 },
+            FovShape::Ellipse { rx: 0.5, ry: 30.0 },
+        ];
+        for shape in shapes {
+            let spans = ShapeSpans::new(shape, 16);
+            for d in (-16..=16).flat_map(|x| (-16..=16).map(move |y| Delta::new(x, y))) {
+                assert_eq!(spans.contains(d), shape.contains(16, d), "{shape} {d:?}");
+            }
+            assert!(!spans.contains(Delta::new(0, 17)));
+        }
+    }
+
+    // Cones take either unit and match the integer 90 degree cone test, edges included.
+    #[test]
+    fn cone_filter() {
+        use crate::math::Degrees;
+        use std::f64::consts::FRAC_PI_2;
+
+        let origin = Coords::new(0, 0);
+        for (facing, fx, fy) in [(0.0, 1, 0), (90.0, 0, 1), (180.0, -1, 0), (-90.0, 0, -1)] {
+            let degrees = Cone::new(origin, Degrees(facing), Degrees(90.0));
+            let radians = Cone::new(origin, Degrees(facing).to_radians(), Radians(FRAC_PI_2));
+            for (dx, dy) in (-6..=6i32).flat_map(|dx| (-6..=6).map(move |dy| (dx, dy))) {
+                let (ahead, across) = (dx * fx + dy * fy, dx * fy - dy * fx);
+                let expected = across.abs() <= ahead;
+                assert_eq!(degrees.contains(Coords::new(dx, dy)), expected, "{facing} {dx},{dy}");
+                assert_eq!(radians.contains(Coords::new(dx, dy)), expected);
+            }
+        }
+        let narrow = Cone::new(Coords::new(5, 5), Degrees(45.0), Degrees(10.0));
+        assert!(narrow.contains(Coords::new(9, 9)) && !narrow.contains(Coords::new(9, 8)));
+    }
+
+    // Every supported config builds with its set type, and its memory estimate is exact.
+    #[test]
+    fn supported_configs_build() {
+        use crate::{simple, standard};
+
+        let configs: Vec<_> = supported_configs().collect();
+        assert_eq!(configs.len(), 9);
+        assert!(configs.iter().all(|c| c.q() <= 128 && !c.use_case.is_empty()));
+        assert_eq!(configs[2].name(), "simple_r16_q32");
+        assert_eq!(configs[2].set_type(), "simple::FovSet32");
+
+        for config in configs {
+            let (rfov, qfactor, shape) = (config.rfov, config.qfactor, FovShape::default());
+            let bytes = match (config.algorithm, config.q()) {
+                (Algorithm::Standard, _) => {
+                    let fov_set = standard::FovSet16::new(rfov, qfactor, shape).unwrap();
+                    fov_set.capacity() * size_of::<standard::FovNode16>()
+                }
+                (_, 8) => simple::FovSet8::new(rfov, qfactor, shape).unwrap().node_bytes(),
+                (_, 16) => simple::FovSet16::new(rfov, qfactor, shape).unwrap().node_bytes(),
+                (_, 32) => simple::FovSet32::new(rfov, qfactor, shape).unwrap().node_bytes(),
+                (_, 64) => simple::FovSet64::new(rfov, qfactor, shape).unwrap().node_bytes(),
+                _ => simple::FovSet128::new(rfov, qfactor, shape).unwrap().node_bytes(),
+            };
+            assert_eq!(config.memory_estimate(), bytes, "{}", config.name());
+        }
+    }
 }
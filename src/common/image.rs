@@ -0,0 +1,859 @@
+//! Raster image decoding and encoding for FOV Visualization - Rust (2D).
+//!
+//! Notes:
+//! - Decodes PNG and binary PGM/PPM (`P5`/`P6`) images to 8-bit grayscale (`GrayImage`).
+//! - Encodes 8-bit RGB images as PNG (`encode_png`) or 24-bit BMP (`encode_bmp`), for
+//!   `Canvas` output. PNGs are compressed with a small fixed-Huffman LZ77 deflater.
+//! - The PNG decoder is self-contained: zlib inflate, scanline filters, and all standard
+//!   color types and bit depths. Interlaced PNGs are not supported.
+//! - Alpha is composited over white, so transparent pixels read as light (open) tiles.
+//! - PNG CRCs and zlib Adler-32 checksums are not verified.
+//! - Like `Canvas`, image row `0` is the top row.
+
+use super::error::FovError;
+
+/// Largest accepted image, in pixels, so corrupt headers cannot force huge allocations.
+const MAX_PIXELS: usize = 1 << 26;
+
+/// 8-bit grayscale image, row-major from the top-left pixel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrayImage {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl GrayImage {
+    /// Creates a new `GrayImage` from row-major `pixels`.
+    ///
+    /// Panics if `pixels` does not hold `width * height` values.
+    pub fn new(width: usize, height: usize, pixels: Vec<u8>) -> Self {
+        assert!(pixels.len() == width * height, "pixel count must be width * height!");
+        Self { width, height, pixels }
+    }
+    /// Decodes a PNG or binary PGM/PPM image, detected from its leading bytes.
+    pub fn decode(bytes: &[u8]) -> Result<Self, FovError> {
+        match bytes {
+            [0x89, b'P', b'N', b'G', ..] => decode_png(bytes),
+            [b'P', b'5' | b'6', ..] => decode_pnm(bytes),
+            _ => Err(FovError::invalid("unsupported image format")),
+        }
+    }
+    /// Returns the width of the image, in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    /// Returns the height of the image, in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    /// Returns the gray level at `(x, y)`, or `None` if out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Option<u8> {
+        match x < self.width && y < self.height {
+            true => Some(self.pixels[y * self.width + x]),
+            false => None,
+        }
+    }
+}
+
+/// Returns the luma of an RGB color (ITU-R BT.601 weights), in integers.
+fn luma(r: u8, g: u8, b: u8) -> u8 {
+    ((299 * r as u32 + 587 * g as u32 + 114 * b as u32 + 500) / 1000) as u8
+}
+
+/// Composites gray level `v` with alpha `a` over white.
+fn over_white(v: u8, a: u8) -> u8 {
+    ((v as u32 * a as u32 + 255 * (255 - a as u32) + 127) / 255) as u8
+}
+
+/// Checks image dimensions are non-zero and within `MAX_PIXELS`.
+fn check_size(width: usize, height: usize) -> Result<(), FovError> {
+    match width > 0 && height > 0 && width.saturating_mul(height) <= MAX_PIXELS {
+        true => Ok(()),
+        false => Err(FovError::invalid("image size is zero or too large")),
+    }
+}
+
+// --- PGM / PPM --- //
+
+/// Decodes a binary PGM (`P5`) or PPM (`P6`) image with a maximum value below 256.
+fn decode_pnm(bytes: &[u8]) -> Result<GrayImage, FovError> {
+    let mut pos = 2;
+    let mut header = [0usize; 3];
+
+    for value in header.iter_mut() {
+        // Skip whitespace and `#` comments, then read one decimal number
+        loop {
+            match bytes.get(pos) {
+                Some(b'#') => {
+                    while bytes.get(pos).is_some_and(|&b| b != b'\n') {
+                        pos += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => pos += 1,
+                _ => break,
+            }
+        }
+        let start = pos;
+        while bytes.get(pos).is_some_and(|b| b.is_ascii_digit()) {
+            pos += 1;
+        }
+        let digits = std::str::from_utf8(&bytes[start..pos]).unwrap_or_default();
+        *value = digits.parse().map_err(|_| FovError::invalid("bad PNM header"))?;
+    }
+
+    let [width, height, max] = header;
+    check_size(width, height)?;
+    if !(1..=255).contains(&max) {
+        return Err(FovError::invalid("PNM maximum value must be 1 to 255"));
+    }
+
+    let channels = if bytes[1] == b'5' { 1 } else { 3 };
+    let data = bytes.get(pos + 1..).unwrap_or_default();
+    if data.len() < width * height * channels {
+        return Err(FovError::invalid("PNM pixel data is truncated"));
+    }
+
+    let scale = |v: u8| (v.min(max as u8) as usize * 255 / max) as u8;
+    let pixels = data
+        .chunks_exact(channels)
+        .take(width * height)
+        .map(|px| match px {
+            [v] => scale(*v),
+            _ => luma(scale(px[0]), scale(px[1]), scale(px[2])),
+        })
+        .collect();
+
+    Ok(GrayImage::new(width, height, pixels))
+}
+
+// --- PNG --- //
+
+/// Decodes a non-interlaced PNG image of any standard color type and bit depth.
+fn decode_png(bytes: &[u8]) -> Result<GrayImage, FovError> {
+    if bytes.get(..8) != Some(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        return Err(FovError::invalid("bad PNG signature"));
+    }
+
+    let mut header = None;
+    let mut palette: Vec<[u8; 4]> = Vec::new();
+    let mut idat = Vec::new();
+    let mut pos = 8;
+
+    while pos + 8 <= bytes.len() {
+        let len = u32::from_be_bytes(bytes[pos..pos + 4].try_into().unwrap()) as usize;
+        let kind = &bytes[pos + 4..pos + 8];
+        let data = bytes
+            .get(pos + 8..(pos + 8).saturating_add(len))
+            .ok_or_else(|| FovError::invalid("PNG chunk is truncated"))?;
+        pos += 12 + len;
+
+        match kind {
+            b"IHDR" if data.len() == 13 => {
+                let be = |i: usize| u32::from_be_bytes(data[i..i + 4].try_into().unwrap()) as usize;
+                header = Some((be(0), be(4), data[8], data[9], data[12]));
+            }
+            b"PLTE" => palette = data.chunks_exact(3).map(|c| [c[0], c[1], c[2], 255]).collect(),
+            b"tRNS" => {
+                for (entry, &alpha) in palette.iter_mut().zip(data) {
+                    entry[3] = alpha;
+                }
+            }
+            b"IDAT" => idat.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+
+    let (width, height, depth, color, interlace) =
+        header.ok_or_else(|| FovError::invalid("missing IHDR"))?;
+    check_size(width, height)?;
+    if interlace != 0 {
+        return Err(FovError::invalid("interlaced PNGs are not supported"));
+    }
+    let channels = match (color, depth) {
+        (0, 1 | 2 | 4 | 8 | 16) => 1,
+        (3, 1 | 2 | 4 | 8) => 1,
+        (2 | 6 | 4, 8 | 16) => [0, 0, 3, 0, 2, 0, 4][color as usize],
+        _ => return Err(FovError::invalid("unsupported PNG color type or bit depth")),
+    };
+
+    // zlib stream: 2-byte header (deflate, no preset dictionary), then raw deflate
+    match idat.get(..2) {
+        Some(&[cmf, flg]) if cmf & 0x0F == 8 && flg & 0x20 == 0 => {}
+        _ => return Err(FovError::invalid("bad zlib header")),
+    }
+    let bits_per_px = channels * depth as usize;
+    let stride = (width * bits_per_px).div_ceil(8);
+    let raw = inflate(&idat[2..], height * (stride + 1))?;
+    let rows = unfilter(&raw, stride, height, bits_per_px.div_ceil(8))?;
+
+    let sample = |row: &[u8], i: usize| -> u16 {
+        match depth {
+            16 => u16::from_be_bytes([row[2 * i], row[2 * i + 1]]) >> 8,
+            8 => row[i] as u16,
+            _ => {
+                let bit = i * depth as usize;
+                let shift = 8 - depth as usize - bit % 8;
+                (row[bit / 8] >> shift) as u16 & ((1 << depth) - 1)
+            }
+        }
+    };
+    // Gray levels below 8 bits are scaled up to `0..=255`; palette indexes are not
+    let gray = |v: u16| match depth {
+        1 | 2 | 4 => (v * 255 / ((1 << depth) - 1)) as u8,
+        _ => v as u8,
+    };
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for row in rows.chunks_exact(stride) {
+        for x in 0..width {
+            let s = |c: usize| sample(row, x * channels + c);
+            let value = match color {
+                0 => gray(s(0)),
+                2 => luma(s(0) as u8, s(1) as u8, s(2) as u8),
+                3 => {
+                    let [r, g, b, a] = *palette
+                        .get(s(0) as usize)
+                        .ok_or_else(|| FovError::invalid("PNG palette index out of range"))?;
+                    over_white(luma(r, g, b), a)
+                }
+                4 => over_white(s(0) as u8, s(1) as u8),
+                _ => over_white(luma(s(0) as u8, s(1) as u8, s(2) as u8), s(3) as u8),
+            };
+            pixels.push(value);
+        }
+    }
+
+    Ok(GrayImage::new(width, height, pixels))
+}
+
+/// Reverses PNG scanline filters, returning `height` rows of `stride` bytes.
+///
+/// `bpp` is the filter unit: bytes per complete pixel, rounded up to `1`.
+fn unfilter(
+    raw: &[u8],
+    stride: usize,
+    height: usize,
+    bpp: usize,
+) -> Result<Vec<u8>, FovError> {
+    if raw.len() < height * (stride + 1) {
+        return Err(FovError::invalid("PNG image data is truncated"));
+    }
+
+    let mut out = vec![0u8; stride * height];
+    for y in 0..height {
+        let line = &raw[y * (stride + 1)..(y + 1) * (stride + 1)];
+        let (done, rest) = out.split_at_mut(y * stride);
+        let prev = match y {
+            0 => None,
+            _ => Some(&done[(y - 1) * stride..]),
+        };
+        let cur = &mut rest[..stride];
+
+        for i in 0..stride {
+            let a = if i >= bpp { cur[i - bpp] } else { 0 };
+            let b = prev.map_or(0, |p| p[i]);
+            let c = match (prev, i >= bpp) {
+                (Some(p), true) => p[i - bpp],
+                _ => 0,
+            };
+            let predictor = match line[0] {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(FovError::invalid("bad PNG filter type")),
+            };
+            cur[i] = line[i + 1].wrapping_add(predictor);
+        }
+    }
+
+    Ok(out)
+}
+
+/// PNG Paeth predictor.
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    match (pa <= pb && pa <= pc, pb <= pc) {
+        (true, _) => a,
+        (false, true) => b,
+        _ => c,
+    }
+}
+
+// --- Inflate (RFC 1951) --- //
+
+const LEN_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115,
+    131, 163, 195, 227, 258,
+];
+const LEN_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order in which code length code lengths are stored in a dynamic block header.
+const CLEN_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+/// LSB-first bit reader over a deflate stream.
+struct Bits<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buf: u32,
+    count: u32,
+}
+
+impl Bits<'_> {
+    /// Reads `n` (at most 16) bits.
+    fn read(&mut self, n: u32) -> Result<u32, FovError> {
+        while self.count < n {
+            let truncated = || FovError::invalid("deflate data is truncated");
+            let byte = self.data.get(self.pos).ok_or_else(truncated)?;
+            self.buf |= (*byte as u32) << self.count;
+            self.pos += 1;
+            self.count += 8;
+        }
+        let value = self.buf & ((1 << n) - 1);
+        self.buf >>= n;
+        self.count -= n;
+        Ok(value)
+    }
+    /// Discards bits up to the next byte boundary.
+    fn align(&mut self) {
+        self.buf = 0;
+        self.count = 0;
+    }
+}
+
+/// Canonical Huffman code: code counts per length, and symbols ordered by code.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut symbols: Vec<u16> =
+            (0..lengths.len() as u16).filter(|&s| lengths[s as usize] > 0).collect();
+        symbols.sort_by_key(|&s| lengths[s as usize]);
+        Self { counts, symbols }
+    }
+    fn decode(&self, bits: &mut Bits) -> Result<u16, FovError> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= bits.read(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(FovError::invalid("bad Huffman code"))
+    }
+}
+
+/// Decompresses a raw deflate stream, expecting about `size_hint` bytes of output.
+///
+/// The hint comes from an untrusted header, so memory is only reserved up front in line
+/// with the length of `data`; the rest grows as output actually arrives.
+fn inflate(data: &[u8], size_hint: usize) -> Result<Vec<u8>, FovError> {
+    let mut bits = Bits { data, pos: 0, buf: 0, count: 0 };
+    let mut out = Vec::with_capacity(size_hint.min(data.len().saturating_mul(8)));
+
+    loop {
+        let last = bits.read(1)? == 1;
+        match bits.read(2)? {
+            0 => {
+                bits.align();
+                let header = data
+                    .get(bits.pos..bits.pos + 4)
+                    .ok_or_else(|| FovError::invalid("bad stored block"))?;
+                let len = u16::from_le_bytes([header[0], header[1]]) as usize;
+                let block = data
+                    .get(bits.pos + 4..bits.pos + 4 + len)
+                    .ok_or_else(|| FovError::invalid("stored block is truncated"))?;
+                out.extend_from_slice(block);
+                bits.pos += 4 + len;
+                if out.len() > MAX_PIXELS * 8 {
+                    return Err(FovError::invalid("decompressed data is too large"));
+                }
+            }
+            1 => {
+                let mut lengths = [8u8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                let (lit, dist) = (Huffman::new(&lengths), Huffman::new(&[5; 30]));
+                inflate_block(&mut bits, &mut out, &lit, &dist)?;
+            }
+            2 => {
+                let (lit, dist) = dynamic_codes(&mut bits)?;
+                inflate_block(&mut bits, &mut out, &lit, &dist)?;
+            }
+            _ => return Err(FovError::invalid("bad deflate block type")),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+/// Reads the literal/length and distance codes of a dynamic Huffman block.
+fn dynamic_codes(bits: &mut Bits) -> Result<(Huffman, Huffman), FovError> {
+    let nlen = bits.read(5)? as usize + 257;
+    let ndist = bits.read(5)? as usize + 1;
+    let ncode = bits.read(4)? as usize + 4;
+
+    let mut clens = [0u8; 19];
+    for &i in CLEN_ORDER.iter().take(ncode) {
+        clens[i] = bits.read(3)? as u8;
+    }
+    let clen = Huffman::new(&clens);
+
+    let mut lengths = Vec::with_capacity(nlen + ndist);
+    while lengths.len() < nlen + ndist {
+        let (value, repeat) = match clen.decode(bits)? {
+            sym @ 0..=15 => (sym as u8, 1),
+            16 => {
+                let prev =
+                    *lengths.last().ok_or_else(|| FovError::invalid("repeat with no length"))?;
+                (prev, 3 + bits.read(2)?)
+            }
+            17 => (0, 3 + bits.read(3)?),
+            _ => (0, 11 + bits.read(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() > nlen + ndist {
+        return Err(FovError::invalid("too many code lengths"));
+    }
+
+    Ok((Huffman::new(&lengths[..nlen]), Huffman::new(&lengths[nlen..])))
+}
+
+/// Decodes one compressed block's symbols into `out`, through end of block.
+fn inflate_block(
+    bits: &mut Bits,
+    out: &mut Vec<u8>,
+    lit: &Huffman,
+    dist: &Huffman,
+) -> Result<(), FovError> {
+    loop {
+        let sym = lit.decode(bits)? as usize;
+        match sym {
+            0..=255 => out.push(sym as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let i = sym - 257;
+                let len = LEN_BASE[i] as usize + bits.read(LEN_EXTRA[i] as u32)? as usize;
+                let d = dist.decode(bits)? as usize;
+                if d >= 30 {
+                    return Err(FovError::invalid("bad deflate distance code"));
+                }
+                let back = DIST_BASE[d] as usize + bits.read(DIST_EXTRA[d] as u32)? as usize;
+                if back > out.len() {
+                    return Err(FovError::invalid("deflate distance is too far back"));
+                }
+                // Copies may overlap their own output, so go byte by byte
+                let start = out.len() - back;
+                for k in 0..len {
+                    out.push(out[start + k]);
+                }
+            }
+            _ => return Err(FovError::invalid("bad deflate length code")),
+        }
+        if out.len() > MAX_PIXELS * 8 {
+            return Err(FovError::invalid("decompressed data is too large"));
+        }
+    }
+}
+
+// --- Encoding --- //
+
+/// Encodes a `width` by `height` image of row-major RGB triples (top row first) as PNG.
+///
+/// Panics if `rgb` does not hold `width * height` pixels.
+pub fn encode_png(width: usize, height: usize, rgb: &[u8]) -> Vec<u8> {
+    assert!(rgb.len() == width * height * 3, "pixel count must be width * height!");
+
+    // Unfiltered scanlines: LZ77 already finds repeats along and between rows
+    let mut raw = Vec::with_capacity(rgb.len() + height);
+    for row in rgb.chunks(width * 3).take(height) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    let mut zlib = vec![0x78, 0x01];
+    zlib.extend_from_slice(&deflate(&raw));
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&(width as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(height as u32).to_be_bytes());
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    for (kind, data) in [(b"IHDR", &ihdr[..]), (b"IDAT", &zlib[..]), (b"IEND", &[][..])] {
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let start = png.len();
+        png.extend_from_slice(kind);
+        png.extend_from_slice(data);
+        let crc = crc32(&png[start..]);
+        png.extend_from_slice(&crc.to_be_bytes());
+    }
+
+    png
+}
+
+/// Encodes a `width` by `height` image of row-major RGB triples (top row first) as a
+/// 24-bit uncompressed BMP.
+///
+/// Panics if `rgb` does not hold `width * height` pixels.
+pub fn encode_bmp(width: usize, height: usize, rgb: &[u8]) -> Vec<u8> {
+    assert!(rgb.len() == width * height * 3, "pixel count must be width * height!");
+    let stride = (width * 3).next_multiple_of(4);
+    let size = 54 + stride * height;
+
+    let mut bmp = Vec::with_capacity(size);
+    bmp.extend_from_slice(b"BM");
+    for field in [size as u32, 0, 54, 40, width as u32, height as u32] {
+        bmp.extend_from_slice(&field.to_le_bytes());
+    }
+    bmp.extend_from_slice(&1u16.to_le_bytes());
+    bmp.extend_from_slice(&24u16.to_le_bytes());
+    for field in [0u32, (stride * height) as u32, 2835, 2835, 0, 0] {
+        bmp.extend_from_slice(&field.to_le_bytes());
+    }
+
+    // Rows are stored bottom-up, as BGR, padded to 4 bytes
+    for row in rgb.chunks(width * 3).take(height).rev() {
+        bmp.extend(row.chunks(3).flat_map(|p| [p[2], p[1], p[0]]));
+        bmp.resize(bmp.len() + stride - width * 3, 0);
+    }
+
+    bmp
+}
+
+/// Largest LZ77 match distance (the deflate window).
+const WINDOW: usize = 32768;
+/// Longest LZ77 match.
+const MAX_MATCH: usize = 258;
+/// Match candidates checked per position; more compress better, but slower.
+const MAX_CHAIN: usize = 32;
+
+/// LSB-first bit writer for a deflate stream.
+struct BitWriter {
+    out: Vec<u8>,
+    buf: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    /// Writes the low `n` bits of `value`, least significant first.
+    fn write(&mut self, value: u32, n: u32) {
+        self.buf |= (value as u64) << self.count;
+        self.count += n;
+        while self.count >= 8 {
+            self.out.push(self.buf as u8);
+            self.buf >>= 8;
+            self.count -= 8;
+        }
+    }
+    /// Writes Huffman `code` of `len` bits, most significant first.
+    fn write_code(&mut self, code: u32, len: u32) {
+        self.write(code.reverse_bits() >> (32 - len), len);
+    }
+    /// Flushes any partial byte and returns the stream.
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.buf as u8);
+        }
+        self.out
+    }
+}
+
+/// Returns the fixed Huffman `(code, length)` of literal/length symbol `sym`.
+fn fixed_code(sym: usize) -> (u32, u32) {
+    let sym = sym as u32;
+    match sym {
+        0..=143 => (0x30 + sym, 8),
+        144..=255 => (0x190 + sym - 144, 9),
+        256..=279 => (sym - 256, 7),
+        _ => (0xC0 + sym - 280, 8),
+    }
+}
+
+/// Compresses `data` as one fixed-Huffman deflate block, with greedy LZ77 matching.
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut bits = BitWriter { out: Vec::with_capacity(data.len() / 4), buf: 0, count: 0 };
+    bits.write(1, 1);
+    bits.write(1, 2);
+
+    // Hash chains of earlier positions, keyed on the next 3 bytes
+    let hash = |i: usize| {
+        let v = (data[i] as usize) << 16 | (data[i + 1] as usize) << 8 | data[i + 2] as usize;
+        (v.wrapping_mul(0x9E37_79B1) >> 17) & (WINDOW - 1)
+    };
+    let mut head = vec![usize::MAX; WINDOW];
+    let mut prev = vec![usize::MAX; data.len()];
+    let insert = |i: usize, head: &mut [usize], prev: &mut [usize]| {
+        if i + 2 < data.len() {
+            let h = hash(i);
+            prev[i] = head[h];
+            head[h] = i;
+        }
+    };
+
+    let mut i = 0;
+    while i < data.len() {
+        let (mut best_len, mut best_dist) = (0, 0);
+        if i + 2 < data.len() {
+            let max_len = MAX_MATCH.min(data.len() - i);
+            let mut candidate = head[hash(i)];
+            for _ in 0..MAX_CHAIN {
+                if candidate == usize::MAX || i - candidate > WINDOW {
+                    break;
+                }
+                let len = (0..max_len).take_while(|&k| data[candidate + k] == data[i + k]).count();
+                if len > best_len {
+                    (best_len, best_dist) = (len, i - candidate);
+                    if len == max_len {
+                        break;
+                    }
+                }
+                candidate = prev[candidate];
+            }
+        }
+
+        if best_len >= 3 {
+            let l = LEN_BASE.iter().rposition(|&b| b as usize <= best_len).unwrap_or(0);
+            let (code, len) = fixed_code(257 + l);
+            bits.write_code(code, len);
+            bits.write((best_len - LEN_BASE[l] as usize) as u32, LEN_EXTRA[l] as u32);
+            let d = DIST_BASE.iter().rposition(|&b| b as usize <= best_dist).unwrap_or(0);
+            bits.write_code(d as u32, 5);
+            bits.write((best_dist - DIST_BASE[d] as usize) as u32, DIST_EXTRA[d] as u32);
+            for k in i..i + best_len {
+                insert(k, &mut head, &mut prev);
+            }
+            i += best_len;
+        } else {
+            let (code, len) = fixed_code(data[i] as usize);
+            bits.write_code(code, len);
+            insert(i, &mut head, &mut prev);
+            i += 1;
+        }
+    }
+
+    let (code, len) = fixed_code(256);
+    bits.write_code(code, len);
+    bits.finish()
+}
+
+/// Returns the zlib Adler-32 checksum of `data`.
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        (a, b) = (a % 65521, b % 65521);
+    }
+    b << 16 | a
+}
+
+/// Returns the PNG (ISO-HDLC) CRC-32 of `data`.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = u32::MAX;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xEDB8_8320,
+                _ => crc >> 1,
+            };
+        }
+    }
+    !crc
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// Returns a PNG with one IDAT chunk holding `zlib` (CRCs are left zero).
+    fn png(
+        w: u32,
+        h: u32,
+        depth: u8,
+        color: u8,
+        extra: &[(&[u8; 4], &[u8])],
+        zlib: &[u8],
+    ) -> Vec<u8> {
+        let mut ihdr = Vec::new();
+        ihdr.extend_from_slice(&w.to_be_bytes());
+        ihdr.extend_from_slice(&h.to_be_bytes());
+        ihdr.extend_from_slice(&[depth, color, 0, 0, 0]);
+
+        let mut bytes = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        let chunks = [(b"IHDR", ihdr.as_slice())].into_iter().chain(extra.iter().copied());
+        for (kind, data) in chunks.chain([(b"IDAT", zlib), (b"IEND", &[][..])]) {
+            bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(kind);
+            bytes.extend_from_slice(data);
+            bytes.extend_from_slice(&[0; 4]);
+        }
+        bytes
+    }
+
+    /// Wraps `raw` in a zlib stream of one stored deflate block (Adler-32 left zero).
+    fn stored(raw: &[u8]) -> Vec<u8> {
+        let len = raw.len() as u16;
+        let mut zlib = vec![0x78, 0x01, 0x01];
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(raw);
+        zlib.extend_from_slice(&[0; 4]);
+        zlib
+    }
+
+    // Filtered 8-bit RGBA and 1-bit palette PNGs decode to the expected gray levels.
+    #[test]
+    fn decode_png_stored() {
+        // 2x2 RGBA: row 0 unfiltered (black, transparent), row 1 "Up" (same again)
+        let raw = [0, 0, 0, 0, 255, 9, 9, 9, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0];
+        let image = GrayImage::decode(&png(2, 2, 8, 6, &[], &stored(&raw))).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 2));
+        assert_eq!(image.pixels, vec![0, 255, 0, 255]);
+
+        // 3x2 1-bit palette (0 white, 1 black), rows `010` and `101` with "Sub" on row 1
+        let plte: &[u8] = &[255, 255, 255, 0, 0, 0];
+        let raw = [0, 0b0100_0000, 1, 0b1010_0000];
+        let image = GrayImage::decode(&png(3, 2, 1, 3, &[(b"PLTE", plte)], &stored(&raw))).unwrap();
+        assert_eq!(image.pixels, vec![255, 0, 255, 0, 255, 0]);
+        assert_eq!(image.get(1, 0), Some(0));
+        assert_eq!(image.get(3, 0), None);
+    }
+
+    // Fixed and dynamic Huffman streams (as written by zlib) inflate correctly.
+    #[test]
+    fn inflate_huffman() {
+        // zlib.compress(b"fov fov fov fov!", 9): fixed Huffman, with a back-reference
+        let fixed = [
+            0x78, 0xda, 0x4b, 0xcb, 0x2f, 0x53, 0x48, 0x43, 0x60, 0x45, 0x00, 0x31, 0xdd, 0x05,
+            0xae,
+        ];
+        assert_eq!(inflate(&fixed[2..], 16).unwrap(), b"fov fov fov fov!");
+
+        // 64x64 8-bit gray checkerboard of 4x4 squares, unfiltered: dynamic Huffman
+        let dynamic = [
+            0x78, 0xda, 0xed, 0xd2, 0x21, 0x0a, 0x00, 0x40, 0x08, 0x45, 0x41, 0xef, 0x7f, 0x69,
+            0xd7, 0x62, 0x34, 0x6d, 0x12, 0xe7, 0x95, 0xdf, 0x04, 0x61, 0x22, 0xaa, 0xac, 0x7e,
+            0x76, 0xfb, 0x81, 0xeb, 0xff, 0x77, 0x1c, 0x70, 0xc0, 0x01, 0x07, 0x1c, 0x70, 0xc0,
+            0x01, 0x07, 0x1c, 0x70, 0xc0, 0x01, 0x07, 0x1c, 0x70, 0xc0, 0x01, 0x07, 0x1c, 0x70,
+            0x30, 0xec, 0x03, 0xdb, 0x9e, 0xf8, 0x6a,
+        ];
+        let image = GrayImage::decode(&png(64, 64, 8, 0, &[], &dynamic)).unwrap();
+        for (i, &v) in image.pixels.iter().enumerate() {
+            let (x, y) = (i % 64, i / 64);
+            assert_eq!(v, if (x / 4 + y / 4) % 2 == 0 { 0 } else { 255 });
+        }
+
+        assert!(inflate(&[0x07], 0).is_err());
+    }
+
+    // Binary PGM and PPM images, with comments, decode to gray levels.
+    #[test]
+    fn decode_pnm_images() {
+        let pgm = b"P5\n# sketch\n2 1\n15\n\x00\x0f";
+        assert_eq!(GrayImage::decode(pgm).unwrap().pixels, vec![0, 255]);
+
+        let ppm = b"P6 1 2 255\n\xff\x00\x00\xff\xff\xff";
+        assert_eq!(GrayImage::decode(ppm).unwrap().pixels, vec![76, 255]);
+
+        assert!(GrayImage::decode(b"P5 0 1 255\n").is_err());
+        assert!(GrayImage::decode(b"GIF89a").is_err());
+    }
+
+    // Encoded PNGs decode back to the same pixels; checksums match known values.
+    #[test]
+    fn encode_png_round_trip() {
+        let (w, h) = (40, 30);
+        let rgb: Vec<u8> = (0..w * h)
+            .flat_map(|i| match (i % w) / 8 + (i / w) / 8 {
+                n if n % 2 == 0 => [200, 200, 200],
+                _ => [(i % 7) as u8 * 30, 20, 80],
+            })
+            .collect();
+        let png = encode_png(w, h, &rgb);
+
+        let zlib_len = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        let raw = inflate(&png[43..41 + zlib_len - 4], 0).unwrap();
+        let rows: Vec<u8> = raw.chunks(w * 3 + 1).flat_map(|r| r[1..].to_vec()).collect();
+        assert_eq!(rows, rgb);
+        assert!(zlib_len < rgb.len() / 4);
+
+        let image = GrayImage::decode(&png).unwrap();
+        assert_eq!((image.width(), image.height()), (w, h));
+        assert_eq!(image.get(0, 0), Some(200));
+
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(inflate(&deflate(&[]), 0).unwrap(), Vec::<u8>::new());
+    }
+
+    // BMP rows are bottom-up BGR, padded to 4 bytes.
+    #[test]
+    fn encode_bmp_layout() {
+        let rgb = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+        let bmp = encode_bmp(2, 2, &rgb);
+        assert_eq!(&bmp[..2], b"BM");
+        assert_eq!(bmp.len(), 54 + 8 * 2);
+        assert_eq!(u32::from_le_bytes(bmp[2..6].try_into().unwrap()), 70);
+        assert_eq!(&bmp[54..], &[9, 8, 7, 12, 11, 10, 0, 0, 3, 2, 1, 6, 5, 4, 0, 0]);
+    }
+
+    // Mutated PNG and PNM files decode to whole images or errors, never panics.
+    #[test]
+    fn decode_mutated_images() {
+        let rgb: Vec<u8> = (0..12 * 9 * 3).map(|i| (i * 37 % 256) as u8).collect();
+        let plte: &[u8] = &[255, 255, 255, 0, 0, 0];
+        let seeds = [
+            encode_png(12, 9, &rgb),
+            png(3, 2, 1, 3, &[(b"PLTE", plte)], &stored(&[0, 0b0100_0000, 1, 0b1010_0000])),
+            b"P6 2 1 255\n\xff\x00\x00\x10\x20\x30".to_vec(),
+        ];
+
+        let mut rng = Rng::new(1950);
+        for seed in seeds.iter() {
+            for _ in 0..2000 {
+                if let Ok(image) = GrayImage::decode(&rng.mutate(seed)) {
+                    assert_eq!(image.pixels.len(), image.width() * image.height());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Point-to-point line of sight for FOV Visualization - Rust (2D).
+//!
+//! FOV sets answer "what can I see from here"; these functions answer "can `from` see `to`",
+//! e.g. for targeting, without building an FOV set or visiting every tile in range.
+//!
+//! Notes:
+//! - The sight line runs from the center of `from` to the center of `to`, and is walked tile
+//!   by tile. Tiles it only touches at a corner are skipped, so a diagonal line passes between
+//!   two opaque tiles that meet at a corner.
+//! - Opaque bodies of tiles strictly between `from` and `to` block sight. The bodies of `from`
+//!   and `to` never do: an observer sees out of its own tile, and sees an opaque target.
+//! - The `_with_walls` variants also test opaque walls (as in _Standard_ FOV) against the
+//!   sight line with `Line::intersects`. Walls touched at an end block, as walls are solid.
+//! - Sight is symmetric: `from` sees `to` exactly when `to` sees `from`.
+//! - `sees_tile_from` takes any point as the observer, e.g. one shifted half a tile to lean
+//!   around a corner. It tests sight lines to sample points across the target tile, so an
+//!   observer sees part of a tile as well as its center.
+
+use crate::{
+    maps::{Coords, Direction, OpacityProvider, WallProvider},
+    math::{supercover_passing_corners, Line, Point},
+};
+
+/// Returns `true` if no opaque tile body lies on the sight line from `from` to `to`.
+pub fn has_line_of_sight<M: OpacityProvider>(map: &M, from: Coords, to: Coords) -> bool {
+    first_blocking_tile(map, from, to).is_none()
+}
+
+/// Returns the first tile, walking from `from` to `to`, whose opaque body blocks sight.
+pub fn first_blocking_tile<M>(map: &M, from: Coords, to: Coords) -> Option<Coords>
+where
+    M: OpacityProvider,
+{
+    let between = |coords: Coords| coords != from && coords != to;
+    sight_line(from, to).find(|&coords| between(coords) && map.body_opaque(coords))
+}
+
+/// Returns `true` if no opaque tile body or wall lies on the sight line from `from` to `to`.
+pub fn has_line_of_sight_with_walls<M>(map: &M, from: Coords, to: Coords) -> bool
+where
+    M: OpacityProvider + WallProvider,
+{
+    first_blocking_tile_with_walls(map, from, to).is_none()
+}
+
+/// Returns the first tile, walking from `from` to `to`, whose opaque body or wall blocks sight.
+///
+/// A wall between two tiles is reported with the tile on the `from` side.
+pub fn first_blocking_tile_with_walls<M>(map: &M, from: Coords, to: Coords) -> Option<Coords>
+where
+    M: OpacityProvider + WallProvider,
+{
+    let line = Line::new(
+        from.x as f64 + 0.5,
+        from.y as f64 + 0.5,
+        to.x as f64 + 0.5,
+        to.y as f64 + 0.5,
+    );
+    sight_line(from, to).find(|&coords| {
+        let body = coords != from && coords != to && map.body_opaque(coords);
+        let wall = |direction| {
+            map.wall_opaque(coords, direction) && line.intersects(wall_line(coords, direction))
+        };
+        body || Direction::ALL.into_iter().any(wall)
+    })
+}
+
+/// Inset of `sees_tile_from` sample points from the target tile's edges.
+const SAMPLE_INSET: f64 = 1.0 / 1024.0;
+
+/// Returns `true` if a sight line from point `from` reaches some part of the tile at `to` past
+/// no opaque tile body.
+///
+/// Lines run to the center, edge midpoints, and corners of `to`, each inset by `SAMPLE_INSET`.
+/// The bodies of `to` and of every tile touching `from` (two or four, if `from` is on a tile
+/// edge or corner) never block.
+pub fn sees_tile_from<M: OpacityProvider>(map: &M, from: Point, to: Coords) -> bool {
+    let touches = |c: Coords| {
+        (0.0..=1.0).contains(&(from.x - c.x as f64)) && (0.0..=1.0).contains(&(from.y - c.y as f64))
+    };
+    let clear = |c: Coords| c == to || touches(c) || !map.body_opaque(c);
+    let samples = [0.5, SAMPLE_INSET, 1.0 - SAMPLE_INSET];
+
+    samples.iter().any(|&fx| {
+        samples.iter().any(|&fy| {
+            let sample = Point::new(to.x as f64 + fx, to.y as f64 + fy);
+            supercover_passing_corners(from, sample).all(clear)
+        })
+    })
+}
+
+/// Returns the tiles the sight line from the center of `from` to the center of `to` passes
+/// through, in order, from `from` to `to` inclusive.
+///
+/// Where the line crosses a tile corner exactly, it steps diagonally (see
+/// `math::supercover_passing_corners`).
+pub fn sight_line(from: Coords, to: Coords) -> impl Iterator<Item = Coords> {
+    let center = |c: Coords| Point::new(c.x as f64 + 0.5, c.y as f64 + 0.5);
+    supercover_passing_corners(center(from), center(to))
+}
+
+/// Returns the wall on side `direction` of the tile at `coords`, in map space.
+fn wall_line(coords: Coords, direction: Direction) -> Line {
+    let (x, y) = (coords.x as f64, coords.y as f64);
+    match direction {
+        Direction::North => Line::new(x, y + 1.0, x + 1.0, y + 1.0),
+        Direction::South => Line::new(x, y, x + 1.0, y),
+        Direction::West => Line::new(x, y, x, y + 1.0),
+        Direction::East => Line::new(x + 1.0, y, x + 1.0, y + 1.0),
+    }
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maps::{EdgeMap, TileMap};
+
+    // Sight lines step through every tile they enter, and diagonally through exact corners.
+    #[test]
+    fn sight_line_tiles() {
+        let tiles = |x, y| sight_line(Coords::new(0, 0), Coords::new(x, y)).collect::<Vec<_>>();
+        let coords = |pts: &[(i32, i32)]| -> Vec<Coords> {
+            pts.iter().map(|&(x, y)| Coords::new(x, y)).collect()
+        };
+
+        assert_eq!(tiles(0, 0), coords(&[(0, 0)]));
+        assert_eq!(tiles(3, 0), coords(&[(0, 0), (1, 0), (2, 0), (3, 0)]));
+        assert_eq!(tiles(2, 2), coords(&[(0, 0), (1, 1), (2, 2)]));
+        assert_eq!(tiles(2, 1), coords(&[(0, 0), (1, 0), (1, 1), (2, 1)]));
+        assert_eq!(tiles(-1, -3), coords(&[(0, 0), (0, -1), (-1, -2), (-1, -3)]));
+
+        // Reversed lines pass through the same tiles, so sight is symmetric
+        for (x, y) in (-9..=9).flat_map(|x| (-9..=9).map(move |y| (x, y))) {
+            let mut back: Vec<_> = sight_line(Coords::new(x, y), Coords::new(0, 0)).collect();
+            back.reverse();
+            assert_eq!(tiles(x, y), back, "({x}, {y})");
+        }
+    }
+
+    // Opaque bodies between the endpoints block sight, in both directions.
+    #[test]
+    fn line_of_sight_bodies() {
+        let map = TileMap::from_ascii(
+            "
+            ......
+            ..#...
+            ......
+            .#....
+            #.....
+            ",
+        )
+        .unwrap();
+        let (a, b) = (Coords::new(0, 0), Coords::new(5, 0));
+        assert!(has_line_of_sight(&map, a, b) && has_line_of_sight(&map, b, a));
+
+        let (c, d) = (Coords::new(0, 2), Coords::new(5, 4));
+        assert_eq!(first_blocking_tile(&map, c, d), Some(Coords::new(2, 3)));
+        assert_eq!(first_blocking_tile(&map, d, c), Some(Coords::new(2, 3)));
+        assert!(!has_line_of_sight(&map, c, d));
+
+        // Opaque endpoints don't block; diagonals pass between tiles meeting at a corner
+        assert!(has_line_of_sight(&map, Coords::new(0, 0), Coords::new(1, 1)));
+        assert!(has_line_of_sight(&map, Coords::new(1, 0), Coords::new(0, 1)));
+        assert!(!has_line_of_sight(&map, Coords::new(2, 0), Coords::new(0, 2)));
+    }
+
+    // Off-center observers see tiles whose centers are hidden, and touching tiles never block.
+    #[test]
+    fn sees_tile_from_points() {
+        let map = TileMap::from_ascii(
+            "
+            ......
+            ......
+            .#....
+            ......
+            ",
+        )
+        .unwrap();
+        let (from, to) = (Coords::new(1, 0), Coords::new(1, 3));
+        assert!(!has_line_of_sight(&map, from, to));
+        assert!(!sees_tile_from(&map, Point::new(1.5, 0.5), to));
+        assert!(sees_tile_from(&map, Point::new(0.75, 0.5), to));
+        assert!(sees_tile_from(&map, Point::new(2.5, 1.5), Coords::new(0, 3)));
+
+        // The opaque tile touched by a corner observer doesn't block
+        assert!(sees_tile_from(&map, Point::new(2.0, 2.0), Coords::new(0, 1)));
+        assert!(sees_tile_from(&map, Point::new(1.5, 1.5), Coords::new(1, 2)));
+    }
+
+    // Walls block only the `_with_walls` variants, and `EdgeMap` agrees with `TileMap`.
+    #[test]
+    fn line_of_sight_walls() {
+        let mut map = TileMap::new(8, 8).unwrap();
+        map.get_mut(Coords::new(4, 2)).unwrap().wall_w_opaque = true;
+        let (a, b) = (Coords::new(1, 2), Coords::new(6, 2));
+        assert!(has_line_of_sight(&map, a, b));
+        assert!(!has_line_of_sight_with_walls(&map, a, b));
+        assert_eq!(first_blocking_tile_with_walls(&map, a, b), Some(Coords::new(3, 2)));
+        assert_eq!(first_blocking_tile_with_walls(&map, b, a), Some(Coords::new(4, 2)));
+        assert!(has_line_of_sight_with_walls(&map, a, Coords::new(3, 7)));
+
+        // The target's far walls don't block; its near walls do
+        map.get_mut(Coords::new(6, 2)).unwrap().wall_n_opaque = true;
+        assert!(has_line_of_sight_with_walls(&map, Coords::new(6, 0), b));
+        assert!(!has_line_of_sight_with_walls(&map, Coords::new(6, 5), b));
+        let edges = EdgeMap::from(&map);
+        for (from, to) in [(a, b), (Coords::new(6, 0), b), (Coords::new(6, 5), b), (a, a)] {
+            assert_eq!(
+                has_line_of_sight_with_walls(&edges, from, to),
+                has_line_of_sight_with_walls(&map, from, to)
+            );
+        }
+    }
+}
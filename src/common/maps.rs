@@ -1,11 +1,21 @@
 //! Tilemaps for FOV Visualization - Rust (2D)
 
-// TODO: finish TileMap
+use std::collections::BTreeMap;
+use std::iter;
+use std::ops::{Index, IndexMut};
 
-use super::math::Point;
+use super::error::FovError;
+use super::fov::Edges;
+use super::math::{Delta, Point};
+use super::rng::Rng;
+
+/// Largest map, in tiles, parsed from ASCII; short rows are padded, so tiles can grow as
+/// the square of the text length.
+const MAX_ASCII_TILES: usize = 1 << 24;
 
 /// 2D map coordinates.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coords {
     pub x: i32,
     pub y: i32,
@@ -15,6 +25,88 @@ impl Coords {
     pub fn new(x: i32, y: i32) -> Self {
         Self { x, y }
     }
+    /// Returns the coordinates one tile away in `direction`.
+    pub fn step(&self, direction: Direction) -> Self {
+        self.offset(direction.delta())
+    }
+    /// Returns the coordinates offset by `d`, saturating at the `i32` limits.
+    pub fn offset(&self, d: Delta) -> Self {
+        Self::new(self.x.saturating_add(d.dx), self.y.saturating_add(d.dy))
+    }
+    /// Returns the row-major tile ID (`y * width + x`) in a map `width` tiles wide.
+    ///
+    /// Returns `None` if either coordinate is negative, `x >= width`, or the ID overflows,
+    /// so bad coordinates never wrap into another tile. `y` is not checked against a height;
+    /// callers check bounds first (see `TileMap::tile_id`).
+    pub fn to_index(&self, width: usize) -> Option<usize> {
+        let x = usize::try_from(self.x).ok().filter(|&x| x < width)?;
+        let y = usize::try_from(self.y).ok()?;
+        y.checked_mul(width)?.checked_add(x)
+    }
+    /// Returns the coordinates of row-major tile ID `id` in a map `width` tiles wide.
+    ///
+    /// Returns `None` if `width` is `0` or a coordinate doesn't fit an `i32`.
+    pub fn from_index(id: usize, width: usize) -> Option<Self> {
+        let (x, y) = (id.checked_rem(width)?, id / width);
+        Some(Self::new(i32::try_from(x).ok()?, i32::try_from(y).ok()?))
+    }
+}
+
+/// Cardinal direction on a map. `North` is `+y`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    East,
+    North,
+    West,
+    South,
+}
+
+impl Direction {
+    /// All four directions, counter-clockwise from east.
+    pub const ALL: [Direction; 4] = [
+        Direction::East,
+        Direction::North,
+        Direction::West,
+        Direction::South,
+    ];
+    /// Returns the unit `(dx, dy)` step in this direction.
+    pub fn delta(&self) -> Delta {
+        match self {
+            Direction::East => Delta::new(1, 0),
+            Direction::North => Delta::new(0, 1),
+            Direction::West => Delta::new(-1, 0),
+            Direction::South => Delta::new(0, -1),
+        }
+    }
+    /// Returns the opposite direction.
+    pub fn opposite(&self) -> Self {
+        match self {
+            Direction::East => Direction::West,
+            Direction::North => Direction::South,
+            Direction::West => Direction::East,
+            Direction::South => Direction::North,
+        }
+    }
+}
+
+/// Axis-aligned rectangle of `width` by `height` tiles, from its lowest corner `(x, y)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: usize, height: usize) -> Self {
+        Self { x, y, width, height }
+    }
+    /// Returns `true` if `coords` lie within the rectangle.
+    pub fn contains(&self, coords: Coords) -> bool {
+        let (dx, dy) = (coords.x as i64 - self.x as i64, coords.y as i64 - self.y as i64);
+        dx >= 0 && dy >= 0 && (dx as u64) < self.width as u64 && (dy as u64) < self.height as u64
+    }
 }
 
 impl From<Point> for Coords {
@@ -26,6 +118,82 @@ impl From<Point> for Coords {
     }
 }
 
+/// Single map tile holding FOV obstruction flags.
+///
+/// Flags are only set if the given part is _present_ and _opaque_.
+///
+/// `tag` is a game-defined terrain type (`0` by default), used for lookups such as
+/// `TileMap::set_radius_modifier`.
+///
+/// `wall_height` is the height of a partial (low) wall in the tile body, such as a fence or
+/// crate: it blocks targets shorter than the wall. `0` is no wall; an opaque body is always
+/// full height (`FULL_HEIGHT`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tile {
+    pub body_opaque: bool,
+    pub wall_n_opaque: bool,
+    pub wall_w_opaque: bool,
+    pub tag: u8,
+    pub wall_height: u8,
+}
+
+/// Number of target height levels distinguished by FOV calculations. Level `0` is ground level.
+pub const HEIGHT_LEVELS: usize = 4;
+
+/// Wall height of an opaque tile body, which blocks targets of every height.
+pub const FULL_HEIGHT: u8 = u8::MAX;
+
+impl Tile {
+    /// Creates a new, fully transparent `Tile`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Creates a new `Tile` with an opaque body.
+    pub fn opaque() -> Self {
+        Self {
+            body_opaque: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// Mapping of ASCII map glyphs to tiles, for `TileMap::from_ascii_with`.
+///
+/// The default maps `#` to an opaque body and `.` to open floor. Add glyphs for walls or
+/// terrain tags with `with`, e.g. `Glyphs::default().with('~', water)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glyphs {
+    tiles: BTreeMap<char, Tile>,
+}
+
+impl Default for Glyphs {
+    fn default() -> Self {
+        Self::new().with('#', Tile::opaque()).with('.', Tile::new())
+    }
+}
+
+impl Glyphs {
+    /// Creates an empty glyph mapping.
+    pub fn new() -> Self {
+        Self {
+            tiles: BTreeMap::new(),
+        }
+    }
+    /// Returns the mapping with `glyph` mapped to `tile`, replacing any previous tile.
+    ///
+    /// Panics if `glyph` is whitespace, which separates and indents rows.
+    pub fn with(mut self, glyph: char, tile: Tile) -> Self {
+        assert!(!glyph.is_whitespace(), "map glyphs must not be whitespace!");
+        self.tiles.insert(glyph, tile);
+        self
+    }
+    /// Returns the tile for `glyph`, or `None` if it is unmapped.
+    pub fn get(&self, glyph: char) -> Option<Tile> {
+        self.tiles.get(&glyph).copied()
+    }
+}
+
 /// 2D map of tiles with FOV obstructions.
 ///
 /// Obstructions include:
@@ -35,7 +203,1246 @@ impl From<Point> for Coords {
 ///
 /// Obstructions are only set if the given part is _present_ and _opaque_.
 /// Some FOV calculations, such as `simple`, may not use all obstructions.
-pub struct TileMap {}
+///
+/// Notes:
+/// - tiles are stored row-major: tile ID is `y * width + x`.
+/// - `+y` is north, matching the octant layout in `fov::Octant`.
+/// - `generation` increases on every mutation, so results computed from an older map
+///   state can be detected (see `VisibilityGrid::is_stale`).
+/// - destructible walls track hit points by tile ID (see `TileMap::damage`).
+/// - deserialized maps are checked: `tiles` must fill `width * height`, and wall hit points
+///   must name tiles on the map (see `TileMapData`).
+/// - maps compare equal by their tiles, ignoring `generation`.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "TileMapData", into = "TileMapData")
+)]
+pub struct TileMap {
+    width: usize,
+    height: usize,
+    tiles: Vec<Tile>,
+    radius_mods: BTreeMap<u8, i32>,
+    wall_hp: BTreeMap<usize, (u8, u8)>,
+    generation: u64,
+}
+
+/// Serialized form of a `TileMap`, checked before it becomes one.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct TileMapData {
+    width: usize,
+    height: usize,
+    tiles: Vec<Tile>,
+    radius_mods: BTreeMap<u8, i32>,
+    wall_hp: BTreeMap<usize, (u8, u8)>,
+    generation: u64,
+}
+
+#[cfg(feature = "serde")]
+impl From<TileMap> for TileMapData {
+    fn from(map: TileMap) -> Self {
+        let TileMap { width, height, tiles, radius_mods, wall_hp, generation } = map;
+        Self { width, height, tiles, radius_mods, wall_hp, generation }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<TileMapData> for TileMap {
+    type Error = String;
+
+    /// Rejects tile counts other than `width * height`, and wall hit points for tiles off
+    /// the map, with no maximum, or above their maximum.
+    fn try_from(data: TileMapData) -> Result<Self, Self::Error> {
+        let TileMapData { width, height, tiles, radius_mods, wall_hp, generation } = data;
+        check_size(width, height).map_err(|e| e.to_string())?;
+        if width.checked_mul(height) != Some(tiles.len()) {
+            let n = tiles.len();
+            return Err(format!("tile count {n} doesn't match a {width}x{height} map"));
+        }
+        let bad_hp = wall_hp.iter().find(|&(&id, &(hp, max_hp))| {
+            id >= tiles.len() || max_hp == 0 || hp > max_hp
+        });
+        if let Some((id, (hp, max_hp))) = bad_hp {
+            return Err(format!("invalid wall hit points {hp}/{max_hp} for tile {id}"));
+        }
+        Ok(Self { width, height, tiles, radius_mods, wall_hp, generation })
+    }
+}
+
+impl PartialEq for TileMap {
+    fn eq(&self, other: &Self) -> bool {
+        let Self { width, height, tiles, radius_mods, wall_hp, generation: _ } = self;
+        (*width, *height, tiles, radius_mods, wall_hp)
+            == (other.width, other.height, &other.tiles, &other.radius_mods, &other.wall_hp)
+    }
+}
+
+impl Eq for TileMap {}
+
+impl TileMap {
+    /// Creates a new `TileMap` of transparent tiles.
+    ///
+    /// Returns an error if `width` or `height` is `0`.
+    pub fn new(width: usize, height: usize) -> Result<Self, FovError> {
+        Self::filled(width, height, Tile::new())
+    }
+    /// Creates a new `TileMap` with every tile set to `tile` (e.g. `Tile::opaque()`, to carve
+    /// rooms out of solid rock). Returns an error as `new` does.
+    pub fn filled(width: usize, height: usize, tile: Tile) -> Result<Self, FovError> {
+        check_size(width, height)?;
+        Ok(Self {
+            width,
+            height,
+            tiles: vec![tile; width * height],
+            radius_mods: BTreeMap::new(),
+            wall_hp: BTreeMap::new(),
+            generation: 0,
+        })
+    }
+    /// Parses a classic roguelike ASCII map: `#` is an opaque body, `.` open floor.
+    ///
+    /// See `from_ascii_with` for the layout rules. Returns an error for any other glyph.
+    pub fn from_ascii(text: &str) -> Result<Self, FovError> {
+        Self::from_ascii_with(text, &Glyphs::default())
+    }
+    /// Parses an ASCII map, one tile per glyph, with tiles looked up in `glyphs`.
+    ///
+    /// The first row is the northmost (highest `y`), so rows read as drawn. Blank lines and
+    /// leading and trailing whitespace are skipped, so maps may be pasted indented into a
+    /// string literal. Rows shorter than the longest are padded with open tiles.
+    ///
+    /// Returns an `InvalidData` error for glyphs missing from `glyphs` or maps over
+    /// `MAX_ASCII_TILES` tiles, and `MapTooSmall` if `text` has no rows.
+    pub fn from_ascii_with(text: &str, glyphs: &Glyphs) -> Result<Self, FovError> {
+        let rows: Vec<&str> = text.lines().map(str::trim).filter(|r| !r.is_empty()).collect();
+        let width = rows.iter().map(|r| r.chars().count()).max().unwrap_or(0);
+        if width.saturating_mul(rows.len()) > MAX_ASCII_TILES {
+            return Err(FovError::invalid("ASCII map is too large"));
+        }
+        let mut map = Self::new(width, rows.len())?;
+
+        for (row, text) in rows.iter().enumerate() {
+            let y = rows.len() - 1 - row;
+            for (x, glyph) in text.chars().enumerate() {
+                let Some(tile) = glyphs.get(glyph) else {
+                    let msg = format!("unknown map glyph {glyph:?} at row {row}, column {x}");
+                    return Err(FovError::invalid(msg));
+                };
+                map.tiles[y * width + x] = tile;
+            }
+        }
+
+        Ok(map)
+    }
+    /// Creates a new `TileMap` with randomly placed opaque tile bodies.
+    ///
+    /// Each tile is opaque with probability `density`. The same `seed` always
+    /// produces the same map. Returns an error as `new` does.
+    pub fn random(width: usize, height: usize, density: f64, seed: u64) -> Result<Self, FovError> {
+        let mut rng = Rng::new(seed);
+        let mut map = Self::new(width, height)?;
+
+        for tile in map.tiles.iter_mut() {
+            tile.body_opaque = rng.chance(density);
+        }
+
+        Ok(map)
+    }
+    /// Returns the width of the map, in tiles.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    /// Returns the height of the map, in tiles.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    /// Returns `true` if `coords` lie within the map.
+    pub fn in_bounds(&self, coords: Coords) -> bool {
+        coords.x >= 0
+            && coords.y >= 0
+            && (coords.x as usize) < self.width
+            && (coords.y as usize) < self.height
+    }
+    /// Returns the tile ID at `coords`, or `None` if out of bounds.
+    pub fn tile_id(&self, coords: Coords) -> Option<usize> {
+        match self.in_bounds(coords) {
+            true => coords.to_index(self.width),
+            false => None,
+        }
+    }
+    /// Returns the tile with ID `id`, or `None` if out of bounds.
+    pub fn tile(&self, id: usize) -> Option<&Tile> {
+        self.tiles.get(id)
+    }
+    /// Returns the tile at `coords`, or `None` if out of bounds.
+    pub fn get(&self, coords: Coords) -> Option<&Tile> {
+        self.tile_id(coords).map(|id| &self.tiles[id])
+    }
+    /// Returns the tile at `coords` mutably, or `None` if out of bounds.
+    ///
+    /// Advances the map generation, since the tile may be modified.
+    pub fn get_mut(&mut self, coords: Coords) -> Option<&mut Tile> {
+        let id = self.tile_id(coords)?;
+        self.bump_generation();
+        Some(&mut self.tiles[id])
+    }
+    /// Returns the map generation, which increases on every mutation.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    /// Advances the map generation. Call after changes that bypass `TileMap` methods.
+    pub fn bump_generation(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+    /// Returns an `OutOfBounds` error for `coords` on this map.
+    fn out_of_bounds(&self, coords: Coords) -> FovError {
+        FovError::OutOfBounds {
+            coords,
+            width: self.width,
+            height: self.height,
+        }
+    }
+    /// Sets body opacity of the tile at `coords`. Out of bounds `coords` are ignored.
+    pub fn set_body_opaque(&mut self, coords: Coords, opaque: bool) {
+        if let Some(tile) = self.get_mut(coords) {
+            tile.body_opaque = opaque;
+        }
+    }
+    /// Returns counts and densities of the map's obstructions and open floor.
+    pub fn stats(&self) -> MapStats {
+        let mut stats = MapStats {
+            tiles: self.tiles.len(),
+            destructible: self.wall_hp.len(),
+            ..MapStats::default()
+        };
+        for tile in self.tiles.iter() {
+            stats.walls += tile.wall_n_opaque as usize + tile.wall_w_opaque as usize;
+            match (tile.body_opaque, tile.wall_height) {
+                (true, _) => stats.opaque += 1,
+                (false, 0) => stats.open += 1,
+                (false, _) => stats.low_walls += 1,
+            }
+        }
+        stats
+    }
+    /// Returns an iterator over all tiles in the map, in tile ID order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Tile> {
+        self.tiles.iter()
+    }
+    /// Sets the FOV radius modifier for observers standing on tiles with terrain `tag`.
+    ///
+    /// Example: `map.set_radius_modifier(TOWER, 4)` lets observers on towers see 4 tiles farther.
+    pub fn set_radius_modifier(&mut self, tag: u8, delta: i32) {
+        self.bump_generation();
+        match delta {
+            0 => self.radius_mods.remove(&tag),
+            _ => self.radius_mods.insert(tag, delta),
+        };
+    }
+    /// Makes the tile at `coords` an intact destructible wall with `max_hp` hit points.
+    ///
+    /// `max_hp` of `0` stops tracking hit points, leaving the tile as it is. Returns an
+    /// `OutOfBounds` error if `coords` is off the map.
+    pub fn set_wall_hp(&mut self, coords: Coords, max_hp: u8) -> Result<(), FovError> {
+        let id = self.tile_id(coords).ok_or_else(|| self.out_of_bounds(coords))?;
+        match max_hp {
+            0 => {
+                self.wall_hp.remove(&id);
+                self.bump_generation();
+            }
+            _ => {
+                self.wall_hp.insert(id, (max_hp, max_hp));
+                self.apply_wall_state(id);
+            }
+        }
+        Ok(())
+    }
+    /// Returns the damage state of the destructible wall at `coords`, if it is one.
+    pub fn wall_state(&self, coords: Coords) -> Option<WallState> {
+        let &(hp, max_hp) = self.wall_hp.get(&self.tile_id(coords)?)?;
+        Some(WallState::from_hp(hp, max_hp))
+    }
+    /// Deals `amount` damage to the destructible wall at `coords`, returning its new state.
+    ///
+    /// Walls above half hit points are intact and opaque. Damaged walls become partial
+    /// occluders: their body turns transparent and their `wall_height` drops with their
+    /// hit points, so they block only shorter targets. Destroyed walls (`0` hit points)
+    /// block nothing. Returns `None` if there is no destructible wall at `coords`.
+    ///
+    /// The map generation advances, so stale `VisibilityGrid`s and cached views (such as
+    /// `FactionLayers`) are detected and rebuilt.
+    pub fn damage(&mut self, coords: Coords, amount: u8) -> Option<WallState> {
+        let id = self.tile_id(coords)?;
+        let (hp, max_hp) = self.wall_hp.get_mut(&id)?;
+        *hp = hp.saturating_sub(amount);
+        let state = WallState::from_hp(*hp, *max_hp);
+        self.apply_wall_state(id);
+        Some(state)
+    }
+    /// Sets the body opacity and wall height of tile `id` from its wall hit points.
+    fn apply_wall_state(&mut self, id: usize) {
+        let Some(&(hp, max_hp)) = self.wall_hp.get(&id) else {
+            return;
+        };
+        let tile = &mut self.tiles[id];
+        (tile.body_opaque, tile.wall_height) = match WallState::from_hp(hp, max_hp) {
+            WallState::Intact => (true, 0),
+            WallState::Damaged => {
+                // Heights `1..HEIGHT_LEVELS`, from near-destroyed up to half hit points
+                let levels = HEIGHT_LEVELS - 1;
+                let height = (hp as usize * 2 * levels).div_ceil(max_hp as usize);
+                (false, height.clamp(1, levels) as u8)
+            }
+            WallState::Destroyed => (false, 0),
+        };
+        self.bump_generation();
+    }
+    /// Stamps `prefab` onto the map, with its anchor at `at`, rotated by `rotation`.
+    ///
+    /// Tiles under the prefab are replaced. Walls are remapped to the rotated edges: a
+    /// wall that ends up on a tile's south or east edge is stored as the north or west
+    /// wall of the neighboring tile. Parts falling outside the map are ignored.
+    pub fn stamp(&mut self, prefab: &Prefab, at: Coords, rotation: Rotation) {
+        let place = |local: Coords| {
+            let anchor = prefab.anchor;
+            let d = rotation.apply(Delta::new(local.x - anchor.x, local.y - anchor.y));
+            Coords::new(at.x + d.dx, at.y + d.dy)
+        };
+        let locals = (0..prefab.tiles.height as i32)
+            .flat_map(|y| (0..prefab.tiles.width as i32).map(move |x| Coords::new(x, y)));
+
+        for local in locals.clone() {
+            if let (Some(tile), Some(id)) = (prefab.tiles.get(local), self.tile_id(place(local))) {
+                self.wall_hp.remove(&id);
+                self.tiles[id] = Tile {
+                    wall_n_opaque: false,
+                    wall_w_opaque: false,
+                    ..*tile
+                };
+            }
+        }
+        for local in locals {
+            let Some(tile) = prefab.tiles.get(local) else {
+                continue;
+            };
+            let walls = [
+                (tile.wall_n_opaque, Direction::North),
+                (tile.wall_w_opaque, Direction::West),
+            ];
+            for (_, direction) in walls.into_iter().filter(|w| w.0) {
+                let coords = place(local);
+                let (coords, wall_n) = match rotation.direction(direction) {
+                    Direction::North => (coords, true),
+                    Direction::West => (coords, false),
+                    Direction::South => (coords.step(Direction::South), true),
+                    Direction::East => (coords.step(Direction::East), false),
+                };
+                if let Some(id) = self.tile_id(coords) {
+                    match wall_n {
+                        true => self.tiles[id].wall_n_opaque = true,
+                        false => self.tiles[id].wall_w_opaque = true,
+                    }
+                }
+            }
+        }
+
+        self.bump_generation();
+    }
+}
+
+impl Index<Coords> for TileMap {
+    type Output = Tile;
+
+    /// Returns the tile at `coords`. Panics if out of bounds (see `TileMap::get`).
+    fn index(&self, coords: Coords) -> &Tile {
+        match self.get(coords) {
+            Some(tile) => tile,
+            None => panic!("{}!", self.out_of_bounds(coords)),
+        }
+    }
+}
+
+impl IndexMut<Coords> for TileMap {
+    /// Returns the tile at `coords` mutably, advancing the map generation. Panics if out of
+    /// bounds (see `TileMap::get_mut`).
+    fn index_mut(&mut self, coords: Coords) -> &mut Tile {
+        let error = self.out_of_bounds(coords);
+        match self.get_mut(coords) {
+            Some(tile) => tile,
+            None => panic!("{error}!"),
+        }
+    }
+}
+
+/// Obstruction counts of a `TileMap`, from `TileMap::stats`.
+///
+/// Each tile is exactly one of `opaque`, `low_walls` or `open`. `walls` counts opaque north
+/// and west wall edges; `destructible` counts tiles with tracked hit points. Doors are not
+/// a tile type; doorways are found by `analysis::choke_points`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MapStats {
+    pub tiles: usize,
+    pub opaque: usize,
+    pub low_walls: usize,
+    pub open: usize,
+    pub walls: usize,
+    pub destructible: usize,
+}
+
+impl MapStats {
+    /// Returns the fraction (`0.0` to `1.0`) of tiles with opaque bodies.
+    pub fn density(&self) -> f64 {
+        self.opaque as f64 / self.tiles.max(1) as f64
+    }
+    /// Returns the fraction (`0.0` to `1.0`) of tiles that are open floor.
+    pub fn open_fraction(&self) -> f64 {
+        self.open as f64 / self.tiles.max(1) as f64
+    }
+    /// Returns the mean number of opaque wall edges per tile (`0.0` to `2.0`).
+    pub fn wall_density(&self) -> f64 {
+        self.walls as f64 / self.tiles.max(1) as f64
+    }
+}
+
+/// Damage state of a destructible wall (see `TileMap::damage`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WallState {
+    /// Above half hit points: fully opaque.
+    Intact,
+    /// At or below half hit points: a partial occluder.
+    Damaged,
+    /// No hit points left: blocks nothing.
+    Destroyed,
+}
+
+impl WallState {
+    /// Returns the state of a wall with `hp` of `max_hp` hit points.
+    pub fn from_hp(hp: u8, max_hp: u8) -> Self {
+        match hp {
+            0 => WallState::Destroyed,
+            _ if hp as u16 * 2 > max_hp as u16 => WallState::Intact,
+            _ => WallState::Damaged,
+        }
+    }
+}
+
+/// Counter-clockwise rotation by a multiple of 90 degrees, e.g. for `TileMap::stamp`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Rotation {
+    #[default]
+    R0,
+    R90,
+    R180,
+    R270,
+}
+
+impl Rotation {
+    /// All four rotations, in counter-clockwise order.
+    pub const ALL: [Rotation; 4] = [Rotation::R0, Rotation::R90, Rotation::R180, Rotation::R270];
+    /// Returns `d` rotated counter-clockwise about the origin.
+    pub fn apply(&self, d: Delta) -> Delta {
+        match self {
+            Rotation::R0 => d,
+            Rotation::R90 => Delta::new(-d.dy, d.dx),
+            Rotation::R180 => Delta::new(-d.dx, -d.dy),
+            Rotation::R270 => Delta::new(d.dy, -d.dx),
+        }
+    }
+    /// Returns `direction` rotated counter-clockwise.
+    pub fn direction(&self, direction: Direction) -> Direction {
+        let steps = *self as usize;
+        let ix = Direction::ALL.iter().position(|d| *d == direction).unwrap_or(0);
+        Direction::ALL[(ix + steps) % 4]
+    }
+}
+
+/// Small map of tiles stamped onto a `TileMap` as a unit, e.g. a room or bunker.
+///
+/// The `anchor` is the prefab tile placed at the stamp position, and the center of
+/// rotation (see `TileMap::stamp`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prefab {
+    tiles: TileMap,
+    anchor: Coords,
+}
+
+impl Prefab {
+    pub fn new(tiles: TileMap, anchor: Coords) -> Self {
+        Self { tiles, anchor }
+    }
+    /// Creates a new `Prefab` from text rows: `#` is an opaque body, anything else transparent.
+    ///
+    /// The first row is the northmost (highest `y`), so rows read as drawn. Rows shorter than
+    /// the longest are padded with transparent tiles. Returns an error if `rows` is empty.
+    /// For other glyphs, parse the tiles with `TileMap::from_ascii_with` and use `new`.
+    pub fn from_rows(rows: &[&str], anchor: Coords) -> Result<Self, FovError> {
+        let tiles = TileMap::from_ascii_with(&ascii_from_rows(rows), &Glyphs::default())?;
+        Ok(Self { tiles, anchor })
+    }
+    /// Returns the prefab's tiles.
+    pub fn tiles(&self) -> &TileMap {
+        &self.tiles
+    }
+    /// Returns the prefab's tiles mutably, e.g. to add walls or tags.
+    pub fn tiles_mut(&mut self) -> &mut TileMap {
+        &mut self.tiles
+    }
+    /// Returns the anchor tile, in prefab coordinates.
+    pub fn anchor(&self) -> Coords {
+        self.anchor
+    }
+}
+
+
+/// 2D map of tile body opacity, packed into `u64` rows (one bit per tile).
+///
+/// Notes:
+/// - each row spans `ceil(width / 64)` words; bit `x % 64` of word `x / 64`.
+/// - only the tile `body` is stored, which is all _Simple_ FOV requires.
+/// - packed rows keep large maps cache-friendly and allow whole-row bitwise ops.
+/// - `generation` increases on every mutation, and is ignored by `==`, as for `TileMap`.
+#[derive(Debug, Clone)]
+pub struct BitTileMap {
+    width: usize,
+    height: usize,
+    row_words: usize,
+    bits: Vec<u64>,
+    generation: u64,
+}
+
+impl PartialEq for BitTileMap {
+    fn eq(&self, other: &Self) -> bool {
+        let Self { width, height, row_words, bits, generation: _ } = self;
+        (*width, *height, *row_words, bits)
+            == (other.width, other.height, other.row_words, &other.bits)
+    }
+}
+
+impl Eq for BitTileMap {}
+
+impl BitTileMap {
+    /// Creates a new `BitTileMap` of transparent tiles.
+    ///
+    /// Returns an error if `width` or `height` is `0`.
+    pub fn new(width: usize, height: usize) -> Result<Self, FovError> {
+        check_size(width, height)?;
+        Ok(Self::blank(width, height))
+    }
+    /// Creates a new `BitTileMap` of transparent tiles, of a size already checked.
+    fn blank(width: usize, height: usize) -> Self {
+        let row_words = width.div_ceil(64);
+
+        Self {
+            width,
+            height,
+            row_words,
+            bits: vec![0; row_words * height],
+            generation: 0,
+        }
+    }
+    /// Returns the width of the map, in tiles.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    /// Returns the height of the map, in tiles.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    /// Returns `true` if `coords` lie within the map.
+    pub fn in_bounds(&self, coords: Coords) -> bool {
+        coords.x >= 0
+            && coords.y >= 0
+            && (coords.x as usize) < self.width
+            && (coords.y as usize) < self.height
+    }
+    /// Returns `true` if the tile body at `coords` is opaque. Out of bounds is transparent.
+    pub fn is_opaque(&self, coords: Coords) -> bool {
+        if !self.in_bounds(coords) {
+            return false;
+        }
+        let (x, y) = (coords.x as usize, coords.y as usize);
+        let word = self.bits[y * self.row_words + x / 64];
+
+        word & (1u64 << (x % 64)) != 0
+    }
+    /// Sets body opacity of the tile at `coords`. Out of bounds `coords` are ignored.
+    pub fn set_opaque(&mut self, coords: Coords, opaque: bool) {
+        if !self.in_bounds(coords) {
+            return;
+        }
+        let (x, y) = (coords.x as usize, coords.y as usize);
+        let word = &mut self.bits[y * self.row_words + x / 64];
+        let mask = 1u64 << (x % 64);
+        self.generation = self.generation.wrapping_add(1);
+
+        match opaque {
+            true => *word |= mask,
+            false => *word &= !mask,
+        }
+    }
+    /// Returns the packed opacity words for row `y`.
+    pub fn row(&self, y: usize) -> &[u64] {
+        let start = y * self.row_words;
+        &self.bits[start..start + self.row_words]
+    }
+    /// Returns the map generation, which increases on every mutation.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    /// Returns the number of opaque tiles in the map.
+    pub fn count_opaque(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
+
+impl From<&TileMap> for BitTileMap {
+    fn from(map: &TileMap) -> Self {
+        let mut bit_map = BitTileMap::blank(map.width(), map.height());
+
+        for y in 0..map.height() as i32 {
+            for x in 0..map.width() as i32 {
+                let coords = Coords::new(x, y);
+                if map.get(coords).is_some_and(|t| t.body_opaque) {
+                    bit_map.set_opaque(coords, true);
+                }
+            }
+        }
+
+        bit_map.generation = map.generation();
+        bit_map
+    }
+}
+
+/// 2D map of opaque walls on all four edges of each tile.
+///
+/// `TileMap` stores north and west walls only, reading south and east walls from the
+/// neighboring tile, so walls on the south and east map edges (e.g. chunk boundaries) are
+/// lost. `EdgeMap` stores every side of every tile instead.
+///
+/// Notes:
+/// - a wall between two tiles may be stored on either side, or both; `set_wall` sets both.
+/// - `inconsistencies` lists walls stored on one side only, e.g. after `edges_mut` edits or
+///   loading from a one-sided source; `make_consistent` copies them to the other side.
+/// - `generation` increases on every mutation, as for `TileMap`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeMap {
+    width: usize,
+    height: usize,
+    edges: Vec<Edges>,
+    generation: u64,
+}
+
+impl EdgeMap {
+    /// Creates a new `EdgeMap` without walls.
+    ///
+    /// Returns an error if `width` or `height` is `0`.
+    pub fn new(width: usize, height: usize) -> Result<Self, FovError> {
+        check_size(width, height)?;
+        Ok(Self::blank(width, height))
+    }
+    /// Creates a new `EdgeMap` without walls, of a size already checked.
+    fn blank(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            edges: vec![Edges::default(); width * height],
+            generation: 0,
+        }
+    }
+    /// Returns the width of the map, in tiles.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    /// Returns the height of the map, in tiles.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    /// Returns `true` if `coords` lie within the map.
+    pub fn in_bounds(&self, coords: Coords) -> bool {
+        coords.x >= 0
+            && coords.y >= 0
+            && (coords.x as usize) < self.width
+            && (coords.y as usize) < self.height
+    }
+    /// Returns the tile ID at `coords`, or `None` if out of bounds.
+    pub fn tile_id(&self, coords: Coords) -> Option<usize> {
+        match self.in_bounds(coords) {
+            true => coords.to_index(self.width),
+            false => None,
+        }
+    }
+    /// Returns the walls stored in the tile at `coords`, or `None` if out of bounds.
+    pub fn edges(&self, coords: Coords) -> Option<&Edges> {
+        self.tile_id(coords).map(|id| &self.edges[id])
+    }
+    /// Returns the walls stored in the tile at `coords` mutably, or `None` if out of bounds.
+    ///
+    /// Edits here are one-sided; advances the map generation.
+    pub fn edges_mut(&mut self, coords: Coords) -> Option<&mut Edges> {
+        let id = self.tile_id(coords)?;
+        self.generation = self.generation.wrapping_add(1);
+        Some(&mut self.edges[id])
+    }
+    /// Sets the wall on side `direction` of the tile at `coords`, and on the matching side of
+    /// its neighbor if in bounds. Out of bounds `coords` are ignored.
+    pub fn set_wall(&mut self, coords: Coords, direction: Direction, opaque: bool) {
+        let Some(edges) = self.edges_mut(coords) else {
+            return;
+        };
+        edges.set(direction, opaque);
+        if let Some(id) = self.tile_id(coords.step(direction)) {
+            self.edges[id].set(direction.opposite(), opaque);
+        }
+    }
+    /// Returns the map generation, which increases on every mutation.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    /// Returns walls stored on only one side of an edge shared by two tiles.
+    ///
+    /// Each is reported once, as the coordinates and side of the tile that stores it, in tile
+    /// ID order. Walls on the map edge have no other side and are always consistent.
+    pub fn inconsistencies(&self) -> Vec<(Coords, Direction)> {
+        let mut found = Vec::new();
+        for (id, edges) in self.edges.iter().enumerate() {
+            let Some(coords) = Coords::from_index(id, self.width) else {
+                continue;
+            };
+            for direction in Direction::ALL {
+                let other = self.edges(coords.step(direction));
+                if edges.get(direction) && other.is_some_and(|e| !e.get(direction.opposite())) {
+                    found.push((coords, direction));
+                }
+            }
+        }
+        found
+    }
+    /// Returns `true` if every shared wall is stored on both sides.
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistencies().is_empty()
+    }
+    /// Copies walls stored on one side of a shared edge to the other side.
+    pub fn make_consistent(&mut self) {
+        for (coords, direction) in self.inconsistencies() {
+            self.set_wall(coords, direction, true);
+        }
+    }
+}
+
+impl From<&TileMap> for EdgeMap {
+    /// Converts north and west walls, storing each on both sides of its edge.
+    fn from(map: &TileMap) -> Self {
+        let mut edge_map = EdgeMap::blank(map.width(), map.height());
+
+        for y in 0..map.height() as i32 {
+            for x in 0..map.width() as i32 {
+                let coords = Coords::new(x, y);
+                for direction in [Direction::North, Direction::West] {
+                    if map.wall_opaque(coords, direction) {
+                        edge_map.set_wall(coords, direction, true);
+                    }
+                }
+            }
+        }
+
+        edge_map.generation = map.generation();
+        edge_map
+    }
+}
+
+/// Provides tile obstruction data to FOV calculations.
+///
+/// Implemented by `TileMap` and `BitTileMap`, so FOV calculations can run
+/// against either representation.
+pub trait OpacityProvider {
+    /// Returns the width of the map, in tiles.
+    fn width(&self) -> usize;
+    /// Returns the height of the map, in tiles.
+    fn height(&self) -> usize;
+    /// Returns `true` if the tile body at `coords` is opaque. Out of bounds is transparent.
+    fn body_opaque(&self, coords: Coords) -> bool;
+    /// Returns `true` if `coords` lie within the map.
+    fn in_bounds(&self, coords: Coords) -> bool {
+        coords.x >= 0
+            && coords.y >= 0
+            && (coords.x as usize) < self.width()
+            && (coords.y as usize) < self.height()
+    }
+    /// Returns the tile ID at `coords`, or `None` if out of bounds.
+    fn tile_id(&self, coords: Coords) -> Option<usize> {
+        match self.in_bounds(coords) {
+            true => coords.to_index(self.width()),
+            false => None,
+        }
+    }
+    /// Returns the wall height at `coords`: `FULL_HEIGHT` if the body is opaque, else `0`.
+    ///
+    /// A wall of height `h` blocks FOV to targets with height level below `h`.
+    fn wall_height(&self, coords: Coords) -> u8 {
+        match self.body_opaque(coords) {
+            true => FULL_HEIGHT,
+            false => 0,
+        }
+    }
+    /// Returns the FOV radius modifier for an observer standing at `coords`. Defaults to `0`.
+    fn radius_modifier(&self, _coords: Coords) -> i32 {
+        0
+    }
+    /// Returns the map generation, stamped into FOV results. Defaults to `0`.
+    fn generation(&self) -> u64 {
+        0
+    }
+}
+
+impl OpacityProvider for TileMap {
+    fn width(&self) -> usize {
+        self.width
+    }
+    fn height(&self) -> usize {
+        self.height
+    }
+    fn body_opaque(&self, coords: Coords) -> bool {
+        self.get(coords).is_some_and(|t| t.body_opaque)
+    }
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+    fn wall_height(&self, coords: Coords) -> u8 {
+        match self.get(coords) {
+            Some(t) if t.body_opaque => FULL_HEIGHT,
+            Some(t) => t.wall_height,
+            None => 0,
+        }
+    }
+    fn radius_modifier(&self, coords: Coords) -> i32 {
+        self.get(coords)
+            .and_then(|t| self.radius_mods.get(&t.tag))
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+impl OpacityProvider for BitTileMap {
+    fn width(&self) -> usize {
+        self.width
+    }
+    fn height(&self) -> usize {
+        self.height
+    }
+    fn body_opaque(&self, coords: Coords) -> bool {
+        self.is_opaque(coords)
+    }
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+impl OpacityProvider for EdgeMap {
+    fn width(&self) -> usize {
+        self.width
+    }
+    fn height(&self) -> usize {
+        self.height
+    }
+    /// `EdgeMap` stores walls only, so tile bodies never block sight.
+    fn body_opaque(&self, _: Coords) -> bool {
+        false
+    }
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+/// Provides tile edge walls to FOV calculations that use them, such as _Standard_ FOV.
+///
+/// Implemented by `TileMap` (north and west walls) and `EdgeMap` (all four edges), so wall
+/// calculations can run against either representation.
+pub trait WallProvider {
+    /// Returns `true` if an opaque wall lies on side `direction` of the tile at `coords`.
+    /// Out of bounds tiles have no walls.
+    fn wall_opaque(&self, coords: Coords, direction: Direction) -> bool;
+}
+
+impl WallProvider for TileMap {
+    /// South and east walls are the neighbor's north and west walls, so there are none on
+    /// the south and east map edges.
+    fn wall_opaque(&self, coords: Coords, direction: Direction) -> bool {
+        if !self.in_bounds(coords) {
+            return false;
+        }
+        let neighbor = self.get(coords.step(direction));
+        match direction {
+            Direction::North => self.get(coords).is_some_and(|t| t.wall_n_opaque),
+            Direction::West => self.get(coords).is_some_and(|t| t.wall_w_opaque),
+            Direction::South => neighbor.is_some_and(|t| t.wall_n_opaque),
+            Direction::East => neighbor.is_some_and(|t| t.wall_w_opaque),
+        }
+    }
+}
+
+impl WallProvider for EdgeMap {
+    /// A wall stored on either side of the edge counts.
+    fn wall_opaque(&self, coords: Coords, direction: Direction) -> bool {
+        if !self.in_bounds(coords) {
+            return false;
+        }
+        let here = self.edges(coords).is_some_and(|e| e.get(direction));
+        let across = self.edges(coords.step(direction));
+        here || across.is_some_and(|e| e.get(direction.opposite()))
+    }
+}
+
+/// `OpacityProvider` view of `map` limited to the tiles within `clip`.
+///
+/// Tiles outside `clip` are out of bounds, so FOV traversal skips them exactly as it
+/// skips tiles past the map edge. Sizes and tile IDs are those of the full map, so
+/// results line up with unclipped queries.
+#[derive(Debug, Clone, Copy)]
+pub struct ClippedMap<'a, M> {
+    map: &'a M,
+    clip: Rect,
+}
+
+impl<'a, M: OpacityProvider> ClippedMap<'a, M> {
+    pub fn new(map: &'a M, clip: Rect) -> Self {
+        Self { map, clip }
+    }
+    /// Returns the clip rectangle.
+    pub fn clip(&self) -> Rect {
+        self.clip
+    }
+}
+
+impl<M: OpacityProvider> OpacityProvider for ClippedMap<'_, M> {
+    fn width(&self) -> usize {
+        self.map.width()
+    }
+    fn height(&self) -> usize {
+        self.map.height()
+    }
+    fn body_opaque(&self, coords: Coords) -> bool {
+        self.in_bounds(coords) && self.map.body_opaque(coords)
+    }
+    fn in_bounds(&self, coords: Coords) -> bool {
+        self.clip.contains(coords) && self.map.in_bounds(coords)
+    }
+    fn wall_height(&self, coords: Coords) -> u8 {
+        match self.in_bounds(coords) {
+            true => self.map.wall_height(coords),
+            false => 0,
+        }
+    }
+    fn radius_modifier(&self, coords: Coords) -> i32 {
+        self.map.radius_modifier(coords)
+    }
+    fn generation(&self) -> u64 {
+        self.map.generation()
+    }
+}
+
+/// Spatial index of the occluders of a map: tiles with any wall height.
+///
+/// Occluders are bucketed into square cells of `cell_size` tiles, so those near an origin
+/// are found without scanning every tile. On mostly open maps (e.g. outdoors), FOV queries
+/// can then read opacity for occluder nodes only (see `get_visible_grid_sparse`).
+///
+/// Notes:
+/// - the index is a snapshot of the map: rebuild it when `is_stale` reports a change.
+/// - within a cell, occluders are kept in tile ID order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OccluderIndex {
+    cell_size: usize,
+    cols: usize,
+    cells: Vec<Vec<Coords>>,
+    len: usize,
+    generation: u64,
+}
+
+impl OccluderIndex {
+    /// Creates a new `OccluderIndex` of `map`. Panics if `cell_size` is `0`.
+    pub fn new<M: OpacityProvider>(map: &M, cell_size: usize) -> Self {
+        assert!(cell_size > 0, "OccluderIndex requires a cell size of at least 1!");
+        let cols = map.width().div_ceil(cell_size);
+        let rows = map.height().div_ceil(cell_size);
+        let mut cells = vec![Vec::new(); cols * rows];
+        let mut len = 0;
+
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                let coords = Coords::new(x as i32, y as i32);
+                if map.wall_height(coords) > 0 {
+                    cells[(y / cell_size) * cols + x / cell_size].push(coords);
+                    len += 1;
+                }
+            }
+        }
+
+        Self {
+            cell_size,
+            cols,
+            cells,
+            len,
+            generation: map.generation(),
+        }
+    }
+    /// Returns the side of each index cell, in tiles.
+    pub fn cell_size(&self) -> usize {
+        self.cell_size
+    }
+    /// Returns the number of occluders.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Returns `true` if the map has no occluders.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Returns the generation of the map the index was built from.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    /// Returns `true` if `map` has changed since the index was built.
+    pub fn is_stale<M: OpacityProvider>(&self, map: &M) -> bool {
+        self.generation != map.generation()
+    }
+    /// Returns an iterator over the occluders within `rect`, cell by cell.
+    pub fn within(&self, rect: Rect) -> impl Iterator<Item = Coords> + '_ {
+        let rows = self.cells.len().checked_div(self.cols).unwrap_or(0);
+        let cell = |v: i64, n: usize| (v.max(0) / self.cell_size as i64).min(n as i64) as usize;
+        let (x0, y0) = (rect.x as i64, rect.y as i64);
+        let (x1, y1) = (x0 + rect.width as i64 - 1, y0 + rect.height as i64 - 1);
+        let empty = rect.width == 0 || rect.height == 0 || x1 < 0 || y1 < 0;
+        let (cx0, cx1) = (cell(x0, self.cols), cell(x1, self.cols.saturating_sub(1)));
+        let (cy0, cy1) = (cell(y0, rows), cell(y1, rows.saturating_sub(1)));
+        let cy_end = match empty || rows == 0 {
+            true => cy0,
+            false => cy1 + 1,
+        };
+
+        (cy0..cy_end)
+            .flat_map(move |cy| (cx0..=cx1).map(move |cx| &self.cells[cy * self.cols + cx]))
+            .flat_map(|cell| cell.iter().copied())
+            .filter(move |&coords| rect.contains(coords))
+    }
+}
+
+/// Identifies an observer faction, for per-faction opacity in `FactionLayers`.
+pub type FactionId = u32;
+
+/// Shared base map with per-faction body opacity overrides.
+///
+/// Notes:
+/// - an override makes a tile opaque or transparent for one faction only (e.g. a magical
+///   wall its casters can see through). Factions without overrides read the base map
+///   directly, with no cached copy.
+/// - each overridden faction's opacity is cached as a `BitTileMap`, rebuilt only when the
+///   base map generation or that faction's overrides change, so repeated queries reuse it.
+/// - each faction that has ever had overrides keeps its own generation counter, advanced on
+///   every override change and every base map change seen since. Its views report that
+///   counter, so no two opacity states it sees share a generation. Other factions' views
+///   report the base map generation.
+#[derive(Debug, Clone)]
+pub struct FactionLayers<M> {
+    map: M,
+    overrides: BTreeMap<FactionId, BTreeMap<usize, bool>>,
+    generations: BTreeMap<FactionId, FactionGeneration>,
+    cache: BTreeMap<FactionId, FactionCache>,
+}
+
+/// Generation counter of one faction, with the base map generation it last saw.
+#[derive(Debug, Clone, Copy)]
+struct FactionGeneration {
+    base: u64,
+    count: u64,
+}
+
+/// Cached opacity of one faction, with the faction generation it was built from.
+#[derive(Debug, Clone)]
+struct FactionCache {
+    generation: u64,
+    opacity: BitTileMap,
+}
+
+impl<M: OpacityProvider> FactionLayers<M> {
+    /// Creates new `FactionLayers` over `map`, with no overrides.
+    pub fn new(map: M) -> Self {
+        Self {
+            map,
+            overrides: BTreeMap::new(),
+            generations: BTreeMap::new(),
+            cache: BTreeMap::new(),
+        }
+    }
+    /// Returns the base map.
+    pub fn map(&self) -> &M {
+        &self.map
+    }
+    /// Returns the base map mutably. Caches rebuild if its generation changes.
+    pub fn map_mut(&mut self) -> &mut M {
+        &mut self.map
+    }
+    /// Sets the body opacity `faction` sees at `coords`; `None` clears the override.
+    ///
+    /// Out of bounds `coords` are ignored.
+    pub fn set_override(&mut self, faction: FactionId, coords: Coords, opaque: Option<bool>) {
+        let Some(id) = self.map.tile_id(coords) else {
+            return;
+        };
+        let layer = self.overrides.entry(faction).or_default();
+        match opaque {
+            Some(opaque) => layer.insert(id, opaque),
+            None => layer.remove(&id),
+        };
+        if layer.is_empty() {
+            self.overrides.remove(&faction);
+            self.cache.remove(&faction);
+        }
+        // Counters start at the base generation, past anything the faction's views reported
+        let base = self.map.generation();
+        let generation = FactionGeneration { base, count: base };
+        let generation = self.generations.entry(faction).or_insert(generation);
+        *generation = FactionGeneration { base, count: generation.count.wrapping_add(1) };
+    }
+    /// Returns the override `faction` has at `coords`, if any.
+    pub fn get_override(&self, faction: FactionId, coords: Coords) -> Option<bool> {
+        let id = self.map.tile_id(coords)?;
+        self.overrides.get(&faction)?.get(&id).copied()
+    }
+    /// Returns an `OpacityProvider` of the map as seen by `faction`.
+    ///
+    /// Factions without overrides view the base map. Otherwise, builds the faction's cached
+    /// opacity first if it is missing or stale.
+    pub fn view(&mut self, faction: FactionId) -> FactionView<'_, M> {
+        let generation = self.generation(faction);
+        if let Some(layer) = self.generations.get_mut(&faction) {
+            *layer = FactionGeneration { base: self.map.generation(), count: generation };
+        }
+        if !self.overrides.contains_key(&faction) {
+            return FactionView { map: &self.map, opacity: None, generation };
+        }
+
+        if !self.is_fresh(faction) {
+            let opacity = self.build_opacity(faction);
+            self.cache.insert(faction, FactionCache { generation, opacity });
+        }
+
+        FactionView { map: &self.map, opacity: Some(&self.cache[&faction].opacity), generation }
+    }
+    /// Returns the generation of the map as seen by `faction`.
+    ///
+    /// The faction's counter advances once if the base map changed since it was last seen.
+    fn generation(&self, faction: FactionId) -> u64 {
+        let base = self.map.generation();
+        match self.generations.get(&faction) {
+            Some(layer) if layer.base != base => layer.count.wrapping_add(1),
+            Some(layer) => layer.count,
+            None => base,
+        }
+    }
+    /// Returns `true` if `faction` has a cached opacity built from its current generation.
+    fn is_fresh(&self, faction: FactionId) -> bool {
+        let generation = self.generation(faction);
+        self.cache.get(&faction).is_some_and(|c| c.generation == generation)
+    }
+    /// Returns the base map opacity with the overrides of `faction` applied.
+    fn build_opacity(&self, faction: FactionId) -> BitTileMap {
+        let (width, height) = (self.map.width(), self.map.height());
+        let mut opacity = BitTileMap::blank(width, height);
+
+        for y in 0..height as i32 {
+            for x in 0..width as i32 {
+                let coords = Coords::new(x, y);
+                if self.map.body_opaque(coords) {
+                    opacity.set_opaque(coords, true);
+                }
+            }
+        }
+        for (&id, &opaque) in self.overrides.get(&faction).into_iter().flatten() {
+            if let Some(coords) = Coords::from_index(id, width) {
+                opacity.set_opaque(coords, opaque);
+            }
+        }
+
+        opacity
+    }
+}
+
+/// `OpacityProvider` view of `FactionLayers` as seen by one faction.
+///
+/// Body opacity comes from the faction's cached layer, if it has overrides; other data from
+/// the base map.
+#[derive(Debug, Clone, Copy)]
+pub struct FactionView<'a, M> {
+    map: &'a M,
+    opacity: Option<&'a BitTileMap>,
+    generation: u64,
+}
+
+impl<M: OpacityProvider> OpacityProvider for FactionView<'_, M> {
+    fn width(&self) -> usize {
+        self.map.width()
+    }
+    fn height(&self) -> usize {
+        self.map.height()
+    }
+    fn body_opaque(&self, coords: Coords) -> bool {
+        match self.opacity {
+            Some(opacity) => opacity.is_opaque(coords),
+            None => self.map.body_opaque(coords),
+        }
+    }
+    fn wall_height(&self, coords: Coords) -> u8 {
+        let Some(opacity) = self.opacity else {
+            return self.map.wall_height(coords);
+        };
+        match (opacity.is_opaque(coords), self.map.body_opaque(coords)) {
+            (true, _) => FULL_HEIGHT,
+            (false, true) => 0,
+            (false, false) => self.map.wall_height(coords),
+        }
+    }
+    fn radius_modifier(&self, coords: Coords) -> i32 {
+        self.map.radius_modifier(coords)
+    }
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+/// Returns `rows` as an ASCII map for `Glyphs::default()`.
+///
+/// `#` is kept and any other character becomes `.`; short rows are padded with `.`, so blank
+/// and indented rows keep their width and place.
+pub(crate) fn ascii_from_rows(rows: &[&str]) -> String {
+    let width = rows.iter().map(|r| r.chars().count()).max().unwrap_or(0);
+    let rows: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            let glyphs = row.chars().map(|c| match c {
+                '#' => '#',
+                _ => '.',
+            });
+            glyphs.chain(iter::repeat('.')).take(width).collect()
+        })
+        .collect();
+    rows.join("\n")
+}
+
+/// Returns a `MapTooSmall` error unless the map has at least one tile.
+fn check_size(width: usize, height: usize) -> Result<(), FovError> {
+    match width > 0 && height > 0 {
+        true => Ok(()),
+        false => Err(FovError::MapTooSmall { width, height }),
+    }
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
 
 #[cfg(test)]
 mod tests {
@@ -60,4 +1467,345 @@ mod tests {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn bit_tile_map_matches_tile_map() {
+        let mut map = TileMap::new(70, 3).unwrap();
+        let opaque =
+            [Coords::new(0, 0), Coords::new(63, 1), Coords::new(64, 1), Coords::new(69, 2)];
+        for coords in opaque.iter() {
+            map.set_body_opaque(*coords, true);
+        }
+        let bit_map = BitTileMap::from(&map);
+
+        assert_eq!(bit_map.count_opaque(), opaque.len());
+        for y in 0..3 {
+            for x in -1..71 {
+                let coords = Coords::new(x, y);
+                assert_eq!(bit_map.body_opaque(coords), map.body_opaque(coords));
+            }
+        }
+        assert_eq!(bit_map.row(1), &[1u64 << 63, 1]);
+    }
+
+    // Overrides apply to their faction only; caches follow base map and override changes.
+    #[test]
+    fn faction_layers() {
+        let (wall, ward) = (Coords::new(2, 1), Coords::new(3, 1));
+        let mut map = TileMap::new(5, 3).unwrap();
+        map.set_body_opaque(wall, true);
+        let mut layers = FactionLayers::new(map);
+        layers.set_override(1, wall, Some(false));
+        layers.set_override(2, ward, Some(true));
+        layers.set_override(2, Coords::new(9, 9), Some(true));
+
+        assert!(layers.view(0).body_opaque(wall) && !layers.view(0).body_opaque(ward));
+        assert!(!layers.view(1).body_opaque(wall) && layers.view(1).wall_height(wall) == 0);
+        assert!(layers.view(2).body_opaque(ward));
+        assert_eq!(layers.view(2).wall_height(ward), FULL_HEIGHT);
+        assert_eq!(layers.get_override(2, Coords::new(9, 9)), None);
+
+        let generation = layers.view(1).generation();
+        layers.map_mut().set_body_opaque(Coords::new(0, 0), true);
+        assert!(layers.view(1).body_opaque(Coords::new(0, 0)));
+        assert!(layers.view(1).generation() > generation);
+
+        let generation = layers.view(1).generation();
+        layers.set_override(1, wall, None);
+        assert!(layers.view(1).body_opaque(wall));
+        assert!(layers.view(1).generation() > generation);
+        assert_eq!(layers.overrides.len(), 1);
+        assert_eq!(layers.cache.len(), 1);
+    }
+
+    // Mutating the base map then clearing an override reports a new generation each time.
+    #[test]
+    fn faction_layers_mutate_then_clear() {
+        let wall = Coords::new(1, 1);
+        let mut layers = FactionLayers::new(TileMap::new(3, 3).unwrap());
+        layers.set_override(1, wall, Some(true));
+        let mut seen = vec![layers.view(1).generation()];
+        assert!(layers.view(1).body_opaque(wall));
+
+        layers.map_mut().set_body_opaque(Coords::new(0, 0), true);
+        seen.push(layers.view(1).generation());
+        layers.set_override(1, wall, None);
+        let view = layers.view(1);
+        assert!(!view.body_opaque(wall) && view.body_opaque(Coords::new(0, 0)));
+        seen.push(view.generation());
+
+        // Clearing without viewing in between still reports a new generation
+        layers.set_override(1, wall, Some(true));
+        layers.map_mut().set_body_opaque(Coords::new(0, 0), false);
+        layers.set_override(1, wall, None);
+        seen.push(layers.view(1).generation());
+        assert!(seen.windows(2).all(|w| w[0] < w[1]), "generations {seen:?}");
+    }
+
+    // An override for one faction leaves other factions' caches fresh.
+    #[test]
+    fn faction_layers_rebuild_per_faction() {
+        let mut layers = FactionLayers::new(TileMap::new(5, 3).unwrap());
+        layers.set_override(1, Coords::new(1, 1), Some(true));
+        layers.set_override(2, Coords::new(2, 1), Some(true));
+        let generation = layers.view(2).generation();
+        layers.view(1);
+
+        layers.set_override(1, Coords::new(3, 1), Some(true));
+        assert!(layers.is_fresh(2) && !layers.is_fresh(1));
+        assert_eq!(layers.view(2).generation(), generation);
+        assert!(layers.view(1).body_opaque(Coords::new(3, 1)));
+        assert!(!layers.view(2).body_opaque(Coords::new(3, 1)));
+    }
+
+    // Bodies follow the rotation about the anchor; walls move to the rotated edges.
+    #[test]
+    fn stamp_prefab_rotations() {
+        let spaced = Prefab::from_rows(&[" #", "", "x"], Coords::new(0, 0)).unwrap();
+        assert_eq!((spaced.tiles().width(), spaced.tiles().height()), (2, 3));
+        assert!(spaced.tiles().body_opaque(Coords::new(1, 2)));
+        assert_eq!(spaced.tiles().stats().opaque, 1);
+
+        let mut prefab = Prefab::from_rows(&["#.", ".."], Coords::new(0, 0)).unwrap();
+        prefab.tiles_mut().get_mut(Coords::new(1, 0)).unwrap().wall_n_opaque = true;
+        prefab.tiles_mut().get_mut(Coords::new(1, 0)).unwrap().wall_w_opaque = true;
+        let at = Coords::new(5, 5);
+
+        // (wall body, north wall tiles, west wall tiles) for each rotation
+        let expected = [
+            ((5, 6), vec![(6, 5)], vec![(6, 5)]),
+            ((4, 5), vec![(5, 5)], vec![(5, 6)]),
+            ((5, 4), vec![(4, 4)], vec![(5, 5)]),
+            ((6, 5), vec![(5, 4)], vec![(6, 4)]),
+        ];
+
+        for (rotation, (body, north, west)) in Rotation::ALL.into_iter().zip(expected) {
+            let mut map = TileMap::new(10, 10).unwrap();
+            map.set_body_opaque(at, true);
+            map.set_body_opaque(Coords::new(9, 9), true);
+            let generation = map.generation();
+            map.stamp(&prefab, at, rotation);
+
+            let find = |f: fn(&Tile) -> bool| -> Vec<(i32, i32)> {
+                (0..100)
+                    .filter(|&id| f(map.tile(id).unwrap()))
+                    .map(|id| (id as i32 % 10, id as i32 / 10))
+                    .collect()
+            };
+            assert_eq!(find(|t| t.body_opaque), vec![body, (9, 9)], "{rotation:?}");
+            assert_eq!(find(|t| t.wall_n_opaque), north, "{rotation:?}");
+            assert_eq!(find(|t| t.wall_w_opaque), west, "{rotation:?}");
+            assert!(map.generation() > generation);
+        }
+    }
+
+    // Walls degrade from opaque to partial occluders to nothing, advancing the generation.
+    #[test]
+    fn wall_damage_states() {
+        let (wall, floor) = (Coords::new(1, 1), Coords::new(0, 0));
+        let mut map = TileMap::new(3, 3).unwrap();
+        map.set_wall_hp(wall, 12).unwrap();
+        assert!(map.body_opaque(wall));
+        assert_eq!(map.damage(floor, 5), None);
+
+        // (damage, state, wall height seen by FOV) after each hit
+        let hits = [
+            (5, WallState::Intact, FULL_HEIGHT),
+            (1, WallState::Damaged, 3),
+            (3, WallState::Damaged, 2),
+            (2, WallState::Damaged, 1),
+            (9, WallState::Destroyed, 0),
+        ];
+        for (amount, state, height) in hits {
+            let generation = map.generation();
+            assert_eq!(map.damage(wall, amount), Some(state));
+            assert_eq!(map.wall_state(wall), Some(state));
+            assert_eq!(map.wall_height(wall), height);
+            assert!(map.generation() > generation);
+        }
+
+        map.set_wall_hp(wall, 4).unwrap();
+        assert!(map.body_opaque(wall));
+        map.set_wall_hp(wall, 0).unwrap();
+        assert_eq!(map.wall_state(wall), None);
+    }
+
+    // Both models agree on interior walls; only `EdgeMap` keeps south and east map edges.
+    #[test]
+    fn edge_map_walls() {
+        let mut map = TileMap::new(4, 3).unwrap();
+        map.get_mut(Coords::new(1, 1)).unwrap().wall_n_opaque = true;
+        map.get_mut(Coords::new(2, 0)).unwrap().wall_w_opaque = true;
+        map.get_mut(Coords::new(0, 2)).unwrap().wall_w_opaque = true;
+
+        let mut edge_map = EdgeMap::from(&map);
+        assert!(edge_map.is_consistent());
+        assert!(edge_map.edges(Coords::new(1, 2)).unwrap().south);
+        assert!(edge_map.edges(Coords::new(1, 0)).unwrap().east);
+        for id in 0..12 {
+            let coords = Coords::new(id % 4, id / 4);
+            for direction in Direction::ALL {
+                assert_eq!(
+                    map.wall_opaque(coords, direction),
+                    edge_map.wall_opaque(coords, direction),
+                    "{coords:?} {direction:?}"
+                );
+            }
+        }
+
+        let corner = Coords::new(3, 0);
+        edge_map.set_wall(corner, Direction::East, true);
+        edge_map.set_wall(corner, Direction::South, true);
+        assert!(edge_map.wall_opaque(corner, Direction::East));
+        assert!(edge_map.wall_opaque(corner, Direction::South));
+        assert!(!map.wall_opaque(corner, Direction::East));
+        assert!(edge_map.is_consistent());
+
+        edge_map.edges_mut(Coords::new(2, 2)).unwrap().east = true;
+        assert_eq!(edge_map.inconsistencies(), vec![(Coords::new(2, 2), Direction::East)]);
+        assert!(edge_map.wall_opaque(Coords::new(3, 2), Direction::West));
+        edge_map.make_consistent();
+        assert!(edge_map.edges(Coords::new(3, 2)).unwrap().west);
+        assert!(edge_map.is_consistent());
+    }
+
+    // Negative, too wide or overflowing coordinates never wrap into another tile.
+    #[test]
+    fn coords_index_conversions() {
+        assert_eq!(Coords::new(3, 2).to_index(10), Some(23));
+        assert_eq!(Coords::from_index(23, 10), Some(Coords::new(3, 2)));
+        assert_eq!(Coords::new(-1, 2).to_index(10), None);
+        assert_eq!(Coords::new(3, -1).to_index(10), None);
+        assert_eq!(Coords::new(10, 0).to_index(10), None);
+        assert_eq!(Coords::new(0, i32::MAX).to_index(usize::MAX), None);
+        assert_eq!(Coords::from_index(5, 0), None);
+        assert_eq!(Coords::from_index(usize::MAX, 1), None);
+
+        let map = TileMap::new(10, 4).unwrap();
+        assert_eq!(map.tile_id(Coords::new(-1, 1)), None);
+        assert_eq!(map.tile_id(Coords::new(10, 0)), None);
+        assert_eq!(map.tile_id(Coords::new(9, 3)), Some(39));
+        assert_eq!(Coords::new(i32::MAX, 0).step(Direction::East), Coords::new(i32::MAX, 0));
+    }
+
+    // Filled maps copy their tile everywhere; indexing by coords reads and writes tiles.
+    #[test]
+    fn tile_map_filled_and_indexing() {
+        let mut map = TileMap::filled(6, 3, Tile::opaque()).unwrap();
+        assert!(map.iter().all(|tile| tile.body_opaque) && map.iter().len() == 18);
+        assert_eq!(map[Coords::new(5, 2)], Tile::opaque());
+
+        let generation = map.generation();
+        map[Coords::new(2, 1)] = Tile::new();
+        map[Coords::new(3, 1)].wall_w_opaque = true;
+        assert!(map.generation() > generation);
+        assert!(!map[Coords::new(2, 1)].body_opaque);
+        assert_eq!(map.get(Coords::new(3, 1)).map(|t| t.wall_w_opaque), Some(true));
+        assert_eq!(map.stats().open_fraction(), 1.0 / 18.0);
+    }
+
+    // ASCII maps read as drawn, with custom glyphs for walls; unknown glyphs are errors.
+    #[test]
+    fn tile_map_from_ascii() {
+        let map = TileMap::from_ascii(
+            "
+            #####
+            #...
+            #.#.#
+            ",
+        )
+        .unwrap();
+        assert_eq!((map.width(), map.height()), (5, 3));
+        assert!(map[Coords::new(0, 2)].body_opaque && map[Coords::new(2, 0)].body_opaque);
+        assert!(!map[Coords::new(1, 1)].body_opaque && !map[Coords::new(4, 1)].body_opaque);
+        assert_eq!(map.stats().opaque, 9);
+
+        let door = Tile {
+            wall_w_opaque: true,
+            tag: 2,
+            ..Tile::new()
+        };
+        let glyphs = Glyphs::default().with('|', door);
+        let map = TileMap::from_ascii_with("#|.", &glyphs).unwrap();
+        assert_eq!(map[Coords::new(1, 0)], door);
+
+        let err = TileMap::from_ascii("##\n#@").unwrap_err();
+        assert!(matches!(err, FovError::InvalidData(_)));
+        assert!(err.to_string().contains("'@' at row 1, column 1"));
+    }
+
+    #[test]
+    #[should_panic(expected = "outside the 6x3 map")]
+    fn tile_map_index_out_of_bounds() {
+        let map = TileMap::new(6, 3).unwrap();
+        let _ = map[Coords::new(6, 0)];
+    }
+
+    // Every tile is opaque, low wall or open; wall edges and destructibles are counted.
+    #[test]
+    fn map_stats() {
+        let mut map = TileMap::new(4, 5).unwrap();
+        assert_eq!(map.stats().open_fraction(), 1.0);
+        map.set_body_opaque(Coords::new(0, 0), true);
+        map.set_body_opaque(Coords::new(1, 0), true);
+        map.get_mut(Coords::new(2, 2)).unwrap().wall_height = 1;
+        map.get_mut(Coords::new(3, 3)).unwrap().wall_n_opaque = true;
+        map.get_mut(Coords::new(3, 3)).unwrap().wall_w_opaque = true;
+        map.set_wall_hp(Coords::new(3, 4), 10).unwrap();
+
+        let stats = map.stats();
+        let expected = MapStats {
+            tiles: 20,
+            opaque: 3,
+            low_walls: 1,
+            open: 16,
+            walls: 2,
+            destructible: 1,
+        };
+        assert_eq!(stats, expected);
+        assert_eq!(stats.density(), 0.15);
+        assert_eq!(stats.open_fraction(), 0.8);
+        assert_eq!(stats.wall_density(), 0.1);
+        assert_eq!(MapStats::default().density(), 0.0);
+    }
+
+    // Occluder indexes find exactly the walled tiles within a rectangle, across cells.
+    #[test]
+    fn occluder_index_within() {
+        let mut map = TileMap::random(23, 17, 0.1, 5).unwrap();
+        map.get_mut(Coords::new(4, 4)).unwrap().wall_height = 2;
+        let index = OccluderIndex::new(&map, 5);
+        let walled = |c: &Coords| map.wall_height(*c) > 0;
+        let all = (0..23 * 17).filter_map(|id| Coords::from_index(id, 23));
+        assert_eq!(index.len(), all.clone().filter(walled).count());
+
+        for rect in [Rect::new(3, 2, 9, 11), Rect::new(-4, -4, 6, 30), Rect::new(20, 15, 40, 40)] {
+            let mut found: Vec<_> = index.within(rect).collect();
+            found.sort_by_key(|c| (c.y, c.x));
+            let expected: Vec<_> = all.clone().filter(|c| walled(c) && rect.contains(*c)).collect();
+            assert_eq!(found, expected);
+        }
+        assert_eq!(index.within(Rect::new(30, 0, 5, 5)).count(), 0);
+        assert_eq!(index.within(Rect::new(0, 0, 0, 5)).count(), 0);
+        assert!(!index.is_stale(&map));
+    }
+
+    // Mutated ASCII maps parse to full maps or errors, never panics.
+    #[test]
+    fn from_ascii_mutated() {
+        let glyphs = Glyphs::default().with('~', Tile { tag: 1, ..Tile::new() });
+        let text = b"\n    #####\n    #.~.#\n    #...\n    #####\n";
+        let mut rng = Rng::new(1950);
+        for _ in 0..5000 {
+            let text = String::from_utf8_lossy(&rng.mutate(text)).into_owned();
+            if let Ok(map) = TileMap::from_ascii_with(&text, &glyphs) {
+                assert_eq!(map.stats().tiles, map.width() * map.height());
+                assert!(map.width() > 0 && map.height() > 0);
+            }
+        }
+
+        // One long row pads every short row out to its width
+        let padded = "#".repeat(1 << 13) + &"\n.".repeat(1 << 12);
+        assert!(TileMap::from_ascii(&padded).is_err());
+    }
 }
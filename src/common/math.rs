@@ -2,10 +2,35 @@
 
 // TODO: continue FovRect; add Ray-Rect intersection
 
+use std::fmt;
+
 use super::maps::Coords;
 
+/// Error returned by validated math constructors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MathError {
+    /// A coordinate or component is NaN or infinite.
+    NonFinite,
+    /// A line's endpoints are identical, so it has no direction.
+    DegenerateLine,
+    /// A vector has zero length, so it cannot be normalized.
+    ZeroVector,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MathError::NonFinite => write!(f, "value is NaN or infinite"),
+            MathError::DegenerateLine => write!(f, "line has zero length"),
+            MathError::ZeroVector => write!(f, "vector has zero length"),
+        }
+    }
+}
+
+impl std::error::Error for MathError {}
+
 /// 2D integer deltas.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Delta {
     pub dx: i32,
     pub dy: i32,
@@ -29,6 +54,18 @@ impl Point {
     pub fn new(x: f64, y: f64) -> Self {
         Self { x, y }
     }
+    /// Creates a new `Point` instance, or an error if `x` or `y` is not finite.
+    pub fn try_new(x: f64, y: f64) -> Result<Self, MathError> {
+        let p = Self::new(x, y);
+        match p.is_finite() {
+            true => Ok(p),
+            false => Err(MathError::NonFinite),
+        }
+    }
+    /// Returns `true` if neither coordinate is NaN or infinite.
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
     /// Returns the distance between `self` and `other`.
     pub fn distance(&self, other: Point) -> f64 {
         let dx_abs = (other.x - self.x).powi(2);
@@ -68,25 +105,44 @@ impl Line {
     pub fn new(x1: f64, y1: f64, x2: f64, y2: f64) -> Self {
         Self { x1, y1, x2, y2 }
     }
-    /// Creates a new line of specified `length` from given `ray`.
-    pub fn from_ray(ray: Ray, length: f64) -> Self {
-        let v = Vector::normalized(ray.r0.x, ray.r0.y);
+    /// Creates a new line, or an error if it is non-finite or has zero length.
+    pub fn try_new(x1: f64, y1: f64, x2: f64, y2: f64) -> Result<Self, MathError> {
+        let line = Self::new(x1, y1, x2, y2);
+        line.validate().map(|_| line)
+    }
+    /// Returns an error if the line is non-finite or has zero length.
+    pub fn validate(&self) -> Result<(), MathError> {
+        let finite = [self.x1, self.y1, self.x2, self.y2].iter().all(|v| v.is_finite());
+
+        match (finite, self.x1 == self.x2 && self.y1 == self.y2) {
+            (false, _) => Err(MathError::NonFinite),
+            (true, true) => Err(MathError::DegenerateLine),
+            (true, false) => Ok(()),
+        }
+    }
+    /// Creates a new line of specified `length` from `ray`'s origin, along its direction.
+    ///
+    /// Returns an error if the ray's direction cannot be normalized.
+    pub fn from_ray(ray: Ray, length: f64) -> Result<Self, MathError> {
+        let v = Vector::normalized(ray.rv.x, ray.rv.y)?;
         let x1 = ray.r0.x;
         let y1 = ray.r0.y;
         let x2 = x1 + v.x * length;
         let y2 = y1 + v.y * length;
 
-        Self { x1, y1, x2, y2 }
+        Ok(Self { x1, y1, x2, y2 })
     }
     /// Returns the length of the line.
     pub fn length(&self) -> f64 {
         let dx = (self.x1 - self.x2).abs();
         let dy = (self.y1 - self.y2).abs();
 
-        return (dx * dx + dy * dy).sqrt();
+        (dx * dx + dy * dy).sqrt()
     }
     /// Returns `true` if `self` intersects `other` line, else `false`.
     ///
+    /// Lines with NaN or infinite coordinates never intersect.
+    ///
     /// - Segment 1 is from `(x1, y1)` to `(x2, y2)`, along `t`.
     /// - Segment 2 is from `(x3, y3)` to `(x4, y4)`, along `u`.
     pub fn intersects(self, other: Self) -> bool {
@@ -99,6 +155,7 @@ impl Line {
         let u_num = (x1 - x3) * (y1 - y2) - (y1 - y3) * (x1 - x2);
 
         !(denom == 0.0
+            || !(denom.is_finite() && t_num.is_finite() && u_num.is_finite())
             || (t_num > 0.0 && t_num > denom)
             || (t_num < 0.0 && t_num < denom)
             || (u_num > 0.0 && u_num > denom)
@@ -106,6 +163,8 @@ impl Line {
     }
     /// Returns intersection point of `self` and `other` line, else `None`.
     ///
+    /// Lines with NaN or infinite coordinates never intersect.
+    ///
     /// - Segment 1 is from `(x1, y1)` to `(x2, y2)`, along `t`.
     /// - Segment 2 is from `(x3, y3)` to `(x4, y4)`, along `u`.
     pub fn intersection(self, other: Self) -> Option<Point> {
@@ -118,6 +177,7 @@ impl Line {
         let u_num = (x1 - x3) * (y1 - y2) - (y1 - y3) * (x1 - x2);
 
         if denom == 0.0
+            || !(denom.is_finite() && t_num.is_finite() && u_num.is_finite())
             || (t_num > 0.0 && t_num > denom)
             || (t_num < 0.0 && t_num < denom)
             || (u_num > 0.0 && u_num > denom)
@@ -143,7 +203,7 @@ impl Line {
 }
 
 /// 3D ray used for FOV, LOS, and intersections.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Ray {
     r0: Point,
     rv: Vector,
@@ -157,22 +217,24 @@ impl Ray {
             rv: Vector::new(vx, vy),
         }
     }
-    /// Creates a new ray with normalized vector..
-    pub fn normalized(x: f64, y: f64) -> Self {
-        let v = Vector::normalized(x, y);
-        Self {
+    /// Creates a new ray with normalized vector.
+    ///
+    /// Returns an error if `(x, y)` has zero length or is not finite.
+    pub fn normalized(x: f64, y: f64) -> Result<Self, MathError> {
+        let v = Vector::normalized(x, y)?;
+        Ok(Self {
             r0: Point { x, y },
             rv: v,
-        }
+        })
     }
-    /// Normalizes the vector component of the ray.
-    pub fn normalize(&mut self) {
-        self.rv.normalize();
+    /// Normalizes the vector component of the ray. On error, the ray is unchanged.
+    pub fn normalize(&mut self) -> Result<(), MathError> {
+        self.rv.normalize()
     }
 }
 
 /// 2D Vector.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Vector {
     pub x: f64,
     pub y: f64,
@@ -183,22 +245,47 @@ impl Vector {
     pub fn new(x: f64, y: f64) -> Self {
         Self { x, y }
     }
+    /// Creates a new vector, or an error if `x` or `y` is not finite.
+    pub fn try_new(x: f64, y: f64) -> Result<Self, MathError> {
+        let v = Self::new(x, y);
+        match v.is_finite() {
+            true => Ok(v),
+            false => Err(MathError::NonFinite),
+        }
+    }
+    /// Returns `true` if neither component is NaN or infinite.
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
     /// Creates a new normalized vector where unit vector `u = v/|v|`.
-    pub fn normalized(x: f64, y: f64) -> Self {
+    ///
+    /// Returns an error if `(x, y)` has zero length or is not finite.
+    pub fn normalized(x: f64, y: f64) -> Result<Self, MathError> {
         let mut v = Vector::new(x, y);
-        v.normalize();
-        v
+        v.normalize()?;
+        Ok(v)
     }
     /// Returns the magnitude of the vector.
     pub fn magnitude(self) -> f64 {
         (self.x * self.x + self.y * self.y).sqrt()
     }
     /// Normalizes a vector, unit vector `u = v/|v|`.
-    pub fn normalize(&mut self) {
+    ///
+    /// Zero-length and non-finite vectors have no direction: an error is returned and the
+    /// vector is unchanged, rather than producing NaN components.
+    pub fn normalize(&mut self) -> Result<(), MathError> {
         let mag = self.magnitude();
 
+        if !mag.is_finite() {
+            return Err(MathError::NonFinite);
+        }
+        if mag == 0.0 {
+            return Err(MathError::ZeroVector);
+        }
+
         self.x /= mag;
         self.y /= mag;
+        Ok(())
     }
 }
 
@@ -224,6 +311,105 @@ impl std::ops::Sub for Vector {
     }
 }
 
+/// Angle in degrees, counter-clockwise from east (`+x`).
+///
+/// Angle-taking APIs (cones, facing) accept `impl Into<Radians>`, so either unit works and
+/// a bare `f64` of the wrong unit can't be passed by mistake.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Degrees(pub f64);
+
+/// Angle in radians, counter-clockwise from east (`+x`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Radians(pub f64);
+
+impl Degrees {
+    /// Converts the angle to `Radians`.
+    pub fn to_radians(self) -> Radians {
+        Radians(self.0.to_radians())
+    }
+    /// Returns the angle normalized to `[0, 360)`.
+    pub fn normalized(self) -> Self {
+        Self(normalize(self.0, 360.0))
+    }
+    /// Returns the angle normalized to `[-180, 180)`, e.g. for the difference of two angles.
+    pub fn normalized_signed(self) -> Self {
+        Self(normalize(self.0 + 180.0, 360.0) - 180.0)
+    }
+}
+
+impl Radians {
+    /// Returns the direction of `d` from the origin. The zero delta is `0`.
+    pub fn of(d: Delta) -> Self {
+        Self((d.dy as f64).atan2(d.dx as f64))
+    }
+    /// Converts the angle to `Degrees`.
+    pub fn to_degrees(self) -> Degrees {
+        Degrees(self.0.to_degrees())
+    }
+    /// Returns the angle normalized to `[0, 2π)`.
+    pub fn normalized(self) -> Self {
+        Self(normalize(self.0, std::f64::consts::TAU))
+    }
+    /// Returns the angle normalized to `[-π, π)`, e.g. for the difference of two angles.
+    pub fn normalized_signed(self) -> Self {
+        let pi = std::f64::consts::PI;
+        Self(normalize(self.0 + pi, 2.0 * pi) - pi)
+    }
+}
+
+/// Returns `value` wrapped into `[0, period)`. Tiny negative values round to `0`, not `period`.
+fn normalize(value: f64, period: f64) -> f64 {
+    let wrapped = value.rem_euclid(period);
+    match wrapped < period {
+        true => wrapped,
+        false => 0.0,
+    }
+}
+
+impl From<Degrees> for Radians {
+    fn from(angle: Degrees) -> Self {
+        angle.to_radians()
+    }
+}
+
+impl From<Radians> for Degrees {
+    fn from(angle: Radians) -> Self {
+        angle.to_degrees()
+    }
+}
+
+impl std::ops::Add for Radians {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl std::ops::Sub for Radians {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl std::ops::Add for Degrees {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl std::ops::Sub for Degrees {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0 - rhs.0)
+    }
+}
+
 /// 3D axis-aligned rectangle specifically made for FOV calculations.
 /// Reference point is closest to origin `(0,0)` - width and height are added to it.
 /// Side vector `s1` is from `p0` to `p1` (width); side vector `s2` is from `p0` to `p2` (height).
@@ -233,11 +419,12 @@ impl std::ops::Sub for Vector {
 /// - `p0`: reference point. Always closest to origin.
 /// - `s1`, `s2`: Side vectors defining width and height. Needed for intersections.
 /// - `s1_abs_mag`, `s2_abs_mag`: absolute magnitude (no square root) of side vectors `s1` and `s2`.
-///    Effectively width squared or height squared.
+///   Effectively width squared or height squared.
 /// - `normal`: defines normal vector to the rectangle plane. Always points toward origin. For
-///    side `A`, normal points toward `x=0`. For side `B`, it points toward `y=0`. For
-///    side `C`, it points toward `z=0`. Will be normalized (in unit form), but does
-///    not need to be.
+///   side `A`, normal points toward `x=0`. For side `B`, it points toward `y=0`. For
+///   side `C`, it points toward `z=0`. Will be normalized (in unit form), but does
+///   not need to be.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct FovRect {
     pub p0: Point,
     pub s1: Vector,
@@ -267,16 +454,134 @@ impl FovRect {
     }
 }
 
+/// Returns every tile the segment from `p0` to `p1` passes through, in order from `p0`.
+///
+/// Tile `(x, y)` covers `[x, x + 1)` by `[y, y + 1)`, so tile centers are at `+0.5`.
+/// Where the segment passes exactly through a tile corner, both tiles sharing that corner
+/// are included before the diagonal tile. Yields nothing if either point is not finite.
+pub fn supercover(p0: Point, p1: Point) -> impl Iterator<Item = Coords> {
+    Supercover::new(p0, p1, true)
+}
+
+/// Returns the tiles the segment from `p0` to `p1` passes through, as `supercover` does,
+/// but skipping tiles only touched at a corner: exact corners step straight to the diagonal.
+pub fn supercover_passing_corners(p0: Point, p1: Point) -> impl Iterator<Item = Coords> {
+    Supercover::new(p0, p1, false)
+}
+
+/// Iterator state for `supercover` (grid traversal after Amanatides and Woo).
+///
+/// `next` is the distance from `p0`, along each axis, to the next vertical and horizontal
+/// tile boundary. Boundaries are compared as `next.0 * dy` against `next.1 * dx` rather
+/// than by accumulated segment parameters, so corners between tile centers are exact (for
+/// segments up to `2^25` tiles long).
+struct Supercover {
+    tile: Coords,
+    step: (i32, i32),
+    next: (f64, f64),
+    delta: (f64, f64),
+    corners: bool,
+    sides: [Option<Coords>; 2],
+    remaining: Option<i32>,
+}
+
+impl Supercover {
+    fn new(p0: Point, p1: Point, corners: bool) -> Self {
+        let (start, end): (Coords, Coords) = (p0.into(), p1.into());
+        let step = ((end.x - start.x).signum(), (end.y - start.y).signum());
+
+        // Distance to the first boundary crossed along one axis, if the tile changes on it
+        let first = |s: i32, p: f64, tile: i32| match s {
+            0 => f64::INFINITY,
+            1 => tile as f64 + 1.0 - p,
+            _ => p - tile as f64,
+        };
+
+        let finite = p0.is_finite() && p1.is_finite();
+        Self {
+            tile: start,
+            step,
+            next: (first(step.0, p0.x, start.x), first(step.1, p0.y, start.y)),
+            delta: ((p1.x - p0.x).abs(), (p1.y - p0.y).abs()),
+            corners,
+            sides: [None, None],
+            remaining: finite.then_some((end.x - start.x).abs() + (end.y - start.y).abs()),
+        }
+    }
+}
+
+impl Iterator for Supercover {
+    type Item = Coords;
+
+    fn next(&mut self) -> Option<Coords> {
+        if let Some(side) = self.sides.iter_mut().find_map(|s| s.take()) {
+            return Some(side);
+        }
+
+        let remaining = self.remaining?;
+        let current = self.tile;
+        if remaining <= 0 {
+            self.remaining = None;
+            return Some(current);
+        }
+
+        let (sx, sy) = self.step;
+        let (t_x, t_y) = (self.next.0 * self.delta.1, self.next.1 * self.delta.0);
+        if t_x < t_y {
+            self.tile.x += sx;
+            self.next.0 += 1.0;
+            self.remaining = Some(remaining - 1);
+        } else if t_y < t_x {
+            self.tile.y += sy;
+            self.next.1 += 1.0;
+            self.remaining = Some(remaining - 1);
+        } else {
+            // Exact corner: both side tiles are touched, then the diagonal tile is entered
+            if self.corners {
+                self.sides = [
+                    Some(Coords::new(current.x + sx, current.y)),
+                    Some(Coords::new(current.x, current.y + sy)),
+                ];
+            }
+            self.tile = Coords::new(current.x + sx, current.y + sy);
+            self.next = (self.next.0 + 1.0, self.next.1 + 1.0);
+            self.remaining = Some(remaining - 2);
+        }
+
+        Some(current)
+    }
+}
+
 /// Convenience function to calculate distance between two `u8` values.
+#[deprecated(note = "compare `dist_sq` against a squared radius instead")]
 pub fn dist_u8(a: u8, b: u8) -> f64 {
     ((a as f64).powi(2) + (b as f64).powi(2)).sqrt()
 }
 
 /// Convenience function to calculate distance between two `u16` values.
+#[deprecated(note = "compare `dist_sq` against a squared radius instead")]
 pub fn dist_u16(a: u16, b: u16) -> f64 {
     ((a as f64).powi(2) + (b as f64).powi(2)).sqrt()
 }
 
+/// Returns the squared distance of integer deltas `(a, b)`, exactly.
+///
+/// For culling, compare against a squared radius computed once (see `radius_sq`),
+/// rather than taking a square root per candidate.
+pub fn dist_sq(a: u16, b: u16) -> u64 {
+    a as u64 * a as u64 + b as u64 * b as u64
+}
+
+/// Returns `radius` squared, for comparison with `dist_sq`.
+///
+/// Negative radii return negative infinity, so no distance is within them.
+pub fn radius_sq(radius: f64) -> f64 {
+    match radius < 0.0 {
+        true => f64::NEG_INFINITY,
+        false => radius * radius,
+    }
+}
+
 //  ########  ########   ######   ########
 //     ##     ##        ##           ##
 //     ##     ######     ######      ##
@@ -292,4 +597,108 @@ mod tests {
         let v1 = Vector::new(3.0, 4.0);
         assert_eq!(v1.magnitude(), 5.0);
     }
+
+    // Validated constructors reject NaN/inf and zero-length lines; bad lines never intersect.
+    #[test]
+    fn non_finite_inputs() {
+        assert_eq!(Point::try_new(1.0, f64::NAN), Err(MathError::NonFinite));
+        assert!(Vector::try_new(f64::INFINITY, 0.0).is_err());
+        assert!(Vector::try_new(3.0, 4.0).is_ok());
+        assert_eq!(Line::try_new(0.0, 0.0, f64::NAN, 1.0), Err(MathError::NonFinite));
+        assert_eq!(Line::try_new(1.0, 1.0, 1.0, 1.0), Err(MathError::DegenerateLine));
+
+        let line = Line::try_new(0.0, 0.0, 2.0, 2.0).unwrap();
+        let cross = Line::new(0.0, 2.0, 2.0, 0.0);
+        let bad = Line::new(0.0, 2.0, f64::NAN, 0.0);
+        assert_eq!(line.intersection(cross), Some(Point::new(1.0, 1.0)));
+        assert!(!line.intersects(bad));
+        assert_eq!(line.intersection(bad), None);
+    }
+
+    // Zero-length vectors cannot be normalized; errors leave values unchanged.
+    #[test]
+    fn normalize_zero_vector() {
+        let v = Vector::normalized(3.0, 4.0).unwrap();
+        assert_eq!((v.x, v.y), (0.6, 0.8));
+
+        let mut zero = Vector::new(0.0, 0.0);
+        assert_eq!(zero.normalize(), Err(MathError::ZeroVector));
+        assert_eq!((zero.x, zero.y), (0.0, 0.0));
+        assert_eq!(Vector::normalized(f64::NAN, 1.0).err(), Some(MathError::NonFinite));
+        assert!(Ray::normalized(0.0, 0.0).is_err());
+
+        let line = Line::from_ray(Ray::new(1.0, 1.0, 0.0, 2.0), 3.0).unwrap();
+        assert_eq!(line, Line::new(1.0, 1.0, 1.0, 4.0));
+        assert_eq!(Line::from_ray(Ray::new(1.0, 1.0, 0.0, 0.0), 3.0), Err(MathError::ZeroVector));
+    }
+
+    // Supercover visits every tile a segment touches, both tiles at exact corners, in order.
+    #[test]
+    fn supercover_tiles() {
+        let tiles = |x0, y0, x1, y1| -> Vec<(i32, i32)> {
+            supercover(Point::new(x0, y0), Point::new(x1, y1)).map(|c| (c.x, c.y)).collect()
+        };
+
+        assert_eq!(tiles(0.5, 0.5, 0.5, 0.5), vec![(0, 0)]);
+        assert_eq!(tiles(0.5, 0.5, 3.5, 0.5), vec![(0, 0), (1, 0), (2, 0), (3, 0)]);
+        assert_eq!(tiles(0.5, 2.5, 0.5, 0.5), vec![(0, 2), (0, 1), (0, 0)]);
+        assert_eq!(
+            tiles(0.5, 0.5, 2.5, 2.5),
+            vec![(0, 0), (1, 0), (0, 1), (1, 1), (2, 1), (1, 2), (2, 2)]
+        );
+        assert_eq!(tiles(-0.5, 0.5, -2.5, -0.5), vec![(-1, 0), (-2, 0), (-2, -1), (-3, -1)]);
+        assert_eq!(tiles(0.5, 0.5, 3.5, 1.5), vec![(0, 0), (1, 0), (2, 0), (1, 1), (2, 1), (3, 1)]);
+        assert!(tiles(0.5, 0.5, f64::NAN, 0.5).is_empty());
+
+        // Passing corners skips the side tiles, also where repeated `1 / d` steps would drift
+        let passing = supercover_passing_corners(Point::new(0.5, 0.5), Point::new(6.5, 2.5));
+        assert_eq!(
+            passing.map(|c| (c.x, c.y)).collect::<Vec<_>>(),
+            vec![(0, 0), (1, 0), (2, 1), (3, 1), (4, 1), (5, 2), (6, 2)]
+        );
+
+        // Away from corners, each step crosses one tile boundary
+        let path = tiles(1.25, 0.5, 9.75, 6.2);
+        assert_eq!((path[0], path[path.len() - 1]), ((1, 0), (9, 6)));
+        assert_eq!(path.len(), 8 + 6 + 1);
+    }
+
+    // Squared-distance culling keeps exactly the nodes that `sqrt` culling keeps.
+    #[test]
+    #[allow(deprecated)]
+    fn squared_distance_culling() {
+        for radius in [0.0, 8.5, 11.0, 16.0, 16.5, 17.25, -1.0] {
+            let limit = radius_sq(radius);
+            for a in 0..=20u8 {
+                for b in 0..=a {
+                    let by_sq = dist_sq(a as u16, b as u16) as f64 <= limit;
+                    let by_sqrt = dist_u8(a, b) <= radius;
+                    assert_eq!(by_sq, by_sqrt, "({a}, {b}) at radius {radius}");
+                }
+            }
+        }
+        assert_eq!(dist_sq(3, 4), 25);
+        assert_eq!(dist_sq(u16::MAX, u16::MAX), 2 * 65535 * 65535u64);
+    }
+
+    // Angle units convert both ways and normalize into their ranges, edges included.
+    #[test]
+    fn angle_units() {
+        use std::f64::consts::{FRAC_PI_2, PI};
+
+        assert_eq!(Radians::from(Degrees(180.0)), Radians(PI));
+        assert_eq!(Degrees::from(Radians(FRAC_PI_2)), Degrees(90.0));
+        assert_eq!(Degrees(-90.0).normalized(), Degrees(270.0));
+        assert_eq!(Degrees(720.0).normalized(), Degrees(0.0));
+        assert_eq!(Degrees(-1e-20).normalized(), Degrees(0.0));
+        assert_eq!(Degrees(270.0).normalized_signed(), Degrees(-90.0));
+        assert_eq!(Degrees(180.0).normalized_signed(), Degrees(-180.0));
+        assert_eq!((Degrees(10.0) - Degrees(350.0)).normalized_signed(), Degrees(20.0));
+        assert_eq!(Radians(-FRAC_PI_2).normalized(), Radians(1.5 * PI));
+        assert!((Radians(3.0 * PI).normalized_signed().0 + PI).abs() < 1e-12);
+
+        assert_eq!(Radians::of(Delta::new(0, 0)), Radians(0.0));
+        assert_eq!(Radians::of(Delta::new(0, 3)), Radians(FRAC_PI_2));
+        assert_eq!(Radians::of(Delta::new(-2, 0)).to_degrees(), Degrees(180.0));
+    }
 }
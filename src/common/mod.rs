@@ -1,7 +1,22 @@
 //! Common data for FOV Visualiztion - Rust (2D).
 
+pub mod analysis;
+pub mod cache;
+#[cfg(feature = "experimental")]
+#[cfg_attr(docsrs, doc(cfg(feature = "experimental")))]
+pub mod cases;
+pub mod computer;
+pub mod diagram;
 pub mod drawing;
+pub mod error;
 pub mod files;
+pub mod font;
 pub mod fov;
+pub mod image;
+pub mod los;
 pub mod math;
-pub mod maps;
\ No newline at end of file
+pub mod maps;
+pub mod report;
+pub mod rng;
+pub mod scenes;
+pub mod visibility;
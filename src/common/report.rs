@@ -0,0 +1,338 @@
+//! Bundled FOV reports for FOV Visualization - Rust (2D).
+//!
+//! Notes:
+//! - A `Report` runs _Simple_ FOV on one map for a list of `ReportConfig`s, and writes
+//!   everything about them to one output directory in a single call.
+//! - `Report::builder()` is typed: `build` only exists once a map has been given, so a
+//!   report without a map does not compile.
+//! - Any radius and Q-Factor pairing with a _Simple_ FOV set type works (`FovSet8` to
+//!   `FovSet128`), built as a `FovSetAny`; R128 at Q-Factor 2 is an error, as it has no
+//!   node type.
+//!
+//! Output files, per config `name`:
+//! - `{name}.png`: FOV render, captioned with the configuration.
+//! - `{name}_nodes.json`: octant 1 nodes (`dpri`, `dsec`, `body` as a hex string, `bits`).
+//! - `metrics.csv`: one row per config (visible tiles, node counts and bytes, timings).
+//! - `manifest.json`: one row per config, with its settings and output files.
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::{
+    analysis::{Cell, Table},
+    drawing::{self, config_caption, ImageFormat, Renderer},
+    error::FovError,
+    files::{save_csv, save_json},
+    fov::Algorithm,
+    maps::{Coords, TileMap},
+    FovComputer, FovRadius, FovSetAny, FovShape, QFactor, VisibilityGrid,
+};
+
+/// One FOV configuration to run in a `Report`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportConfig {
+    /// Name used for the config's output files; should be unique within a report.
+    ///
+    /// Must be ASCII letters, digits, `_` and `-` only, so files stay in the output directory.
+    pub name: String,
+    pub rfov: FovRadius,
+    pub qfactor: QFactor,
+    pub shape: FovShape,
+    pub origin: Coords,
+    /// Query radius, at most the radius of `rfov`.
+    pub radius: usize,
+}
+
+impl ReportConfig {
+    /// Creates a new `ReportConfig` with the default `FovShape`, querying the full radius.
+    pub fn new(name: &str, rfov: FovRadius, qfactor: QFactor, origin: Coords) -> Self {
+        Self {
+            name: name.to_string(),
+            rfov,
+            qfactor,
+            shape: FovShape::default(),
+            origin,
+            radius: rfov.to_int() as usize,
+        }
+    }
+}
+
+/// Results of one `ReportConfig`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportEntry {
+    pub config: ReportConfig,
+    pub grid: VisibilityGrid,
+    /// Octant 1 nodes as `(dpri, dsec, body)`.
+    pub nodes: Vec<(u8, u8, u128)>,
+    pub node_bytes: usize,
+    pub build_micros: f64,
+    pub query_micros: f64,
+}
+
+/// Marker for a `ReportBuilder` that has no map yet.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoMap;
+
+/// Typed builder for a `Report`: `M` is `NoMap` until `map` is called.
+#[derive(Debug, Clone)]
+pub struct ReportBuilder<M> {
+    map: M,
+    configs: Vec<ReportConfig>,
+    tile_px: usize,
+}
+
+impl<M> ReportBuilder<M> {
+    /// Sets the map all configs run on.
+    pub fn map(self, map: &TileMap) -> ReportBuilder<&TileMap> {
+        ReportBuilder {
+            map,
+            configs: self.configs,
+            tile_px: self.tile_px,
+        }
+    }
+    /// Adds a configuration to run.
+    pub fn config(mut self, config: ReportConfig) -> Self {
+        self.configs.push(config);
+        self
+    }
+    /// Adds several configurations to run, in order.
+    pub fn configs<I: IntoIterator<Item = ReportConfig>>(mut self, configs: I) -> Self {
+        self.configs.extend(configs);
+        self
+    }
+    /// Sets the side length of a rendered tile, in pixels. Defaults to `8`.
+    pub fn tile_px(mut self, tile_px: usize) -> Self {
+        self.tile_px = tile_px;
+        self
+    }
+}
+
+impl<'a> ReportBuilder<&'a TileMap> {
+    /// Builds the `Report`. Panics if no configs were added or `tile_px` is `0`.
+    pub fn build(self) -> Report<'a> {
+        assert!(!self.configs.is_empty(), "report requires at least one config!");
+        assert!(self.tile_px > 0, "report tile_px must be at least 1!");
+        Report {
+            map: self.map,
+            configs: self.configs,
+            tile_px: self.tile_px,
+        }
+    }
+    /// Builds the `Report` and writes it to `out_dir`, returning the written file paths.
+    pub fn write<P: AsRef<Path>>(self, out_dir: P) -> Result<Vec<PathBuf>, FovError> {
+        self.build().write(out_dir)
+    }
+}
+
+/// FOV runs of several configurations on one map, written as a bundle.
+#[derive(Debug, Clone)]
+pub struct Report<'a> {
+    map: &'a TileMap,
+    configs: Vec<ReportConfig>,
+    tile_px: usize,
+}
+
+impl Report<'_> {
+    /// Returns a new `ReportBuilder` with no map and no configs.
+    pub fn builder() -> ReportBuilder<NoMap> {
+        ReportBuilder {
+            map: NoMap,
+            configs: Vec::new(),
+            tile_px: 8,
+        }
+    }
+    /// Returns the report's configurations, in run order.
+    pub fn configs(&self) -> &[ReportConfig] {
+        &self.configs
+    }
+    /// Builds each config's FOV set and runs its query, in order.
+    ///
+    /// Returns the first error building a config's FOV set (e.g. for an invalid shape).
+    pub fn run(&self) -> Result<Vec<ReportEntry>, FovError> {
+        self.configs.iter().map(|config| run_config(self.map, config)).collect()
+    }
+    /// Runs the report and writes its files to `out_dir`, returning the written file paths.
+    ///
+    /// `out_dir` is created if needed; existing files with the same names are replaced.
+    /// Returns an error, before writing anything, if a config name is not a safe file name.
+    pub fn write<P: AsRef<Path>>(&self, out_dir: P) -> Result<Vec<PathBuf>, FovError> {
+        if let Some(config) = self.configs.iter().find(|c| !is_safe_name(&c.name)) {
+            let name = &config.name;
+            let msg = format!("report config name {name:?} must be letters, digits, _ or -");
+            return Err(FovError::invalid(msg));
+        }
+        let out_dir = out_dir.as_ref();
+        std::fs::create_dir_all(out_dir)?;
+
+        let entries = self.run()?;
+        let mut written = Vec::new();
+        let columns = ["name", "visible", "nodes", "node_bytes", "build_micros", "query_micros"];
+        let mut metrics = Table::new(&columns);
+        let mut manifest = Table::new(&[
+            "name", "radius", "q", "shape", "x", "y", "query_radius", "image", "nodes",
+        ]);
+
+        for entry in entries.iter() {
+            let config = &entry.config;
+            let caption = config_caption("simple", config.rfov, config.qfactor, config.shape);
+            let mut renderer = Renderer::new(self.tile_px);
+            renderer.legend = true;
+            renderer.caption = Some(format!("{}: {caption}", config.name));
+
+            let image = format!("{}.png", config.name);
+            let canvas = renderer.render(self.map, &entry.grid, config.origin);
+            drawing::save(out_dir.join(&image), &canvas, ImageFormat::Png)?;
+            written.push(out_dir.join(&image));
+
+            let nodes = format!("{}_nodes.json", config.name);
+            save_json(out_dir.join(&nodes), &node_table(&entry.nodes))?;
+            written.push(out_dir.join(&nodes));
+
+            metrics.push_row(vec![
+                config.name.as_str().into(),
+                entry.grid.len().into(),
+                entry.nodes.len().into(),
+                entry.node_bytes.into(),
+                entry.build_micros.into(),
+                entry.query_micros.into(),
+            ]);
+            let r = config.rfov.to_int() as usize;
+            manifest.push_row(vec![
+                config.name.as_str().into(),
+                r.into(),
+                (r * config.qfactor.to_int() as usize).into(),
+                config.shape.to_string().into(),
+                (config.origin.x as i64).into(),
+                (config.origin.y as i64).into(),
+                config.radius.into(),
+                image.into(),
+                nodes.into(),
+            ]);
+        }
+
+        save_csv(out_dir.join("metrics.csv"), &metrics)?;
+        written.push(out_dir.join("metrics.csv"));
+        save_json(out_dir.join("manifest.json"), &manifest)?;
+        written.push(out_dir.join("manifest.json"));
+
+        Ok(written)
+    }
+}
+
+/// Returns `true` if `name` is non-empty and only ASCII letters, digits, `_` and `-`.
+fn is_safe_name(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+}
+
+/// Builds the FOV set for `config` and queries it on `map`, timing both.
+fn run_config(map: &TileMap, config: &ReportConfig) -> Result<ReportEntry, FovError> {
+    let start = Instant::now();
+    let fov_set = FovSetAny::new(Algorithm::Simple, config.rfov, config.qfactor, config.shape)?;
+    let build_micros = start.elapsed().as_secs_f64() * 1e6;
+
+    let start = Instant::now();
+    let grid = fov_set.visible_grid(config.origin, config.radius, map);
+    let query_micros = start.elapsed().as_secs_f64() * 1e6;
+
+    Ok(ReportEntry {
+        config: config.clone(),
+        grid,
+        nodes: fov_set.octant_1_nodes(),
+        node_bytes: fov_set.node_bytes(),
+        build_micros,
+        query_micros,
+    })
+}
+
+/// Returns a table of FOV nodes, with bodies as hex strings so no JSON reader rounds them.
+fn node_table(nodes: &[(u8, u8, u128)]) -> Table {
+    let mut table = Table::new(&["dpri", "dsec", "body", "bits"]);
+    for &(dpri, dsec, body) in nodes {
+        table.push_row(vec![
+            Cell::Int(dpri as i64),
+            Cell::Int(dsec as i64),
+            Cell::Text(format!("{body:#x}")),
+            Cell::Int(body.count_ones() as i64),
+        ]);
+    }
+    table
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Each config runs on its own FOV set type; Double Q sees the same open disk as Single.
+    #[test]
+    fn report_run() {
+        let map = TileMap::new(33, 33).unwrap();
+        let origin = Coords::new(16, 16);
+        let report = Report::builder()
+            .map(&map)
+            .config(ReportConfig::new("r16", FovRadius::R16, QFactor::Single, origin))
+            .configs([
+                ReportConfig::new("r16q2", FovRadius::R16, QFactor::Double, origin),
+                ReportConfig::new("r8", FovRadius::R8, QFactor::Single, origin),
+            ])
+            .build();
+        let entries = report.run().unwrap();
+
+        assert_eq!(entries.len(), report.configs().len());
+        assert_eq!(entries[0].grid, entries[1].grid);
+        assert!(entries[2].grid.len() < entries[0].grid.len());
+        assert_eq!(entries[0].nodes.len(), entries[1].nodes.len());
+        assert_eq!(entries[0].nodes[0].2, u16::MAX as u128);
+        assert_eq!(entries[1].nodes[0].2, u32::MAX as u128);
+        assert!(entries[2].node_bytes < entries[0].node_bytes);
+    }
+
+    // One call writes an image and node dump per config, plus metrics and a manifest.
+    #[test]
+    fn report_write() {
+        let out_dir = std::env::temp_dir().join(format!("fov2d_report_{}", std::process::id()));
+        let mut map = TileMap::new(17, 17).unwrap();
+        map.set_body_opaque(Coords::new(10, 8), true);
+        let written = Report::builder()
+            .tile_px(2)
+            .map(&map)
+            .config(ReportConfig::new("pillar", FovRadius::R8, QFactor::Single, Coords::new(8, 8)))
+            .write(&out_dir)
+            .unwrap();
+
+        let names: Vec<_> = written.iter().map(|p| p.file_name().unwrap().to_owned()).collect();
+        assert_eq!(names, ["pillar.png", "pillar_nodes.json", "metrics.csv", "manifest.json"]);
+        assert!(written.iter().all(|p| p.is_file()));
+        let metrics = std::fs::read_to_string(out_dir.join("metrics.csv")).unwrap();
+        assert!(metrics.starts_with("name,visible,nodes,node_bytes"));
+        assert!(metrics.contains("\npillar,"));
+        let nodes = std::fs::read_to_string(out_dir.join("pillar_nodes.json")).unwrap();
+        assert!(nodes.contains("\"body\": \"0xff\""));
+
+        std::fs::remove_dir_all(out_dir).unwrap();
+    }
+
+    // Names that could leave the output directory are rejected before anything is written.
+    #[test]
+    fn report_rejects_unsafe_names() {
+        let out_dir = std::env::temp_dir().join(format!("fov2d_unsafe_{}", std::process::id()));
+        let map = TileMap::new(9, 9).unwrap();
+        for name in ["../escape", "a/b", "..", "", "c:d"] {
+            let config = ReportConfig::new(name, FovRadius::R8, QFactor::Single, Coords::new(4, 4));
+            let result = Report::builder().map(&map).config(config).write(&out_dir);
+            assert!(matches!(result, Err(FovError::InvalidData(_))), "{name:?}");
+        }
+        assert!(!out_dir.exists());
+    }
+
+    #[test]
+    #[should_panic(expected = "report requires at least one config")]
+    fn report_requires_config() {
+        Report::builder().map(&TileMap::new(4, 4).unwrap()).build();
+    }
+}
@@ -0,0 +1,132 @@
+//! Seeded random numbers for FOV Visualization - Rust (2D).
+//!
+//! Notes:
+//! - `Rng` is xoshiro256** seeded via SplitMix64: small, fast, and dependency-free.
+//! - All stochastic features take a `u64` seed, so results reproduce exactly.
+//! - Not suitable for cryptographic use.
+
+/// Seeded pseudo-random number generator (xoshiro256**).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    /// Creates a new `Rng` from a `u64` seed.
+    pub fn new(seed: u64) -> Self {
+        let mut sm = seed;
+        let mut next = || {
+            sm = sm.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = sm;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        };
+
+        Self {
+            state: [next(), next(), next(), next()],
+        }
+    }
+    /// Returns the next random `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+
+        result
+    }
+    /// Returns a random `f64` in `[0.0, 1.0)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+    /// Returns a random `u64` in `[0, n)`. Panics if `n` is `0`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "n must be > 0!");
+        // Lemire's multiply-shift with rejection, free of modulo bias
+        let threshold = n.wrapping_neg() % n;
+        loop {
+            let m = self.next_u64() as u128 * n as u128;
+            if (m as u64) >= threshold {
+                return (m >> 64) as u64;
+            }
+        }
+    }
+    /// Returns a random `f64` in `[lo, hi)`.
+    pub fn range_f64(&mut self, lo: f64, hi: f64) -> f64 {
+        lo + (hi - lo) * self.next_f64()
+    }
+    /// Returns `true` with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        self.next_f64() < p
+    }
+    /// Shuffles `items` in place (Fisher-Yates).
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+    /// Returns `bytes` with one to four random edits: flipped bits, boundary or random byte
+    /// values, inserted or removed bytes, or truncation. Drives parser property tests.
+    #[cfg(test)]
+    pub(crate) fn mutate(&mut self, bytes: &[u8]) -> Vec<u8> {
+        let mut out = bytes.to_vec();
+        for _ in 0..=self.below(4) {
+            let at = self.below(out.len() as u64 + 1) as usize;
+            let byte = match self.below(4) {
+                0 => 0x00,
+                1 => 0xFF,
+                2 => 0x7F,
+                _ => self.next_u64() as u8,
+            };
+            match (self.below(5), at < out.len()) {
+                (0, true) => out[at] ^= 1 << self.below(8),
+                (1, true) => out[at] = byte,
+                (2, true) => {
+                    out.remove(at);
+                }
+                (3, _) => out.truncate(at),
+                _ => out.insert(at, byte),
+            }
+        }
+        out
+    }
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Same seed reproduces the same sequence; values stay within requested ranges.
+    #[test]
+    fn rng_reproducible() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let mut c = Rng::new(43);
+        let seq_a: Vec<_> = (0..8).map(|_| a.next_u64()).collect();
+        let seq_b: Vec<_> = (0..8).map(|_| b.next_u64()).collect();
+        let seq_c: Vec<_> = (0..8).map(|_| c.next_u64()).collect();
+
+        assert_eq!(seq_a, seq_b);
+        assert_ne!(seq_a, seq_c);
+
+        for _ in 0..1000 {
+            assert!(a.below(7) < 7);
+            let f = a.next_f64();
+            assert!((0.0..1.0).contains(&f));
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! Benchmark scenes for FOV Visualization - Rust (2D).
+//!
+//! Notes:
+//! - Scenes are realistic dungeon layouts, so FOV optimizations are measured against
+//!   representative occlusion rather than only empty or solid maps.
+//! - Layouts were generated (rooms and corridors, cellular automaton caves, rubble-strewn
+//!   ruins) and are shipped as fixed data, so results stay comparable across versions.
+//! - Layouts are stored run-length encoded (see `decode_layout` and `encode_layout`).
+//! - Each scene comes with observer origins on transparent tiles, picked from a fixed seed.
+
+use super::maps::{Coords, OpacityProvider, TileMap};
+use super::rng::Rng;
+
+/// Number of observer origins picked for each scene.
+pub const ORIGINS_PER_SCENE: usize = 16;
+
+/// Most tiles a decoded layout may hold, so huge run lengths fail before allocating.
+const MAX_LAYOUT_TILES: usize = 1 << 24;
+
+/// Benchmark map with observer origins to run FOV queries from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchScene {
+    pub name: &'static str,
+    pub map: TileMap,
+    pub origins: Vec<Coords>,
+}
+
+impl BenchScene {
+    /// Returns the fraction (`0.0` to `1.0`) of tiles with opaque bodies.
+    pub fn density(&self) -> f64 {
+        self.map.stats().density()
+    }
+}
+
+/// Returns the benchmark scenes: `rooms`, `caves`, and `ruins`, each 64 by 48 tiles.
+///
+/// - `rooms`: rooms joined by corridors, some with pillars. Dense, short sight lines.
+/// - `caves`: organic cellular automaton caverns. Medium density, irregular walls.
+/// - `ruins`: open ground with broken walls and rubble. Sparse, long sight lines.
+pub fn bench_scenes() -> Vec<BenchScene> {
+    [("rooms", ROOMS, 1), ("caves", CAVES, 2), ("ruins", RUINS, 3)]
+        .into_iter()
+        .map(|(name, layout, seed)| {
+            let map = decode_layout(layout).expect("bench scene layouts are valid!");
+            let origins = pick_origins(&map, seed);
+            BenchScene { name, map, origins }
+        })
+        .collect()
+}
+
+/// Decodes a run-length encoded layout into a `TileMap`, or `None` if it is malformed.
+///
+/// Rows are separated by `/`, northmost (highest `y`) first. Each run is an optional
+/// count followed by a tile: `#` opaque or `.` transparent, so `3#2.` is `###..`.
+/// All rows must be the same width, with at most `MAX_LAYOUT_TILES` tiles in total.
+pub fn decode_layout(layout: &str) -> Option<TileMap> {
+    let (mut rows, mut tiles) = (Vec::new(), 0usize);
+
+    for text in layout.split('/') {
+        let (mut row, mut count) = (Vec::new(), None::<usize>);
+        for c in text.chars() {
+            match c {
+                '0'..='9' => {
+                    let digit = c.to_digit(10)? as usize;
+                    count = Some(count.unwrap_or(0).checked_mul(10)?.checked_add(digit)?);
+                }
+                '#' | '.' => {
+                    let n = count.take().unwrap_or(1);
+                    tiles = tiles.checked_add(n).filter(|&t| t <= MAX_LAYOUT_TILES)?;
+                    row.resize(row.len() + n, c == '#');
+                }
+                _ => return None,
+            }
+        }
+        if count.is_some() {
+            return None;
+        }
+        rows.push(row);
+    }
+
+    let width = rows[0].len();
+    if rows.iter().any(|r| r.len() != width) {
+        return None;
+    }
+
+    let mut map = TileMap::new(width, rows.len()).ok()?;
+    for (row, tiles) in rows.iter().enumerate() {
+        let y = (rows.len() - 1 - row) as i32;
+        for (x, _) in tiles.iter().enumerate().filter(|(_, opaque)| **opaque) {
+            map.set_body_opaque(Coords::new(x as i32, y), true);
+        }
+    }
+
+    Some(map)
+}
+
+/// Encodes the tile bodies of `map` as a layout read by `decode_layout`.
+pub fn encode_layout(map: &TileMap) -> String {
+    let mut rows = Vec::with_capacity(map.height());
+    for y in (0..map.height() as i32).rev() {
+        let (mut row, mut x) = (String::new(), 0);
+        while x < map.width() as i32 {
+            let opaque = map.body_opaque(Coords::new(x, y));
+            let run = (x..map.width() as i32)
+                .take_while(|&x| map.body_opaque(Coords::new(x, y)) == opaque)
+                .count();
+            if run > 1 {
+                row.push_str(&run.to_string());
+            }
+            row.push(match opaque {
+                true => '#',
+                false => '.',
+            });
+            x += run as i32;
+        }
+        rows.push(row);
+    }
+    rows.join("/")
+}
+
+/// Picks up to `ORIGINS_PER_SCENE` distinct transparent tiles of `map`, from `seed`.
+fn pick_origins(map: &TileMap, seed: u64) -> Vec<Coords> {
+    let mut open: Vec<Coords> = (0..map.height() as i32)
+        .flat_map(|y| (0..map.width() as i32).map(move |x| Coords::new(x, y)))
+        .filter(|&c| !map.body_opaque(c))
+        .collect();
+
+    Rng::new(seed).shuffle(&mut open);
+    open.truncate(ORIGINS_PER_SCENE);
+    open
+}
+
+const ROOMS: &str = "\
+    64#/29#10.25#/16#23.25#/16#.12#10.25#/16#.12#10.25#/16#.17#.29#/16#.17#.29#/16#.17#.29#/\
+    16#.10#9.28#/16#.10#30.7#/16#.10#9.20#.7#/14#11.2#9.20#.7#/14#11.6#.2#.21#.7#/14#2.#2.#2\
+    .#2.6#.2#.17#8.4#/14#11.6#.2#.17#8.4#/14#27.11#8.4#/2#6.6#11.6#10.11#8.4#/2#6.6#2.#2.#2.\
+    #2.6#29.4#/2#6.6#11.6#10.11#8.4#/2#58.4#/2#6.8#.2#.11#.4#.15#8.4#/2#6.8#.2#.11#.4#.15#8.\
+    4#/2#6.8#.2#.11#.4#.16#.10#/5#.10#.2#.11#.4#.16#.10#/5#.10#.2#.11#.4#.16#.10#/5#.10#.2#.\
+    5#12.16#.10#/5#.10#.2#.5#12.16#.10#/5#.10#.2#.5#2.#2.#2.#3.10#12.5#/5#.5#11.3#12.10#12.5\
+    #/5#54.5#/5#.5#2.#2.#2.#2.3#12.10#12.5#/5#.5#11.3#2.#2.#2.#3.10#12.5#/5#.5#11.3#12.10#12\
+    .5#/5#.5#11.3#12.27#/5#.5#2.#2.#2.#2.14#.27#/5#.5#11.14#.18#7.2#/5#.5#11.14#.18#7.2#/5#.\
+    13#.16#.18#7.2#/5#.13#.15#27.2#/5#.13#.15#5.15#7.2#/2#6.11#.15#5.15#7.2#/2#6.11#.15#5.15\
+    #7.2#/2#38.15#7.2#/2#6.27#5.24#/2#6.27#5.24#/2#6.27#5.24#/35#5.24#/64#";
+
+const CAVES: &str = "\
+    64#/7#2.19#2.34#/3#9.15#4.3#3.10#2.9#3.3#/2#11.14#13.4#6.7#5.2#/2#11.13#25.6#5.2#/2#11.5\
+    #2.4#27.7#4.2#/2#12.3#35.6#3.3#/2#54.3#.4#/2#35.3#17.7#/3#33.4#17.7#/3#29.#3.4#14.#3.6#/\
+    4#27.3#3.4#12.3#2.6#/4#27.2#6.#19.5#/3#27.3#27.4#/2#28.4#27.3#/2#28.5#26.3#/2#27.12#21.2\
+    #/2#24.23#7.#5.2#/2#23.13#2.10#5.3#4.2#/2#22.12#5.9#5.4#2.3#/2#9.#10.7#3.2#12.3#7.8#/2#8\
+    .2#9.7#29.7#/2#9.#9.4#26.2#4.7#/2#19.3#26.4#3.7#/3#18.3#26.14#/5#16.2#13.2#12.14#/6#14.2\
+    #13.3#12.14#/6#14.2#13.3#11.15#/6#15.2#12.3#10.7#4.5#/6#11.#3.3#5.2#4.3#9.7#7.3#/6#10.3#\
+    3.3#3.4#4.#10.6#9.2#/2#13.4#3.3#3.4#15.5#10.2#/#13.4#4.3#4.2#31.2#/#12.4#5.3#36.3#/#11.2\
+    #7.3#16.4#9.2#6.3#/#11.#6.4#16.6#6.5#5.3#/#18.3#17.7#4.7#4.3#/2#17.2#6.2#10.7#4.8#4.2#/2\
+    #24.5#9.7#2.10#3.2#/2#24.6#2.3#3.19#3.2#/2#25.10#4.17#4.2#/2#25.10#5.9#11.2#/2#25.5#11.7\
+    #13.#/2#14.16#11.7#13.#/3#11.18#10.9#3.3#5.2#/4#9.20#4.21#4.2#/10#2.47#2.3#/64#";
+
+const RUINS: &str = "\
+    22.#.#.#2.3#29.#2./31.#32./22.#8.#32./26.#29.#7./14.#4.7#.2#18.#16./19.#2.#5.#4.#18.#10.\
+    #/19.#2.#5.#35./19.#2.3#3.#.2#32./64./28.#5.#29./23.#4.#11.#23./19.#26.#17./19.#.3#.4#35\
+    ./40.#23./32.#16.3#2.5#5./45.#3.#14./9.#48.#5./9.#8.#29.2#8.#5./3.#25.#19.#8.#5./37.#5.#\
+    5.#8.#5./48.#9.#5./20.#28.2#2.6#2.#2./5.#10.#47./30.#7.#.5#19./13.4#3.#17.#5.#19./13.#30\
+    .#19./42.#.#19./13.#6.#18.#2.#.#19./20.#43./20.#43./20.#43./14.5#.2#12.2#.4#.#21./8.#15.\
+    #9.#.4#.3#.3#16./34.#.2#9.#16./32.#6.#22.#./18.#3.#13.#27./4.#21.6#2.14#6.#9./26.#7.#4.#\
+    2.#21./20.#5.#12.#2.#8.#12./26.#7.4#.4#21./11.#14.#8.#3.#16.#7./26.#12.#3.#3.#15.#/10.#2\
+    8.#24./26.#10.3#2.3#.3#.#4.#8./26.2#2.#3.#.4#24./40.#4.#4.#13./34.#2.#26./3.#33.14#13.";
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Layouts decode to full-size maps spanning sparse to dense, with open origins.
+    #[test]
+    fn scenes_decode() {
+        let scenes = bench_scenes();
+        let names: Vec<_> = scenes.iter().map(|s| s.name).collect();
+        assert_eq!(names, ["rooms", "caves", "ruins"]);
+
+        for scene in scenes.iter() {
+            assert_eq!((scene.map.width(), scene.map.height()), (64, 48));
+            assert_eq!(scene.origins.len(), ORIGINS_PER_SCENE);
+            assert!(scene.origins.iter().all(|&c| !scene.map.body_opaque(c)));
+        }
+        assert!(scenes[0].density() > 0.5);
+        assert!((0.2..0.5).contains(&scenes[1].density()));
+        assert!(scenes[2].density() < 0.2);
+
+        let map = decode_layout("3#./.2#.").unwrap();
+        assert!(map.body_opaque(Coords::new(0, 1)) && !map.body_opaque(Coords::new(3, 1)));
+        assert!(!map.body_opaque(Coords::new(0, 0)) && map.body_opaque(Coords::new(2, 0)));
+        assert!(decode_layout("3#/2#").is_none());
+        assert!(decode_layout("3#x").is_none());
+        assert!(decode_layout("3#2").is_none());
+        assert!(decode_layout("18446744073709551615#").is_none());
+        assert!(decode_layout(&format!("{0}#/{0}#", MAX_LAYOUT_TILES / 2 + 1)).is_none());
+        for scene in scenes.iter() {
+            assert_eq!(decode_layout(&encode_layout(&scene.map)).as_ref(), Some(&scene.map));
+        }
+        assert_eq!(encode_layout(&map), "3#./.2#.");
+    }
+
+    // Mutated layouts decode to bounded maps or `None`, never panics.
+    #[test]
+    fn decode_mutated_layouts() {
+        let layout = b"12#/#10.#/#3.4#3.#/12#";
+        let mut rng = Rng::new(1950);
+        for _ in 0..5000 {
+            let text = String::from_utf8_lossy(&rng.mutate(layout)).into_owned();
+            if let Some(map) = decode_layout(&text) {
+                assert!(map.width() * map.height() <= MAX_LAYOUT_TILES);
+            }
+        }
+    }
+}
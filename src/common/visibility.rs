@@ -0,0 +1,702 @@
+//! Visibility grids for FOV Visualization - Rust (2D).
+//!
+//! A `VisibilityGrid` records which tiles of a map are visible, one bit per
+//! tile, using the same row-major tile IDs as `TileMap`.
+//!
+//! It is the set result type of FOV queries: membership (`contains`) is O(1), where a
+//! `Vec<VisibleTile>` must be searched. Convert with `insert_id` per `VisibleTile::id`, as
+//! `FovComputer::visible_grid` does.
+//!
+//! Grids are stamped with the `generation` of the map they were computed from, so
+//! stale grids (computed before the map last changed) can be detected.
+//!
+//! Grids can be saved run-length encoded (see `write_rle`), so fog-of-war state such as
+//! an explored-tiles grid (the union of every grid seen so far) persists cheaply.
+//!
+//! Grids combine as sets, in place (`union_with`, `intersect_with`, `difference_with`,
+//! `complement`) or with operators on references: `&a | &b`, `&a & &b`, `&a - &b` and `!&a`.
+//! Results keep the left grid's generation.
+//!
+//! A `SpottedGrid` remembers recently seen tiles for a number of turns, decaying from
+//! visible to spotted to unknown as successive grids are fed to it.
+//!
+//! RLE format notes:
+//! - header: magic `FOVG`, format version (`u8`), then width and height (`u32`) and the
+//!   map generation (`u64`), all little-endian.
+//! - body: lengths of alternating hidden and visible runs in tile ID order, starting with
+//!   hidden (possibly `0`), as LEB128 varints. Runs must cover the grid exactly.
+
+use std::io::{self, Read, Write};
+
+use super::error::FovError;
+use super::maps::{Coords, OpacityProvider};
+
+/// Magic bytes starting a run-length encoded `VisibilityGrid`.
+const RLE_MAGIC: &[u8; 4] = b"FOVG";
+/// Current run-length encoded `VisibilityGrid` format version.
+pub const RLE_VERSION: u8 = 1;
+/// Largest grid, in tiles, accepted when reading, so bad headers can't exhaust memory.
+const RLE_MAX_TILES: u64 = 1 << 30;
+
+/// Change in a tile's visibility between two `VisibilityGrid`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Became {
+    /// Tile was hidden, and is now visible.
+    Visible,
+    /// Tile was visible, and is now hidden.
+    Hidden,
+}
+
+/// Map-sized bitset of visible tiles.
+///
+/// Grids compare equal by their visible tiles, ignoring `generation`.
+#[derive(Debug, Clone)]
+pub struct VisibilityGrid {
+    width: usize,
+    height: usize,
+    generation: u64,
+    bits: Vec<u64>,
+}
+
+impl PartialEq for VisibilityGrid {
+    fn eq(&self, other: &Self) -> bool {
+        let Self { width, height, generation: _, bits } = self;
+        (*width, *height, bits) == (other.width, other.height, &other.bits)
+    }
+}
+
+impl Eq for VisibilityGrid {}
+
+impl VisibilityGrid {
+    /// Creates a new `VisibilityGrid` with no visible tiles, at generation `0`.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            generation: 0,
+            bits: vec![0; (width * height).div_ceil(64)],
+        }
+    }
+    /// Creates a new, empty `VisibilityGrid` sized to `map` and stamped with its generation.
+    pub fn for_map<M: OpacityProvider>(map: &M) -> Self {
+        Self {
+            generation: map.generation(),
+            ..Self::new(map.width(), map.height())
+        }
+    }
+    /// Returns the generation of the map this grid was computed from.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    /// Sets the map generation this grid was computed from.
+    pub fn set_generation(&mut self, generation: u64) {
+        self.generation = generation;
+    }
+    /// Returns `true` if `map` has changed since this grid was computed, or differs in size.
+    pub fn is_stale<M: OpacityProvider>(&self, map: &M) -> bool {
+        self.generation != map.generation() || self.width != map.width() || self.height != map.height()
+    }
+    /// Returns the width of the grid, in tiles.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    /// Returns the height of the grid, in tiles.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    /// Returns the tile ID at `coords`, or `None` if out of bounds.
+    pub fn tile_id(&self, coords: Coords) -> Option<usize> {
+        let in_bounds = coords.x >= 0
+            && coords.y >= 0
+            && (coords.x as usize) < self.width
+            && (coords.y as usize) < self.height;
+
+        match in_bounds {
+            true => coords.to_index(self.width),
+            false => None,
+        }
+    }
+    /// Returns `true` if the tile at `coords` is visible.
+    pub fn contains(&self, coords: Coords) -> bool {
+        self.tile_id(coords).is_some_and(|id| self.contains_id(id))
+    }
+    /// Returns `true` if the tile with ID `id` is visible.
+    ///
+    /// Out of bounds IDs (`id >= width * height`) are never visible.
+    pub fn contains_id(&self, id: usize) -> bool {
+        id < self.width * self.height && self.bits[id / 64] & (1u64 << (id % 64)) != 0
+    }
+    /// Marks the tile at `coords` visible. Returns `true` if it was not already visible.
+    ///
+    /// Out of bounds `coords` are ignored.
+    pub fn insert(&mut self, coords: Coords) -> bool {
+        match self.tile_id(coords) {
+            Some(id) => self.insert_id(id),
+            None => false,
+        }
+    }
+    /// Marks the tile with ID `id` visible. Returns `true` if it was not already visible.
+    ///
+    /// Out of bounds IDs (`id >= width * height`) are ignored.
+    pub fn insert_id(&mut self, id: usize) -> bool {
+        if id >= self.width * self.height {
+            return false;
+        }
+        let word = &mut self.bits[id / 64];
+        let mask = 1u64 << (id % 64);
+        let added = *word & mask == 0;
+        *word |= mask;
+        added
+    }
+    /// Marks the tile at `coords` hidden. Out of bounds `coords` are ignored.
+    pub fn remove(&mut self, coords: Coords) {
+        if let Some(id) = self.tile_id(coords) {
+            self.bits[id / 64] &= !(1u64 << (id % 64));
+        }
+    }
+    /// Marks all tiles hidden.
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+    }
+    /// Returns the number of visible tiles.
+    pub fn len(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+    /// Returns `true` if no tiles are visible.
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|w| *w == 0)
+    }
+    /// Adds all tiles visible in `other` to `self`. Grids must be the same size.
+    pub fn union_with(&mut self, other: &Self) {
+        self.combine_with(other, |a, b| a | b);
+    }
+    /// Keeps only tiles also visible in `other`. Grids must be the same size.
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.combine_with(other, |a, b| a & b);
+    }
+    /// Hides all tiles visible in `other`, e.g. "seen by A but not B". Grids must be the
+    /// same size.
+    pub fn difference_with(&mut self, other: &Self) {
+        self.combine_with(other, |a, b| a & !b);
+    }
+    /// Flips every tile: visible tiles become hidden, hidden tiles visible.
+    pub fn complement(&mut self) {
+        self.bits.iter_mut().for_each(|w| *w = !*w);
+        let tail = (self.width * self.height) % 64;
+        if let (Some(last), true) = (self.bits.last_mut(), tail != 0) {
+            *last &= (1u64 << tail) - 1;
+        }
+    }
+    /// Combines each word of `self` with the matching word of `other` by `f`.
+    ///
+    /// Words are combined in one flat pass over both slices, which compilers vectorize.
+    fn combine_with(&mut self, other: &Self, f: impl Fn(u64, u64) -> u64) {
+        assert_eq!(self.bits.len(), other.bits.len(), "grid sizes must match!");
+        for (a, b) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *a = f(*a, *b);
+        }
+    }
+    /// Returns an iterator over the IDs of visible tiles, in ascending order.
+    pub fn iter_ids(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bits.iter().enumerate().flat_map(|(w, &word)| set_bits(w, word))
+    }
+    /// Returns an iterator over tiles whose visibility changed since `previous`, in tile ID order.
+    ///
+    /// Only words that differ are scanned, so unchanged regions cost one comparison per
+    /// 64 tiles. Grids must be the same size.
+    pub fn diff<'a>(&'a self, previous: &'a Self) -> impl Iterator<Item = (Coords, Became)> + 'a {
+        assert_eq!(self.bits.len(), previous.bits.len(), "grid sizes must match!");
+        let width = self.width;
+
+        self.bits
+            .iter()
+            .zip(previous.bits.iter())
+            .enumerate()
+            .filter(|(_, (now, before))| now != before)
+            .flat_map(|(w, (&now, &before))| {
+                set_bits(w, now ^ before).map(move |id| {
+                    let became = match now & (1u64 << (id % 64)) != 0 {
+                        true => Became::Visible,
+                        false => Became::Hidden,
+                    };
+                    (id, became)
+                })
+            })
+            .filter_map(move |(id, became)| Some((Coords::from_index(id, width)?, became)))
+    }
+    /// Returns a 64-bit FNV-1a checksum of the grid's size and visible tiles.
+    ///
+    /// Grids are integer-only, so equal checksums across machines indicate identical
+    /// results, e.g. for desync detection in lockstep multiplayer.
+    pub fn checksum(&self) -> u64 {
+        let header = [self.width as u64, self.height as u64];
+        fnv1a(header.iter().chain(self.bits.iter()))
+    }
+    /// Returns an iterator over the coordinates of visible tiles, in tile ID order.
+    pub fn iter(&self) -> impl Iterator<Item = Coords> + '_ {
+        self.iter_ids()
+            .filter_map(|id| Coords::from_index(id, self.width))
+    }
+    /// Returns a grayscale fog-of-war mask: one byte per tile, `255` visible and `0` hidden.
+    ///
+    /// Bytes are in tile ID order (row `y = 0` first), ready to upload as a `width` by
+    /// `height` single-channel texture. For soft edges from FOV bit coverage, see
+    /// `fovcalc::get_coverage_mask`.
+    pub fn to_image_mask(&self) -> Vec<u8> {
+        (0..self.width * self.height)
+            .map(|id| if self.contains_id(id) { u8::MAX } else { 0 })
+            .collect()
+    }
+    /// Writes the grid run-length encoded, with a versioned header. See the module notes.
+    pub fn write_rle<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let mut out = Vec::with_capacity(32);
+        out.extend_from_slice(RLE_MAGIC);
+        out.push(RLE_VERSION);
+        out.extend_from_slice(&(self.width as u32).to_le_bytes());
+        out.extend_from_slice(&(self.height as u32).to_le_bytes());
+        out.extend_from_slice(&self.generation.to_le_bytes());
+
+        let (mut visible, mut run) = (false, 0u64);
+        for id in 0..self.width * self.height {
+            if self.contains_id(id) != visible {
+                write_varint(&mut out, run);
+                (visible, run) = (!visible, 0);
+            }
+            run += 1;
+        }
+        write_varint(&mut out, run);
+
+        w.write_all(&out)
+    }
+    /// Reads a grid written by `write_rle`.
+    ///
+    /// Returns an `InvalidData` error for a bad header, an unknown version, or runs that
+    /// don't cover the grid exactly.
+    pub fn read_rle<R: Read>(mut r: R) -> Result<Self, FovError> {
+        let mut header = [0u8; 21];
+        r.read_exact(&mut header)?;
+        if &header[..4] != RLE_MAGIC {
+            return Err(FovError::invalid("not a visibility grid"));
+        }
+        if header[4] != RLE_VERSION {
+            return Err(FovError::invalid("unsupported visibility grid version"));
+        }
+
+        let width = u32::from_le_bytes(header[5..9].try_into().unwrap()) as usize;
+        let height = u32::from_le_bytes(header[9..13].try_into().unwrap()) as usize;
+        let total = width as u64 * height as u64;
+        if total > RLE_MAX_TILES {
+            return Err(FovError::invalid("visibility grid too large"));
+        }
+
+        // Runs are checked before the grid is allocated, so a bad body costs no more memory
+        // than its own length
+        let (mut visible, mut id, mut runs) = (false, 0u64, Vec::new());
+        while id < total || (id == 0 && !visible) {
+            let run = read_varint(&mut r)?;
+            if run > total - id {
+                return Err(FovError::invalid("visibility grid runs overflow the grid"));
+            }
+            if visible && run > 0 {
+                runs.push(id as usize..(id + run) as usize);
+            }
+            (visible, id) = (!visible, id + run);
+        }
+
+        let mut grid = Self::new(width, height);
+        grid.generation = u64::from_le_bytes(header[13..21].try_into().unwrap());
+        for run in runs {
+            grid.insert_run(run);
+        }
+
+        Ok(grid)
+    }
+    /// Marks the tiles with IDs in `ids` visible, a word at a time.
+    fn insert_run(&mut self, ids: std::ops::Range<usize>) {
+        let mut id = ids.start;
+        while id < ids.end {
+            let n = (64 - id % 64).min(ids.end - id);
+            self.bits[id / 64] |= (u64::MAX >> (64 - n)) << (id % 64);
+            id += n;
+        }
+    }
+}
+
+impl std::ops::BitOr for &VisibilityGrid {
+    type Output = VisibilityGrid;
+
+    /// Tiles visible in either grid, e.g. to any of several observers.
+    fn bitor(self, rhs: Self) -> Self::Output {
+        let mut grid = self.clone();
+        grid.union_with(rhs);
+        grid
+    }
+}
+
+impl std::ops::BitAnd for &VisibilityGrid {
+    type Output = VisibilityGrid;
+
+    /// Tiles visible in both grids.
+    fn bitand(self, rhs: Self) -> Self::Output {
+        let mut grid = self.clone();
+        grid.intersect_with(rhs);
+        grid
+    }
+}
+
+impl std::ops::Sub for &VisibilityGrid {
+    type Output = VisibilityGrid;
+
+    /// Tiles visible in `self` but not in `rhs`.
+    fn sub(self, rhs: Self) -> Self::Output {
+        let mut grid = self.clone();
+        grid.difference_with(rhs);
+        grid
+    }
+}
+
+impl std::ops::Not for &VisibilityGrid {
+    type Output = VisibilityGrid;
+
+    /// Tiles hidden in `self`.
+    fn not(self) -> Self::Output {
+        let mut grid = self.clone();
+        grid.complement();
+        grid
+    }
+}
+
+/// Knowledge of a tile in a `SpottedGrid`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Spotted {
+    /// Visible in the latest grid.
+    Visible,
+    /// Seen recently; forgotten after this many more updates without being seen.
+    Recently(u8),
+    /// Never seen, or forgotten.
+    Unknown,
+}
+
+/// Map-sized grid of recently seen tiles, for games with delayed information.
+///
+/// Each `update` with the latest `VisibilityGrid` marks visible tiles `Visible`. Tiles
+/// that drop out of view stay spotted for `turns` updates, counting down, then become
+/// `Unknown` again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpottedGrid {
+    width: usize,
+    height: usize,
+    turns: u8,
+    timers: Vec<u16>,
+    expired: Vec<usize>,
+}
+
+impl SpottedGrid {
+    /// Creates a new `SpottedGrid` of unknown tiles, remembering tiles for `turns` updates.
+    pub fn new(width: usize, height: usize, turns: u8) -> Self {
+        Self {
+            width,
+            height,
+            turns,
+            timers: vec![0; width * height],
+            expired: Vec::new(),
+        }
+    }
+    /// Returns the number of updates a tile stays spotted after leaving view.
+    pub fn turns(&self) -> u8 {
+        self.turns
+    }
+    /// Advances one turn: tiles visible in `grid` become `Visible`, others count down.
+    ///
+    /// Grids must be the same size.
+    pub fn update(&mut self, grid: &VisibilityGrid) {
+        assert!(
+            (grid.width, grid.height) == (self.width, self.height),
+            "grid sizes must match!"
+        );
+        self.expired.clear();
+        let visible = self.turns as u16 + 1;
+
+        for (id, timer) in self.timers.iter_mut().enumerate() {
+            match grid.contains_id(id) {
+                true => *timer = visible,
+                false if *timer == 1 => {
+                    *timer = 0;
+                    self.expired.push(id);
+                }
+                false => *timer = timer.saturating_sub(1),
+            }
+        }
+    }
+    /// Returns what is known about the tile at `coords`. Out of bounds tiles are `Unknown`.
+    pub fn get(&self, coords: Coords) -> Spotted {
+        let in_bounds = coords.x >= 0
+            && coords.y >= 0
+            && (coords.x as usize) < self.width
+            && (coords.y as usize) < self.height;
+        let Some(id) = coords.to_index(self.width).filter(|_| in_bounds) else {
+            return Spotted::Unknown;
+        };
+
+        match self.timers[id] {
+            0 => Spotted::Unknown,
+            t if t > self.turns as u16 => Spotted::Visible,
+            t => Spotted::Recently(t as u8),
+        }
+    }
+    /// Returns an iterator over tiles forgotten this turn (by the last `update`), in tile ID
+    /// order, e.g. to remove their last-known markers.
+    pub fn expired(&self) -> impl Iterator<Item = Coords> + '_ {
+        self.expired
+            .iter()
+            .filter_map(|&id| Coords::from_index(id, self.width))
+    }
+}
+
+/// Appends `value` to `out` as an unsigned LEB128 varint.
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Reads an unsigned LEB128 varint from `r`.
+fn read_varint<R: Read>(r: &mut R) -> Result<u64, FovError> {
+    let mut value = 0u64;
+
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8];
+        r.read_exact(&mut byte)?;
+        value |= ((byte[0] & 0x7f) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(FovError::invalid("visibility grid run too long"))
+}
+
+/// Returns the 64-bit FNV-1a hash of `words`, in little-endian byte order.
+pub(crate) fn fnv1a<'a>(words: impl Iterator<Item = &'a u64>) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+
+    for byte in words.flat_map(|w| w.to_le_bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+
+    hash
+}
+
+/// Returns an iterator over the tile IDs of set bits in `word`, the `w`-th word of a grid.
+fn set_bits(w: usize, word: u64) -> impl Iterator<Item = usize> {
+    let mut word = word;
+
+    std::iter::from_fn(move || {
+        if word == 0 {
+            return None;
+        }
+        let bit = word.trailing_zeros() as usize;
+        word &= word - 1;
+        Some(w * 64 + bit)
+    })
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn grid_insert_iter_union() {
+        let mut a = VisibilityGrid::new(10, 10);
+        let mut b = VisibilityGrid::new(10, 10);
+
+        assert!(a.insert(Coords::new(1, 2)));
+        assert!(!a.insert(Coords::new(1, 2)));
+        assert!(!a.insert(Coords::new(10, 2)));
+        b.insert(Coords::new(9, 9));
+        a.union_with(&b);
+
+        let actual: Vec<_> = a.iter().collect();
+        let expected = [Coords::new(1, 2), Coords::new(9, 9)];
+
+        assert_eq!(actual, expected);
+        assert_eq!(a.len(), 2);
+
+        // IDs past `width * height`, in the last word's spare bits or beyond it, are ignored
+        assert!(!a.insert_id(100) && !a.insert_id(127) && !a.insert_id(1000));
+        assert!(!a.contains_id(100) && !a.contains_id(1000));
+        assert_eq!(a.len(), 2);
+    }
+
+    #[test]
+    fn grid_diff() {
+        let mut before = VisibilityGrid::new(100, 3);
+        let mut now = VisibilityGrid::new(100, 3);
+        before.insert(Coords::new(0, 0));
+        before.insert(Coords::new(70, 1));
+        now.insert(Coords::new(70, 1));
+        now.insert(Coords::new(99, 2));
+
+        let actual: Vec<_> = now.diff(&before).collect();
+        let expected = [
+            (Coords::new(0, 0), Became::Hidden),
+            (Coords::new(99, 2), Became::Visible),
+        ];
+
+        assert_eq!(actual, expected);
+        assert_eq!(now.diff(&now).count(), 0);
+    }
+
+    // Set operations match per-tile logic; complements never mark tiles past the grid.
+    #[test]
+    fn grid_set_algebra() {
+        let (mut a, mut b) = (VisibilityGrid::new(10, 7), VisibilityGrid::new(10, 7));
+        for id in (0..70).filter(|id| id % 3 == 0) {
+            a.insert_id(id);
+        }
+        for id in (0..70).filter(|id| id % 2 == 0) {
+            b.insert_id(id);
+        }
+        a.set_generation(4);
+
+        let check = |grid: VisibilityGrid, rule: fn(usize) -> bool| {
+            let expected: Vec<_> = (0..70).filter(|&id| rule(id)).collect();
+            assert_eq!(grid.iter_ids().collect::<Vec<_>>(), expected);
+            assert_eq!(grid.generation(), 4);
+        };
+        check(&a | &b, |id| id % 3 == 0 || id % 2 == 0);
+        check(&a & &b, |id| id % 6 == 0);
+        check(&a - &b, |id| id % 3 == 0 && id % 2 != 0);
+        check(!&a, |id| id % 3 != 0);
+
+        let mut empty = VisibilityGrid::new(10, 7);
+        empty.complement();
+        assert_eq!(empty.len(), 70);
+        empty.difference_with(&empty.clone());
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn grid_staleness() {
+        use crate::maps::TileMap;
+
+        let mut map = TileMap::new(4, 4).unwrap();
+        let grid = VisibilityGrid::for_map(&map);
+        assert!(!grid.is_stale(&map));
+
+        map.set_body_opaque(Coords::new(1, 1), true);
+        assert!(grid.is_stale(&map));
+        assert!(!VisibilityGrid::for_map(&map).is_stale(&map));
+        assert!(VisibilityGrid::new(3, 4).is_stale(&TileMap::new(4, 4).unwrap()));
+    }
+
+    // Masks are one byte per tile in tile ID order: 255 visible, 0 hidden.
+    #[test]
+    fn grid_image_mask() {
+        let mut grid = VisibilityGrid::new(3, 2);
+        grid.insert(Coords::new(0, 0));
+        grid.insert(Coords::new(2, 1));
+        assert_eq!(grid.to_image_mask(), vec![255, 0, 0, 0, 0, 255]);
+    }
+
+    // Grids survive an RLE round trip; long runs stay compact; bad input is rejected.
+    #[test]
+    fn grid_rle_round_trip() {
+        let mut grid = VisibilityGrid::new(200, 100);
+        grid.set_generation(42);
+        for x in 10..150 {
+            for y in 20..60 {
+                grid.insert(Coords::new(x, y));
+            }
+        }
+
+        let mut bytes = Vec::new();
+        grid.write_rle(&mut bytes).unwrap();
+        assert!(bytes.len() < 21 + 40 * 4);
+        let loaded = VisibilityGrid::read_rle(&bytes[..]).unwrap();
+        assert_eq!((&loaded, loaded.generation()), (&grid, 42));
+
+        for grid in [VisibilityGrid::new(0, 0), VisibilityGrid::new(3, 1)] {
+            let mut bytes = Vec::new();
+            grid.write_rle(&mut bytes).unwrap();
+            assert_eq!(VisibilityGrid::read_rle(&bytes[..]).unwrap(), grid);
+        }
+
+        let mut bad = bytes.clone();
+        bad[4] = RLE_VERSION + 1;
+        assert!(VisibilityGrid::read_rle(&bad[..]).is_err());
+        let mut bad = bytes.clone();
+        *bad.last_mut().unwrap() = 4;
+        assert!(VisibilityGrid::read_rle(&bad[..]).is_err());
+        assert!(VisibilityGrid::read_rle(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    // Tiles decay from visible, through spotted countdown, to unknown.
+    #[test]
+    fn spotted_grid_decay() {
+        let (a, b) = (Coords::new(0, 0), Coords::new(2, 1));
+        let mut spotted = SpottedGrid::new(3, 2, 2);
+        let mut grid = VisibilityGrid::new(3, 2);
+        grid.insert(a);
+        grid.insert(b);
+        spotted.update(&grid);
+        assert_eq!(spotted.get(a), Spotted::Visible);
+        assert_eq!(spotted.get(Coords::new(1, 0)), Spotted::Unknown);
+
+        grid.remove(a);
+        spotted.update(&grid);
+        assert_eq!(spotted.get(a), Spotted::Recently(2));
+        grid.remove(b);
+        spotted.update(&grid);
+        assert_eq!(spotted.get(a), Spotted::Recently(1));
+        assert_eq!(spotted.get(b), Spotted::Recently(2));
+        assert_eq!(spotted.expired().count(), 0);
+
+        spotted.update(&grid);
+        assert_eq!(spotted.get(a), Spotted::Unknown);
+        assert_eq!(spotted.expired().collect::<Vec<_>>(), [a]);
+        spotted.update(&grid);
+        assert_eq!(spotted.expired().collect::<Vec<_>>(), [b]);
+        assert_eq!(spotted.get(Coords::new(-1, 0)), Spotted::Unknown);
+
+        let mut instant = SpottedGrid::new(3, 2, 0);
+        instant.update(&VisibilityGrid::new(3, 2));
+        grid.insert(a);
+        instant.update(&grid);
+        assert_eq!(instant.get(a), Spotted::Visible);
+        instant.update(&VisibilityGrid::new(3, 2));
+        assert_eq!((instant.get(a), instant.expired().count()), (Spotted::Unknown, 1));
+    }
+
+    // Mutated RLE files load as grids that round trip, or errors, never panics.
+    #[test]
+    fn read_mutated_rle() {
+        let mut grid = VisibilityGrid::new(30, 20);
+        for x in 3..25 {
+            grid.insert(Coords::new(x, x % 20));
+        }
+        let mut bytes = Vec::new();
+        grid.write_rle(&mut bytes).unwrap();
+
+        let mut rng = Rng::new(1950);
+        for _ in 0..5000 {
+            let Ok(loaded) = VisibilityGrid::read_rle(&rng.mutate(&bytes)[..]) else {
+                continue;
+            };
+            assert!(loaded.len() <= loaded.width() * loaded.height());
+            let mut again = Vec::new();
+            loaded.write_rle(&mut again).unwrap();
+            assert_eq!(VisibilityGrid::read_rle(&again[..]).unwrap(), loaded);
+        }
+    }
+}
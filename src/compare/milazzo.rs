@@ -0,0 +1,242 @@
+//! Adam Milazzo's "beveled walls" FOV, for comparison.
+//!
+//! Port of the algorithm described in _My Algorithm_ at
+//! <http://www.adammil.net/blog/v125_Roguelike_Vision_Algorithms.html>.
+//!
+//! Notes:
+//! - Shadowcasting over each octant, with slopes kept as exact integer fractions.
+//! - Opaque tiles are treated as having beveled corners where no neighboring wall fills
+//!   the corner, so light passes diagonally between walls that only touch at a corner.
+//! - A transparent tile is visible if the light reaches its inner (central) square; opaque
+//!   tiles are visible if light reaches any part of them.
+//! - Local octant coordinates are `(x, y)` with `x >= y >= 0`, mapped to the map via
+//!   `Octant::dpds_to_delta`.
+
+use crate::{
+    maps::{Coords, OpacityProvider},
+    Octant, VisibilityGrid,
+};
+
+use super::{blocks, in_range};
+
+/// Slope `y / x` as an exact integer fraction.
+#[derive(Debug, Clone, Copy)]
+struct Slope {
+    y: i64,
+    x: i64,
+}
+
+impl Slope {
+    fn new(y: i64, x: i64) -> Self {
+        Self { y, x }
+    }
+    /// Returns `true` if `self > y / x`.
+    fn greater(&self, y: i64, x: i64) -> bool {
+        self.y * x > self.x * y
+    }
+    /// Returns `true` if `self >= y / x`.
+    fn greater_or_equal(&self, y: i64, x: i64) -> bool {
+        self.y * x >= self.x * y
+    }
+    /// Returns `true` if `self < y / x`.
+    fn less(&self, y: i64, x: i64) -> bool {
+        self.y * x < self.x * y
+    }
+}
+
+/// Map and origin shared by the octant scans of one query.
+struct Scan<'a, M> {
+    map: &'a M,
+    origin: Coords,
+    r: i64,
+    grid: VisibilityGrid,
+}
+
+impl<M: OpacityProvider> Scan<'_, M> {
+    /// Returns map coordinates of local octant coordinates `(x, y)`.
+    fn coords(&self, octant: Octant, x: i64, y: i64) -> Coords {
+        self.origin.offset(octant.dpds_to_delta(x as u16, y as u16))
+    }
+    fn blocks_light(&self, octant: Octant, x: i64, y: i64) -> bool {
+        blocks(self.map, self.coords(octant, x, y))
+    }
+    fn set_visible(&mut self, octant: Octant, x: i64, y: i64) {
+        let coords = self.coords(octant, x, y);
+        if self.map.in_bounds(coords) {
+            self.grid.insert(coords);
+        }
+    }
+    /// Scans columns `x..=r` of `octant` between slopes `top` and `bottom`.
+    fn compute(&mut self, octant: Octant, mut x: i64, mut top: Slope, mut bottom: Slope) {
+        while x <= self.r {
+            // Top and bottom tiles of the column, adjusted for beveled corners
+            let top_y = match top.x == 1 {
+                true => x,
+                false => {
+                    let mut top_y = ((x * 2 - 1) * top.y + top.x) / (top.x * 2);
+                    if self.blocks_light(octant, x, top_y) {
+                        if top.greater_or_equal(top_y * 2 + 1, x * 2)
+                            && !self.blocks_light(octant, x, top_y + 1)
+                        {
+                            top_y += 1;
+                        }
+                    } else {
+                        let mut ax = x * 2;
+                        if self.blocks_light(octant, x + 1, top_y + 1) {
+                            ax += 1;
+                        }
+                        if top.greater(top_y * 2 + 1, ax) {
+                            top_y += 1;
+                        }
+                    }
+                    top_y
+                }
+            };
+            let bottom_y = match bottom.y == 0 {
+                true => 0,
+                false => {
+                    let mut bottom_y = ((x * 2 - 1) * bottom.y + bottom.x) / (bottom.x * 2);
+                    if bottom.greater_or_equal(bottom_y * 2 + 1, x * 2)
+                        && self.blocks_light(octant, x, bottom_y)
+                        && !self.blocks_light(octant, x, bottom_y + 1)
+                    {
+                        bottom_y += 1;
+                    }
+                    bottom_y
+                }
+            };
+
+            // -1: no tile seen yet, 0: last tile transparent, 1: last tile opaque
+            let mut was_opaque = -1;
+            let mut y = top_y;
+            while y >= bottom_y {
+                if in_range(x as i32, y as i32, self.r as usize) {
+                    let is_opaque = self.blocks_light(octant, x, y);
+                    let is_visible = is_opaque
+                        || ((y != top_y || top.greater(y * 4 - 1, x * 4 + 1))
+                            && (y != bottom_y || bottom.less(y * 4 + 1, x * 4 - 1)));
+                    if is_visible {
+                        self.set_visible(octant, x, y);
+                    }
+
+                    if x != self.r {
+                        if is_opaque {
+                            if was_opaque == 0 {
+                                // Transparent to opaque: scan above the wall in a child scan
+                                let (mut nx, ny) = (x * 2, y * 2 + 1);
+                                if self.blocks_light(octant, x, y + 1) {
+                                    nx -= 1;
+                                }
+                                if top.greater(ny, nx) {
+                                    if y == bottom_y {
+                                        bottom = Slope::new(ny, nx);
+                                        break;
+                                    }
+                                    self.compute(octant, x + 1, top, Slope::new(ny, nx));
+                                } else if y == bottom_y {
+                                    return;
+                                }
+                            }
+                            was_opaque = 1;
+                        } else {
+                            if was_opaque > 0 {
+                                // Opaque to transparent: lower the top slope past the wall
+                                let (mut nx, ny) = (x * 2, y * 2 + 1);
+                                if self.blocks_light(octant, x + 1, y + 1) {
+                                    nx += 1;
+                                }
+                                if bottom.greater_or_equal(ny, nx) {
+                                    return;
+                                }
+                                top = Slope::new(ny, nx);
+                            }
+                            was_opaque = 0;
+                        }
+                    }
+                }
+                y -= 1;
+            }
+
+            if was_opaque != 0 {
+                break;
+            }
+            x += 1;
+        }
+    }
+}
+
+/// Returns a `VisibilityGrid` of tiles visible from `origin` out to radius `r`.
+pub fn get_visible_grid<M: OpacityProvider>(map: &M, origin: Coords, r: usize) -> VisibilityGrid {
+    let mut scan = Scan {
+        map,
+        origin,
+        r: r as i64,
+        grid: VisibilityGrid::for_map(map),
+    };
+
+    if !map.in_bounds(origin) {
+        return scan.grid;
+    }
+
+    scan.grid.insert(origin);
+    for octant in Octant::ALL {
+        scan.compute(octant, 1, Slope::new(1, 1), Slope::new(0, 1));
+    }
+
+    scan.grid
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maps::TileMap;
+
+    // Open maps see a full disk; everything in a convex room is visible, corners included.
+    #[test]
+    fn open_map_and_room() {
+        let origin = Coords::new(10, 10);
+        let map = TileMap::new(21, 21).unwrap();
+        let grid = get_visible_grid(&map, origin, 8);
+        let disk = (0..21 * 21)
+            .map(|id| Coords::new(id % 21, id / 21))
+            .filter(|c| in_range(c.x - 10, c.y - 10, 8))
+            .count();
+        assert_eq!(grid.len(), disk);
+
+        let mut room = TileMap::new(21, 21).unwrap();
+        for i in 4..=16 {
+            for c in [(i, 4), (i, 16), (4, i), (16, i)] {
+                room.set_body_opaque(Coords::new(c.0, c.1), true);
+            }
+        }
+        let grid = get_visible_grid(&room, origin, 8);
+        assert_eq!(grid.len(), 13 * 13);
+        assert!(grid.contains(Coords::new(4, 4)));
+        assert!(grid.contains(Coords::new(16, 10)));
+        assert!(!grid.contains(Coords::new(17, 10)));
+    }
+
+    // Pillars cast shadows; light passes diagonally between corner-touching walls.
+    #[test]
+    fn pillar_and_bevels() {
+        let origin = Coords::new(10, 10);
+        let mut map = TileMap::new(21, 21).unwrap();
+        map.set_body_opaque(Coords::new(12, 10), true);
+        let grid = get_visible_grid(&map, origin, 8);
+        assert!(grid.contains(Coords::new(12, 10)));
+        assert!(!grid.contains(Coords::new(13, 10)));
+        assert!(!grid.contains(Coords::new(16, 10)));
+
+        let mut map = TileMap::new(21, 21).unwrap();
+        map.set_body_opaque(Coords::new(11, 12), true);
+        map.set_body_opaque(Coords::new(12, 11), true);
+        let grid = get_visible_grid(&map, origin, 8);
+        assert!(grid.contains(Coords::new(13, 13)));
+    }
+}
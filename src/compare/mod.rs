@@ -0,0 +1,146 @@
+//! Third-party FOV rulesets, for comparison against this crate's FOV.
+//!
+//! Notes:
+//! - Each ruleset is a from-scratch port running over the same `OpacityProvider` maps.
+//! - Each returns a `VisibilityGrid`, so results can be diffed, rendered, and timed alike.
+//! - Radius culling matches _Simple_ FOV with the default `FovShape`: a tile is in range if its
+//!   center is within `r + 0.5` tiles of the origin.
+//! - libtcod's precise permissive FOV is not yet ported; `raycast` covers libtcod's basic mode.
+//! - `agreement_table` tabulates how each ruleset differs from _Simple_ FOV on shared fixtures.
+
+pub mod milazzo;
+pub mod raycast;
+
+use crate::{
+    analysis::Table,
+    maps::{Coords, OpacityProvider, TileMap},
+    simple::{fovcalc, FovSet16},
+    VisibilityGrid,
+};
+
+/// FOV ruleset under comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Ruleset {
+    /// This crate's _Simple_ FOV.
+    Simple,
+    /// Adam Milazzo's beveled-wall shadowcasting (see `milazzo`).
+    Milazzo,
+    /// libtcod-style basic raycasting (see `raycast`).
+    Raycast,
+}
+
+impl Ruleset {
+    /// All rulesets, _Simple_ first.
+    pub const ALL: [Ruleset; 3] = [Ruleset::Simple, Ruleset::Milazzo, Ruleset::Raycast];
+    /// Returns the label of the ruleset, for tables and figures.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Ruleset::Simple => "simple",
+            Ruleset::Milazzo => "milazzo",
+            Ruleset::Raycast => "raycast",
+        }
+    }
+    /// Returns tiles visible from `origin` out to radius `r`. `fov_set` is used by `Simple` only.
+    pub fn visible_grid<M: OpacityProvider>(
+        &self,
+        fov_set: &FovSet16,
+        map: &M,
+        origin: Coords,
+        r: usize,
+    ) -> VisibilityGrid {
+        match self {
+            Ruleset::Simple => fovcalc::get_visible_grid(fov_set, map, origin, r),
+            Ruleset::Milazzo => milazzo::get_visible_grid(map, origin, r),
+            Ruleset::Raycast => raycast::get_visible_grid(map, origin, r),
+        }
+    }
+}
+
+/// Returns a `Table` comparing each ruleset to _Simple_ FOV over named `fixtures`.
+///
+/// Columns: `fixture`, `density` (opaque tile fraction, see `TileMap::stats`), `ruleset`,
+/// `visible`, `only_ruleset` and `only_simple` (tiles seen by one side only), and `agreement`
+/// (shared tiles over tiles seen by either, `0.0..=1.0`).
+pub fn agreement_table(
+    fov_set: &FovSet16,
+    fixtures: &[(&str, TileMap)],
+    origin: Coords,
+    r: usize,
+) -> Table {
+    let columns = [
+        "fixture",
+        "density",
+        "ruleset",
+        "visible",
+        "only_ruleset",
+        "only_simple",
+        "agreement",
+    ];
+    let mut table = Table::new(&columns);
+
+    for (name, map) in fixtures {
+        let density = map.stats().density();
+        let simple = Ruleset::Simple.visible_grid(fov_set, map, origin, r);
+        for ruleset in Ruleset::ALL {
+            let grid = ruleset.visible_grid(fov_set, map, origin, r);
+            let only_ruleset = grid.iter().filter(|&c| !simple.contains(c)).count();
+            let only_simple = simple.iter().filter(|&c| !grid.contains(c)).count();
+            let either = grid.len() + only_simple;
+            let agreement = match either {
+                0 => 1.0,
+                _ => (either - only_ruleset - only_simple) as f64 / either as f64,
+            };
+            table.push_row(vec![
+                (*name).into(),
+                density.into(),
+                ruleset.label().into(),
+                grid.len().into(),
+                only_ruleset.into(),
+                only_simple.into(),
+                agreement.into(),
+            ]);
+        }
+    }
+
+    table
+}
+
+/// Returns `true` if offset `(dx, dy)` is within `r + 0.5` tiles of the origin.
+pub(crate) fn in_range(dx: i32, dy: i32, r: usize) -> bool {
+    let r2 = 2 * r as i64 + 1;
+    4 * (dx as i64 * dx as i64 + dy as i64 * dy as i64) <= r2 * r2
+}
+
+/// Returns `true` if the tile at `coords` blocks sight. Out of bounds tiles block.
+pub(crate) fn blocks<M: OpacityProvider>(map: &M, coords: Coords) -> bool {
+    !map.in_bounds(coords) || map.body_opaque(coords)
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{analysis::Cell, FovRadius, FovShape, QFactor};
+
+    // On an open map every ruleset sees the same disk; obstacles make them disagree.
+    #[test]
+    fn agreement_open_and_cluttered() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let fixtures = [
+            ("open", TileMap::new(33, 33).unwrap()),
+            ("cluttered", TileMap::random(33, 33, 0.2, 5).unwrap()),
+        ];
+        let table = agreement_table(&fov_set, &fixtures, Coords::new(16, 16), 16);
+        let agreement: Vec<_> = table.rows().map(|row| row[6].clone()).collect();
+
+        assert_eq!(table.len(), 6);
+        assert!(agreement[..3].iter().all(|a| *a == Cell::Float(1.0)));
+        assert_eq!(agreement[3], Cell::Float(1.0));
+        assert!(agreement[4..].iter().all(|a| *a != Cell::Float(1.0)));
+    }
+}
@@ -0,0 +1,140 @@
+//! libtcod-style basic raycasting FOV, for comparison.
+//!
+//! Modeled on libtcod's `FOV_BASIC` ("light walls" enabled).
+//!
+//! Notes:
+//! - A Bresenham ray is cast from the origin to every tile on the perimeter of the square
+//!   of radius `r`. Tiles along each ray are visible up to and including the first opaque tile.
+//! - Rays stop at the first out of range tile, so the result is cut to a disk.
+//! - A post-processing pass lights opaque tiles adjacent (away from the origin) to visible
+//!   transparent tiles, fixing walls that rays skip past, as libtcod does.
+
+use crate::{
+    maps::{Coords, OpacityProvider},
+    VisibilityGrid,
+};
+
+use super::{blocks, in_range};
+
+/// Returns a `VisibilityGrid` of tiles visible from `origin` out to radius `r`.
+pub fn get_visible_grid<M: OpacityProvider>(map: &M, origin: Coords, r: usize) -> VisibilityGrid {
+    let mut grid = VisibilityGrid::for_map(map);
+    if !map.in_bounds(origin) {
+        return grid;
+    }
+    grid.insert(origin);
+
+    let ri = r as i32;
+    for i in -ri..=ri {
+        for (dx, dy) in [(i, ri), (i, -ri), (ri, i), (-ri, i)] {
+            cast_ray(map, &mut grid, origin, dx, dy, r);
+        }
+    }
+
+    light_walls(map, &mut grid, origin, r);
+    grid
+}
+
+/// Marks tiles along the Bresenham ray from `origin` toward offset `(dx, dy)`.
+fn cast_ray<M: OpacityProvider>(
+    map: &M,
+    grid: &mut VisibilityGrid,
+    origin: Coords,
+    dx: i32,
+    dy: i32,
+    r: usize,
+) {
+    let (sx, sy) = (dx.signum(), dy.signum());
+    let (ax, ay) = (dx.abs(), dy.abs());
+    let (mut x, mut y) = (0, 0);
+    let mut err = ax - ay;
+
+    while (x, y) != (dx, dy) {
+        let e2 = 2 * err;
+        if e2 > -ay {
+            err -= ay;
+            x += sx;
+        }
+        if e2 < ax {
+            err += ax;
+            y += sy;
+        }
+
+        let coords = Coords::new(origin.x + x, origin.y + y);
+        if !in_range(x, y, r) || !map.in_bounds(coords) {
+            return;
+        }
+        grid.insert(coords);
+        if map.body_opaque(coords) {
+            return;
+        }
+    }
+}
+
+/// Lights opaque tiles next to visible transparent tiles, on the side away from the origin.
+fn light_walls<M: OpacityProvider>(map: &M, grid: &mut VisibilityGrid, origin: Coords, r: usize) {
+    let ri = r as i32;
+    let mut lit = Vec::new();
+
+    for dy in -ri..=ri {
+        for dx in -ri..=ri {
+            let coords = Coords::new(origin.x + dx, origin.y + dy);
+            if !grid.contains(coords) || blocks(map, coords) {
+                continue;
+            }
+            let (sx, sy) = (dx.signum(), dy.signum());
+            for (nx, ny) in [(dx + sx, dy), (dx, dy + sy), (dx + sx, dy + sy)] {
+                let next = Coords::new(origin.x + nx, origin.y + ny);
+                let outward = (nx, ny) != (dx, dy) && in_range(nx, ny, r);
+                if outward && map.in_bounds(next) && map.body_opaque(next) {
+                    lit.push(next);
+                }
+            }
+        }
+    }
+
+    for coords in lit {
+        grid.insert(coords);
+    }
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maps::TileMap;
+
+    // Open maps see a full disk; pillars cast shadows; room walls are lit by post-processing.
+    #[test]
+    fn raycast_basics() {
+        let origin = Coords::new(10, 10);
+        let map = TileMap::new(21, 21).unwrap();
+        let grid = get_visible_grid(&map, origin, 8);
+        let disk = (0..21 * 21)
+            .map(|id| Coords::new(id % 21, id / 21))
+            .filter(|c| in_range(c.x - 10, c.y - 10, 8))
+            .count();
+        assert_eq!(grid.len(), disk);
+
+        let mut map = TileMap::new(21, 21).unwrap();
+        map.set_body_opaque(Coords::new(12, 10), true);
+        let grid = get_visible_grid(&map, origin, 8);
+        assert!(grid.contains(Coords::new(12, 10)));
+        assert!(!grid.contains(Coords::new(16, 10)));
+
+        let mut room = TileMap::new(21, 21).unwrap();
+        for i in 5..=15 {
+            for c in [(i, 5), (i, 15), (5, i), (15, i)] {
+                room.set_body_opaque(Coords::new(c.0, c.1), true);
+            }
+        }
+        let grid = get_visible_grid(&room, origin, 8);
+        assert!((5..=15).all(|i| grid.contains(Coords::new(i, 15)) && grid.contains(Coords::new(5, i))));
+        assert!(!grid.contains(Coords::new(10, 16)));
+    }
+}
@@ -1,11 +1,39 @@
 //! FOV Visualization - Rust (2D): `fov2d`
+//!
+//! Stability:
+//! - The core (`maps`, `simple`, `standard`, `drawing`, `files`, and the rest of `common`)
+//!   follows semver.
+//! - Modules still in flux are behind the `experimental` feature and may change in any
+//!   release: `compare` (ported third-party rulesets) and `cases` (shared test case schema).
+//!   New subsystems (e.g. 3D FOV, portals, GPU queries) start there too.
 
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+#[cfg(feature = "bracket-style")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bracket-style")))]
+pub mod bracket;
 pub mod common;
+#[cfg(feature = "experimental")]
+#[cfg_attr(docsrs, doc(cfg(feature = "experimental")))]
+pub mod compare;
 pub mod simple;
 pub mod standard;
 
+pub use common::analysis;
+pub use common::cache;
+#[cfg(feature = "experimental")]
+pub use common::cases;
+pub use common::computer::{self, FovComputer, FovSetAny};
+pub use common::diagram;
 pub use common::drawing;
+pub use common::error::{self, FovError};
 pub use common::files;
-pub use common::fov::{self, FovRadius, Octant, QFactor};
+pub use common::fov::{self, FovRadius, FovShape, Octant, QFactor};
+pub use common::image;
+pub use common::los;
 pub use common::math;
 pub use common::maps;
+pub use common::report::{self, Report, ReportConfig};
+pub use common::rng::{self, Rng};
+pub use common::scenes::{self, bench_scenes};
+pub use common::visibility::{self, Became, Spotted, SpottedGrid, VisibilityGrid};
@@ -1,16 +1,23 @@
 //! FOV Visualization - Rust (2D)
 //! 
 //! FOV Structure:
-//! - Field of View (FOV) data is stored in an _FOV data_ struct.
-//! - FOV data is broken up into _FOV sets_ of eight _FOV octants_.
+//! - Field of View (FOV) data is stored in _FOV sets_ of eight _FOV octants_.
 //! - Octants are comprised of _FOV nodes_.
-//! - FOV nodes are used to reference _FOV tiles_ in _FOV maps_, which are derived from _Tile maps_.
+//! - FOV nodes are used to reference tiles in _Tile maps_.
 //! - FOV nodes relate obstacles in FOV tiles (e.g. walls) to _quantized_ bits.
 //! - Quantized bits are used to determine tile visiblity.
 //! - Quantized values range from `16` to `128` depending on max _FOV radius_ (`rFOV`).
 //! - FOV calculations depend on radius and _Q-value_ (number of quantized bits).
 //! - The larger the radius and Q-value, the longer the FOV calculation will take.
 //! 
+//! Run with `list` to print every supported configuration (`fov::supported_configs`).
+//! 
+//! Run with `verify --baseline DIR [--max-regression PCT] [--update]` to check FOV results
+//! over every bench scene origin against golden checksums, and query timings against
+//! baseline timings, in `DIR`. Exits nonzero on any checksum mismatch, or on a scene more
+//! than `PCT` percent (default 20) slower than its baseline. A missing baseline, or
+//! `--update`, writes a new one instead. Build with `--release` for meaningful timings.
+//! 
 //! 
 //! FOV 
 //! 
@@ -34,6 +41,22 @@
 //! ```
 
 fn main() {
+    if std::env::args().nth(1).as_deref() == Some("list") {
+        list();
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("verify") {
+        let args: Vec<String> = std::env::args().skip(2).collect();
+        match verify(&args) {
+            Ok(true) => return,
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("verify: {e}");
+                std::process::exit(2);
+            }
+        }
+    }
+
     println!("===== FOV VISUALIZATION - RUST (2D) =====\n");
     
     use fov2d::fov::*;
@@ -53,14 +76,14 @@ fn main() {
 
     // Check Line 1 vs far tile, 8 tiles away
     let line_dp8 = Line::new(8.0, 0.0, 8.0, 1.0);
-    let isect_dp8 = lines1[0].clone().intersection(line_dp8);
+    let isect_dp8 = lines1[0].intersection(line_dp8);
     println!("Intersection: {isect_dp8:?}");
 
     // --- Node Check --- //
     use fov2d::simple::*;
 
     let fov_lines = FovLines::new(rfov, qfactor);
-    let nodes_o1 = build_fov_nodes_q16(rfov, &fov_lines, 0.5);
+    let nodes_o1 = build_fov_nodes_q16(rfov, &fov_lines, FovShape::default()).unwrap();
 
     println!("nodes O1, Q8, rFOV = 8:");
     for node in nodes_o1.iter() {
@@ -69,18 +92,173 @@ fn main() {
     println!("{} nodes: in total", nodes_o1.len());
 
     // --- Octant Check --- //
-    let nodes_q16 = build_fov_nodes_q16(rfov, &fov_lines, 0.5);
+    let nodes_q16 = build_fov_nodes_q16(rfov, &fov_lines, FovShape::default()).unwrap();
     for fov_node in nodes_q16.iter() {
         println!("{fov_node:?}");
     }
 
-    // let octant_q16 = FovOctant16::new(&nodes_q16, rfov, octant);
+    // let octant_q16 = FovOctant16::new(&nodes_q16, rfov, Octant::O1);
 
-    let fov_map_q16 = FovSet16::new(rfov, qfactor, 0.50);
-    fov_map_q16.summarize();
+    let fov_set_q16 = FovSet16::new(rfov, qfactor, FovShape::default()).unwrap();
+    fov_set_q16.summarize();
 
     // TODO: redo octant ordering with Y=0 at top of screen?
-    println!("size of FovSet16: {}", size_of_val(&fov_map_q16));
-    println!("size of FovOctant6: {}", size_of::<FovOctant16>());
+    println!("size of FovSet16: {}", size_of_val(&fov_set_q16));
+    println!("size of FovOctant16: {}", size_of::<FovOctant16>());
+
+}
+
+/// Prints every supported configuration with its memory estimate and use case.
+fn list() {
+    use fov2d::fov::supported_configs;
+
+    println!("{:<18} {:<18} {:>10}  use case", "config", "set type", "memory");
+    for config in supported_configs() {
+        let kib = config.memory_estimate() as f64 / 1024.0;
+        let (name, set_type) = (config.name(), config.set_type());
+        println!("{name:<18} {set_type:<18} {kib:>6.1} KiB  {}", config.use_case);
+    }
+}
+
+/// Golden checksums file in a verify baseline directory: `scene,x,y,checksum` rows.
+const GOLDENS_CSV: &str = "goldens.csv";
+/// Timings file in a verify baseline directory: `scene,micros` rows.
+const TIMINGS_CSV: &str = "timings.csv";
+/// Default slowdown, in percent, tolerated before `verify` reports a regression.
+const MAX_REGRESSION: f64 = 20.0;
+/// Queries timed per bench scene origin, per round.
+const ITERATIONS: u32 = 100;
+/// Timing rounds per bench scene; the fastest is kept, as the least disturbed by noise.
+const ROUNDS: u32 = 5;
+
+/// Verifies FOV results and timings over every bench scene against a baseline directory.
+///
+/// Returns `Ok(false)` if any checksum mismatches or any scene regressed.
+fn verify(args: &[String]) -> Result<bool, fov2d::FovError> {
+    use std::io::{Error, ErrorKind};
+    use std::path::PathBuf;
+    use std::time::Instant;
+
+    use fov2d::analysis::{Cell, Table};
+    use fov2d::simple::{fovcalc::get_visible_grid, FovSet16};
+    use fov2d::{bench_scenes, files::save_csv, FovRadius, FovShape, QFactor};
 
+    let invalid = |msg: String| Error::new(ErrorKind::InvalidInput, msg);
+    let (mut dir, mut max_regression, mut update) = (None, MAX_REGRESSION, false);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--baseline" => dir = args.next().map(PathBuf::from),
+            "--max-regression" => {
+                let pct = args.next().and_then(|v| v.parse::<f64>().ok());
+                max_regression = pct.ok_or_else(|| invalid("bad --max-regression".into()))?;
+            }
+            "--update" => update = true,
+            other => return Err(invalid(format!("unknown argument {other}")).into()),
+        }
+    }
+    let dir = dir.ok_or_else(|| {
+        invalid("usage: verify --baseline DIR [--max-regression PCT] [--update]".into())
+    })?;
+
+    let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default())?;
+    let mut goldens = Table::new(&["scene", "x", "y", "checksum"]);
+    let mut timings = Table::new(&["scene", "micros"]);
+
+    for scene in bench_scenes() {
+        for &origin in scene.origins.iter() {
+            let grid = get_visible_grid(&fov_set, &scene.map, origin, 16);
+            goldens.push_row(vec![
+                Cell::Text(scene.name.to_string()),
+                Cell::Int(origin.x as i64),
+                Cell::Int(origin.y as i64),
+                Cell::Text(format!("{:016x}", grid.checksum())),
+            ]);
+        }
+
+        let mut secs = f64::INFINITY;
+        for _ in 0..ROUNDS {
+            let start = Instant::now();
+            for _ in 0..ITERATIONS {
+                for &origin in scene.origins.iter() {
+                    std::hint::black_box(get_visible_grid(&fov_set, &scene.map, origin, 16));
+                }
+            }
+            secs = secs.min(start.elapsed().as_secs_f64());
+        }
+        let micros = secs * 1e6 / (ITERATIONS as f64 * scene.origins.len() as f64);
+        timings.push_row(vec![Cell::Text(scene.name.to_string()), Cell::Float(micros)]);
+    }
+
+    let (goldens_path, timings_path) = (dir.join(GOLDENS_CSV), dir.join(TIMINGS_CSV));
+    if update || !goldens_path.exists() || !timings_path.exists() {
+        std::fs::create_dir_all(&dir)?;
+        save_csv(&goldens_path, &goldens)?;
+        save_csv(&timings_path, &timings)?;
+        println!("wrote baseline to {}", dir.display());
+        return Ok(true);
+    }
+
+    // Baseline rows, keyed by every column but the last
+    let read_rows = |path: &PathBuf| -> std::io::Result<Vec<(String, String)>> {
+        let text = std::fs::read_to_string(path)?;
+        let rows = text.lines().skip(1).filter(|line| !line.is_empty());
+        let split = |line: &str| line.rsplit_once(',').map(|(k, v)| (k.into(), v.into()));
+        rows.map(|line| split(line).ok_or_else(|| invalid(format!("bad row: {line}")))).collect()
+    };
+    let row_key = |row: &Vec<Cell>| -> String {
+        let cells = row[..row.len() - 1].iter().map(|cell| match cell {
+            Cell::Int(v) => v.to_string(),
+            Cell::Float(v) => v.to_string(),
+            Cell::Text(v) => v.clone(),
+        });
+        cells.collect::<Vec<_>>().join(",")
+    };
+    let last_text = |row: &Vec<Cell>| match row.last() {
+        Some(Cell::Text(v)) => v.clone(),
+        _ => String::new(),
+    };
+    let last_float = |row: &Vec<Cell>| match row.last() {
+        Some(Cell::Float(v)) => *v,
+        _ => f64::NAN,
+    };
+
+    let mut passed = true;
+    let golden_rows = read_rows(&goldens_path)?;
+    for row in goldens.rows() {
+        let key = row_key(row);
+        let expected = golden_rows.iter().find(|(k, _)| *k == key).map(|(_, v)| v.as_str());
+        let actual = last_text(row);
+        match expected == Some(actual.as_str()) {
+            true => (),
+            false => {
+                println!("MISMATCH {key}: {actual} (baseline {})", expected.unwrap_or("none"));
+                passed = false;
+            }
+        }
+    }
+
+    let timing_rows = read_rows(&timings_path)?;
+    for row in timings.rows() {
+        let (key, micros) = (row_key(row), last_float(row));
+        let baseline = timing_rows.iter().find(|(k, _)| *k == key);
+        let Some(baseline) = baseline.and_then(|(_, v)| v.parse::<f64>().ok()) else {
+            println!("{key}: {micros:.2} us/query (no baseline)");
+            continue;
+        };
+        let change = (micros / baseline - 1.0) * 100.0;
+        let regressed = change > max_regression;
+        let status = match regressed {
+            true => "REGRESSED",
+            false => "ok",
+        };
+        println!("{key}: {micros:.2} us/query ({change:+.1}% vs {baseline:.2}) {status}");
+        passed &= !regressed;
+    }
+
+    match passed {
+        true => println!("verify passed"),
+        false => println!("verify FAILED"),
+    }
+    Ok(passed)
 }
@@ -0,0 +1,1563 @@
+//! Simple FOV calculation for FOV Visualization - Rust (2D).
+//!
+//! _Simple_ FOV determines visiblity for the tile `body` subpart only.
+//!
+//! Calculation:
+//! - Queries are generic over node bits, so they serve every tier (`FovSet8` to `FovSet128`).
+//! - Each octant is traversed outward from the origin, node by node.
+//! - A node is visible if any of its FOV bits are not yet blocked.
+//! - Visible nodes with an opaque `body` add their FOV bits to the blocked bits.
+//! - Blocked bits are kept per target height level: low walls only block shorter targets.
+//! - Opacity is read through `OpacityProvider`, so `TileMap` and `BitTileMap` both work.
+//! - An optional `VisibilityFilter` may reject visible tiles before they are recorded.
+//! - The query radius is adjusted by the `radius_modifier` of the origin tile's terrain, and
+//!   clamped to the FOV set's radius.
+//! - `FovSet::compute` is the one-call query: visible tiles at the full FOV radius.
+//! - `FovSet::compute_with` streams visible tiles to a callback, which may stop traversal early.
+//! - `get_visible_small` serves queries up to `SMALL_RADIUS` without heap allocation.
+//! - `get_visible_tiles_ordered` returns tiles by distance or angle, per `TileOrder`.
+//! - `get_visible_tiles_hinted` sizes its buffer from a caller-owned `CapacityHint`.
+//! - `peek` models leaning around a corner: an origin shifted half a tile, with a forward
+//!   180° `Cone`.
+//! - `get_visible_tiles_edges` reports which edges of each visible tile the observer sees.
+//! - `get_visible_grid_sparse` reads opacity from an `OccluderIndex`, for mostly open maps.
+//! - `get_visible_grid_clipped` limits a query to a rectangle, e.g. the screen of a huge map.
+//! - `get_coverage_mask` returns a fog-of-war texture with soft values from bit coverage.
+//! - `get_light_levels` composites ambient light and `Light` sources with FOV, so darkness
+//!   shortens how far the observer sees, except where lights reach.
+//! - The origin tile is handled per the FOV set's `OriginRules`.
+//! - Nodes are visited in the FOV set's `TraversalOrder`: storage order unless replaced
+//!   with `FovSet::set_traversal_order`.
+//! - With an LOD distance set (`FovSet::set_lod_distance`), nodes from that distance on are
+//!   evaluated per 2x2 block: all of a block's tiles are visible if any of its merged bits
+//!   are not yet blocked, and a visible block with any walled tile adds all of its merged
+//!   bits to the blocked bits. Distant shadows are coarser (and a little wider), in exchange
+//!   for fewer node tests.
+//! - `FovSet::blocked_mask` exposes an octant's blocked FOV bits after traversal.
+//! - With the `tracing` feature, queries enter a `fov_query` span (`radius`, `q`,
+//!   `tiles_visible`, and `allocations` for hinted queries) and each octant a nested
+//!   `fov_octant` span (`nodes_visited`).
+
+use std::convert::Infallible;
+use std::ops::ControlFlow;
+
+use super::{FovBits, FovOctant, FovSet};
+use crate::{
+    fov::{
+        lit_enough_sq, AcceptAll, CapacityHint, Cone, Edges, Falloff, FovShape, Light, OriginRules,
+        ShapeSpans, SmallVisible, TileOrder, VisInfo, VisibilityFilter, VisibleTile,
+        SMALL_RADIUS,
+    },
+    los::sees_tile_from,
+    maps::{ClippedMap, Coords, Direction, OccluderIndex, OpacityProvider, Rect, HEIGHT_LEVELS},
+    math::{Degrees, Delta, Point, Radians},
+    Octant, VisibilityGrid,
+};
+
+impl<B: FovBits> FovSet<B> {
+    /// Returns a `VisibilityGrid` of tiles visible from `origin`, out to the FOV set's radius.
+    ///
+    /// Each octant's node offsets are mapped to world coordinates around `origin`; nodes
+    /// off the map are skipped. Use `get_visible_grid` for a shorter radius.
+    pub fn compute<M: OpacityProvider>(&self, map: &M, origin: Coords) -> VisibilityGrid {
+        get_visible_grid(self, map, origin, self.radius().to_int() as usize)
+    }
+    /// Returns a `VisibilityGrid` of visible tiles accepted by `filter` (e.g. a vision `Cone`),
+    /// out to the FOV set's radius.
+    ///
+    /// Rejected tiles still block FOV, so a `Cone` restricts an existing FOV set to a guard's
+    /// view without rebuilding it.
+    pub fn compute_filtered<M, F>(&self, map: &M, origin: Coords, filter: &mut F) -> VisibilityGrid
+    where
+        M: OpacityProvider,
+        F: VisibilityFilter,
+    {
+        get_visible_grid_filtered(self, map, origin, self.radius().to_int() as usize, filter)
+    }
+    /// Returns a `VisibilityGrid` of visible tiles within `shape` (e.g. an `FovShape::Ellipse`)
+    /// around `origin`, out to the FOV set's radius, without rebuilding the FOV set.
+    ///
+    /// The shape is reduced to integer row spans once, so tiles are tested in integers.
+    pub fn compute_shaped<M>(&self, map: &M, origin: Coords, shape: FovShape) -> VisibilityGrid
+    where
+        M: OpacityProvider,
+    {
+        let spans = ShapeSpans::new(shape, self.radius().to_int());
+        let mut within = |coords: Coords, _: &VisInfo| {
+            spans.contains(Delta::new(coords.x - origin.x, coords.y - origin.y))
+        };
+        self.compute_filtered(map, origin, &mut within)
+    }
+    /// Streams each visible tile and its `VisInfo` to `f`, out to radius `r`.
+    ///
+    /// Each tile is reported once, in traversal order (octant by octant, outward from the
+    /// origin). Returning `ControlFlow::Break` from `f` stops traversal immediately and
+    /// returns the break value, e.g. once a quarry is spotted.
+    pub fn compute_with<M, C, F>(&self, map: &M, origin: Coords, r: usize, mut f: F) -> ControlFlow<C>
+    where
+        M: OpacityProvider,
+        F: FnMut(Coords, &VisInfo) -> ControlFlow<C>,
+    {
+        #[cfg(feature = "tracing")]
+        let span = query_span("stream", origin, r, B::BITS);
+        let mut seen = VisibilityGrid::new(map.width(), map.height());
+        let rules = self.origin_rules();
+
+        for octant in Octant::ALL {
+            traverse(self.octant(octant), rules, map, origin, r, ground, |coords, info| {
+                match seen.insert(coords) {
+                    true => f(coords, info),
+                    false => ControlFlow::Continue(()),
+                }
+            })?;
+        }
+
+        #[cfg(feature = "tracing")]
+        span.record("tiles_visible", seen.len());
+        ControlFlow::Continue(())
+    }
+    /// Returns the FOV bits of `octant` blocked for ground-level targets, out to radius `r`.
+    ///
+    /// Bit `i` is set if FOV line `i` of the octant is blocked by obstructions within `r` of
+    /// `origin`, once the whole octant has been traversed. Custom rules can start from this
+    /// mask, e.g. to let smoke block a share of the lines that remain. An opaque origin that
+    /// blocks per `OriginRules` blocks every line; an out of bounds origin blocks none.
+    pub fn blocked_mask<M: OpacityProvider>(
+        &self,
+        map: &M,
+        origin: Coords,
+        r: usize,
+        octant: Octant,
+    ) -> B {
+        let rules = self.origin_rules();
+        let visit = |_: Coords, _: &VisInfo| ControlFlow::<Infallible>::Continue(());
+        match traverse(self.octant(octant), rules, map, origin, r, ground, visit) {
+            ControlFlow::Continue(blocked) => blocked,
+            ControlFlow::Break(never) => match never {},
+        }
+    }
+}
+
+/// Returns visible tile IDs (and their constitutent subnodes) for all FOV octants.
+///
+/// Tiles on octant boundaries are shared by neighboring octants, but are only returned once.
+pub fn get_visible_tiles<B: FovBits, M: OpacityProvider>(
+    fov_set: &FovSet<B>,
+    map: &M,
+    origin: Coords,
+    r: usize,
+) -> Vec<VisibleTile> {
+    let grid = get_visible_grid(fov_set, map, origin, r);
+    let mut tiles = Vec::with_capacity(grid.len());
+    tiles.extend(grid.iter_ids().map(VisibleTile::body));
+    tiles
+}
+
+/// Returns visible tile coordinates in a stack-allocated `SmallVisible`, for small radii.
+///
+/// For the hottest tiny queries (e.g. AI checks), this skips the heap: shared octant
+/// boundary tiles are deduplicated with a bit mask rather than a `VisibilityGrid`. Tiles
+/// are returned once each, in traversal order. Returns `None` if the query radius, after
+/// the origin's terrain modifier, exceeds `SMALL_RADIUS`; use `get_visible_grid` instead.
+pub fn get_visible_small<B: FovBits, M: OpacityProvider>(
+    fov_set: &FovSet<B>,
+    map: &M,
+    origin: Coords,
+    r: usize,
+) -> Option<SmallVisible> {
+    if effective_radius(map, origin, r, max_radius(fov_set)) > SMALL_RADIUS {
+        return None;
+    }
+
+    // One bit per tile of the square around the origin: (2 * 4 + 1)^2 = 81 bits
+    let side = 2 * SMALL_RADIUS as i32 + 1;
+    let mut seen = 0u128;
+    let mut tiles = SmallVisible::new();
+
+    for octant in Octant::ALL {
+        let (fov_octant, rules) = (fov_set.octant(octant), fov_set.origin_rules());
+        let _ = traverse(fov_octant, rules, map, origin, r, ground, |coords, _| {
+            let (lx, ly) = (coords.x - origin.x, coords.y - origin.y);
+            let bit = 1u128 << ((ly + SMALL_RADIUS as i32) * side + lx + SMALL_RADIUS as i32);
+            if seen & bit == 0 {
+                seen |= bit;
+                tiles.push(coords);
+            }
+            ControlFlow::<Infallible>::Continue(())
+        });
+    }
+
+    Some(tiles)
+}
+
+/// Returns visible tiles for all FOV octants, in the given `order`.
+///
+/// Ordered output suits animations (e.g. a reveal spiralling outward) and target selection.
+/// `TileOrder::Unspecified` skips sorting and returns tiles in traversal order.
+pub fn get_visible_tiles_ordered<B: FovBits, M: OpacityProvider>(
+    fov_set: &FovSet<B>,
+    map: &M,
+    origin: Coords,
+    r: usize,
+    order: TileOrder,
+) -> Vec<VisibleTile> {
+    get_visible_tiles_hinted(fov_set, map, origin, r, order, &mut CapacityHint::new())
+}
+
+/// Returns visible tiles in the given `order`, like `get_visible_tiles_ordered`, with the
+/// working buffer sized by `hint`.
+///
+/// The result size is recorded in `hint`, so callers that run many similar queries (e.g.
+/// one per monster per turn) stop paying for buffer growth after the first few.
+pub fn get_visible_tiles_hinted<B: FovBits, M: OpacityProvider>(
+    fov_set: &FovSet<B>,
+    map: &M,
+    origin: Coords,
+    r: usize,
+    order: TileOrder,
+    hint: &mut CapacityHint,
+) -> Vec<VisibleTile> {
+    #[cfg(feature = "tracing")]
+    let span = query_span("tiles", origin, r, B::BITS);
+    let mut tiles = Vec::with_capacity(hint.capacity());
+    // The `seen` grid of `compute_with`, the hinted buffer, each growth of it, the result.
+    // `Vec::with_capacity(0)` does not allocate.
+    #[cfg(feature = "tracing")]
+    let mut allocations = 1 + usize::from(tiles.capacity() > 0);
+
+    let _ = fov_set.compute_with(map, origin, r, |coords, _| {
+        if let Some(id) = map.tile_id(coords) {
+            #[cfg(feature = "tracing")]
+            if tiles.len() == tiles.capacity() {
+                allocations += 1;
+            }
+            tiles.push((Delta::new(coords.x - origin.x, coords.y - origin.y), id));
+        }
+        ControlFlow::<()>::Continue(())
+    });
+    hint.record(tiles.len());
+
+    if order != TileOrder::Unspecified {
+        tiles.sort_unstable_by(|a, b| order.compare(a.0, b.0));
+    }
+
+    #[cfg(feature = "tracing")]
+    {
+        let allocations = allocations + usize::from(!tiles.is_empty());
+        span.record("tiles_visible", tiles.len());
+        span.record("allocations", allocations);
+    }
+    let mut result = Vec::with_capacity(tiles.len());
+    result.extend(tiles.into_iter().map(|(_, id)| VisibleTile::body(id)));
+    result
+}
+
+/// Returns visible tiles for all FOV octants, with brightness set by `falloff`.
+///
+/// Brightness is computed from node distance during traversal, so renderers get
+/// "torch-lit" gradients without a second pass. Tiles are returned in tile ID order.
+pub fn get_visible_tiles_lit<B: FovBits, M: OpacityProvider>(
+    fov_set: &FovSet<B>,
+    map: &M,
+    origin: Coords,
+    r: usize,
+    falloff: &Falloff,
+) -> Vec<VisibleTile> {
+    let mut tiles = Vec::new();
+    let radius = effective_radius(map, origin, r, max_radius(fov_set)) as f64;
+
+    let _ = fov_set.compute_with(map, origin, r, |coords, info| {
+        if let Some(id) = map.tile_id(coords) {
+            let brightness = falloff.brightness((info.dist_sq as f64).sqrt(), radius);
+            tiles.push(VisibleTile { brightness, ..VisibleTile::body(id) });
+        }
+        ControlFlow::<()>::Continue(())
+    });
+
+    tiles.sort_by_key(|t| t.id);
+    tiles
+}
+
+/// Returns the light level (`0.0` to `1.0`) of each tile as seen from `origin`, in tile ID order.
+///
+/// A tile's light is the `ambient` level (e.g. `1.0` at noon, `0.2` at night) plus the
+/// falloff brightness of each of `lights` that can see it, clamped to `1.0`. Tiles outside
+/// the observer's FOV, or too dark to make out at their distance (see `fov::lit_enough_sq`),
+/// are `0.0`: in darkness, the FOV radius effectively shrinks to the lit tiles.
+///
+/// Which tiles are made out is decided in integers; the levels themselves are floats.
+pub fn get_light_levels<B: FovBits, M: OpacityProvider>(
+    fov_set: &FovSet<B>,
+    map: &M,
+    origin: Coords,
+    r: usize,
+    ambient: f64,
+    lights: &[Light],
+) -> Vec<f64> {
+    let mut light = vec![ambient.clamp(0.0, 1.0); map.width() * map.height()];
+    for source in lights {
+        let max_r = max_radius(fov_set);
+        let radius = effective_radius(map, source.origin, source.radius, max_r) as f64;
+        let _ = fov_set.compute_with(map, source.origin, source.radius, |coords, info| {
+            if let Some(id) = map.tile_id(coords) {
+                let brightness = source.falloff.brightness((info.dist_sq as f64).sqrt(), radius);
+                light[id] = (light[id] + brightness).min(1.0);
+            }
+            ControlFlow::<()>::Continue(())
+        });
+    }
+
+    let mut levels = vec![0.0; light.len()];
+    let radius = effective_radius(map, origin, r, max_radius(fov_set)) as u32;
+    let _ = fov_set.compute_with(map, origin, r, |coords, info| {
+        if let Some(id) = map.tile_id(coords) {
+            if lit_enough_sq(light[id], info.dist_sq, radius) {
+                levels[id] = light[id];
+            }
+        }
+        ControlFlow::<()>::Continue(())
+    });
+
+    levels
+}
+
+/// Returns a grayscale fog-of-war mask with soft values from FOV bit coverage.
+///
+/// One byte per tile in tile ID order, like `VisibilityGrid::to_image_mask`: hidden tiles
+/// are `0`, and visible tiles scale from `1` (one unblocked bit) to `255` (fully
+/// unblocked). Tiles on octant boundaries take their better coverage. Integer-only.
+pub fn get_coverage_mask<B: FovBits, M: OpacityProvider>(
+    fov_set: &FovSet<B>,
+    map: &M,
+    origin: Coords,
+    r: usize,
+) -> Vec<u8> {
+    let mut mask = vec![0u8; map.width() * map.height()];
+
+    for octant in Octant::ALL {
+        let (fov_octant, rules) = (fov_set.octant(octant), fov_set.origin_rules());
+        let _ = traverse(fov_octant, rules, map, origin, r, ground, |coords, info| {
+            if let Some(id) = map.tile_id(coords) {
+                let level = (info.unblocked_bits * 255).div_ceil(info.total_bits) as u8;
+                mask[id] = mask[id].max(level);
+            }
+            ControlFlow::<()>::Continue(())
+        });
+    }
+
+    mask
+}
+
+/// Returns visible tiles for all FOV octants, with the `edges` the observer sees.
+///
+/// An edge is seen if it faces the observer (see `Edges::facing`) and is exposed: the tile
+/// across the edge has a transparent body. Renderers of 2.5D scenes draw facades for the
+/// seen edges of opaque tiles. _Simple_ FOV tracks the tile `body` only, so a seen edge is
+/// not checked against `wall_n`/`wall_w` obstructions. Tiles are returned in tile ID order.
+pub fn get_visible_tiles_edges<B: FovBits, M: OpacityProvider>(
+    fov_set: &FovSet<B>,
+    map: &M,
+    origin: Coords,
+    r: usize,
+) -> Vec<VisibleTile> {
+    let grid = get_visible_grid(fov_set, map, origin, r);
+
+    grid.iter()
+        .map(|c| {
+            let id = map.tile_id(c).unwrap_or_default();
+            VisibleTile { edges: seen_edges(map, origin, c), ..VisibleTile::body(id) }
+        })
+        .collect()
+}
+
+/// Returns the edges of the tile at `c` that face `origin` and are exposed.
+fn seen_edges<M: OpacityProvider>(map: &M, origin: Coords, c: Coords) -> Edges {
+    let facing = Edges::facing(Delta::new(c.x - origin.x, c.y - origin.y));
+    let open = |x, y| !map.body_opaque(Coords::new(x, y));
+    Edges {
+        north: facing.north && open(c.x, c.y + 1),
+        east: facing.east && open(c.x + 1, c.y),
+        south: facing.south && open(c.x, c.y - 1),
+        west: facing.west && open(c.x - 1, c.y),
+    }
+}
+
+/// Returns tiles visible while leaning from `origin` toward `direction`, all tagged `peeked`.
+///
+/// The observer shifts half a tile, to the middle of the edge `origin` shares with its
+/// neighbor in `direction`, and the view is restricted to a 180° `Cone` at `origin` facing
+/// `direction`: tiles level with or ahead of `origin`. FOV nodes are built for tile-centered
+/// origins, so candidates are the tiles in the FOV of `origin` or of the neighbor, each kept
+/// if `los::sees_tile_from` the shifted point. If the neighbor is opaque or out of bounds
+/// there is no room to lean, and the view is from `origin`. `edges` are those facing the
+/// neighbor (see `get_visible_tiles_edges`); no falloff is applied, so `brightness` is
+/// `1.0`. Tiles are returned in tile ID order.
+pub fn peek<B: FovBits, M: OpacityProvider>(
+    fov_set: &FovSet<B>,
+    map: &M,
+    origin: Coords,
+    direction: Direction,
+    r: usize,
+) -> Vec<VisibleTile> {
+    let mut forward = Cone::new(origin, Radians::of(direction.delta()), Degrees(180.0));
+    let mut grid = get_visible_grid_filtered(fov_set, map, origin, r, &mut forward);
+    let lean = origin.step(direction);
+    let from = match map.in_bounds(lean) && !map.body_opaque(lean) {
+        true => lean,
+        false => origin,
+    };
+
+    if from == lean {
+        grid.union_with(&get_visible_grid_filtered(fov_set, map, lean, r, &mut forward));
+        let delta = direction.delta();
+        let eye = Point::new(
+            origin.x as f64 + 0.5 + delta.dx as f64 / 2.0,
+            origin.y as f64 + 0.5 + delta.dy as f64 / 2.0,
+        );
+        let hidden: Vec<Coords> = grid.iter().filter(|&c| !sees_tile_from(map, eye, c)).collect();
+        hidden.into_iter().for_each(|c| grid.remove(c));
+    }
+
+    grid.iter()
+        .map(|c| {
+            let (id, edges) = (map.tile_id(c).unwrap_or_default(), seen_edges(map, from, c));
+            VisibleTile { edges, peeked: true, ..VisibleTile::body(id) }
+        })
+        .collect()
+}
+
+/// Returns a `VisibilityGrid` of visible tiles for all FOV octants.
+pub fn get_visible_grid<B: FovBits, M: OpacityProvider>(
+    fov_set: &FovSet<B>,
+    map: &M,
+    origin: Coords,
+    r: usize,
+) -> VisibilityGrid {
+    get_visible_grid_filtered(fov_set, map, origin, r, &mut AcceptAll)
+}
+
+/// Returns a `VisibilityGrid` of visible tiles accepted by `filter`, for all FOV octants.
+pub fn get_visible_grid_filtered<B, M, F>(
+    fov_set: &FovSet<B>,
+    map: &M,
+    origin: Coords,
+    r: usize,
+    filter: &mut F,
+) -> VisibilityGrid
+where
+    B: FovBits,
+    M: OpacityProvider,
+    F: VisibilityFilter,
+{
+    #[cfg(feature = "tracing")]
+    let span = query_span("grid", origin, r, B::BITS);
+    let mut grid = VisibilityGrid::for_map(map);
+
+    for octant in Octant::ALL {
+        let (fov_octant, rules) = (fov_set.octant(octant), fov_set.origin_rules());
+        let _ = traverse(fov_octant, rules, map, origin, r, ground, |coords, info| {
+            if filter.accept(coords, info) {
+                grid.insert(coords);
+            }
+            ControlFlow::<()>::Continue(())
+        });
+    }
+
+    #[cfg(feature = "tracing")]
+    span.record("tiles_visible", grid.len());
+    grid
+}
+
+/// Returns a `VisibilityGrid` of visible tiles for all FOV octants, reading opacity from
+/// `occluders` instead of from each node's tile.
+///
+/// For mostly open maps: each octant's blocked bits come from the few occluder nodes in
+/// range, and every other node is tested against them with bit operations alone, without
+/// touching the map. Results match `get_visible_grid` for ground-level targets; LOD distances
+/// are ignored, so they are exact. Panics if `occluders` is stale for `map`.
+pub fn get_visible_grid_sparse<B: FovBits, M: OpacityProvider>(
+    fov_set: &FovSet<B>,
+    map: &M,
+    occluders: &OccluderIndex,
+    origin: Coords,
+    r: usize,
+) -> VisibilityGrid {
+    assert!(!occluders.is_stale(map), "occluder index is stale for this map!");
+    #[cfg(feature = "tracing")]
+    let span = query_span("sparse", origin, r, B::BITS);
+    let mut grid = VisibilityGrid::for_map(map);
+    if !map.in_bounds(origin) {
+        return grid;
+    }
+
+    let rules = fov_set.origin_rules();
+    if rules.visible {
+        grid.insert(origin);
+    }
+    if rules.opaque_blocks && map.body_opaque(origin) {
+        return grid;
+    }
+
+    let r = effective_radius(map, origin, r, max_radius(fov_set));
+    let (ri, side) = (r as i32, 2 * r + 1);
+    let near = Rect::new(origin.x.saturating_sub(ri), origin.y.saturating_sub(ri), side, side);
+    let near: Vec<Delta> = occluders
+        .within(near)
+        .filter(|&c| c != origin)
+        .map(|c| Delta::new(c.x - origin.x, c.y - origin.y))
+        .collect();
+
+    for octant in Octant::ALL {
+        let fov_octant = fov_set.octant(octant);
+        // Shapes may cull the outer band, leaving the octant short of the set's radius
+        let max_ix = fov_octant.max_node_index(r.min(fov_octant.radius()));
+        // Storage order visits every node after its blockers, like `DefaultOrder`
+        let mut walls: Vec<usize> = near
+            .iter()
+            .filter_map(|&d| octant.delta_to_dpds(d))
+            .filter_map(|(dp, ds)| fov_octant.node_index(dp, ds))
+            .filter(|&ix| ix <= max_ix)
+            .collect();
+        walls.sort_unstable();
+        let mut walls = walls.into_iter().peekable();
+        let mut blocked = B::ZERO;
+
+        for ix in 1..=max_ix {
+            let is_wall = walls.next_if_eq(&ix).is_some();
+            let body = fov_octant.node(ix).body;
+            if body & !blocked == B::ZERO {
+                continue;
+            }
+            // Out of bounds nodes are never walls, and the grid ignores them
+            grid.insert(fov_octant.world_coords_of(ix, origin));
+            if is_wall {
+                blocked |= body;
+                if blocked == B::MAX {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    span.record("tiles_visible", grid.len());
+    grid
+}
+
+/// Returns a `VisibilityGrid` of visible tiles within `clip`, for all FOV octants.
+///
+/// If `clip` holds `origin`, nodes outside `clip` are skipped like nodes past the map edge.
+/// This is exact: rectangles are convex, so no sight line from `origin` to a tile in `clip`
+/// leaves it. Otherwise an off-screen blocker may matter, so the full query is run and
+/// then cut to `clip`.
+pub fn get_visible_grid_clipped<B: FovBits, M: OpacityProvider>(
+    fov_set: &FovSet<B>,
+    map: &M,
+    origin: Coords,
+    r: usize,
+    clip: Rect,
+) -> VisibilityGrid {
+    if clip.contains(origin) {
+        return get_visible_grid(fov_set, &ClippedMap::new(map, clip), origin, r);
+    }
+
+    let mut grid = VisibilityGrid::for_map(map);
+    for coords in get_visible_grid(fov_set, map, origin, r).iter() {
+        if clip.contains(coords) {
+            grid.insert(coords);
+        }
+    }
+    grid
+}
+
+/// Returns a `VisibilityGrid` of tiles where a target of height `target_level(coords)` is seen.
+///
+/// Partial walls (`OpacityProvider::wall_height`) block targets shorter than the wall, so a
+/// tall target behind a low wall is visible while a ground-level one is not. Levels are
+/// clamped to `0..HEIGHT_LEVELS`; the observer's eye is above all partial walls.
+pub fn get_visible_grid_heights<B, M, T>(
+    fov_set: &FovSet<B>,
+    map: &M,
+    origin: Coords,
+    r: usize,
+    target_level: T,
+) -> VisibilityGrid
+where
+    B: FovBits,
+    M: OpacityProvider,
+    T: Fn(Coords) -> usize,
+{
+    #[cfg(feature = "tracing")]
+    let span = query_span("heights", origin, r, B::BITS);
+    let mut grid = VisibilityGrid::for_map(map);
+
+    for octant in Octant::ALL {
+        let (fov_octant, rules) = (fov_set.octant(octant), fov_set.origin_rules());
+        let _ = traverse(fov_octant, rules, map, origin, r, &target_level, |coords, _| {
+            grid.insert(coords);
+            ControlFlow::<()>::Continue(())
+        });
+    }
+
+    #[cfg(feature = "tracing")]
+    span.record("tiles_visible", grid.len());
+    grid
+}
+
+/// Returns visible tile IDs (and their constitutent subnodes) in a given FOV octant.
+pub fn fov_calc<B: FovBits, M: OpacityProvider>(
+    fov_octant: &FovOctant<B>,
+    map: &M,
+    origin: Coords,
+    r: usize,
+) -> Vec<VisibleTile> {
+    let mut tiles = Vec::new();
+
+    let rules = OriginRules::default();
+    let _ = traverse(fov_octant, rules, map, origin, r, ground, |coords, _| {
+        if let Some(id) = map.tile_id(coords) {
+            tiles.push(VisibleTile::body(id));
+        }
+        ControlFlow::<()>::Continue(())
+    });
+
+    tiles
+}
+
+/// Returns query radius `r` adjusted by the origin's terrain modifier, clamped to `0..=max_r`
+/// (the FOV set's radius).
+///
+/// Huge `r` (e.g. `usize::MAX` for "full radius") saturates rather than wrapping.
+pub fn effective_radius<M>(map: &M, origin: Coords, r: usize, max_r: usize) -> usize
+where
+    M: OpacityProvider,
+{
+    let r = i64::try_from(r).unwrap_or(i64::MAX);
+    r.saturating_add(map.radius_modifier(origin) as i64).clamp(0, max_r as i64) as usize
+}
+
+/// Returns the radius of `fov_set`, the most `effective_radius` allows its queries.
+fn max_radius<B: FovBits>(fov_set: &FovSet<B>) -> usize {
+    fov_set.radius().to_int() as usize
+}
+
+/// Target height level for ground-level targets on every tile.
+fn ground(_: Coords) -> usize {
+    0
+}
+
+/// Traverses FOV octant nodes out to radius `r`, calling `visit` on each visible tile.
+///
+/// The origin node is visited and blocks per `origin_rules`. Out of bounds nodes are skipped.
+/// Radius `r` is adjusted by the origin's terrain modifier, and clamped to the octant's radius.
+/// Tiles are tested against the blocked bits for their `target_level`. Nodes from the
+/// octant's LOD distance on are traversed as blocks, in storage order.
+/// Traversal stops early if `visit` returns `ControlFlow::Break`; otherwise the bits blocked
+/// for ground-level targets are returned.
+fn traverse<B, M, T, C, F>(
+    fov_octant: &FovOctant<B>,
+    origin_rules: OriginRules,
+    map: &M,
+    origin: Coords,
+    r: usize,
+    target_level: T,
+    mut visit: F,
+) -> ControlFlow<C, B>
+where
+    B: FovBits,
+    M: OpacityProvider,
+    T: Fn(Coords) -> usize,
+    F: FnMut(Coords, &VisInfo) -> ControlFlow<C>,
+{
+    if !map.in_bounds(origin) {
+        return ControlFlow::Continue(B::ZERO);
+    }
+
+    #[cfg(feature = "tracing")]
+    let mut count = NodeCount::enter(fov_octant.octant());
+    let r = effective_radius(map, origin, r, fov_octant.radius());
+    let lod = fov_octant.lod_distance().filter(|&d| d <= r);
+    // Bits blocked for targets at each height level; lower levels are blocked by more walls
+    let mut blocked = [B::ZERO; HEIGHT_LEVELS];
+
+    for ix in fov_octant.traversal(lod.map_or(r, |d| d - 1)) {
+        let node = fov_octant.node(ix);
+        let coords = fov_octant.world_coords_of(ix, origin);
+        #[cfg(feature = "tracing")]
+        {
+            count.visited += 1;
+        }
+
+        if !map.in_bounds(coords) {
+            continue;
+        }
+        let level = target_level(coords).min(HEIGHT_LEVELS - 1);
+        let unblocked = node.body & !blocked[level];
+        if unblocked == B::ZERO {
+            continue;
+        }
+
+        let (dp, ds) = (node.dpri as u32, node.dsec as u32);
+        let info = VisInfo {
+            unblocked_bits: unblocked.count_ones(),
+            total_bits: node.body.count_ones(),
+            dist_sq: dp * dp + ds * ds,
+        };
+        if node.dpri == 0 {
+            if origin_rules.visible {
+                visit(coords, &info)?;
+            }
+            match origin_rules.opaque_blocks && map.body_opaque(coords) {
+                true => return ControlFlow::Continue(node.body),
+                false => continue,
+            }
+        }
+        visit(coords, &info)?;
+
+        let wall = (map.wall_height(coords) as usize).min(HEIGHT_LEVELS);
+        if wall > 0 {
+            blocked[..wall].iter_mut().for_each(|b| *b |= node.body);
+            if blocked[HEIGHT_LEVELS - 1] == B::MAX {
+                return ControlFlow::Continue(B::MAX);
+            }
+        }
+    }
+
+    if lod.is_none() {
+        return ControlFlow::Continue(blocked[0]);
+    }
+
+    for block in fov_octant.blocks() {
+        if block.dpri as usize > r {
+            break;
+        }
+        #[cfg(feature = "tracing")]
+        {
+            count.visited += 1;
+        }
+
+        let mut wall = 0;
+        for (dpri, dsec) in block.tiles().filter(|&(dpri, _)| dpri as usize <= r) {
+            let delta = fov_octant.octant().dpds_to_delta(dpri as u16, dsec as u16);
+            let coords = origin.offset(delta);
+            if !map.in_bounds(coords) {
+                continue;
+            }
+            let level = target_level(coords).min(HEIGHT_LEVELS - 1);
+            let unblocked = block.body & !blocked[level];
+            if unblocked == B::ZERO {
+                continue;
+            }
+
+            let (dp, ds) = (dpri as u32, dsec as u32);
+            let info = VisInfo {
+                unblocked_bits: unblocked.count_ones(),
+                total_bits: block.body.count_ones(),
+                dist_sq: dp * dp + ds * ds,
+            };
+            visit(coords, &info)?;
+            wall = wall.max((map.wall_height(coords) as usize).min(HEIGHT_LEVELS));
+        }
+
+        if wall > 0 {
+            blocked[..wall].iter_mut().for_each(|b| *b |= block.body);
+            if blocked[HEIGHT_LEVELS - 1] == B::MAX {
+                return ControlFlow::Continue(B::MAX);
+            }
+        }
+    }
+
+    ControlFlow::Continue(blocked[0])
+}
+
+/// Enters a `fov_query` span for a query of `kind` from `origin` out to radius `r`, on nodes
+/// with Q-value `q`.
+#[cfg(feature = "tracing")]
+fn query_span(kind: &'static str, origin: Coords, r: usize, q: u32) -> tracing::span::EnteredSpan {
+    let (x, y) = (origin.x, origin.y);
+    let (tiles_visible, allocations) = (tracing::field::Empty, tracing::field::Empty);
+    let radius = r;
+    tracing::debug_span!("fov_query", kind, x, y, radius, q, tiles_visible, allocations).entered()
+}
+
+/// Entered `fov_octant` span that records `nodes_visited` when traversal ends, however it ends.
+#[cfg(feature = "tracing")]
+struct NodeCount {
+    span: tracing::span::EnteredSpan,
+    visited: usize,
+}
+
+#[cfg(feature = "tracing")]
+impl NodeCount {
+    fn enter(octant: Octant) -> Self {
+        let nodes_visited = tracing::field::Empty;
+        let span = tracing::trace_span!("fov_octant", ?octant, nodes_visited).entered();
+        Self { span, visited: 0 }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for NodeCount {
+    fn drop(&mut self) {
+        self.span.record("nodes_visited", self.visited);
+    }
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fov::{is_valid_order, AngleSweep, DefaultOrder, MinBits, NearestFirst, TraversalOrder},
+        maps::{BitTileMap, TileMap},
+        simple::{FovSet128, FovSet16, FovSet32, FovSet64, FovSet8},
+        FovRadius, QFactor,
+    };
+
+    // Simple FOV sanity check:
+    // - Tiles in front of a pillar are visible, tiles directly behind it are not.
+    // - `TileMap` and `BitTileMap` produce identical results.
+    #[test]
+    fn visible_tiles_pillar() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(16, 16);
+        let mut map = TileMap::new(33, 33).unwrap();
+        map.set_body_opaque(Coords::new(20, 16), true);
+        let bit_map = BitTileMap::from(&map);
+
+        let grid = get_visible_grid(&fov_set, &map, origin, 16);
+        let bit_grid = get_visible_grid(&fov_set, &bit_map, origin, 16);
+
+        assert_eq!(grid, bit_grid);
+        assert!(grid.contains(origin));
+        assert!(grid.contains(Coords::new(19, 16)));
+        assert!(grid.contains(Coords::new(20, 16)));
+        assert!(!grid.contains(Coords::new(21, 16)));
+        assert!(!grid.contains(Coords::new(30, 16)));
+        assert!(grid.contains(Coords::new(12, 16)));
+
+        let tiles = get_visible_tiles(&fov_set, &map, origin, 16);
+        assert_eq!(tiles.len(), grid.len());
+        assert_eq!(fov_set.compute(&map, origin), grid);
+
+        // Near a corner, nodes off the map are clipped
+        let corner = fov_set.compute(&map, Coords::new(0, 0));
+        assert!(corner.contains(Coords::new(16, 0)) && corner.contains(Coords::new(0, 16)));
+        assert_eq!(corner.len(), get_visible_grid(&fov_set, &map, Coords::new(0, 0), 16).len());
+    }
+
+    // Per-octant queries return correct tile IDs within their own octant, and together
+    // cover exactly the tiles of the full query.
+    #[test]
+    fn fov_calc_octants() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let map = TileMap::random(33, 33, 0.15, 21).unwrap();
+        let origin = Coords::new(16, 16);
+        let mut union = VisibilityGrid::for_map(&map);
+
+        for octant in Octant::ALL {
+            let tiles = fov_calc(fov_set.octant(octant), &map, origin, 12);
+            assert_eq!(tiles[0].id, map.tile_id(origin).unwrap());
+            for tile in tiles.iter() {
+                let coords = Coords::from_index(tile.id, 33).unwrap();
+                let d = Delta::new(coords.x - origin.x, coords.y - origin.y);
+                let (dp, _) = octant.delta_to_dpds(d).unwrap();
+                assert!(dp <= 12);
+                union.insert_id(tile.id);
+            }
+        }
+
+        let tiles = get_visible_tiles(&fov_set, &map, origin, 12);
+        let ids: Vec<_> = tiles.iter().map(|t| t.id).collect();
+        assert_eq!(union.iter_ids().collect::<Vec<_>>(), ids);
+    }
+
+    // Filters hide tiles from results, but rejected opaque tiles still block.
+    #[test]
+    fn visible_tiles_filtered() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(16, 16);
+        let pillar = Coords::new(20, 16);
+        let mut map = TileMap::new(33, 33).unwrap();
+        map.set_body_opaque(pillar, true);
+
+        let mut near = |_: Coords, info: &VisInfo| info.dist_sq <= 64;
+        let grid = get_visible_grid_filtered(&fov_set, &map, origin, 16, &mut near);
+        assert!(grid.contains(Coords::new(16, 24)));
+        assert!(!grid.contains(Coords::new(16, 25)));
+
+        let mut hide_pillar = |coords: Coords, _: &VisInfo| coords != pillar;
+        let grid = get_visible_grid_filtered(&fov_set, &map, origin, 16, &mut hide_pillar);
+        assert!(!grid.contains(pillar));
+        assert!(!grid.contains(Coords::new(21, 16)));
+
+        let all = get_visible_grid(&fov_set, &map, origin, 16);
+        let partial = get_visible_grid_filtered(&fov_set, &map, origin, 16, &mut MinBits(2));
+        assert!(partial.len() < all.len());
+        let mut hide_pillar = |coords: Coords, _: &VisInfo| coords != pillar;
+        assert_eq!(fov_set.compute_filtered(&map, origin, &mut hide_pillar), grid);
+    }
+
+    // Streaming reports each visible tile once, and stops at the first `Break`.
+    #[test]
+    fn compute_with_streaming() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(16, 16);
+        let map = TileMap::random(33, 33, 0.1, 7).unwrap();
+        let grid = get_visible_grid(&fov_set, &map, origin, 16);
+
+        let mut streamed = VisibilityGrid::for_map(&map);
+        let mut count = 0;
+        let flow = fov_set.compute_with(&map, origin, 16, |coords, _| {
+            streamed.insert(coords);
+            count += 1;
+            ControlFlow::<()>::Continue(())
+        });
+        assert_eq!(flow, ControlFlow::Continue(()));
+        assert_eq!(streamed, grid);
+        assert_eq!(count, grid.len());
+
+        let mut visited = 0;
+        let flow = fov_set.compute_with(&map, origin, 16, |coords, info| {
+            visited += 1;
+            match coords == origin {
+                true => ControlFlow::Break(info.dist_sq),
+                false => ControlFlow::Continue(()),
+            }
+        });
+        assert_eq!(flow, ControlFlow::Break(0));
+        assert_eq!(visited, 1);
+    }
+
+    // Ordered output holds the same tiles as unordered output, starting at the origin.
+    #[test]
+    fn visible_tiles_ordered() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(16, 16);
+        let map = TileMap::random(33, 33, 0.1, 7).unwrap();
+        let coords = |t: &VisibleTile| Coords::new((t.id % 33) as i32, (t.id / 33) as i32);
+        let dist_sq = |c: Coords| (c.x - origin.x).pow(2) + (c.y - origin.y).pow(2);
+        let sorted_ids = |tiles: &[VisibleTile]| {
+            let mut ids: Vec<_> = tiles.iter().map(|t| t.id).collect();
+            ids.sort_unstable();
+            ids
+        };
+
+        let expected = get_visible_tiles(&fov_set, &map, origin, 16);
+        let expected: Vec<_> = expected.iter().map(|t| t.id).collect();
+
+        for order in [TileOrder::Unspecified, TileOrder::ByDistance, TileOrder::ByAngle] {
+            let tiles = get_visible_tiles_ordered(&fov_set, &map, origin, 16, order);
+            assert_eq!(sorted_ids(&tiles), expected);
+            assert_eq!(coords(&tiles[0]), origin);
+        }
+
+        let tiles = get_visible_tiles_ordered(&fov_set, &map, origin, 16, TileOrder::ByDistance);
+        assert!(tiles.windows(2).all(|w| dist_sq(coords(&w[0])) <= dist_sq(coords(&w[1]))));
+    }
+
+    // Dihedral symmetry: rotating or reflecting the map about the origin rotates or reflects
+    // the visible tiles the same way. Catches sign errors in the octant transforms.
+    #[test]
+    fn visible_tiles_octant_symmetry() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(16, 16);
+        let map = TileMap::random(33, 33, 0.15, 99).unwrap();
+        let grid = get_visible_grid(&fov_set, &map, origin, 16);
+
+        // All 8 symmetries of the square: optional swap of axes, then optional negation of each
+        for (swap, sx, sy) in (0..8).map(|i| (i & 4 != 0, 1 - 2 * (i & 1), 1 - (i & 2))) {
+            let transform = |c: Coords| {
+                let (dx, dy) = (c.x - origin.x, c.y - origin.y);
+                let (dx, dy) = if swap { (dy, dx) } else { (dx, dy) };
+                Coords::new(origin.x + sx * dx, origin.y + sy * dy)
+            };
+
+            let mut moved = TileMap::new(33, 33).unwrap();
+            for y in 0..33 {
+                for x in 0..33 {
+                    let c = Coords::new(x, y);
+                    moved.set_body_opaque(transform(c), map.get(c).unwrap().body_opaque);
+                }
+            }
+
+            let mut expected = VisibilityGrid::for_map(&moved);
+            grid.iter().for_each(|c| _ = expected.insert(transform(c)));
+            let actual = get_visible_grid(&fov_set, &moved, origin, 16);
+            assert_eq!(actual, expected, "swap: {swap}, sx: {sx}, sy: {sy}");
+        }
+    }
+
+    // Leaning past a corner reveals tiles hidden from the standing position, never behind.
+    //
+    // Observer at (16, 10) hugs a wall at x = 17 (y <= 11); the area north-east is open.
+    #[test]
+    fn peek_around_corner() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(16, 10);
+        let mut map = TileMap::new(33, 33).unwrap();
+        for y in 0..=11 {
+            map.set_body_opaque(Coords::new(17, y), true);
+        }
+
+        let standing = get_visible_grid(&fov_set, &map, origin, 16);
+        let peeked = peek(&fov_set, &map, origin, Direction::North, 16);
+        let coords = |t: &VisibleTile| Coords::new((t.id % 33) as i32, (t.id / 33) as i32);
+
+        assert!(peeked.iter().all(|t| t.peeked && coords(t).y >= origin.y));
+        assert!(peeked.iter().any(|t| !standing.contains(coords(t))));
+        assert!(peeked.iter().any(|t| coords(t) == Coords::new(19, 16)));
+        assert!(!standing.contains(Coords::new(19, 16)));
+
+        // Half a tile out, the view past the corner is narrower than from the next tile over
+        let stepped = get_visible_grid(&fov_set, &map, Coords::new(16, 11), 16);
+        assert!(stepped.contains(Coords::new(20, 16)));
+        assert!(peeked.iter().all(|t| coords(t) != Coords::new(20, 16)));
+        let edges = get_visible_tiles_edges(&fov_set, &map, Coords::new(16, 11), 16);
+        assert!(peeked.iter().all(|t| edges.iter().any(|e| e.id == t.id && e.edges == t.edges)));
+
+        // No room to lean into the wall: the forward cone is taken from the origin
+        let blocked = peek(&fov_set, &map, origin, Direction::East, 16);
+        assert!(blocked.iter().all(|t| coords(t).x >= origin.x && standing.contains(coords(t))));
+    }
+
+    // Low walls hide ground-level targets, but not targets taller than the wall.
+    #[test]
+    fn visible_tiles_wall_heights() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(16, 16);
+        let behind = Coords::new(24, 16);
+        let mut map = TileMap::new(33, 33).unwrap();
+        for y in 8..=24 {
+            map.get_mut(Coords::new(20, y)).unwrap().wall_height = 2;
+        }
+
+        let ground = get_visible_grid(&fov_set, &map, origin, 16);
+        let short = get_visible_grid_heights(&fov_set, &map, origin, 16, |_| 1);
+        let tall = get_visible_grid_heights(&fov_set, &map, origin, 16, |_| 2);
+        let only_behind = get_visible_grid_heights(&fov_set, &map, origin, 16, |c| {
+            if c == behind { 3 } else { 0 }
+        });
+
+        assert_eq!(get_visible_grid_heights(&fov_set, &map, origin, 16, |_| 0), ground);
+        assert!(ground.contains(Coords::new(20, 16)));
+        assert!(!ground.contains(behind) && !short.contains(behind));
+        assert!(tall.contains(behind) && only_behind.contains(behind));
+        assert!(!only_behind.contains(Coords::new(23, 16)));
+        let open = get_visible_grid(&fov_set, &TileMap::new(33, 33).unwrap(), origin, 16);
+        assert_eq!(tall, open);
+    }
+
+    // A wall segment shows only its observer-facing, exposed edges.
+    //
+    // Wall tiles at (20, 15..=17), observer at (16, 16): west faces are seen. The end tiles'
+    // observer-facing faces (north of (20, 15), south of (20, 17)) are flush against the wall.
+    #[test]
+    fn visible_tiles_edges() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(16, 16);
+        let mut map = TileMap::new(33, 33).unwrap();
+        for y in 15..=17 {
+            map.set_body_opaque(Coords::new(20, y), true);
+        }
+
+        let tiles = get_visible_tiles_edges(&fov_set, &map, origin, 16);
+        let edges = |c: Coords| {
+            let id = map.tile_id(c).unwrap();
+            tiles.iter().find(|t| t.id == id).unwrap().edges
+        };
+
+        assert_eq!(tiles.len(), get_visible_grid(&fov_set, &map, origin, 16).len());
+        assert!(edges(origin).is_empty());
+        assert_eq!(edges(Coords::new(20, 16)), Edges { west: true, ..Edges::default() });
+        assert_eq!(edges(Coords::new(20, 15)), Edges { west: true, ..Edges::default() });
+        assert_eq!(edges(Coords::new(20, 17)), Edges { west: true, ..Edges::default() });
+        assert_eq!(edges(Coords::new(16, 20)), Edges { south: true, ..Edges::default() });
+    }
+
+    #[test]
+    fn visible_tiles_falloff() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(16, 16);
+        let map = TileMap::new(33, 33).unwrap();
+        let tiles = get_visible_tiles_lit(&fov_set, &map, origin, 16, &Falloff::Linear);
+        let brightness = |c: Coords| {
+            let id = map.tile_id(c).unwrap();
+            tiles.iter().find(|t| t.id == id).unwrap().brightness
+        };
+
+        assert_eq!(tiles.len(), get_visible_grid(&fov_set, &map, origin, 16).len());
+        assert_eq!(brightness(origin), 1.0);
+        assert_eq!(brightness(Coords::new(24, 16)), 0.5);
+        assert_eq!(brightness(Coords::new(16, 0)), 0.0);
+    }
+
+    // Deterministic results: node tables and query results match golden checksums, so any
+    // platform-dependent drift (float build or runtime) fails this test.
+    #[test]
+    fn deterministic_checksums() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let map = TileMap::random(48, 48, 0.15, 1234).unwrap();
+        let origin = Coords::new(24, 24);
+        let grid = get_visible_grid(&fov_set, &map, origin, 16);
+
+        assert_eq!(fov_set.checksum(), 0x988b_a2d0_8e2f_2025);
+        assert_eq!(grid.checksum(), 0x5507_4093_cec5_d661);
+
+        // Shaped queries and the lit-enough test decide visibility in integers too
+        let squint = FovShape::Ellipse { rx: 12.5, ry: 5.5 };
+        assert_eq!(fov_set.compute_shaped(&map, origin, squint).checksum(), 0x6d9b_f7ef_dcad_bc64);
+        let torch = Light::new(Coords::new(30, 20), 6, Falloff::Linear);
+        let levels = get_light_levels(&fov_set, &map, origin, 16, 0.3, &[torch]);
+        let mut lit = VisibilityGrid::for_map(&map);
+        levels.iter().enumerate().filter(|(_, &level)| level > 0.0).for_each(|(id, _)| {
+            lit.insert_id(id);
+        });
+        assert_eq!(lit.checksum(), 0x6f33_116e_4053_dbcd);
+    }
+
+    // Huge radii mean the full radius, for every query built on `effective_radius`.
+    #[test]
+    fn huge_radius_saturates() {
+        use crate::standard::{fovcalc::get_visible_tiles as standard_tiles, FovSet16 as Std16};
+
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(16, 16);
+        let mut map = TileMap::new(33, 33).unwrap();
+        let full = get_visible_grid(&fov_set, &map, origin, 16);
+        assert!(full.len() > 1);
+
+        for r in [usize::MAX, 1 << 63] {
+            assert_eq!(get_visible_grid(&fov_set, &map, origin, r), full);
+            assert!(get_visible_small(&fov_set, &map, origin, r).is_none());
+        }
+        map.set_radius_modifier(0, -4);
+        assert_eq!(effective_radius(&map, origin, usize::MAX, 16), 16);
+
+        let standard = Std16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let expected = standard_tiles(&standard, &map, origin, 20);
+        assert!(expected.len() > 1);
+        assert_eq!(standard_tiles(&standard, &map, origin, usize::MAX), expected);
+    }
+
+    // Observers on high ground see farther; modifiers apply inside the query path.
+    #[test]
+    fn terrain_radius_modifier() {
+        const TOWER: u8 = 1;
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(16, 16);
+        let mut map = TileMap::new(33, 33).unwrap();
+        map.set_radius_modifier(TOWER, 4);
+
+        let ground = get_visible_grid(&fov_set, &map, origin, 8);
+        map.get_mut(origin).unwrap().tag = TOWER;
+        let tower = get_visible_grid(&fov_set, &map, origin, 8);
+
+        assert_eq!(effective_radius(&map, origin, 8, 16), 12);
+        assert_eq!(effective_radius(&map, origin, 14, 16), 16);
+        assert_eq!(effective_radius(&map, origin, usize::MAX, 16), 16);
+        assert!(!ground.contains(Coords::new(28, 16)));
+        assert!(tower.contains(Coords::new(28, 16)));
+        assert!(!tower.contains(Coords::new(29, 16)));
+    }
+
+    // Origin rules: the origin may be left out of results, and an opaque origin may blind.
+    #[test]
+    fn origin_rules() {
+        let mut fov_set =
+            FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(16, 16);
+        let mut map = TileMap::new(33, 33).unwrap();
+        map.set_body_opaque(origin, true);
+
+        let default = get_visible_grid(&fov_set, &map, origin, 16);
+        assert!(default.contains(origin));
+        assert!(default.contains(Coords::new(30, 16)));
+
+        fov_set.set_origin_rules(OriginRules { visible: false, opaque_blocks: false });
+        let hidden = get_visible_grid(&fov_set, &map, origin, 16);
+        assert!(!hidden.contains(origin));
+        assert_eq!(hidden.len(), default.len() - 1);
+
+        fov_set.set_origin_rules(OriginRules { visible: true, opaque_blocks: true });
+        let blind = get_visible_grid(&fov_set, &map, origin, 16);
+        assert_eq!(blind.iter().collect::<Vec<_>>(), vec![origin]);
+        let mut seen = 0;
+        let _ = fov_set.compute_with(&map, origin, 16, |_, _| {
+            seen += 1;
+            ControlFlow::<()>::Continue(())
+        });
+        assert_eq!(seen, 1);
+
+        // A transparent origin never blinds
+        map.set_body_opaque(origin, false);
+        assert_eq!(get_visible_grid(&fov_set, &map, origin, 16), default);
+    }
+
+    // Coverage masks agree with the grid mask on which tiles are visible, with partially
+    // shadowed tiles softened.
+    #[test]
+    fn coverage_mask_soft_edges() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(16, 16);
+        let mut map = TileMap::new(33, 33).unwrap();
+        map.set_body_opaque(Coords::new(20, 16), true);
+
+        let hard = get_visible_grid(&fov_set, &map, origin, 16).to_image_mask();
+        let soft = get_coverage_mask(&fov_set, &map, origin, 16);
+        assert_eq!(soft.len(), 33 * 33);
+        assert!(hard.iter().zip(soft.iter()).all(|(h, s)| (*h == 0) == (*s == 0)));
+
+        let id = |x: usize, y: usize| y * 33 + x;
+        assert_eq!(soft[id(16, 16)], 255);
+        assert_eq!(soft[id(20, 16)], 255);
+        assert_eq!(soft[id(24, 16)], 0);
+        assert!(soft.iter().any(|&v| v > 0 && v < 255));
+    }
+
+    // Clipped queries match the full query cut to the clip, with the origin inside or out.
+    #[test]
+    fn visible_grid_clipped() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let map = TileMap::random(64, 64, 0.15, 9).unwrap();
+        let origin = Coords::new(30, 30);
+        let full = get_visible_grid(&fov_set, &map, origin, 16);
+
+        for clip in [Rect::new(20, 25, 20, 12), Rect::new(0, 0, 64, 64), Rect::new(40, 10, 20, 30)] {
+            let clipped = get_visible_grid_clipped(&fov_set, &map, origin, 16, clip);
+            let expected: Vec<_> = full.iter().filter(|&c| clip.contains(c)).collect();
+            assert_eq!(clipped.iter().collect::<Vec<_>>(), expected);
+            assert_eq!((clipped.width(), clipped.height()), (64, 64));
+        }
+    }
+
+    // Daylight sees the whole FOV; at night only near tiles and torch-lit tiles are seen.
+    #[test]
+    fn light_levels_ambient() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let (map, origin) = (TileMap::new(33, 33).unwrap(), Coords::new(16, 16));
+        let id = |x: i32, y: i32| map.tile_id(Coords::new(x, y)).unwrap();
+        let full = get_visible_grid(&fov_set, &map, origin, 16);
+
+        let day = get_light_levels(&fov_set, &map, origin, 16, 1.0, &[]);
+        assert_eq!(day.iter().filter(|&&l| l > 0.0).count(), full.len());
+
+        let torch = Light::new(Coords::new(28, 16), 3, Falloff::Linear);
+        let night = get_light_levels(&fov_set, &map, origin, 16, 0.25, &[torch]);
+        assert_eq!(night[id(16, 16)], 0.25);
+        assert_eq!(night[id(20, 16)], 0.25);
+        assert_eq!(night[id(21, 16)], 0.0);
+        assert_eq!(night[id(28, 16)], 1.0);
+        assert!((night[id(27, 16)] - (0.25 + 2.0 / 3.0)).abs() < 1e-9);
+        assert_eq!(night[id(16, 28)], 0.0);
+    }
+
+    // Blocked masks: none on open maps, the pillar's bits behind it, all for a walled-in origin.
+    #[test]
+    fn blocked_masks() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(16, 16);
+        let mut map = TileMap::new(33, 33).unwrap();
+        assert!(Octant::ALL.iter().all(|&o| fov_set.blocked_mask(&map, origin, 16, o) == 0));
+
+        let pillar = Coords::new(19, 16);
+        map.set_body_opaque(pillar, true);
+        let o1 = fov_set.octant(Octant::O1);
+        let ix = (0..=o1.max_node_index(16))
+            .find(|&ix| o1.world_coords_of(ix, origin) == pillar)
+            .unwrap();
+        let pillar_bits = o1.iter().nth(ix).unwrap().body;
+        assert_eq!(fov_set.blocked_mask(&map, origin, 16, Octant::O1), pillar_bits);
+        assert_eq!(fov_set.blocked_mask(&map, origin, 2, Octant::O1), 0);
+        assert_eq!(fov_set.blocked_mask(&map, origin, 16, Octant::O3), 0);
+
+        map.set_body_opaque(origin, true);
+        let mut fov_set = fov_set;
+        fov_set.set_origin_rules(OriginRules { visible: true, opaque_blocks: true });
+        assert_eq!(fov_set.blocked_mask(&map, origin, 16, Octant::O5), u16::MAX);
+        assert_eq!(fov_set.blocked_mask(&map, Coords::new(-1, 0), 16, Octant::O5), 0);
+    }
+
+    // Small queries match full grid queries, and decline radii past `SMALL_RADIUS`.
+    #[test]
+    fn small_queries() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        for seed in 0..4 {
+            let map = TileMap::random(20, 20, 0.2, seed).unwrap();
+            let cases = [(Coords::new(10, 10), 4), (Coords::new(1, 2), 3), (Coords::new(19, 0), 1)];
+            for (origin, r) in cases {
+                let small = get_visible_small(&fov_set, &map, origin, r).unwrap();
+                let grid = get_visible_grid(&fov_set, &map, origin, r);
+                assert_eq!(small.len(), grid.len());
+                assert!(small.iter().all(|&c| grid.contains(c)));
+            }
+        }
+
+        let mut map = TileMap::new(20, 20).unwrap();
+        assert!(get_visible_small(&fov_set, &map, Coords::new(5, 5), 5).is_none());
+        map.get_mut(Coords::new(5, 5)).unwrap().tag = 1;
+        map.set_radius_modifier(1, 2);
+        assert!(get_visible_small(&fov_set, &map, Coords::new(5, 5), 3).is_none());
+        assert!(get_visible_small(&fov_set, &map, Coords::new(6, 5), 3).is_some());
+    }
+
+    // Custom traversal orders give identical results; orders that skip blockers are invalid.
+    #[test]
+    fn traversal_orders() {
+        let mut fov_set =
+            FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let map = TileMap::random(33, 33, 0.15, 9).unwrap();
+        let origin = Coords::new(16, 16);
+        let expected = [16, 7].map(|r| get_visible_grid(&fov_set, &map, origin, r));
+        let check = |fov_set: &FovSet16| {
+            for (i, r) in [16, 7].into_iter().enumerate() {
+                assert_eq!(get_visible_grid(fov_set, &map, origin, r), expected[i]);
+            }
+        };
+
+        fov_set.set_traversal_order(&NearestFirst);
+        check(&fov_set);
+        let fov_octant = fov_set.octant(Octant::O1);
+        let dists: Vec<u32> = fov_octant
+            .traversal(16)
+            .map(|ix| fov_octant.node(ix))
+            .map(|n| n.dpri as u32 * n.dpri as u32 + n.dsec as u32 * n.dsec as u32)
+            .collect();
+        assert!(dists.windows(2).all(|w| w[0] <= w[1]));
+
+        fov_set.set_traversal_order(&AngleSweep);
+        check(&fov_set);
+        fov_set.set_traversal_order(&DefaultOrder);
+        check(&fov_set);
+
+        struct Reversed;
+        impl TraversalOrder for Reversed {
+            fn order(&self, offsets: &[(u16, u16)]) -> Vec<usize> {
+                (0..offsets.len()).rev().collect()
+            }
+        }
+        let offsets = [(0, 0), (1, 0), (1, 1), (2, 0)];
+        assert!(is_valid_order(&offsets, &AngleSweep.order(&offsets)));
+        assert!(!is_valid_order(&offsets, &Reversed.order(&offsets)));
+        assert!(!is_valid_order(&offsets, &[0, 1, 1, 3]));
+        assert!(!is_valid_order(&offsets, &[0, 1, 2]));
+    }
+
+    // Hinted queries match unhinted ones; the hint learns result sizes and decays slowly.
+    #[test]
+    fn capacity_hints() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let map = TileMap::random(33, 33, 0.15, 3).unwrap();
+        let origin = Coords::new(16, 16);
+        let mut hint = CapacityHint::new();
+        assert_eq!(hint.capacity(), 0);
+
+        let order = TileOrder::ByDistance;
+        let tiles = get_visible_tiles_hinted(&fov_set, &map, origin, 16, order, &mut hint);
+        assert_eq!(tiles, get_visible_tiles_ordered(&fov_set, &map, origin, 16, order));
+        assert!(hint.capacity() >= tiles.len());
+        let large = hint.capacity();
+
+        let small = get_visible_tiles_hinted(&fov_set, &map, origin, 3, order, &mut hint);
+        assert!(small.len() < tiles.len());
+        assert!(hint.capacity() < large && hint.capacity() >= tiles.len() * 15 / 16);
+        for _ in 0..100 {
+            hint.record(small.len());
+        }
+        assert_eq!(hint.capacity(), small.len() + small.len() / 8);
+    }
+
+    // Sparse queries match full traversal on sparse maps with low walls, at edges and
+    // corners, with any origin rules.
+    #[test]
+    fn sparse_occluder_queries() {
+        let mut fov_set =
+            FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let mut map = TileMap::random(40, 30, 0.04, 17).unwrap();
+        for x in [5, 20, 33] {
+            map.get_mut(Coords::new(x, 12)).unwrap().wall_height = 1;
+        }
+        map.set_body_opaque(Coords::new(20, 15), true);
+        let ids = |grid: VisibilityGrid| grid.iter_ids().collect::<Vec<_>>();
+
+        for rules in [(true, false), (false, false), (true, true)] {
+            let (visible, opaque_blocks) = rules;
+            fov_set.set_origin_rules(OriginRules { visible, opaque_blocks });
+            let index = OccluderIndex::new(&map, 8);
+            for (x, y, r) in [(20, 15, 16), (0, 0, 16), (39, 29, 9), (21, 13, 12), (6, 11, 0)] {
+                let origin = Coords::new(x, y);
+                let full = get_visible_grid(&fov_set, &map, origin, r);
+                let sparse = get_visible_grid_sparse(&fov_set, &map, &index, origin, r);
+                assert_eq!(ids(sparse), ids(full), "origin {origin:?}, rules {rules:?}");
+            }
+        }
+    }
+
+    // Sparse queries on sets whose shape culls the outer band work at the nominal radius.
+    #[test]
+    fn sparse_culled_shape() {
+        let shape = FovShape::Circle { adjust: -0.5 };
+        let fov_set = FovSet8::new(FovRadius::R8, QFactor::Single, shape).unwrap();
+        assert!(fov_set.octant(Octant::O1).radius() < 8);
+        let map = TileMap::random(17, 17, 0.1, 5).unwrap();
+        let index = OccluderIndex::new(&map, 8);
+        let origin = Coords::new(8, 8);
+
+        let full = get_visible_grid(&fov_set, &map, origin, 8);
+        let sparse = get_visible_grid_sparse(&fov_set, &map, &index, origin, 8);
+        assert_eq!(sparse.iter_ids().collect::<Vec<_>>(), full.iter_ids().collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic(expected = "occluder index is stale")]
+    fn sparse_rejects_stale_index() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let mut map = TileMap::new(20, 20).unwrap();
+        let index = OccluderIndex::new(&map, 8);
+        map.set_body_opaque(Coords::new(1, 1), true);
+        get_visible_grid_sparse(&fov_set, &map, &index, Coords::new(5, 5), 8);
+    }
+
+    /// Asserts that an open map shows `fov_set` a disk of its radius, that a pillar shadows
+    /// the tiles behind it, and that each tile is reported once.
+    fn assert_disk_and_pillar<B: FovBits>(fov_set: &FovSet<B>) {
+        let r = fov_set.radius().to_int() as i32;
+        let (size, o) = (2 * r as usize + 1, r);
+        let origin = Coords::new(o, o);
+        let map = TileMap::new(size, size).unwrap();
+        let grid = get_visible_grid(fov_set, &map, origin, r as usize);
+        let limit = (r as f64 + 0.5).powi(2);
+        let disk = (0..size * size)
+            .filter_map(|id| Coords::from_index(id, size))
+            .filter(|c| (((c.x - o).pow(2) + (c.y - o).pow(2)) as f64) <= limit)
+            .count();
+        assert_eq!(grid.len(), disk);
+        assert_eq!(get_visible_tiles(fov_set, &map, origin, r as usize).len(), disk);
+
+        let mut map = TileMap::new(size, size).unwrap();
+        map.set_body_opaque(Coords::new(o + 2, o), true);
+        let grid = get_visible_grid(fov_set, &map, origin, r as usize);
+        assert!(grid.contains(Coords::new(o + 2, o)));
+        assert!(!grid.contains(Coords::new(o + r, o)));
+        assert!(grid.contains(Coords::new(o, o + r)));
+
+        let mut streamed = 0;
+        let _ = fov_set.compute_with(&map, origin, r as usize, |_, _| {
+            streamed += 1;
+            ControlFlow::<()>::Continue(())
+        });
+        assert_eq!(streamed, grid.len());
+        let octant = fov_calc(fov_set.octant(Octant::O1), &map, origin, r as usize);
+        assert!(octant.iter().all(|t| grid.contains_id(t.id)));
+    }
+
+    // Every tier sees an open disk out to its radius; pillars shadow the tiles behind them.
+    #[test]
+    fn visible_grid_tiers() {
+        let (single, shape) = (QFactor::Single, FovShape::default());
+        assert_disk_and_pillar(&FovSet8::new(FovRadius::R8, single, shape).unwrap());
+        assert_disk_and_pillar(&FovSet32::new(FovRadius::R32, single, shape).unwrap());
+        assert_disk_and_pillar(&FovSet64::new(FovRadius::R64, single, shape).unwrap());
+        assert_disk_and_pillar(&FovSet128::new(FovRadius::R128, single, shape).unwrap());
+    }
+
+    // Radii clamp to the set's radius, e.g. 16 for R16 at Q-Factor 2.
+    #[test]
+    fn radius_clamps_to_set() {
+        let r8 = FovSet8::new(FovRadius::R8, QFactor::Single, FovShape::default()).unwrap();
+        let map = TileMap::new(21, 21).unwrap();
+        let origin = Coords::new(10, 10);
+        let full = get_visible_grid(&r8, &map, origin, 8);
+        assert_eq!(get_visible_grid(&r8, &map, origin, 12), full);
+        assert!(get_visible_grid(&r8, &map, origin, 3).len() < full.len());
+
+        let fov_set = FovSet32::new(FovRadius::R16, QFactor::Double, FovShape::default()).unwrap();
+        let (origin, map) = (Coords::new(20, 20), TileMap::new(41, 41).unwrap());
+        let full = get_visible_grid(&fov_set, &map, origin, 16);
+        assert_eq!(get_visible_grid(&fov_set, &map, origin, 40), full);
+        assert!(!full.contains(Coords::new(20, 37)));
+
+        let fov_set = FovSet128::new(FovRadius::R64, QFactor::Double, FovShape::default()).unwrap();
+        let (origin, map) = (Coords::new(64, 64), TileMap::new(131, 131).unwrap());
+        let full = get_visible_grid(&fov_set, &map, origin, 64);
+        assert_eq!(get_visible_grid(&fov_set, &map, origin, 128), full);
+        assert!(!full.contains(Coords::new(64, 129)));
+        assert!(get_visible_grid(&fov_set, &map, origin, 3).len() < full.len());
+    }
+
+    // Q-Factor 2 at R16 sees the same open disk as `FovSet16`, with at least as many bits.
+    #[test]
+    fn double_q_matches_q16_tiles() {
+        let fine = FovSet32::new(FovRadius::R16, QFactor::Double, FovShape::default()).unwrap();
+        let coarse = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(20, 20);
+        let mut map = TileMap::new(41, 41).unwrap();
+        let open = get_visible_grid(&fine, &map, origin, 16);
+        assert_eq!(open, get_visible_grid(&coarse, &map, origin, 16));
+
+        map.set_body_opaque(Coords::new(23, 21), true);
+        let mut bits = vec![(0, 0); 41 * 41];
+        let _ = fine.compute_with(&map, origin, 16, |c, info| {
+            bits[map.tile_id(c).unwrap()].0 = info.total_bits;
+            ControlFlow::<()>::Continue(())
+        });
+        let _ = coarse.compute_with(&map, origin, 16, |c, info| {
+            bits[map.tile_id(c).unwrap()].1 = info.total_bits;
+            ControlFlow::<()>::Continue(())
+        });
+        assert!(bits.iter().all(|&(fine, coarse)| fine >= coarse));
+        assert!(bits.iter().any(|&(fine, coarse)| fine > coarse));
+    }
+
+    // LOD keeps open maps exact; distant shadows are coarser, near ones unchanged.
+    #[test]
+    fn lod_blocks() {
+        let mut fov_set =
+            FovSet32::new(FovRadius::R32, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(40, 40);
+        let map = TileMap::new(81, 81).unwrap();
+        let exact = get_visible_grid(&fov_set, &map, origin, 32);
+        let exact_27 = get_visible_grid(&fov_set, &map, origin, 27);
+        fov_set.set_lod_distance(Some(20));
+        assert_eq!(fov_set.lod_distance(), Some(20));
+        assert_eq!(get_visible_grid(&fov_set, &map, origin, 32), exact);
+        assert_eq!(get_visible_grid(&fov_set, &map, origin, 27), exact_27);
+
+        let fov_octant = fov_set.octant(Octant::O1);
+        let far = fov_octant.iter().filter(|n| n.dpri >= 20).count();
+        assert!(fov_octant.blocks().len() * 3 < far);
+        assert!(fov_octant.blocks().iter().all(|b| b.dpri >= 20 && b.dpri % 2 == 0));
+        assert_eq!(fov_octant.blocks().iter().map(|b| b.tiles().count()).sum::<usize>(), far);
+
+        let mut map = TileMap::new(81, 81).unwrap();
+        map.set_body_opaque(Coords::new(43, 40), true);
+        map.set_body_opaque(Coords::new(40, 64), true);
+        let lod = get_visible_grid(&fov_set, &map, origin, 32);
+        fov_set.set_lod_distance(None);
+        let exact = get_visible_grid(&fov_set, &map, origin, 32);
+        let near = |c: Coords| (c.x - 40).pow(2) + (c.y - 40).pow(2) < 20 * 20;
+        assert!(exact.iter().filter(|&c| near(c)).all(|c| lod.contains(c)));
+        assert!(!lod.contains(Coords::new(70, 40)) && !lod.contains(Coords::new(40, 70)));
+
+        // Any tier can merge blocks, e.g. R16 sets for cheaper distant AI checks
+        let mut q16 = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let (origin, map) = (Coords::new(16, 16), TileMap::new(33, 33).unwrap());
+        let exact = get_visible_grid(&q16, &map, origin, 16);
+        q16.set_lod_distance(Some(10));
+        assert!(!q16.octant(Octant::O7).blocks().is_empty());
+        assert_eq!(get_visible_grid(&q16, &map, origin, 16), exact);
+    }
+
+    #[test]
+    #[should_panic(expected = "LOD distance must be at least 1")]
+    fn lod_rejects_zero_distance() {
+        let mut fov_set =
+            FovSet32::new(FovRadius::R32, QFactor::Single, FovShape::default()).unwrap();
+        fov_set.set_lod_distance(Some(0));
+    }
+}
@@ -0,0 +1,876 @@
+//! Simple FOV sets for FOV Visualization - Rust (2D).
+//!
+//! Notes:
+//! - An `FovSet` holds eight `FovOctant`s, which share one list of `FovNode`s.
+//! - Simple FOV uses one tile part as an obstruction: the tile `body`.
+//! - `FovSet` is generic over the width of its node bits (`FovBits`), which is its Q-value.
+//!   Each tier is named by an alias: `FovSet8` (R8), `FovSet16` (R16), `FovSet32` (R32, or
+//!   R16 at Q-Factor 2), `FovSet64` (R64, or R32 at Q-Factor 2) and `FovSet128` (R128, or R64
+//!   at Q-Factor 2). Q256 (R128 at Q-Factor 2) has no node type.
+//! - Nodes take 3 bytes (Q8) to 32 bytes (Q128), and each doubling of the radius holds about
+//!   four times the nodes, so pick the smallest tier that covers the sight radius.
+//! - Level of detail (LOD): beyond a set distance, nodes can be merged into 2x2 `FovBlock`s
+//!   whose bits are the union of their nodes' bits, so distant tiles are tested per block.
+//!
+//! Building an FOV Set:
+//! - Create a list of FOV Nodes (`Vec<FovNode>`), same for each octant.
+//! - Create 8 FOV octant (`FovOctant`) instances from FOV nodes.
+//! - Create an FOV set (`FovSet`) from the 8 octants.
+
+use std::sync::Arc;
+
+use crate::{
+    error::{check_config, FovError},
+    fov::{Algorithm, FovShape, is_valid_order, FovLines, OriginRules, TraversalOrder},
+    FovRadius, FovSetAny, Octant, QFactor,
+};
+
+use super::fovnode::{
+    build_fov_nodes, build_node_masks_batch, build_nodes_within, in_storage_order, FovBits,
+    FovBlock, FovNode, FovOctant, ProgressSink,
+};
+
+/// FOV set of eight FOV octants, each comprised of FOV nodes with `B` FOV bits.
+///
+/// Octants share their nodes, so they are serialized once (see `SetData`), and checked
+/// against the FOV radius and Q-value on load. Cloning is cheap, as clones share nodes.
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "SetData<B>",
+        into = "SetData<B>",
+        bound(serialize = "B: FovBits + serde::Serialize"),
+        bound(deserialize = "B: FovBits + serde::Deserialize<'de>")
+    )
+)]
+pub struct FovSet<B> {
+    rfov: FovRadius,
+    capacity: usize,
+    origin_rules: OriginRules,
+    octant_1: FovOctant<B>,
+    octant_2: FovOctant<B>,
+    octant_3: FovOctant<B>,
+    octant_4: FovOctant<B>,
+    octant_5: FovOctant<B>,
+    octant_6: FovOctant<B>,
+    octant_7: FovOctant<B>,
+    octant_8: FovOctant<B>,
+}
+
+impl<B: FovBits> FovSet<B> {
+    /// Creates a new _Simple_ `FovSet` with Q-value `B::BITS`, its nodes culled to `shape`
+    /// (`FovShape::default()` for the usual circle).
+    ///
+    /// Returns an error unless `rfov` and `qfactor` give Q-value `B::BITS` (see
+    /// `fov::supported_configs`), if `shape` is invalid, or if it differs by octant (an
+    /// `Ellipse` with unequal radii): build those octants with `build_fov_nodes_shaped`.
+    pub fn new(rfov: FovRadius, qfactor: QFactor, shape: FovShape) -> Result<Self, FovError> {
+        Self::with_progress(rfov, qfactor, shape, &mut |_, _| {})
+    }
+    /// Creates a new _Simple_ `FovSet` as `new` does, reporting node build progress to
+    /// `progress` (see `ProgressSink`).
+    pub fn with_progress(
+        rfov: FovRadius,
+        qfactor: QFactor,
+        shape: FovShape,
+        progress: &mut dyn ProgressSink,
+    ) -> Result<Self, FovError> {
+        let (name, q) = (set_name::<B>(), B::BITS as usize);
+        check_config(name, Algorithm::Simple, Some(q), rfov, qfactor, shape)?;
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("fov_set_build", radius = rfov.to_int(), q, ?shape)
+            .entered();
+        let fov_lines = FovLines::new(rfov, qfactor);
+        let nodes = build_nodes_within(rfov.to_int(), &fov_lines, shape, progress)?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(nodes = nodes.len(), capacity = nodes.len() * 8, "built FOV nodes");
+
+        Self::from_nodes(rfov, qfactor, &nodes)
+    }
+    /// Creates a new _Simple_ `FovSet` with Q-value `B::BITS` from prebuilt `nodes`, e.g.
+    /// loaded from a cache file (see `cache`).
+    ///
+    /// Returns an error if `rfov` and `qfactor` don't give Q-value `B::BITS`, if `nodes` are
+    /// not in storage order from the origin (see `FovOctant`), or if they reach past `rfov`.
+    /// Shapes may cull the outermost nodes, so nodes may stop short of `rfov`.
+    pub fn from_nodes(
+        rfov: FovRadius,
+        qfactor: QFactor,
+        nodes: &[FovNode<B>],
+    ) -> Result<Self, FovError> {
+        let set_type = set_name::<B>();
+        if rfov.to_int() as usize * qfactor.to_int() as usize != B::BITS as usize {
+            return Err(FovError::UnsupportedConfig { set_type, rfov, qfactor });
+        }
+        if !in_storage_order(nodes.iter().map(|n| (n.dpri, n.dsec))) {
+            let msg = format!("{set_type} nodes must start at the origin, in storage order");
+            return Err(FovError::invalid(msg));
+        }
+        let r = nodes[nodes.len() - 1].dpri;
+        if r > rfov.to_int() {
+            let msg = format!("{set_type} nodes reach radius {r}, past {rfov:?}");
+            return Err(FovError::invalid(msg));
+        }
+        let shared = FovOctant::new(nodes, Octant::O1);
+        Ok(Self::from_octant(rfov, OriginRules::default(), shared))
+    }
+    /// Creates a new _Simple_ `FovSet` whose eight octants view the nodes of `shared`.
+    fn from_octant(rfov: FovRadius, origin_rules: OriginRules, shared: FovOctant<B>) -> Self {
+        Self {
+            rfov,
+            capacity: shared.len() * 8,
+            origin_rules,
+            octant_1: shared.for_octant(Octant::O1),
+            octant_2: shared.for_octant(Octant::O2),
+            octant_3: shared.for_octant(Octant::O3),
+            octant_4: shared.for_octant(Octant::O4),
+            octant_5: shared.for_octant(Octant::O5),
+            octant_6: shared.for_octant(Octant::O6),
+            octant_7: shared.for_octant(Octant::O7),
+            octant_8: shared.for_octant(Octant::O8),
+        }
+    }
+    /// Prints a summary of `FovSet` data.
+    pub fn summarize(&self) {
+        println!("[{}] Summary:", set_name::<B>());
+        println!("  radius:    {}", self.rfov.to_int());
+        println!("  octant 1:  {} nodes", self.octant_1.len());
+        println!("  octant 2:  {} nodes", self.octant_2.len());
+        println!("  octant 3:  {} nodes", self.octant_3.len());
+        println!("  octant 4:  {} nodes", self.octant_4.len());
+        println!("  octant 5:  {} nodes", self.octant_5.len());
+        println!("  octant 6:  {} nodes", self.octant_6.len());
+        println!("  octant 7:  {} nodes", self.octant_7.len());
+        println!("  octant 8:  {} nodes", self.octant_8.len());
+        println!("  total:     {} nodes", self.capacity);
+        println!("  size:      {} bytes", size_of::<Self>());
+        println!("  size mem:  {} bytes (shared by all octants)", self.node_bytes());
+    }
+    /// Returns the maxiumum number of FOV nodes in the FOV set.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    /// Returns the memory held by node data, in bytes.
+    ///
+    /// Nodes are stored once, and shared by all eight octants.
+    pub fn node_bytes(&self) -> usize {
+        self.octant_1.len() * size_of::<FovNode<B>>()
+    }
+    /// Returns a 64-bit FNV-1a checksum of all octant nodes.
+    ///
+    /// Node bits are built with IEEE-754 basic operations only, so the checksum is
+    /// identical on all platforms; compare it at startup to verify lockstep peers agree.
+    /// Nodes wider than 48 bits hash their `body` as separate words.
+    pub fn checksum(&self) -> u64 {
+        let mut words = Vec::new();
+        for node in Octant::ALL.iter().flat_map(|&o| self.octant(o).iter()) {
+            let (body, offset) = (node.body.to_mask(), (node.dpri as u64) << 8 | node.dsec as u64);
+            match B::BITS <= 48 {
+                true => words.push((body as u64) << 16 | offset),
+                false => words.extend([body as u64, (body >> 64) as u64, offset]),
+            }
+        }
+        crate::visibility::fnv1a(words.iter())
+    }
+    /// Returns the FOV radius of the FOV set.
+    pub fn radius(&self) -> FovRadius {
+        self.rfov
+    }
+    /// Returns how queries on this FOV set treat the origin tile.
+    pub fn origin_rules(&self) -> OriginRules {
+        self.origin_rules
+    }
+    /// Sets how queries on this FOV set treat the origin tile.
+    pub fn set_origin_rules(&mut self, origin_rules: OriginRules) {
+        self.origin_rules = origin_rules;
+    }
+    /// Sets the order in which queries on this FOV set visit each octant's nodes.
+    ///
+    /// `DefaultOrder` restores storage order, the fastest. Panics if `order` returns an
+    /// order that could visit a node before one of its blockers (see `TraversalOrder`).
+    pub fn set_traversal_order<T: TraversalOrder>(&mut self, order: &T) {
+        // Nodes are the same in every octant, so one order serves all eight
+        let offsets: Vec<(u16, u16)> = self
+            .octant_1
+            .iter()
+            .map(|n| (n.dpri as u16, n.dsec as u16))
+            .collect();
+        let order = order.order(&offsets);
+        assert!(is_valid_order(&offsets, &order), "traversal order must visit blockers first!");
+        let is_default = order.iter().enumerate().all(|(pos, &ix)| pos == ix);
+        let order: Option<Arc<[usize]>> = (!is_default).then(|| order.into());
+
+        for fov_octant in self.octants_mut() {
+            fov_octant.set_order(order.clone());
+        }
+    }
+    /// Returns the distance from which queries evaluate 2x2 blocks, if LOD is on.
+    pub fn lod_distance(&self) -> Option<usize> {
+        self.octant_1.lod_distance()
+    }
+    /// Sets the distance from which queries evaluate 2x2 blocks, or `None` for exact FOV.
+    ///
+    /// Panics if `distance` is `Some(0)`.
+    pub fn set_lod_distance(&mut self, distance: Option<usize>) {
+        // Blocks are built once, and shared by every octant
+        self.octant_1.set_lod_distance(distance);
+        let shared = self.octant_1.clone();
+        for fov_octant in self.octants_mut() {
+            *fov_octant = shared.for_octant(fov_octant.octant());
+        }
+    }
+    /// Returns the FOV octant for the given `octant`.
+    pub fn octant(&self, octant: Octant) -> &FovOctant<B> {
+        match octant {
+            Octant::O1 => &self.octant_1,
+            Octant::O2 => &self.octant_2,
+            Octant::O3 => &self.octant_3,
+            Octant::O4 => &self.octant_4,
+            Octant::O5 => &self.octant_5,
+            Octant::O6 => &self.octant_6,
+            Octant::O7 => &self.octant_7,
+            Octant::O8 => &self.octant_8,
+        }
+    }
+    /// Returns all eight FOV octants, mutably.
+    fn octants_mut(&mut self) -> [&mut FovOctant<B>; 8] {
+        [
+            &mut self.octant_1,
+            &mut self.octant_2,
+            &mut self.octant_3,
+            &mut self.octant_4,
+            &mut self.octant_5,
+            &mut self.octant_6,
+            &mut self.octant_7,
+            &mut self.octant_8,
+        ]
+    }
+}
+
+/// Serialized form of a `FovSet`: one octant's nodes and settings, shared by all eight.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(bound(serialize = "B: FovBits + serde::Serialize"))]
+#[serde(bound(deserialize = "B: FovBits + serde::Deserialize<'de>"))]
+struct SetData<B> {
+    rfov: FovRadius,
+    origin_rules: OriginRules,
+    nodes: FovOctant<B>,
+}
+
+#[cfg(feature = "serde")]
+impl<B: FovBits> From<FovSet<B>> for SetData<B> {
+    fn from(fov_set: FovSet<B>) -> Self {
+        Self {
+            rfov: fov_set.rfov,
+            origin_rules: fov_set.origin_rules,
+            nodes: fov_set.octant_1,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<B: FovBits> TryFrom<SetData<B>> for FovSet<B> {
+    type Error = String;
+
+    /// Rejects radii with no Q-Factor giving Q-value `B::BITS`, and nodes past the radius.
+    fn try_from(data: SetData<B>) -> Result<Self, Self::Error> {
+        let (name, rfov) = (set_name::<B>(), data.rfov);
+        let supported = crate::fov::supported_configs().any(|c| {
+            c.algorithm == Algorithm::Simple && c.rfov == rfov && c.q() == B::BITS as usize
+        });
+        if !supported {
+            return Err(format!("{name} does not support {rfov:?}"));
+        }
+        let r = data.nodes.radius();
+        if r > rfov.to_int() as usize {
+            return Err(format!("{name} nodes reach radius {r}, past {rfov:?}"));
+        }
+        Ok(Self::from_octant(rfov, data.origin_rules, data.nodes))
+    }
+}
+
+/// Returns the name of the FOV set type with `B` FOV bits, for messages.
+pub(crate) fn set_name<B: FovBits>() -> &'static str {
+    match B::BITS {
+        8 => "FovSet8",
+        16 => "FovSet16",
+        32 => "FovSet32",
+        64 => "FovSet64",
+        _ => "FovSet128",
+    }
+}
+
+/// FOV set of eight FOV octants, each comprised of 8-bit FOV nodes (`FovRadius::R8`).
+pub type FovSet8 = FovSet<u8>;
+/// One of eight FOV octants, comprised of 8-bit FOV nodes (see `FovOctant`).
+pub type FovOctant8 = FovOctant<u8>;
+/// Node in an FOV octant representing a single tile with 8 FOV bits (`Q=8`).
+pub type FovNode8 = FovNode<u8>;
+
+/// FOV set of eight FOV octants, each comprised of 16-bit FOV nodes.
+pub type FovSet16 = FovSet<u16>;
+/// One of eight FOV octants, comprised of 16-bit FOV nodes (see `FovOctant`).
+pub type FovOctant16 = FovOctant<u16>;
+/// Node in an FOV octant representing a single tile with 16 FOV bits (`Q=16`).
+pub type FovNode16 = FovNode<u16>;
+
+/// FOV set of eight FOV octants, each comprised of 32-bit FOV nodes.
+pub type FovSet32 = FovSet<u32>;
+/// One of eight FOV octants, comprised of 32-bit FOV nodes (see `FovOctant`).
+pub type FovOctant32 = FovOctant<u32>;
+/// Node in an FOV octant representing a single tile with 32 FOV bits (`Q=32`).
+pub type FovNode32 = FovNode<u32>;
+/// Block of up to 2x2 FOV nodes with 32 FOV bits, merged for level of detail FOV.
+pub type FovBlock32 = FovBlock<u32>;
+
+/// FOV set of eight FOV octants, each comprised of 64-bit FOV nodes.
+pub type FovSet64 = FovSet<u64>;
+/// One of eight FOV octants, comprised of 64-bit FOV nodes (see `FovOctant`).
+pub type FovOctant64 = FovOctant<u64>;
+/// Node in an FOV octant representing a single tile with 64 FOV bits (`Q=64`).
+pub type FovNode64 = FovNode<u64>;
+
+/// FOV set of eight FOV octants, each comprised of 128-bit FOV nodes.
+pub type FovSet128 = FovSet<u128>;
+/// One of eight FOV octants, comprised of 128-bit FOV nodes (see `FovOctant`).
+pub type FovOctant128 = FovOctant<u128>;
+/// Node in an FOV octant representing a single tile with 128 FOV bits (`Q=128`).
+pub type FovNode128 = FovNode<u128>;
+
+/// Creates nodes for a _Simple_ FOV octant with Q-value `16`.
+///
+/// Note: for Simple FOV, the first node `(0,0)` is always visible (all bits set).
+///
+/// Returns an error if `fov_lines` has more than 16 lines (see `FovSet32`), or as for
+/// `build_fov_nodes`.
+pub fn build_fov_nodes_q16(
+    rfov: FovRadius,
+    fov_lines: &FovLines,
+    shape: FovShape,
+) -> Result<Vec<FovNode16>, FovError> {
+    build_fov_nodes(rfov, fov_lines, shape)
+}
+
+/// Creates one _Simple_ FOV set per radius in `radii`, all at `qfactor` and culled to
+/// `shape`, in the order given.
+///
+/// FOV lines are computed once per radius, and node geometry once for all of them, so each
+/// node tests only the few FOV lines near its angle (see `build_node_masks_batch`). Sets
+/// match separate `FovSet::new` builds node for node, built in much less time. Each set is
+/// the type for its Q-value, e.g. R8 gives `FovSetAny::Simple8`.
+///
+/// Returns an error as `FovSetAny::new` does for any of the radii.
+pub fn build_fov_sets(
+    radii: &[FovRadius],
+    qfactor: QFactor,
+    shape: FovShape,
+) -> Result<Vec<FovSetAny>, FovError> {
+    for &rfov in radii {
+        check_config("build_fov_sets", Algorithm::Simple, None, rfov, qfactor, shape)?;
+    }
+    let mut distinct = radii.to_vec();
+    distinct.sort_by_key(|rfov| rfov.to_int());
+    distinct.dedup();
+
+    let fov_lines: Vec<FovLines> = distinct.iter().map(|&r| FovLines::new(r, qfactor)).collect();
+    let builds: Vec<(u8, &FovLines)> =
+        distinct.iter().zip(fov_lines.iter()).map(|(r, lines)| (r.to_int(), lines)).collect();
+    let masks = build_node_masks_batch(&builds, shape);
+
+    let sets = distinct
+        .iter()
+        .zip(masks.iter())
+        .map(|(&rfov, masks)| set_from_masks(rfov, qfactor, masks))
+        .collect::<Result<Vec<_>, _>>()?;
+    let set_for = |rfov: &FovRadius| {
+        let ix = distinct.iter().position(|r| r == rfov).expect("every radius was built!");
+        sets[ix].clone()
+    };
+    Ok(radii.iter().map(set_for).collect())
+}
+
+/// Returns the _Simple_ FOV set type for the Q-value of `rfov` and `qfactor`, from node
+/// `masks` (see `build_node_masks_batch`).
+fn set_from_masks(
+    rfov: FovRadius,
+    qfactor: QFactor,
+    masks: &[(u128, u8, u8)],
+) -> Result<FovSetAny, FovError> {
+    fn nodes<B: FovBits>(masks: &[(u128, u8, u8)]) -> Vec<FovNode<B>> {
+        let node = |&(body, dpri, dsec): &(u128, u8, u8)| FovNode {
+            body: B::from_mask(body),
+            dpri,
+            dsec,
+        };
+        masks.iter().map(node).collect()
+    }
+
+    Ok(match rfov.to_int() as usize * qfactor.to_int() as usize {
+        8 => FovSetAny::Simple8(FovSet8::from_nodes(rfov, qfactor, &nodes(masks))?),
+        16 => FovSetAny::Simple16(FovSet16::from_nodes(rfov, qfactor, &nodes(masks))?),
+        32 => FovSetAny::Simple32(FovSet32::from_nodes(rfov, qfactor, &nodes(masks))?),
+        64 => FovSetAny::Simple64(FovSet64::from_nodes(rfov, qfactor, &nodes(masks))?),
+        _ => FovSetAny::Simple128(FovSet128::from_nodes(rfov, qfactor, &nodes(masks))?),
+    })
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fov::body_lines, maps::Coords, math::{Delta, Line}};
+
+    // FOV Node sanity check:
+    // - All FOV lines should pass through the 0th FOV Node.
+    // - For Single Q-Factor:
+    //   - FOV Node at `(dpri, dsec)` = `(rFOV, 0)` has one FOV bit set.
+    //   - FOV Nodes at `(dpri, dsec)` = `(rFOV, >0)` have two FOV bits set.
+    // - For Double Q-Factor:
+    //   - FOV Node at `(dpri, dsec)` = `(rFOV, 0)` has one FOV bit set.
+    //   - FOV Nodes at `(dpri, dsec)` = `(rFOV, >0)` have _at least two_ FOV bits set.
+    #[test]
+    fn fov_nodes_bits_set_q16() {
+        let rfov = FovRadius::R16;
+        let qsingle = QFactor::Single;
+        let fov_lines_16s = FovLines::new(rfov, qsingle);
+        let fov_octant_16s =
+            build_fov_nodes_q16(rfov, &fov_lines_16s, FovShape::default()).unwrap();
+
+        for fov_node in fov_octant_16s.iter() {
+            if fov_node.dpri == 16 {
+                let body_ct = fov_node.body.count_ones();
+                if fov_node.dsec == 0 {
+                    assert_eq!(body_ct, 1);
+                } else {
+                    assert!(body_ct > 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fov_nodes_reject_nan_adjust() {
+        let fov_lines = FovLines::new(FovRadius::R16, QFactor::Single);
+        let nan = FovShape::Circle { adjust: f64::NAN };
+        let err = build_fov_nodes_q16(FovRadius::R16, &fov_lines, nan).unwrap_err();
+        assert_eq!(err.to_string(), "circle adjustment must be finite, got NaN");
+    }
+
+    // Shaped sets cull to their silhouette at build time, as query-time shapes do.
+    #[test]
+    fn shaped_set_silhouettes() {
+        let (rfov, qfactor) = (FovRadius::R16, QFactor::Single);
+        let map = crate::maps::TileMap::new(33, 33).unwrap();
+        let origin = Coords::new(16, 16);
+        let circle = FovSet16::new(rfov, qfactor, FovShape::default()).unwrap();
+
+        for shape in [FovShape::Diamond, FovShape::Octagon] {
+            let shaped = FovSet16::new(rfov, qfactor, shape).unwrap();
+            assert!(shaped.capacity() < circle.capacity(), "{shape:?}");
+            assert_eq!(shaped.compute(&map, origin), circle.compute_shaped(&map, origin, shape));
+        }
+        let square = FovSet16::new(rfov, qfactor, FovShape::Square).unwrap();
+        assert_eq!(square.compute(&map, origin).len(), 33 * 33);
+    }
+
+    // Batched builds match separate builds node for node, for each radius, Q-Factor and shape.
+    #[test]
+    fn batched_sets_match_separate_builds() {
+        fn same<B: FovBits>(batched: &FovSet<B>, rfov: FovRadius, q: QFactor, shape: FovShape) {
+            let separate = FovSet::<B>::new(rfov, q, shape).unwrap();
+            assert_eq!(batched.radius(), rfov);
+            assert_eq!(batched.octant(Octant::O1), separate.octant(Octant::O1), "{rfov:?}");
+        }
+
+        let (r8, r16, r32) = (FovRadius::R8, FovRadius::R16, FovRadius::R32);
+        let batches = [
+            (QFactor::Single, FovShape::default(), vec![r32, r8, r16, r8]),
+            (QFactor::Single, FovShape::Circle { adjust: -0.5 }, vec![r8, r16, r32]),
+            (QFactor::Double, FovShape::Diamond, vec![r32, r16]),
+        ];
+        for (qfactor, shape, radii) in batches {
+            let sets = build_fov_sets(&radii, qfactor, shape).unwrap();
+            assert_eq!(sets.len(), radii.len());
+            for (&rfov, set) in radii.iter().zip(sets.iter()) {
+                match set {
+                    FovSetAny::Simple8(s) => same(s, rfov, qfactor, shape),
+                    FovSetAny::Simple16(s) => same(s, rfov, qfactor, shape),
+                    FovSetAny::Simple32(s) => same(s, rfov, qfactor, shape),
+                    FovSetAny::Simple64(s) => same(s, rfov, qfactor, shape),
+                    _ => panic!("unexpected set type for {rfov:?}!"),
+                }
+            }
+        }
+        let sets = build_fov_sets(&[FovRadius::R64], QFactor::Single, FovShape::Octagon).unwrap();
+        let FovSetAny::Simple64(set) = &sets[0] else { panic!("R64 must build a FovSet64!") };
+        same(set, FovRadius::R64, QFactor::Single, FovShape::Octagon);
+
+        let ellipse = FovShape::Ellipse { rx: 8.5, ry: 4.5 };
+        assert!(build_fov_sets(&[FovRadius::R8], QFactor::Single, ellipse).is_err());
+        assert!(build_fov_sets(&[r16, r8], QFactor::Double, FovShape::default()).is_err());
+    }
+
+    #[test]
+    fn shaped_set_rejects_ellipse() {
+        let ellipse = FovShape::Ellipse { rx: 16.5, ry: 8.5 };
+        let err = FovSet16::new(FovRadius::R16, QFactor::Single, ellipse).err().unwrap();
+        assert!(err.to_string().starts_with("FovSet16 requires a shape symmetric across octants"));
+    }
+
+    // Each octant maps its nodes into its own eighth of the world, around `origin`.
+    #[test]
+    fn world_coords_of_octants() {
+        let fov_lines = FovLines::new(FovRadius::R16, QFactor::Single);
+        let nodes = build_fov_nodes_q16(FovRadius::R16, &fov_lines, FovShape::default()).unwrap();
+        let origin = Coords::new(100, 50);
+        // Node 2 is `(dpri, dsec) = (1, 1)`; the last node is `(16, 16)`
+        let last = nodes.len() - 1;
+        assert_eq!((nodes[2].dpri, nodes[2].dsec), (1, 1));
+
+        for octant in Octant::ALL {
+            let fov_octant = FovOctant16::new(&nodes, octant);
+            assert_eq!(fov_octant.octant(), octant);
+            assert_eq!(fov_octant.world_coords_of(0, origin), origin);
+
+            for ix in [1, 2, last] {
+                let node = &nodes[ix];
+                let (dx, dy) = octant.dpds_to_dxdy(node.dpri as u16, node.dsec as u16);
+                let expected = Coords::new(100 + dx as i32, 50 + dy as i32);
+                assert_eq!(fov_octant.world_coords_of(ix, origin), expected);
+            }
+        }
+
+        let east = FovOctant16::new(&nodes, Octant::O1);
+        let south = FovOctant16::new(&nodes, Octant::O7);
+        assert_eq!(east.world_coords_of(1, origin), Coords::new(101, 50));
+        assert_eq!(south.world_coords_of(1, origin), Coords::new(100, 49));
+    }
+
+    // Radius bands partition the octant's nodes, in order, by `dpri`.
+    #[test]
+    fn bands_by_radius() {
+        let fov_lines = FovLines::new(FovRadius::R16, QFactor::Single);
+        let nodes = build_fov_nodes_q16(FovRadius::R16, &fov_lines, FovShape::default()).unwrap();
+        let fov_octant = FovOctant16::new(&nodes, Octant::O1);
+
+        let mut total = 0;
+        for r in 0..=16 {
+            let band = fov_octant.band(r);
+            // Circular culling trims the corners of outer bands
+            assert!(!band.is_empty() && band.len() <= r + 1);
+            assert!(band.iter().all(|n| n.dpri as usize == r));
+            total += band.len();
+        }
+        assert_eq!(total, fov_octant.len());
+    }
+
+    /// Returns the FOV bits of `fov_lines` crossing any of `shape`, offset to `(dpri, dsec)`.
+    fn shape_bits(fov_lines: &FovLines, shape: &[Line], dpri: u8, dsec: u8) -> u64 {
+        let shape: Vec<_> = shape.iter().map(|l| l.shifted_by(dpri as f64, dsec as f64)).collect();
+        fov_lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| shape.iter().any(|s| line.intersects(*s)))
+            .fold(0, |bits, (i, _)| bits | 1 << i)
+    }
+
+    /// Returns `true` if the set bits of `bits` form one contiguous run.
+    fn contiguous(bits: u64) -> bool {
+        let shifted = bits >> bits.trailing_zeros();
+        bits != 0 && shifted & (shifted + 1) == 0
+    }
+
+    // Builder invariants per radius band, for Single and Double Q and every symmetric shape:
+    // - Node bits are one contiguous run of FOV lines (lines are sorted by slope).
+    // - Along a band, runs move up (never down) as `dsec` grows.
+    // - Every FOV line crosses every band that culling leaves whole.
+    // - `build_fov_nodes_q16` agrees with the generic shape test for Single Q.
+    #[test]
+    fn band_bit_invariants() {
+        let (body_1, body_2) = body_lines();
+        for qfactor in [QFactor::Single, QFactor::Double] {
+            let fov_lines = FovLines::new(FovRadius::R16, qfactor);
+            let all = (1u64 << fov_lines.len()) - 1;
+
+            let circles = [0.0, 0.5, 1.0].map(|adjust| FovShape::Circle { adjust });
+            let others = [FovShape::Square, FovShape::Diamond, FovShape::Octagon];
+            for shape in circles.into_iter().chain(others) {
+                for dpri in 1..=16u8 {
+                    let band: Vec<_> = (0..=dpri)
+                        .filter(|&dsec| shape.contains(16, Delta::new(dpri as i32, dsec as i32)))
+                        .map(|dsec| shape_bits(&fov_lines, &[body_1, body_2], dpri, dsec))
+                        .collect();
+
+                    assert!(band.iter().all(|&bits| contiguous(bits)));
+                    let lowest: Vec<_> = band.iter().map(|bits| bits.trailing_zeros()).collect();
+                    assert!(lowest.windows(2).all(|w| w[0] <= w[1]));
+                    if band.len() == dpri as usize + 1 {
+                        let union = band.iter().fold(0, |a, b| a | b);
+                        assert_eq!(union, all, "band {dpri} of {shape:?}");
+                    }
+                }
+
+                if qfactor == QFactor::Single {
+                    // Skips the origin node, which has all bits set by convention
+                    let nodes = build_fov_nodes_q16(FovRadius::R16, &fov_lines, shape).unwrap();
+                    let inside = |&(dp, ds): &(i32, i32)| shape.contains(16, Delta::new(dp, ds));
+                    let offsets = (0..=16).flat_map(|dp| (0..=dp).map(move |ds| (dp, ds)));
+                    assert_eq!(nodes.len(), offsets.filter(inside).count(), "{shape:?}");
+                    for node in nodes.iter().skip(1) {
+                        let (dp, ds) = (node.dpri, node.dsec);
+                        let expected = shape_bits(&fov_lines, &[body_1, body_2], dp, ds);
+                        assert_eq!(node.body as u64, expected);
+                    }
+                }
+            }
+        }
+    }
+
+    // Wall lines are tile edges: in octant 1, `wall_n` is the bottom edge of the tile
+    // above, and `wall_w` is the left edge of the body, so their bits match.
+    #[test]
+    fn wall_shape_bits() {
+        use crate::fov::{wall_n_line, wall_w_line};
+
+        let (body_left, body_bottom) = body_lines();
+        for qfactor in [QFactor::Single, QFactor::Double] {
+            let fov_lines = FovLines::new(FovRadius::R16, qfactor);
+            for dpri in 1..=16u8 {
+                for dsec in 0..dpri {
+                    let wall_n = shape_bits(&fov_lines, &[wall_n_line(Octant::O1)], dpri, dsec);
+                    let above = shape_bits(&fov_lines, &[body_bottom], dpri, dsec + 1);
+                    let wall_w = shape_bits(&fov_lines, &[wall_w_line(Octant::O1)], dpri, dsec);
+                    let left = shape_bits(&fov_lines, &[body_left], dpri, dsec);
+
+                    assert_eq!(wall_n, above);
+                    assert_eq!(wall_w, left);
+                }
+            }
+        }
+    }
+
+    // Serialized FOV sets and maps load back unchanged, and answer the same queries. Octants
+    // share one node list, so it is written once and shared again on load.
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        use crate::{fov::NearestFirst, maps::TileMap, simple::fovcalc::get_visible_grid};
+
+        let mut fov_set =
+            FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        fov_set.set_origin_rules(OriginRules { visible: false, ..OriginRules::default() });
+        fov_set.set_traversal_order(&NearestFirst);
+        fov_set.set_lod_distance(Some(12));
+        let mut map = TileMap::random(33, 33, 0.2, 7).unwrap();
+        map.set_wall_hp(Coords::new(3, 4), 10).unwrap();
+        let origin = Coords::new(16, 16);
+
+        let json = serde_json::to_string(&fov_set).unwrap();
+        assert_eq!(json.matches("dpri").count(), fov_set.octant(Octant::O1).len());
+        let loaded: FovSet16 = serde_json::from_str(&json).unwrap();
+        let loaded_map: TileMap = serde_json::from_str(&serde_json::to_string(&map).unwrap())
+            .unwrap();
+        assert_eq!((&loaded_map, loaded_map.generation()), (&map, map.generation()));
+        assert_eq!(loaded.checksum(), fov_set.checksum());
+        assert_eq!(loaded.origin_rules(), fov_set.origin_rules());
+        assert_eq!(loaded.lod_distance(), Some(12));
+        for octant in Octant::ALL {
+            assert_eq!(loaded.octant(octant), fov_set.octant(octant));
+            assert!(loaded.octant(octant).shares_nodes(loaded.octant(Octant::O1)));
+        }
+        assert_eq!(
+            get_visible_grid(&loaded, &loaded_map, origin, 16),
+            get_visible_grid(&fov_set, &map, origin, 16)
+        );
+
+        let json = serde_json::to_string(&(FovRadius::R32, QFactor::Double, Octant::O5)).unwrap();
+        assert_eq!(json, r#"["R32","Double","O5"]"#);
+    }
+
+    // Malformed FOV sets and maps are rejected on load, rather than panicking in queries.
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_rejects_malformed() {
+        use crate::{maps::TileMap, standard};
+        use serde_json::{json, Value};
+
+        /// Returns the error loading `value` as a `T`, which must fail.
+        fn error<T: serde::de::DeserializeOwned>(value: &Value) -> String {
+            let loaded = serde_json::from_value::<T>(value.clone());
+            loaded.err().expect("malformed payload must be rejected!").to_string()
+        }
+        let fov_set = FovSet8::new(FovRadius::R8, QFactor::Single, FovShape::default()).unwrap();
+        let valid = serde_json::to_value(&fov_set).unwrap();
+        assert!(serde_json::from_value::<FovSet8>(valid.clone()).is_ok());
+
+        let mut radius = valid.clone();
+        radius["rfov"] = json!("R16");
+        assert!(error::<FovSet8>(&radius).contains("FovSet8 does not support R16"));
+        assert!(error::<FovSet16>(&valid).contains("FovSet16 does not support R8"));
+        let wide = FovSet32::new(FovRadius::R32, QFactor::Single, FovShape::default()).unwrap();
+        let mut past = serde_json::to_value(&wide).unwrap();
+        past["rfov"] = json!("R16");
+        assert!(error::<FovSet32>(&past).contains("reach radius 32, past R16"));
+
+        let mut skipped = valid.clone();
+        skipped["nodes"]["nodes"].as_array_mut().unwrap().remove(1);
+        assert!(error::<FovSet8>(&skipped).contains("storage order"));
+        let mut empty = valid.clone();
+        empty["nodes"]["nodes"] = json!([]);
+        assert!(error::<FovSet8>(&empty).contains("storage order"));
+        let mut order = valid.clone();
+        let n = fov_set.octant(Octant::O1).len();
+        order["nodes"]["order"] = json!((0..n).rev().collect::<Vec<_>>());
+        assert!(error::<FovSet8>(&order).contains("blockers first"));
+        let mut lod = valid.clone();
+        lod["nodes"]["lod_distance"] = json!(0);
+        assert!(error::<FovSet8>(&lod).contains("at least 1"));
+
+        let standard =
+            standard::FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::Square).unwrap();
+        let mut swapped = serde_json::to_value(&standard).unwrap();
+        swapped["octants"].as_array_mut().unwrap().swap(0, 1);
+        assert!(error::<standard::FovSet16>(&swapped).contains("expected O1"));
+
+        let mut map = serde_json::to_value(TileMap::new(4, 3).unwrap()).unwrap();
+        map["width"] = json!(5);
+        assert!(error::<TileMap>(&map).contains("doesn't match a 5x3 map"));
+        map["width"] = json!(usize::MAX);
+        assert!(error::<TileMap>(&map).contains("doesn't match"));
+        map["width"] = json!(4);
+        map["wall_hp"] = json!({ "12": [1, 1] });
+        assert!(error::<TileMap>(&map).contains("tile 12"));
+        map["wall_hp"] = json!({ "11": [2, 1] });
+        assert!(error::<TileMap>(&map).contains("2/1"));
+    }
+
+    // Q8 nodes match the Q16 builder at radius 8, with edge nodes as in `fov_nodes_bits_set_q16`.
+    #[test]
+    fn fov_nodes_bits_set_q8() {
+        let fov_lines = FovLines::new(FovRadius::R8, QFactor::Single);
+        let nodes = build_fov_nodes::<u8>(FovRadius::R8, &fov_lines, FovShape::default()).unwrap();
+        let wide = build_fov_nodes_q16(FovRadius::R8, &fov_lines, FovShape::default()).unwrap();
+
+        assert_eq!(nodes.len(), wide.len());
+        assert_eq!(nodes[0].body, u8::MAX);
+        for (node, wide) in nodes.iter().zip(wide.iter()) {
+            assert_eq!(node.body as u16, wide.body & 0xFF);
+            assert_eq!((node.dpri, node.dsec), (wide.dpri, wide.dsec));
+            if node.dpri == 8 {
+                match node.dsec {
+                    0 => assert_eq!(node.body.count_ones(), 1),
+                    _ => assert!(node.body.count_ones() > 1),
+                }
+            }
+        }
+        assert!(nodes.iter().all(|n| n.dpri <= 8));
+        assert_eq!(size_of::<FovNode8>(), 3);
+    }
+
+    /// Asserts that Single Q-Factor nodes of width `B` cover `rfov` with all bits in use, with
+    /// edge nodes as in `fov_nodes_bits_set_q16`.
+    fn assert_full_width<B: FovBits>(rfov: FovRadius) {
+        let fov_lines = FovLines::new(rfov, QFactor::Single);
+        let nodes = build_fov_nodes::<B>(rfov, &fov_lines, FovShape::default()).unwrap();
+        let r = rfov.to_int();
+
+        assert_eq!(nodes[0].body, B::MAX);
+        assert_eq!(nodes.iter().skip(1).fold(B::ZERO, |bits, n| bits | n.body), B::MAX);
+        for node in nodes.iter().filter(|n| n.dpri == r) {
+            match node.dsec {
+                0 => assert_eq!(node.body.count_ones(), 1),
+                _ => assert!(node.body.count_ones() > 1),
+            }
+        }
+        assert!(nodes.iter().all(|n| n.dpri <= r && n.dsec <= n.dpri));
+    }
+
+    // Q32 to Q128 nodes cover radius 32 to 128 with all bits in use.
+    #[test]
+    fn fov_nodes_bits_set_wide() {
+        assert_full_width::<u32>(FovRadius::R32);
+        assert_full_width::<u64>(FovRadius::R64);
+        assert_full_width::<u128>(FovRadius::R128);
+        assert_eq!(size_of::<FovNode32>(), 8);
+    }
+
+    /// Asserts that `fine` (Q-Factor 2) covers the same tiles as `coarse` (Q-Factor 1, at the
+    /// same radius), with at least as many bits per node.
+    fn assert_finer<A: FovBits, B: FovBits>(fine: &FovSet<A>, coarse: &FovSet<B>) {
+        let (fine, coarse) = (fine.octant(Octant::O1), coarse.octant(Octant::O1));
+        assert_eq!(fine.radius(), coarse.radius());
+        assert_eq!(fine.len(), coarse.len());
+        assert!(fine.iter().zip(coarse.iter()).all(|(a, b)| {
+            (a.dpri, a.dsec) == (b.dpri, b.dsec) && a.body.count_ones() >= b.body.count_ones()
+        }));
+    }
+
+    // Every tier builds the octants its radius calls for; Q-Factor 2 refines the tier below.
+    #[test]
+    fn fov_set_tiers() {
+        let fov_set = FovSet8::new(FovRadius::R8, QFactor::Single, FovShape::default()).unwrap();
+        let fov_octant = fov_set.octant(Octant::O3);
+        assert_eq!(fov_octant.octant(), Octant::O3);
+        assert_eq!(fov_set.capacity(), fov_octant.len() * 8);
+        assert_eq!(fov_octant.max_node_index(0), 0);
+        assert_eq!(fov_octant.max_node_index(8), fov_octant.len() - 1);
+        assert!(fov_octant.iter().take(fov_octant.max_node_index(4) + 1).all(|n| n.dpri <= 4));
+        assert_eq!(fov_octant.world_coords_of(1, Coords::new(10, 10)), Coords::new(10, 11));
+        assert!(fov_set.node_bytes() < fov_set.capacity() * 4);
+
+        let fov_set =
+            FovSet128::new(FovRadius::R128, QFactor::Single, FovShape::default()).unwrap();
+        let fov_octant = fov_set.octant(Octant::O3);
+        assert_eq!(fov_octant.radius(), 128);
+        assert_eq!(fov_octant.max_node_index(128), fov_octant.len() - 1);
+        assert!(fov_octant.iter().take(fov_octant.max_node_index(64) + 1).all(|n| n.dpri <= 64));
+        assert_eq!(fov_octant.world_coords_of(1, Coords::new(200, 200)), Coords::new(200, 201));
+
+        let (r16, r32, r64) = (FovRadius::R16, FovRadius::R32, FovRadius::R64);
+        let (single, double) = (QFactor::Single, QFactor::Double);
+        let q16 = FovSet16::new(r16, single, FovShape::default()).unwrap();
+        let q32 = FovSet32::new(r32, single, FovShape::default()).unwrap();
+        let q64 = FovSet64::new(r64, single, FovShape::default()).unwrap();
+        assert_finer(&FovSet32::new(r16, double, FovShape::default()).unwrap(), &q16);
+        assert_finer(&FovSet64::new(r32, double, FovShape::default()).unwrap(), &q32);
+        assert_finer(&FovSet128::new(r64, double, FovShape::default()).unwrap(), &q64);
+    }
+
+    #[test]
+    fn fov_set_rejects_other_q() {
+        let err = FovSet8::new(FovRadius::R16, QFactor::Single, FovShape::default()).err().unwrap();
+        assert_eq!(err.to_string(), "FovSet8 does not support R16 with Single Q-Factor");
+    }
+
+    // Prebuilt nodes must start at the origin, follow storage order and stay within radius.
+    #[test]
+    fn from_nodes_rejects_malformed() {
+        let node = |dpri, dsec| FovNode { body: u16::MAX, dpri, dsec };
+        let error = |nodes: &[FovNode<u16>]| {
+            let err = FovSet16::from_nodes(FovRadius::R16, QFactor::Single, nodes).err().unwrap();
+            assert!(matches!(err, FovError::InvalidData(_)));
+            err.to_string()
+        };
+
+        assert!(error(&[]).contains("storage order"));
+        assert!(error(&[node(3, 9)]).contains("storage order"));
+        assert!(error(&[node(0, 0), node(1, 0), node(0, 0)]).contains("storage order"));
+        assert!(error(&[node(0, 0), node(1, 1), node(1, 0)]).contains("storage order"));
+        assert!(error(&[node(0, 0), node(2, 0)]).contains("storage order"));
+
+        let mut far: Vec<_> = (0..=17).flat_map(|p| (0..=p).map(move |s| node(p, s))).collect();
+        assert!(error(&far).contains("reach radius 17, past R16"));
+        far.retain(|n| n.dpri <= 16);
+        assert!(FovSet16::from_nodes(FovRadius::R16, QFactor::Single, &far).is_ok());
+        assert!(FovSet16::from_nodes(FovRadius::R16, QFactor::Single, &far[..3]).is_ok());
+    }
+}
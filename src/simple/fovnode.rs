@@ -0,0 +1,765 @@
+//! Generic _Simple_ FOV nodes, octants and builders for FOV Visualization - Rust (2D).
+//!
+//! Notes:
+//! - Every Q tier stores the same nodes and octants and builds them the same way; only the
+//!   width of node bits differs. `FovBits` abstracts that width (`u8` to `u128`), so
+//!   `FovNode`, `FovOctant`, `FovBlock` and `build_fov_nodes` serve every tier.
+//! - Each tier names its instances next to the generic `FovSet`, e.g. `FovNode32 = FovNode<u32>`.
+//! - Every octant supports custom traversal orders and LOD blocks (see `FovSet`).
+//! - Simple FOV octants differ only in orientation, so node data is reference counted:
+//!   `FovOctant::for_octant` views the same nodes (and orders and blocks) from another
+//!   octant, and a whole FOV set holds one copy of its nodes rather than eight.
+//! - Builds are silent. `build_fov_nodes_with` (and each FOV set's `with_progress`) reports
+//!   progress to a `ProgressSink` once per radius band, e.g. to drive a progress bar.
+//! - With the `rayon` feature, node FOV bits (one line intersection test per FOV line and
+//!   node) are computed in parallel within each radius band (`par_extend`); bands run in
+//!   order, so progress is reported between them. Results are identical, in the same order.
+
+use std::fmt::Debug;
+use std::ops::{BitAnd, BitOr, BitOrAssign, Not};
+use std::sync::Arc;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{
+    error::FovError,
+    fov::{body_lines, FovLines, FovShape},
+    maps::Coords,
+    math::{Delta, Line},
+    FovRadius, Octant,
+};
+
+/// Receives progress of FOV node builds, e.g. to drive a GUI progress bar.
+///
+/// Implemented for closures `FnMut(done, total)`.
+pub trait ProgressSink {
+    /// Called once per radius band built: `done` of `total` nodes have their FOV bits.
+    fn progress(&mut self, done: usize, total: usize);
+}
+
+impl<F: FnMut(usize, usize)> ProgressSink for F {
+    fn progress(&mut self, done: usize, total: usize) {
+        self(done, total)
+    }
+}
+
+/// Unsigned integer holding one bit per FOV line of a node: its width is the Q-value.
+pub trait FovBits:
+    Copy
+    + Eq
+    + Debug
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitOrAssign
+    + Not<Output = Self>
+{
+    /// Number of FOV bits held, i.e. the Q-value.
+    const BITS: u32;
+    /// No bits set.
+    const ZERO: Self;
+    /// All `BITS` bits set.
+    const MAX: Self;
+    /// Returns the lowest `BITS` bits of `mask`.
+    fn from_mask(mask: u128) -> Self;
+    /// Returns the bits, widened to `u128`.
+    fn to_mask(self) -> u128;
+    /// Returns the number of set bits.
+    fn count_ones(self) -> u32;
+}
+
+impl FovBits for u8 {
+    const BITS: u32 = u8::BITS;
+    const ZERO: Self = 0;
+    const MAX: Self = u8::MAX;
+    fn from_mask(mask: u128) -> Self {
+        mask as u8
+    }
+    fn to_mask(self) -> u128 {
+        self as u128
+    }
+    fn count_ones(self) -> u32 {
+        u8::count_ones(self)
+    }
+}
+
+impl FovBits for u16 {
+    const BITS: u32 = u16::BITS;
+    const ZERO: Self = 0;
+    const MAX: Self = u16::MAX;
+    fn from_mask(mask: u128) -> Self {
+        mask as u16
+    }
+    fn to_mask(self) -> u128 {
+        self as u128
+    }
+    fn count_ones(self) -> u32 {
+        u16::count_ones(self)
+    }
+}
+
+impl FovBits for u32 {
+    const BITS: u32 = u32::BITS;
+    const ZERO: Self = 0;
+    const MAX: Self = u32::MAX;
+    fn from_mask(mask: u128) -> Self {
+        mask as u32
+    }
+    fn to_mask(self) -> u128 {
+        self as u128
+    }
+    fn count_ones(self) -> u32 {
+        u32::count_ones(self)
+    }
+}
+
+impl FovBits for u64 {
+    const BITS: u32 = u64::BITS;
+    const ZERO: Self = 0;
+    const MAX: Self = u64::MAX;
+    fn from_mask(mask: u128) -> Self {
+        mask as u64
+    }
+    fn to_mask(self) -> u128 {
+        self as u128
+    }
+    fn count_ones(self) -> u32 {
+        u64::count_ones(self)
+    }
+}
+
+impl FovBits for u128 {
+    const BITS: u32 = u128::BITS;
+    const ZERO: Self = 0;
+    const MAX: Self = u128::MAX;
+    fn from_mask(mask: u128) -> Self {
+        mask
+    }
+    fn to_mask(self) -> u128 {
+        self
+    }
+    fn count_ones(self) -> u32 {
+        u128::count_ones(self)
+    }
+}
+
+/// One of eight FOV octants, comprised of FOV nodes with `B` FOV bits.
+///
+/// Notes:
+/// - for Simple FOV, octants differ only in dx/dy values. The content of each
+///   FOV node is the same.
+/// - `octant` maps node `(dpri, dsec)` offsets back to world `(dx, dy)` offsets.
+/// - `node_indexes` holds the highest node index for a given radius (`r=0` to `r=rfov`).
+/// - Nodes are ordered by `dpri`, so each radius band is a contiguous slice (see `band`).
+/// - `order` holds a custom visiting order of node indexes, if any (see `traversal`).
+/// - `blocks` holds the merged nodes at or beyond `lod_distance`, and is empty without LOD.
+/// - Node data is shared with every octant created by `for_octant`; cloning is cheap.
+/// - Serialized as its nodes and settings only (see `OctantData`), and checked on load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "OctantData<B>",
+        into = "OctantData<B>",
+        bound(serialize = "B: FovBits + serde::Serialize"),
+        bound(deserialize = "B: FovBits + serde::Deserialize<'de>")
+    )
+)]
+pub struct FovOctant<B> {
+    octant: Octant,
+    nodes: Arc<[FovNode<B>]>,
+    node_indexes: Arc<[usize]>,
+    order: Option<Arc<[usize]>>,
+    lod_distance: Option<usize>,
+    blocks: Arc<[FovBlock<B>]>,
+}
+
+impl<B: FovBits> FovOctant<B> {
+    /// Creates a new `FovOctant` for `octant`. Panics if `nodes` is empty.
+    pub fn new(nodes: &[FovNode<B>], octant: Octant) -> Self {
+        assert!(!nodes.is_empty(), "FovOctant requires at least the origin node!");
+        let mut node_indexes = Vec::new();
+
+        for (i, node) in nodes.iter().enumerate().skip(1) {
+            if node.dpri as usize > node_indexes.len() {
+                node_indexes.push(i - 1);
+            }
+        }
+
+        // Highest node index for max radius is always the last node
+        node_indexes.push(nodes.len() - 1);
+
+        Self {
+            octant,
+            nodes: nodes.into(),
+            node_indexes: node_indexes.into(),
+            order: None,
+            lod_distance: None,
+            blocks: Arc::new([]),
+        }
+    }
+    /// Returns an FOV octant for `octant` sharing this octant's nodes, order and blocks.
+    pub fn for_octant(&self, octant: Octant) -> Self {
+        Self {
+            octant,
+            ..self.clone()
+        }
+    }
+    /// Returns `true` if `other` shares this octant's node storage (see `for_octant`).
+    pub fn shares_nodes(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.nodes, &other.nodes)
+    }
+    /// Returns the octant this FOV octant covers.
+    pub fn octant(&self) -> Octant {
+        self.octant
+    }
+    /// Returns the world coordinates of node `node_ix`, for an observer at `origin`.
+    ///
+    /// Panics if `node_ix` is out of range.
+    pub fn world_coords_of(&self, node_ix: usize, origin: Coords) -> Coords {
+        let node = &self.nodes[node_ix];
+        origin.offset(self.octant.dpds_to_delta(node.dpri as u16, node.dsec as u16))
+    }
+    /// Returns the FOV node with index `node_ix`. Panics if `node_ix` is out of range.
+    pub fn node(&self, node_ix: usize) -> &FovNode<B> {
+        &self.nodes[node_ix]
+    }
+    /// Returns the index of the node at `(dpri, dsec)`, or `None` if the octant has no such
+    /// node (culled, or past the FOV radius).
+    pub fn node_index(&self, dpri: u16, dsec: u16) -> Option<usize> {
+        let first = match dpri {
+            0 => 0,
+            _ => *self.node_indexes.get(dpri as usize - 1)? + 1,
+        };
+        let ix = first + dsec as usize;
+        let last = *self.node_indexes.get(dpri as usize)?;
+        (ix <= last && self.nodes[ix].dsec as u16 == dsec).then_some(ix)
+    }
+    /// Returns an iterator over node indexes out to `radius`, in traversal order.
+    ///
+    /// This is storage order unless a custom order was set with
+    /// `FovSet::set_traversal_order`.
+    pub fn traversal(&self, radius: usize) -> impl Iterator<Item = usize> + '_ {
+        let max_ix = self.max_node_index(radius);
+        let (default, custom) = match &self.order {
+            Some(order) => (None, Some(order.iter().copied().filter(move |&ix| ix <= max_ix))),
+            None => (Some(0..=max_ix), None),
+        };
+        default.into_iter().flatten().chain(custom.into_iter().flatten())
+    }
+    /// Sets a custom traversal order of node indexes, or `None` for storage order.
+    pub(crate) fn set_order(&mut self, order: Option<Arc<[usize]>>) {
+        self.order = order;
+    }
+    /// Returns an iterator over the FOV nodes in the octant.
+    pub fn iter(&self) -> std::slice::Iter<'_, FovNode<B>> {
+        self.nodes.iter()
+    }
+    /// Returns the number of nodes in the octant.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    /// Returns `true` if the octant holds no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+    /// Returns the distance from which the octant's nodes are merged into blocks, if any.
+    pub fn lod_distance(&self) -> Option<usize> {
+        self.lod_distance
+    }
+    /// Merges nodes at or beyond `distance` into 2x2 blocks, or drops them for `None`.
+    ///
+    /// Panics if `distance` is `Some(0)`: the origin node is never merged.
+    pub fn set_lod_distance(&mut self, distance: Option<usize>) {
+        assert!(distance != Some(0), "LOD distance must be at least 1!");
+        self.lod_distance = distance;
+        self.blocks = match distance {
+            Some(distance) => build_fov_blocks(&self.nodes, distance).into(),
+            None => Arc::new([]),
+        };
+    }
+    /// Returns the octant's 2x2 blocks, nearest first. Empty unless LOD is on.
+    pub fn blocks(&self) -> &[FovBlock<B>] {
+        &self.blocks
+    }
+    /// Returns the largest radius covered by the octant's nodes.
+    pub fn radius(&self) -> usize {
+        self.node_indexes.len() - 1
+    }
+    /// Returns the maximum FOV node index for a given radius.
+    pub fn max_node_index(&self, radius: usize) -> usize {
+        assert!(radius <= self.radius(), "radius must be <= {}!", self.radius());
+        self.node_indexes[radius]
+    }
+    /// Returns the nodes at radius band `r`, i.e. those with `dpri == r`.
+    pub fn band(&self, r: usize) -> &[FovNode<B>] {
+        assert!(r <= self.radius(), "radius must be <= {}!", self.radius());
+        let first = match r {
+            0 => 0,
+            _ => self.node_indexes[r - 1] + 1,
+        };
+        &self.nodes[first..=self.node_indexes[r]]
+    }
+}
+
+/// Serialized form of an `FovOctant`: node indexes and blocks are rebuilt on load.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct OctantData<B> {
+    octant: Octant,
+    nodes: Vec<FovNode<B>>,
+    order: Option<Vec<usize>>,
+    lod_distance: Option<usize>,
+}
+
+#[cfg(feature = "serde")]
+impl<B: FovBits> From<FovOctant<B>> for OctantData<B> {
+    fn from(fov_octant: FovOctant<B>) -> Self {
+        Self {
+            octant: fov_octant.octant,
+            nodes: fov_octant.nodes.to_vec(),
+            order: fov_octant.order.map(|order| order.to_vec()),
+            lod_distance: fov_octant.lod_distance,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<B: FovBits> TryFrom<OctantData<B>> for FovOctant<B> {
+    type Error = String;
+
+    /// Rejects nodes out of storage order, traversal orders that could visit a node before
+    /// one of its blockers, and an LOD distance of `0`.
+    fn try_from(data: OctantData<B>) -> Result<Self, Self::Error> {
+        if !in_storage_order(data.nodes.iter().map(|n| (n.dpri, n.dsec))) {
+            return Err("FOV octant nodes out of storage order".to_string());
+        }
+        if data.lod_distance == Some(0) {
+            return Err("FOV octant LOD distance must be at least 1".to_string());
+        }
+        let mut fov_octant = FovOctant::new(&data.nodes, data.octant);
+        if let Some(order) = data.order {
+            let offsets: Vec<_> =
+                fov_octant.iter().map(|n| (n.dpri as u16, n.dsec as u16)).collect();
+            if !crate::fov::is_valid_order(&offsets, &order) {
+                return Err("FOV octant traversal order must visit blockers first".to_string());
+            }
+            fov_octant.set_order(Some(order.into()));
+        }
+        fov_octant.set_lod_distance(data.lod_distance);
+        Ok(fov_octant)
+    }
+}
+
+/// Returns `true` if node `(dpri, dsec)` offsets follow storage order: the origin first,
+/// then outward by `dpri` with no band skipped, each band running from `dsec` 0 up to at
+/// most `dpri` with no gaps. Every builder culls to such a layout, and `FovOctant` lookups
+/// rely on it.
+pub(crate) fn in_storage_order(mut offsets: impl Iterator<Item = (u8, u8)>) -> bool {
+    if offsets.next() != Some((0, 0)) {
+        return false;
+    }
+    let mut last = (0u16, 0u16);
+    offsets.all(|(dpri, dsec)| {
+        let (dpri, dsec) = (dpri as u16, dsec as u16);
+        let next_band = dpri == last.0 + 1 && dsec == 0;
+        let same_band = dpri == last.0 && dsec == last.1 + 1 && dsec <= dpri;
+        last = (dpri, dsec);
+        next_band || same_band
+    })
+}
+
+/// Node in an FOV octant representing a single tile, with one FOV bit per FOV line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FovNode<B> {
+    pub body: B,
+    pub dpri: u8,
+    pub dsec: u8,
+}
+
+/// Block of up to 2x2 FOV nodes, merged for level of detail FOV.
+///
+/// Covers `dpri..=dpri + 1` and `dsec..=dsec + 1`; `members` has bit `2 * pri + sec` set for
+/// each offset that holds a node, and `body` is the union of those nodes' bits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FovBlock<B> {
+    pub body: B,
+    pub dpri: u8,
+    pub dsec: u8,
+    pub members: u8,
+}
+
+impl<B> FovBlock<B> {
+    /// Returns the `(dpri, dsec)` of each node in the block.
+    pub fn tiles(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+        (0..4u8)
+            .filter(|i| self.members & (1 << i) != 0)
+            .map(|i| (self.dpri + i / 2, self.dsec + i % 2))
+    }
+}
+
+/// Merges `nodes` at or beyond `distance` into 2x2 blocks, in storage order.
+///
+/// Block rows start at `distance`, so every block lies entirely beyond it.
+pub fn build_fov_blocks<B: FovBits>(nodes: &[FovNode<B>], distance: usize) -> Vec<FovBlock<B>> {
+    let mut blocks: Vec<FovBlock<B>> = Vec::new();
+
+    for node in nodes.iter().filter(|n| n.dpri as usize >= distance) {
+        let dpri = (distance + (node.dpri as usize - distance) / 2 * 2) as u8;
+        let dsec = node.dsec / 2 * 2;
+        let member = 1 << ((node.dpri - dpri) * 2 + node.dsec - dsec);
+
+        match blocks.iter_mut().rev().find(|b| (b.dpri, b.dsec) == (dpri, dsec)) {
+            Some(block) => {
+                block.body |= node.body;
+                block.members |= member;
+            }
+            None => blocks.push(FovBlock {
+                body: node.body,
+                dpri,
+                dsec,
+                members: member,
+            }),
+        }
+    }
+
+    blocks
+}
+
+/// Creates nodes for a _Simple_ FOV octant with Q-value `B::BITS`, culled to `shape`.
+///
+/// Note: for Simple FOV, the first node `(0,0)` is always visible (all bits set).
+///
+/// Returns an error if `fov_lines` has more than `B::BITS` lines, if `shape` is invalid or
+/// differs by octant (see `build_fov_nodes_shaped`), or if any FOV line is non-finite or
+/// zero length.
+pub fn build_fov_nodes<B: FovBits>(
+    rfov: FovRadius,
+    fov_lines: &FovLines,
+    shape: FovShape,
+) -> Result<Vec<FovNode<B>>, FovError> {
+    build_fov_nodes_with(rfov, fov_lines, shape, &mut |_, _| {})
+}
+
+/// Creates nodes as `build_fov_nodes` does, reporting progress to `progress`.
+pub fn build_fov_nodes_with<B: FovBits>(
+    rfov: FovRadius,
+    fov_lines: &FovLines,
+    shape: FovShape,
+    progress: &mut dyn ProgressSink,
+) -> Result<Vec<FovNode<B>>, FovError> {
+    if !shape.is_symmetric() {
+        return Err(FovError::AsymmetricShape {
+            set_type: "build_fov_nodes",
+            shape,
+        });
+    }
+    build_nodes_within(rfov.to_int(), fov_lines, shape, progress)
+}
+
+/// Creates nodes for _Simple_ FOV octant `octant` as `build_fov_nodes` does, for a `shape`
+/// that may differ by octant (an `FovShape::Ellipse` with unequal radii).
+///
+/// Elliptical nodes differ by octant, so build one list per `FovOctant::new(&nodes, octant)`.
+/// Returns an error as `build_fov_nodes` does, except that `shape` may differ by octant.
+pub fn build_fov_nodes_shaped<B: FovBits>(
+    rfov: FovRadius,
+    fov_lines: &FovLines,
+    shape: FovShape,
+    octant: Octant,
+) -> Result<Vec<FovNode<B>>, FovError> {
+    build_nodes_within(rfov.to_int(), fov_lines, shape.for_octant(octant), &mut |_, _| {})
+}
+
+/// Creates _Simple_ FOV nodes within radius `max_r` and `shape` (in pri/sec axes) from
+/// `fov_lines`, after checking `fov_lines` and `shape` as `build_fov_nodes` does.
+pub(crate) fn build_nodes_within<B: FovBits>(
+    max_r: u8,
+    fov_lines: &FovLines,
+    shape: FovShape,
+    progress: &mut dyn ProgressSink,
+) -> Result<Vec<FovNode<B>>, FovError> {
+    let (q, lines) = (B::BITS as usize, fov_lines.len());
+    if lines > q {
+        return Err(FovError::TooManyLines { q, lines });
+    }
+    if !shape.is_valid() {
+        return Err(FovError::InvalidShape(shape));
+    }
+    for (index, line) in fov_lines.iter().enumerate() {
+        line.validate().map_err(|error| FovError::InvalidLine { index, error })?;
+    }
+
+    let nodes = build_node_masks(max_r, fov_lines, shape, progress)
+        .into_iter()
+        .map(|(body, dpri, dsec)| FovNode {
+            body: B::from_mask(body),
+            dpri,
+            dsec,
+        })
+        .collect();
+    Ok(nodes)
+}
+
+/// Returns the `(body, dpri, dsec)` of each _Simple_ FOV node within radius `max_r` and
+/// `shape` (in pri/sec axes), with one `body` bit per line of `fov_lines`, in storage order.
+///
+/// Shared by the builders of every node width, which narrow `body` to their own bits. The
+/// caller checks `fov_lines` (at most 128 valid lines) and `shape`.
+fn build_node_masks(
+    max_r: u8,
+    fov_lines: &FovLines,
+    shape: FovShape,
+    progress: &mut dyn ProgressSink,
+) -> Vec<(u128, u8, u8)> {
+    let all = u128::MAX >> (128 - fov_lines.len().max(1));
+    let mut nodes = vec![(all, 0, 0)];
+
+    // Baseline FOV node lines that define the `body`. Offset by `(dpri, dsec)`.
+    let (body_base_1, body_base_2) = body_lines();
+
+    // Get (ds,dp) and cull to the shape (circular by default, on squared distances)
+    let offsets: Vec<(u8, u8)> = node_offsets(max_r)
+        .into_iter()
+        .filter(|&(dpri, dsec)| shape.contains(max_r, Delta::new(dpri as i32, dsec as i32)))
+        .collect();
+
+    // Generate FOV bits: each node is independent, so nodes may be built in parallel
+    let body_bits = |&(dpri, dsec): &(u8, u8)| {
+        let body_line_1 = body_base_1.shifted_by(dpri as f64, dsec as f64);
+        let body_line_2 = body_base_2.shifted_by(dpri as f64, dsec as f64);
+        let mut body = 0u128;
+
+        for (bit_ix, fov_line) in fov_lines.iter().enumerate() {
+            let to_set = 1u128 << bit_ix;
+
+            body |= to_set * fov_line.intersects(body_line_1) as u128;
+            body |= to_set * fov_line.intersects(body_line_2) as u128;
+        }
+
+        (body, dpri, dsec)
+    };
+    let total = offsets.len() + 1;
+    progress.progress(1, total);
+    for band in offsets.chunk_by(|a, b| a.0 == b.0) {
+        #[cfg(feature = "rayon")]
+        nodes.par_extend(band.par_iter().map(body_bits));
+        #[cfg(not(feature = "rayon"))]
+        nodes.extend(band.iter().map(body_bits));
+        progress.progress(nodes.len(), total);
+    }
+
+    nodes
+}
+
+/// Returns the `(body, dpri, dsec)` of each _Simple_ FOV node, as `build_node_masks` does,
+/// for each `(max_r, fov_lines)` in `builds`, all culled to `shape`.
+///
+/// Node body edges, and the slopes they span as seen from the observer, are computed once
+/// out to the largest radius and shared by every build. Each node then runs the line
+/// intersection test only for the FOV lines whose slope falls within its span (found by
+/// binary search) rather than for every line. A line outside the span can't cross the
+/// body, so the masks are identical to separate builds. The caller checks each build as
+/// `build_node_masks` requires.
+pub(crate) fn build_node_masks_batch(
+    builds: &[(u8, &FovLines)],
+    shape: FovShape,
+) -> Vec<Vec<(u128, u8, u8)>> {
+    let max_r = builds.iter().map(|&(r, _)| r).max().unwrap_or(0);
+    let (body_base_1, body_base_2) = body_lines();
+    let spans: Vec<NodeSpan> = node_offsets(max_r)
+        .into_iter()
+        .map(|(dpri, dsec)| NodeSpan::new(dpri, dsec, body_base_1, body_base_2))
+        .collect();
+
+    builds
+        .iter()
+        .map(|&(r, fov_lines)| {
+            let all = u128::MAX >> (128 - fov_lines.len().max(1));
+            let lines: Vec<Line> = fov_lines.iter().copied().collect();
+            let mut by_slope: Vec<(f64, usize)> = lines
+                .iter()
+                .enumerate()
+                .map(|(bit_ix, line)| ((line.y2 - line.y1) / (line.x2 - line.x1), bit_ix))
+                .collect();
+            by_slope.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let body_bits = |span: &NodeSpan| {
+                let first = by_slope.partition_point(|&(slope, _)| slope < span.slopes.0);
+                let body = by_slope[first..]
+                    .iter()
+                    .take_while(|&&(slope, _)| slope <= span.slopes.1)
+                    .filter(|&&(_, bit_ix)| span.crossed_by(lines[bit_ix]))
+                    .fold(0u128, |body, &(_, bit_ix)| body | 1 << bit_ix);
+                (body, span.dpri, span.dsec)
+            };
+            let within = |span: &&NodeSpan| {
+                shape.contains(r, Delta::new(span.dpri as i32, span.dsec as i32))
+            };
+
+            let mut nodes = vec![(all, 0, 0)];
+            #[cfg(feature = "rayon")]
+            nodes.par_extend(spans.par_iter().filter(within).map(body_bits));
+            #[cfg(not(feature = "rayon"))]
+            nodes.extend(spans.iter().filter(within).map(body_bits));
+            nodes
+        })
+        .collect()
+}
+
+/// Returns the `(dpri, dsec)` of every node out to radius `max_r`, origin excluded, in
+/// storage order.
+fn node_offsets(max_r: u8) -> Vec<(u8, u8)> {
+    let n_total = (0..max_r as u32 + 2).sum::<u32>() - 1;
+    let mut offsets = Vec::with_capacity(n_total as usize);
+
+    // Octant traversal values
+    let mut dpri: u8 = 0;
+    let mut dsec: u8 = 0;
+    let mut dsec_target: u8 = 0;
+
+    for _ in 0..n_total {
+        let sec_eq = dsec == dsec_target;
+        dpri += sec_eq as u8;
+        dsec = dsec * !sec_eq as u8 + !sec_eq as u8;
+        dsec_target += sec_eq as u8;
+        offsets.push((dpri, dsec));
+    }
+
+    offsets
+}
+
+/// Widens node slope spans, so rounding never drops a line that `Line::intersects` accepts.
+const SLOPE_MARGIN: f64 = 1e-9;
+
+/// Body edges of one node, and the slopes (`sec / pri` from the observer at `(0.5, 0.5)`)
+/// they span, for `build_node_masks_batch`.
+struct NodeSpan {
+    dpri: u8,
+    dsec: u8,
+    body: (Line, Line),
+    slopes: (f64, f64),
+}
+
+impl NodeSpan {
+    fn new(dpri: u8, dsec: u8, base_1: Line, base_2: Line) -> Self {
+        let body = (
+            base_1.shifted_by(dpri as f64, dsec as f64),
+            base_2.shifted_by(dpri as f64, dsec as f64),
+        );
+        // Both edges meet at the near corner, so three points bound the span
+        let corners = [(body.0.x1, body.0.y1), (body.0.x2, body.0.y2), (body.1.x2, body.1.y2)];
+        let slope = |(pri, sec): (f64, f64)| (sec - 0.5) / (pri - 0.5);
+        let (lo, hi) = corners
+            .into_iter()
+            .map(slope)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), s| (lo.min(s), hi.max(s)));
+        Self { dpri, dsec, body, slopes: (lo - SLOPE_MARGIN, hi + SLOPE_MARGIN) }
+    }
+    /// Returns `true` if `fov_line` crosses either body edge.
+    fn crossed_by(&self, fov_line: Line) -> bool {
+        fov_line.intersects(self.body.0) || fov_line.intersects(self.body.1)
+    }
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::QFactor;
+
+    // Every width builds the same node layout; narrower widths keep the low bits.
+    #[test]
+    fn build_fov_nodes_widths() {
+        let fov_lines = FovLines::new(FovRadius::R8, QFactor::Single);
+        let shape = FovShape::default();
+        let wide = build_fov_nodes::<u128>(FovRadius::R8, &fov_lines, shape).unwrap();
+        let narrow = build_fov_nodes::<u8>(FovRadius::R8, &fov_lines, shape).unwrap();
+        let mid = build_fov_nodes::<u32>(FovRadius::R8, &fov_lines, shape).unwrap();
+
+        assert_eq!(narrow.len(), wide.len());
+        for ((n, m), w) in narrow.iter().zip(mid.iter()).zip(wide.iter()) {
+            assert_eq!((n.dpri, n.dsec), (w.dpri, w.dsec));
+            assert_eq!(n.body.to_mask(), w.body & 0xFF);
+            assert_eq!(FovBits::count_ones(m.body), FovBits::count_ones(n.body));
+        }
+        assert_eq!(u16::from_mask(u128::MAX), u16::MAX);
+
+        let fov_octant = FovOctant::new(&narrow, Octant::O2);
+        assert_eq!(fov_octant.radius(), 8);
+        assert_eq!(fov_octant.node_index(8, 0), Some(fov_octant.max_node_index(7) + 1));
+
+        // Reoriented octants share node storage; separately built ones do not
+        let o5 = fov_octant.for_octant(Octant::O5);
+        assert_eq!((o5.octant(), o5.len()), (Octant::O5, fov_octant.len()));
+        assert!(o5.shares_nodes(&fov_octant));
+        assert!(!FovOctant::new(&narrow, Octant::O5).shares_nodes(&fov_octant));
+    }
+
+    // Progress is reported once per radius band, rising to the node count; output is unchanged.
+    #[test]
+    fn build_fov_nodes_progress() {
+        let fov_lines = FovLines::new(FovRadius::R16, QFactor::Single);
+        let shape = FovShape::default();
+        let mut reports = Vec::new();
+        let nodes = build_fov_nodes_with::<u16>(FovRadius::R16, &fov_lines, shape, &mut |d, t| {
+            reports.push((d, t))
+        })
+        .unwrap();
+
+        assert_eq!(nodes, build_fov_nodes::<u16>(FovRadius::R16, &fov_lines, shape).unwrap());
+        assert_eq!(reports.len(), 17);
+        assert_eq!(reports.first(), Some(&(1, nodes.len())));
+        assert_eq!(reports.last(), Some(&(nodes.len(), nodes.len())));
+        assert!(reports.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    // Circles build the default nodes; ellipses keep exactly the offsets within them, per octant.
+    #[test]
+    fn build_fov_nodes_shaped_ellipse() {
+        let fov_lines = FovLines::new(FovRadius::R16, QFactor::Single);
+        let circle = FovShape::Circle { adjust: 0.5 };
+        let build = |shape, octant| {
+            build_fov_nodes_shaped::<u16>(FovRadius::R16, &fov_lines, shape, octant).unwrap()
+        };
+        let default = build(FovShape::default(), Octant::O1);
+        assert_eq!(build(circle, Octant::O2), default);
+
+        let ellipse = FovShape::Ellipse { rx: 16.5, ry: 8.5 };
+        let (east, north) = (build(ellipse, Octant::O1), build(ellipse, Octant::O2));
+        assert!(north.len() < east.len() && east.len() < default.len());
+        assert_eq!(east.iter().map(|n| n.dpri).max(), Some(16));
+        assert_eq!(north.iter().map(|n| n.dpri).max(), Some(8));
+        for node in &east {
+            let (dx, dy) = (node.dpri as i32, node.dsec as i32);
+            assert!(ellipse.contains(16, Delta::new(dx, dy)));
+            let same = |n: &&FovNode<u16>| (n.dpri, n.dsec) == (node.dpri, node.dsec);
+            assert_eq!(default.iter().find(same), Some(node));
+        }
+    }
+
+    // Too many lines, invalid or asymmetric shapes and invalid lines are reported.
+    #[test]
+    fn build_fov_nodes_errors() {
+        let fov_lines = FovLines::new(FovRadius::R16, QFactor::Single);
+        let err = build_fov_nodes::<u8>(FovRadius::R16, &fov_lines, FovShape::default());
+        let err = err.unwrap_err();
+        assert!(matches!(err, FovError::TooManyLines { q: 8, lines: 16 }));
+        assert_eq!(err.to_string(), "Q8 nodes hold at most 8 FOV lines, got 16");
+
+        let ellipse = FovShape::Ellipse { rx: 16.5, ry: 8.5 };
+        let err = build_fov_nodes::<u16>(FovRadius::R16, &fov_lines, ellipse).unwrap_err();
+        assert!(matches!(err, FovError::AsymmetricShape { .. }));
+        let nan = FovShape::Circle { adjust: f64::NAN };
+        let err = build_fov_nodes_shaped::<u16>(FovRadius::R16, &fov_lines, nan, Octant::O1);
+        assert!(matches!(err, Err(FovError::InvalidShape(_))));
+    }
+}
@@ -1,6 +1,22 @@
 //! Simple 2D FOV builders and calculations.
 
-pub mod fovcalc_q16;
-pub mod fovdata_q16;
+pub mod fovcalc;
+pub mod fovdata;
+pub mod fovnode;
 
-pub use fovdata_q16::*;
+pub use fovdata::*;
+pub use fovnode::{
+    build_fov_blocks, build_fov_nodes, build_fov_nodes_shaped, build_fov_nodes_with, FovBits,
+    FovBlock, FovNode, FovOctant, ProgressSink,
+};
+
+// Former names, from when modules were named for their Q tier, kept as aliases
+
+#[deprecated(note = "use simple::fovcalc")]
+pub mod fovcalc_q16 {
+    pub use super::fovcalc::*;
+}
+#[deprecated(note = "use simple::fovdata")]
+pub mod fovdata_q16 {
+    pub use super::fovdata::*;
+}
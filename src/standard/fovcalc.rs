@@ -0,0 +1,310 @@
+//! Standard FOV calculation for FOV Visualization - Rust (2D).
+//!
+//! _Standard_ FOV determines visibility for `body`, `wall_n`, and `wall_w` subparts.
+//!
+//! Calculation:
+//! - Each octant is traversed outward from the origin, node by node, as for _Simple_ FOV.
+//! - A tile's walls are either near (between the observer and its body) or far (behind its
+//!   body), depending on the octant. Near walls block the tile's own body and far wall.
+//! - A tile part is visible if any of its FOV bits are not yet blocked, or with
+//!   `PartThresholds`, if enough of them are: `body` for bodies, `wall` for walls.
+//! - Opaque bodies and walls add their FOV bits to the blocked bits once visited.
+//! - Wall flags of `VisibleTile` are only set for opaque walls: open edges are not walls.
+//! - The observer stands inside the origin tile, so its body and walls are always visible,
+//!   and its walls are all far walls.
+//!
+//! Notes:
+//! - Walls are read through `maps::WallProvider`, so either wall model can be consumed:
+//!   `TileMap` (north and west walls, south and east read from neighbors) or `EdgeMap`
+//!   (all four edges, including walls on the south and east map edges).
+
+use std::collections::BTreeMap;
+
+use super::{FovOctant16, FovSet16};
+use crate::{
+    fov::{wall_n_line, wall_w_line, PartThresholds, Threshold, VisInfo, VisibleTile},
+    maps::{Coords, Direction, OpacityProvider, WallProvider},
+    math::Line,
+    simple::fovcalc::effective_radius,
+    Octant,
+};
+
+/// Returns visible tiles, with their visible subparts, for all FOV octants.
+///
+/// Tiles on octant boundaries are shared by neighboring octants, but are only returned once,
+/// with the subparts seen from either octant. Tiles are returned in tile ID order.
+pub fn get_visible_tiles<M>(
+    fov_set: &FovSet16,
+    map: &M,
+    origin: Coords,
+    r: usize,
+) -> Vec<VisibleTile>
+where
+    M: OpacityProvider + WallProvider,
+{
+    get_visible_tiles_thresholds(fov_set, map, origin, r, PartThresholds::default())
+}
+
+/// Returns visible tiles as `get_visible_tiles` does, with each subpart held to its threshold
+/// in `parts`: a part is visible if enough of its own FOV bits are unblocked.
+///
+/// Thresholds apply per octant, so a tile on an octant boundary is visible if either octant
+/// accepts it.
+pub fn get_visible_tiles_thresholds<M>(
+    fov_set: &FovSet16,
+    map: &M,
+    origin: Coords,
+    r: usize,
+    parts: PartThresholds,
+) -> Vec<VisibleTile>
+where
+    M: OpacityProvider + WallProvider,
+{
+    let mut tiles: BTreeMap<usize, VisibleTile> = BTreeMap::new();
+
+    for octant in Octant::ALL {
+        traverse(fov_set.octant(octant), map, origin, r, parts, |tile| {
+            match tiles.get_mut(&tile.id) {
+                Some(seen) => {
+                    seen.body |= tile.body;
+                    seen.wall_n |= tile.wall_n;
+                    seen.wall_w |= tile.wall_w;
+                }
+                None => {
+                    tiles.insert(tile.id, tile);
+                }
+            }
+        });
+    }
+
+    tiles.into_values().collect()
+}
+
+/// Returns visible tiles (and their visible subparts) in a given FOV octant.
+pub fn fov_calc<M>(
+    fov_octant: &FovOctant16,
+    map: &M,
+    origin: Coords,
+    r: usize,
+) -> Vec<VisibleTile>
+where
+    M: OpacityProvider + WallProvider,
+{
+    let mut tiles = Vec::new();
+    traverse(fov_octant, map, origin, r, PartThresholds::default(), |tile| tiles.push(tile));
+    tiles
+}
+
+/// Returns `true` if wall `line` is on the near side of its node, i.e. touches the corner
+/// closest to the observer.
+fn is_near(line: Line) -> bool {
+    line.x1 == 0.0 && line.y1 == 0.0
+}
+
+/// Returns `true` if enough of a part's FOV `bits` are not `hidden` to meet `threshold`.
+///
+/// A part with no unblocked bits is never seen, whatever the threshold.
+fn part_seen(threshold: Threshold, bits: u16, hidden: u16, dist_sq: u32) -> bool {
+    let unblocked = bits & !hidden;
+    let info = VisInfo {
+        unblocked_bits: unblocked.count_ones(),
+        total_bits: bits.count_ones(),
+        dist_sq,
+    };
+    unblocked != 0 && threshold.accepts(&info)
+}
+
+/// Traverses FOV octant nodes out to radius `r`, calling `visit` on each visible tile.
+///
+/// Out of bounds nodes are skipped. Radius `r` is adjusted by the origin's terrain modifier,
+/// and clamped to the octant's radius.
+fn traverse<M, F>(
+    fov_octant: &FovOctant16,
+    map: &M,
+    origin: Coords,
+    r: usize,
+    parts: PartThresholds,
+    mut visit: F,
+) where
+    M: OpacityProvider + WallProvider,
+    F: FnMut(VisibleTile),
+{
+    if !map.in_bounds(origin) {
+        return;
+    }
+
+    let octant = fov_octant.octant();
+    let (near_n, near_w) = (is_near(wall_n_line(octant)), is_near(wall_w_line(octant)));
+    // Shapes may cull the outer band, leaving the octant short of the set's R16
+    let r = effective_radius(map, origin, r, fov_octant.radius());
+    let mut blocked = 0u16;
+
+    for ix in 0..=fov_octant.max_node_index(r) {
+        let node = fov_octant.node(ix);
+        let coords = fov_octant.world_coords_of(ix, origin);
+        let Some(id) = map.tile_id(coords) else {
+            continue;
+        };
+
+        // FOV bits of opaque tile parts only
+        let opaque = |is_opaque: bool, bits: u16| match is_opaque {
+            true => bits,
+            false => 0,
+        };
+        let wall_n = opaque(map.wall_opaque(coords, Direction::North), node.wall_n);
+        let wall_w = opaque(map.wall_opaque(coords, Direction::West), node.wall_w);
+
+        if node.dpri == 0 {
+            visit(VisibleTile {
+                wall_n: map.wall_opaque(coords, Direction::North),
+                wall_w: map.wall_opaque(coords, Direction::West),
+                ..VisibleTile::body(id)
+            });
+            blocked |= wall_n | wall_w;
+            continue;
+        }
+
+        let near = opaque(near_n, wall_n) | opaque(near_w, wall_w);
+        let body = opaque(map.body_opaque(coords), node.body);
+        // Far walls are seen past the near walls and through the body
+        let behind = |is_near: bool| match is_near {
+            true => blocked,
+            false => blocked | near | body,
+        };
+        let (dp, ds) = (node.dpri as u32, node.dsec as u32);
+        let dist_sq = dp * dp + ds * ds;
+        let tile = VisibleTile {
+            body: part_seen(parts.body, node.body, blocked | near, dist_sq),
+            wall_n: part_seen(parts.wall, wall_n, behind(near_n), dist_sq),
+            wall_w: part_seen(parts.wall, wall_w, behind(near_w), dist_sq),
+            ..VisibleTile::body(id)
+        };
+        if tile.body || tile.wall_n || tile.wall_w {
+            visit(tile);
+        }
+
+        blocked |= near | body | wall_n | wall_w;
+        if blocked == u16::MAX {
+            break;
+        }
+    }
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        maps::{EdgeMap, TileMap},
+        simple, FovRadius, FovShape, QFactor,
+    };
+
+    // Without walls, Standard FOV sees the same tile bodies as Simple FOV.
+    #[test]
+    fn bodies_match_simple() {
+        let standard = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let simple =
+            simple::FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let mut map = TileMap::random(33, 33, 0.15, 3).unwrap();
+        let origin = Coords::new(16, 16);
+        map.set_body_opaque(origin, false);
+
+        let tiles = get_visible_tiles(&standard, &map, origin, 16);
+        let expected = simple::fovcalc::get_visible_tiles(&simple, &map, origin, 16);
+        assert_eq!(tiles, expected);
+        assert!(tiles.iter().all(|t| t.body && !t.wall_n && !t.wall_w));
+    }
+
+    // Near walls hide their tile's body, far walls are seen through it, and both cast shadows.
+    #[test]
+    fn walls_visible_and_blocking() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(10, 10);
+        let mut map = TileMap::new(21, 21).unwrap();
+        map.get_mut(Coords::new(13, 10)).unwrap().wall_w_opaque = true;
+        map.get_mut(Coords::new(10, 13)).unwrap().wall_n_opaque = true;
+        map.get_mut(origin).unwrap().wall_w_opaque = true;
+
+        let tiles = get_visible_tiles(&fov_set, &map, origin, 8);
+        let tile = |x, y| tiles.iter().find(|t| t.id == Coords::new(x, y).to_index(21).unwrap());
+
+        // East: the west wall faces the observer, so the tile behind it is hidden
+        let east = tile(13, 10).unwrap();
+        assert!(east.wall_w && !east.body && !east.wall_n);
+        assert!(tile(14, 10).is_none() && tile(12, 10).is_some_and(|t| t.body));
+
+        // North: the north wall is the far edge, seen over the tile's body
+        let north = tile(10, 13).unwrap();
+        assert!(north.body && north.wall_n && !north.wall_w);
+        assert!(tile(10, 14).is_none());
+
+        // West: the origin's own west wall blocks the view west
+        assert!(tile(10, 10).is_some_and(|t| t.body && t.wall_w && !t.wall_n));
+        assert!(tile(9, 10).is_none() && tile(7, 10).is_none());
+    }
+
+    // Wall thresholds hide walls seen only as a sliver past a corner, and leave the rest.
+    #[test]
+    fn wall_thresholds() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(10, 10);
+        let mut map = TileMap::new(21, 21).unwrap();
+        map.set_body_opaque(Coords::new(12, 11), true);
+        map.get_mut(Coords::new(14, 11)).unwrap().wall_w_opaque = true;
+        map.get_mut(Coords::new(14, 9)).unwrap().wall_w_opaque = true;
+        let parts = PartThresholds {
+            wall: Threshold::Fraction { num: 1, den: 2 },
+            ..PartThresholds::default()
+        };
+
+        let wall_w = |tiles: &[VisibleTile], x, y| {
+            let id = Coords::new(x, y).to_index(21).unwrap();
+            tiles.iter().any(|t| t.id == id && t.wall_w)
+        };
+        let any_bit = get_visible_tiles(&fov_set, &map, origin, 8);
+        let half = get_visible_tiles_thresholds(&fov_set, &map, origin, 8, parts);
+        assert!(wall_w(&any_bit, 14, 11) && !wall_w(&half, 14, 11));
+        assert!(wall_w(&any_bit, 14, 9) && wall_w(&half, 14, 9));
+        assert_eq!(
+            get_visible_tiles_thresholds(&fov_set, &map, origin, 8, PartThresholds::default()),
+            any_bit
+        );
+    }
+
+    // `TileMap` and its `EdgeMap` conversion produce identical results.
+    #[test]
+    fn edge_map_matches_tile_map() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let origin = Coords::new(12, 12);
+        let mut map = TileMap::new(25, 25).unwrap();
+        for (x, y) in [(14, 12), (15, 16), (9, 8), (12, 15), (6, 13)] {
+            map.get_mut(Coords::new(x, y)).unwrap().wall_n_opaque = true;
+            map.get_mut(Coords::new(y, x)).unwrap().wall_w_opaque = true;
+        }
+
+        let tiles = get_visible_tiles(&fov_set, &map, origin, 12);
+        assert_eq!(get_visible_tiles(&fov_set, &EdgeMap::from(&map), origin, 12), tiles);
+        assert!(tiles.iter().any(|t| t.wall_n) && tiles.iter().any(|t| t.wall_w));
+
+        let octant = fov_calc(fov_set.octant(Octant::O1), &map, origin, 12);
+        assert!(octant.iter().all(|t| tiles.iter().any(|s| s.id == t.id)));
+    }
+
+    // Shapes that cull the outer band leave short octants, queried at the set's radius.
+    #[test]
+    fn culled_shapes_at_full_radius() {
+        let origin = Coords::new(16, 16);
+        let map = TileMap::new(33, 33).unwrap();
+        for shape in [FovShape::Circle { adjust: -0.5 }, FovShape::Ellipse { rx: 16.5, ry: 8.5 }] {
+            let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, shape).unwrap();
+            let tiles = get_visible_tiles(&fov_set, &map, origin, 16);
+            assert!(!tiles.is_empty());
+            assert!(tiles.iter().all(|t| t.body));
+        }
+    }
+}
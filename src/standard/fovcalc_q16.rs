@@ -1,4 +0,0 @@
-//! Standard FOV calculation for FOV Visualization - Rust (2D).
-//!
-//! _Standard_ FOV determines visibility for `body`, `wall_n`, and `wall_w` subparts.
-
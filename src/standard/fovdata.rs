@@ -0,0 +1,381 @@
+//! Standard FOV sets for FOV Visualization - Rust (2D).
+//!
+//! Notes:
+//! - `FovSet16` holds eight `FovOctant16`s, each with its own `FovNode16`s.
+//! - Standard FOV uses three tile parts as obstructions: the tile `body`, west-facing wall `wall_w`, and north-facing wall `wall_n`.
+//! - Node `body` bits are the same as _Simple_ FOV's. Wall bits are set for the FOV lines
+//!   that cross the wall's tile edge (`fov::wall_n_line`, `fov::wall_w_line`), whose side
+//!   of the node depends on the octant.
+//!
+//! Building an FOV set:
+//! - Create a list of FOV Nodes (`Vec<FovNode>`) specific to each octant (wall position varies).
+//! - Create 8 FOV octant (`FovOctant`) instances from FOV nodes.
+//! - Create an FOV set (`FovSet`) from the 8 octants.
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{
+    error::{check_config, FovError},
+    fov::{wall_n_line, wall_w_line, Algorithm, FovLines, FovShape},
+    maps::Coords,
+    math::Line,
+    simple, FovRadius, Octant, QFactor,
+};
+
+/// _Standard_ FOV set of eight FOV octants, each comprised of 16-bit FOV nodes.
+///
+/// Deserialized sets are checked: R16 only, with each octant in its own slot.
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "SetData16", into = "SetData16")
+)]
+pub struct FovSet16 {
+    rfov: FovRadius,
+    capacity: usize,
+    octant_1: FovOctant16,
+    octant_2: FovOctant16,
+    octant_3: FovOctant16,
+    octant_4: FovOctant16,
+    octant_5: FovOctant16,
+    octant_6: FovOctant16,
+    octant_7: FovOctant16,
+    octant_8: FovOctant16,
+}
+
+impl FovSet16 {
+    /// Creates a new _Standard_ `FovSet` with Q-value `16`, its nodes culled to `shape`
+    /// (`FovShape::default()` for the usual circle).
+    ///
+    /// Octants are built separately, so `shape` may differ by octant (e.g. an ellipse).
+    /// Returns an error unless `rfov` is `R16` with single Q-Factor, or if `shape` is invalid.
+    pub fn new(rfov: FovRadius, qfactor: QFactor, shape: FovShape) -> Result<Self, FovError> {
+        check_config("FovSet16", Algorithm::Standard, Some(16), rfov, qfactor, shape)?;
+
+        let fov_lines = FovLines::new(rfov, qfactor);
+        let octant = |&octant: &Octant| {
+            let nodes = build_fov_nodes_q16(rfov, &fov_lines, shape, octant)?;
+            Ok::<_, FovError>(FovOctant16::new(&nodes, octant))
+        };
+        // Octants differ in wall bits, so each is built separately (in parallel with `rayon`)
+        #[cfg(feature = "rayon")]
+        let octants: Result<Vec<FovOctant16>, _> = Octant::ALL.par_iter().map(octant).collect();
+        #[cfg(not(feature = "rayon"))]
+        let octants: Result<Vec<FovOctant16>, _> = Octant::ALL.iter().map(octant).collect();
+        let [octant_1, octant_2, octant_3, octant_4, octant_5, octant_6, octant_7, octant_8] =
+            <[FovOctant16; 8]>::try_from(octants?).unwrap();
+
+        Ok(Self {
+            rfov,
+            capacity: octant_1.len() * 8,
+            octant_1,
+            octant_2,
+            octant_3,
+            octant_4,
+            octant_5,
+            octant_6,
+            octant_7,
+            octant_8,
+        })
+    }
+    /// Returns the maxiumum number of FOV nodes in the FOV set.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    /// Returns the FOV radius of the FOV set.
+    pub fn radius(&self) -> FovRadius {
+        self.rfov
+    }
+    /// Returns the memory held by node data, in bytes.
+    ///
+    /// Each octant holds its own nodes, as wall bits differ by octant.
+    pub fn node_bytes(&self) -> usize {
+        let nodes: usize = Octant::ALL.iter().map(|&o| self.octant(o).len()).sum();
+        nodes * size_of::<FovNode16>()
+    }
+    /// Returns the FOV octant for the given `octant`.
+    pub fn octant(&self, octant: Octant) -> &FovOctant16 {
+        match octant {
+            Octant::O1 => &self.octant_1,
+            Octant::O2 => &self.octant_2,
+            Octant::O3 => &self.octant_3,
+            Octant::O4 => &self.octant_4,
+            Octant::O5 => &self.octant_5,
+            Octant::O6 => &self.octant_6,
+            Octant::O7 => &self.octant_7,
+            Octant::O8 => &self.octant_8,
+        }
+    }
+}
+
+/// One of eight _Standard_ FOV octants, comprised of 16-bit FOV nodes.
+///
+/// Notes:
+/// - Unlike _Simple_ FOV, octants differ in wall bits as well as dx/dy values.
+/// - `node_indexes` holds the highest node index for a given radius (`r=0` to `r=16`).
+/// - Serialized without `node_indexes`, which are rebuilt once nodes are checked for storage
+///   order (see `OctantData16`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "OctantData16", into = "OctantData16")
+)]
+pub struct FovOctant16 {
+    octant: Octant,
+    nodes: Vec<FovNode16>,
+    node_indexes: Vec<usize>,
+}
+
+/// Serialized form of a _Standard_ `FovSet16`: its radius and eight octants, in order.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SetData16 {
+    rfov: FovRadius,
+    octants: [FovOctant16; 8],
+}
+
+#[cfg(feature = "serde")]
+impl From<FovSet16> for SetData16 {
+    fn from(fov_set: FovSet16) -> Self {
+        Self {
+            rfov: fov_set.rfov,
+            octants: [
+                fov_set.octant_1,
+                fov_set.octant_2,
+                fov_set.octant_3,
+                fov_set.octant_4,
+                fov_set.octant_5,
+                fov_set.octant_6,
+                fov_set.octant_7,
+                fov_set.octant_8,
+            ],
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SetData16> for FovSet16 {
+    type Error = String;
+
+    /// Rejects radii other than R16, octants out of order, and nodes past the radius.
+    fn try_from(data: SetData16) -> Result<Self, Self::Error> {
+        if data.rfov != FovRadius::R16 {
+            return Err(format!("FovSet16 requires FOV radius of 16, got {:?}", data.rfov));
+        }
+        for (fov_octant, octant) in data.octants.iter().zip(Octant::ALL) {
+            if fov_octant.octant() != octant {
+                return Err(format!("FovSet16 expected {octant:?}, got {:?}", fov_octant.octant()));
+            }
+            if fov_octant.node_indexes.len() > 17 {
+                return Err(format!("FovSet16 {octant:?} nodes reach past radius 16"));
+            }
+        }
+        let [octant_1, octant_2, octant_3, octant_4, octant_5, octant_6, octant_7, octant_8] =
+            data.octants;
+
+        Ok(Self {
+            rfov: data.rfov,
+            capacity: octant_1.len() * 8,
+            octant_1,
+            octant_2,
+            octant_3,
+            octant_4,
+            octant_5,
+            octant_6,
+            octant_7,
+            octant_8,
+        })
+    }
+}
+
+/// Serialized form of a _Standard_ `FovOctant16`: its octant and nodes.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct OctantData16 {
+    octant: Octant,
+    nodes: Vec<FovNode16>,
+}
+
+#[cfg(feature = "serde")]
+impl From<FovOctant16> for OctantData16 {
+    fn from(fov_octant: FovOctant16) -> Self {
+        Self {
+            octant: fov_octant.octant,
+            nodes: fov_octant.nodes,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<OctantData16> for FovOctant16 {
+    type Error = String;
+
+    /// Rejects nodes out of storage order (see `simple::FovOctant`).
+    fn try_from(data: OctantData16) -> Result<Self, Self::Error> {
+        match simple::fovnode::in_storage_order(data.nodes.iter().map(|n| (n.dpri, n.dsec))) {
+            true => Ok(FovOctant16::new(&data.nodes, data.octant)),
+            false => Err("FOV octant nodes out of storage order".to_string()),
+        }
+    }
+}
+
+impl FovOctant16 {
+    /// Creates a new `FovOctant` for `octant` from nodes built for it.
+    ///
+    /// Panics if `nodes` is empty.
+    pub fn new(nodes: &[FovNode16], octant: Octant) -> Self {
+        assert!(!nodes.is_empty(), "FovOctant16 requires at least the origin node!");
+        let mut node_indexes = Vec::new();
+
+        for (i, node) in nodes.iter().enumerate().skip(1) {
+            if node.dpri as usize > node_indexes.len() {
+                node_indexes.push(i - 1);
+            }
+        }
+
+        // Highest node index for max radius is always the last node
+        node_indexes.push(nodes.len() - 1);
+
+        Self {
+            octant,
+            nodes: nodes.to_vec(),
+            node_indexes,
+        }
+    }
+    /// Returns the octant this FOV octant covers.
+    pub fn octant(&self) -> Octant {
+        self.octant
+    }
+    /// Returns the world coordinates of node `node_ix`, for an observer at `origin`.
+    ///
+    /// Panics if `node_ix` is out of range.
+    pub fn world_coords_of(&self, node_ix: usize, origin: Coords) -> Coords {
+        let node = &self.nodes[node_ix];
+        origin.offset(self.octant.dpds_to_delta(node.dpri as u16, node.dsec as u16))
+    }
+    /// Returns the FOV node with index `node_ix`. Panics if `node_ix` is out of range.
+    pub fn node(&self, node_ix: usize) -> &FovNode16 {
+        &self.nodes[node_ix]
+    }
+    /// Returns an iterator over the FOV nodes in the octant.
+    pub fn iter(&self) -> std::slice::Iter<'_, FovNode16> {
+        self.nodes.iter()
+    }
+    /// Returns the number of nodes in the octant.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    /// Returns `true` if the octant holds no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+    /// Returns the largest radius covered by the octant's nodes.
+    pub fn radius(&self) -> usize {
+        self.node_indexes.len() - 1
+    }
+    /// Returns the maximum FOV node index for a given radius.
+    pub fn max_node_index(&self, radius: usize) -> usize {
+        assert!(radius <= self.radius(), "radius must be <= {}!", self.radius());
+        self.node_indexes[radius]
+    }
+}
+
+/// Node in a _Standard_ FOV octant representing a single tile with 16 FOV bits (`Q=16`)
+/// for each of its `body`, north wall `wall_n`, and west wall `wall_w`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FovNode16 {
+    pub body: u16,
+    pub wall_n: u16,
+    pub wall_w: u16,
+    pub dpri: u8,
+    pub dsec: u8,
+}
+
+/// Creates nodes for a _Standard_ FOV octant with Q-value `16`, culled to `shape`.
+///
+/// Note: `body` bits match `simple::build_fov_nodes_shaped`, so the first node `(0,0)` has
+/// all body bits set. Wall bits depend on `octant`, as wall edges sit on different node sides.
+///
+/// Returns an error as `simple::build_fov_nodes_shaped` does.
+pub fn build_fov_nodes_q16(
+    rfov: FovRadius,
+    fov_lines: &FovLines,
+    shape: FovShape,
+    octant: Octant,
+) -> Result<Vec<FovNode16>, FovError> {
+    let (wall_n_base, wall_w_base) = (wall_n_line(octant), wall_w_line(octant));
+
+    let nodes = simple::build_fov_nodes_shaped::<u16>(rfov, fov_lines, shape, octant)?
+        .into_iter()
+        .map(|node| {
+            let (dpri, dsec) = (node.dpri as f64, node.dsec as f64);
+            FovNode16 {
+                body: node.body,
+                wall_n: line_bits(fov_lines, wall_n_base.shifted_by(dpri, dsec)),
+                wall_w: line_bits(fov_lines, wall_w_base.shifted_by(dpri, dsec)),
+                dpri: node.dpri,
+                dsec: node.dsec,
+            }
+        })
+        .collect();
+    Ok(nodes)
+}
+
+/// Returns the bits of the FOV lines in `fov_lines` that intersect `edge`.
+fn line_bits(fov_lines: &FovLines, edge: Line) -> u16 {
+    fov_lines
+        .iter()
+        .enumerate()
+        .fold(0, |bits, (bit_ix, fov_line)| bits | (fov_line.intersects(edge) as u16) << bit_ix)
+}
+
+//  ########  ########   ######   ########
+//     ##     ##        ##           ##
+//     ##     ######     ######      ##
+//     ##     ##              ##     ##
+//     ##     ########  #######      ##
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Bodies match Simple FOV; walls mirror across octants as `wall_n_line`/`wall_w_line` say.
+    #[test]
+    fn fov_nodes_walls_q16() {
+        let rfov = FovRadius::R16;
+        let fov_lines = FovLines::new(rfov, QFactor::Single);
+        let simple = simple::build_fov_nodes_q16(rfov, &fov_lines, FovShape::default()).unwrap();
+        let nodes = |octant| {
+            build_fov_nodes_q16(rfov, &fov_lines, FovShape::default(), octant).unwrap()
+        };
+        let (o1, o4, o8) = (nodes(Octant::O1), nodes(Octant::O4), nodes(Octant::O8));
+
+        assert_eq!(o1.len(), simple.len());
+        assert!(o1.iter().zip(simple.iter()).all(|(n, s)| n.body == s.body));
+        assert!(o1.iter().zip(o4.iter()).all(|(a, b)| a.wall_n == b.wall_n));
+        assert!(o1.iter().zip(o8.iter()).all(|(a, b)| a.wall_w == b.wall_w));
+        assert!(o1.iter().any(|n| n.wall_n != n.wall_w));
+
+        // In octant 1 the west wall is the node's near primary edge, which the body covers
+        assert!(o1.iter().all(|n| n.wall_w & !n.body == 0));
+        assert!(o1.iter().any(|n| n.wall_n != 0));
+    }
+
+    // Sets build one node list per octant, all the same length.
+    #[test]
+    fn fov_set_q16_standard() {
+        let fov_set = FovSet16::new(FovRadius::R16, QFactor::Single, FovShape::default()).unwrap();
+        let fov_octant = fov_set.octant(Octant::O6);
+        assert_eq!(fov_octant.octant(), Octant::O6);
+        assert_eq!(fov_set.capacity(), fov_octant.len() * 8);
+        assert_eq!(fov_octant.max_node_index(16), fov_octant.len() - 1);
+        assert_eq!(fov_octant.node(0).body, u16::MAX);
+
+        let origin = Coords::new(10, 10);
+        assert_eq!(fov_octant.world_coords_of(1, origin), Coords::new(10, 9));
+    }
+}
@@ -1,4 +1,17 @@
 //! Standard 2D FOV builders and calculations.
 
-pub mod fovcalc_q16;
-pub mod fovdata_q16;
+pub mod fovcalc;
+pub mod fovdata;
+
+pub use fovdata::*;
+
+// Former names, from when modules were named for their Q tier, kept as aliases
+
+#[deprecated(note = "use standard::fovcalc")]
+pub mod fovcalc_q16 {
+    pub use super::fovcalc::*;
+}
+#[deprecated(note = "use standard::fovdata")]
+pub mod fovdata_q16 {
+    pub use super::fovdata::*;
+}